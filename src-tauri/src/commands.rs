@@ -1,6 +1,10 @@
 // Tauri Commands - API endpoints for frontend
 
-use crate::{editor, encoding, file_ops, fonts, plugin, settings, syntax};
+use crate::{
+    conversion, data_format, document, editor, encoding, file_index, file_ops, fonts, formatter,
+    full_text, hex, large_file, log_follow, lsp, plugin, problems, protocol, recent, settings,
+    single_instance, structural, syntax, tasks, terminal, terminal_history, validation,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tauri::{AppHandle, Manager};
@@ -18,13 +22,28 @@ pub struct FileInfo {
     pub created: Option<String>,
     pub encoding: String,
     pub language: String,
+    /// 超过阈值，应使用大文件模式打开
+    pub is_large: bool,
+    /// 二进制文件，应使用十六进制编辑器打开
+    pub is_binary: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SearchResult {
+    /// 行、列从 1 开始，列为 UTF-16 码元
     pub line: usize,
     pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    /// UTF-16 码元数
     pub length: usize,
+    /// 全文中的字节偏移 [start, end)
+    pub byte_start: usize,
+    pub byte_end: usize,
+    /// 全文中的字符（Unicode 标量值）偏移 [start, end)
+    pub char_start: usize,
+    pub char_end: usize,
     pub text: String,
     pub context: String,
 }
@@ -90,15 +109,187 @@ pub async fn list_directory(path: String) -> Result<Vec<DirectoryEntry>, String>
     file_ops::list_directory(&path).await.map_err(|e| e.to_string())
 }
 
+// Large File Operations
+#[tauri::command]
+pub fn open_large_file(app: AppHandle, path: String) -> Result<large_file::LargeFileStatus, String> {
+    large_file::open_file(app, &path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn close_large_file(path: String) {
+    large_file::close_file(&path);
+}
+
+#[tauri::command]
+pub fn get_large_file_status(path: String) -> Option<large_file::LargeFileStatus> {
+    large_file::file_status(&path)
+}
+
+#[tauri::command]
+pub async fn read_large_file_lines(
+    path: String,
+    start: usize,
+    count: usize,
+) -> Result<large_file::LineRange, String> {
+    large_file::read_lines(&path, start, count).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn refresh_large_file(app: AppHandle, path: String) -> Result<large_file::LargeFileStatus, String> {
+    large_file::refresh(app, &path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn append_to_large_file(
+    app: AppHandle,
+    path: String,
+    text: String,
+) -> Result<large_file::LargeFileStatus, String> {
+    large_file::append(app, &path, &text).map_err(|e| e.to_string())
+}
+
+// Hex Editor Operations
+#[tauri::command]
+pub fn open_hex_document(path: String) -> Result<hex::HexStatus, String> {
+    hex::open_document(&path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn close_hex_document(path: String) {
+    hex::close_document(&path);
+}
+
+#[tauri::command]
+pub async fn read_hex_bytes(path: String, offset: u64, length: u64) -> Result<hex::HexRange, String> {
+    hex::read_bytes(&path, offset, length).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn write_hex_bytes(path: String, offset: u64, bytes: Vec<u8>) -> Result<hex::HexStatus, String> {
+    hex::write_bytes(&path, offset, &bytes).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn insert_hex_bytes(path: String, offset: u64, bytes: Vec<u8>) -> Result<hex::HexStatus, String> {
+    hex::insert_bytes(&path, offset, &bytes).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_hex_bytes(path: String, offset: u64, length: u64) -> Result<hex::HexStatus, String> {
+    hex::delete_bytes(&path, offset, length).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_hex_document(path: String) -> Result<hex::HexStatus, String> {
+    hex::save(&path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_hex_document_as(path: String, target: String) -> Result<hex::HexStatus, String> {
+    hex::save_as(&path, &target).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn search_hex(
+    path: String,
+    query: hex::HexSearchQuery,
+    start: Option<u64>,
+    limit: Option<usize>,
+) -> Result<hex::HexSearchResult, String> {
+    tokio::task::spawn_blocking(move || hex::search(&path, &query, start.unwrap_or(0), limit))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+// Protocol Operations
+/// 设置预览附件协议（kairofile://）允许访问的目录：当前文件夹、笔记所在目录和设置中的附件目录，
+/// 返回实际登记的目录
+#[tauri::command]
+pub async fn set_file_protocol_roots(workspace: Option<String>, note: Option<String>) -> Result<Vec<String>, String> {
+    let settings = settings::get_settings().await.map_err(|e| e.to_string())?;
+    Ok(protocol::set_file_roots(workspace.as_deref(), note.as_deref(), &settings.asset_roots))
+}
+
+// Log Follow Operations
+#[tauri::command]
+pub fn start_log_follow(
+    app: AppHandle,
+    path: String,
+    options: Option<log_follow::FollowOptions>,
+) -> Result<String, String> {
+    log_follow::start(app, &path, &options.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn stop_log_follow(id: String) {
+    log_follow::stop(&id);
+}
+
+#[tauri::command]
+pub fn update_log_follow_filter(id: String, options: log_follow::FollowOptions) -> Result<(), String> {
+    log_follow::update_filter(&id, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn detect_log_levels(lines: Vec<String>) -> Vec<Option<log_follow::LogLevel>> {
+    lines.iter().map(|line| log_follow::detect_level(line)).collect()
+}
+
+// File Index Operations
+#[tauri::command]
+pub fn open_file_index(app: AppHandle, root: String) -> Result<file_index::FileIndexStatus, String> {
+    file_index::open_index(app, &root).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn close_file_index(root: String) {
+    file_index::close_index(&root);
+}
+
+#[tauri::command]
+pub fn get_file_index_status(root: String) -> Option<file_index::FileIndexStatus> {
+    file_index::index_status(&root)
+}
+
+#[tauri::command]
+pub fn find_files(root: String, query: String, limit: Option<usize>) -> Result<file_index::FileSearchResult, String> {
+    file_index::find_files(&root, &query, limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+// Full Text Index Operations
+#[tauri::command]
+pub fn open_full_text_index(app: AppHandle, root: String) -> Result<full_text::FullTextStatus, String> {
+    full_text::open_index(app, &root).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn close_full_text_index(root: String) {
+    full_text::close_index(&root);
+}
+
+#[tauri::command]
+pub fn get_full_text_index_status(root: String) -> Option<full_text::FullTextStatus> {
+    full_text::index_status(&root)
+}
+
+#[tauri::command]
+pub async fn search_full_text(
+    root: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<full_text::NoteSearchResult, String> {
+    full_text::search(&root, &query, limit.unwrap_or(50)).await.map_err(|e| e.to_string())
+}
+
 // Search Operations
 #[tauri::command]
 pub async fn search_in_file(
     content: String,
     query: String,
-    case_sensitive: bool,
-    use_regex: bool,
+    options: editor::SearchOptions,
 ) -> Result<Vec<SearchResult>, String> {
-    editor::search_in_content(&content, &query, case_sensitive, use_regex)
+    editor::search_in_content(&content, &query, &options)
         .map_err(|e| e.to_string())
 }
 
@@ -107,28 +298,209 @@ pub async fn search_and_replace(
     content: String,
     search: String,
     replace: String,
-    case_sensitive: bool,
-    use_regex: bool,
-    replace_all: bool,
+    options: editor::ReplaceOptions,
 ) -> Result<String, String> {
-    editor::search_and_replace(&content, &search, &replace, case_sensitive, use_regex, replace_all)
+    editor::search_and_replace(&content, &search, &replace, &options)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn preview_replace(
+    content: String,
+    search: String,
+    replace: String,
+    options: editor::ReplaceOptions,
+) -> Result<Vec<editor::ReplacePreview>, String> {
+    editor::preview_replace(&content, &search, &replace, &options)
         .map_err(|e| e.to_string())
 }
 
+// Document Operations
+// 打开和增量同步为同步命令，按调用顺序执行
+#[tauri::command]
+pub fn open_document(
+    tab_id: String,
+    content: String,
+    version: i64,
+    path: Option<String>,
+    language: Option<String>,
+) -> document::DocumentStats {
+    document::open_document(&tab_id, &content, version, path, language)
+}
+
+#[tauri::command]
+pub fn close_document(tab_id: String) {
+    document::close_document(&tab_id);
+}
+
+#[tauri::command]
+pub fn apply_document_changes(
+    tab_id: String,
+    base_version: i64,
+    version: i64,
+    changes: Vec<document::ContentChange>,
+) -> Result<(), String> {
+    document::apply_changes(&tab_id, base_version, version, &changes).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_document_text(tab_id: String) -> Result<String, String> {
+    document::document_text(&tab_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_document_stats(tab_id: String) -> Result<document::DocumentStats, String> {
+    document::document_stats(&tab_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn search_document(
+    tab_id: String,
+    query: String,
+    options: editor::SearchOptions,
+) -> Result<Vec<SearchResult>, String> {
+    document::search(&tab_id, &query, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn preview_document_replace(
+    tab_id: String,
+    search: String,
+    replace: String,
+    options: editor::ReplaceOptions,
+) -> Result<Vec<editor::ReplacePreview>, String> {
+    document::preview_replace(&tab_id, &search, &replace, &options).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_document_highlight(
+    tab_id: String,
+    language: Option<String>,
+) -> Result<Vec<syntax::HighlightToken>, String> {
+    document::highlight(&tab_id, language.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn structural_search_document(
+    tab_id: String,
+    language: Option<String>,
+    query: structural::StructuralQuery,
+) -> Result<Vec<structural::StructuralMatch>, String> {
+    document::structural_search(&tab_id, language.as_deref(), &query).map_err(|e| e.to_string())
+}
+
+// Structural Search Operations
+/// 未指定语言时根据文件路径检测
+fn structural_language(path: Option<String>, language: Option<String>) -> Result<String, String> {
+    language
+        .or_else(|| path.map(|path| syntax::detect_language(&path, None)))
+        .ok_or_else(|| "Language or path is required".to_string())
+}
+
+#[tauri::command]
+pub fn get_structural_languages() -> Vec<&'static str> {
+    structural::SUPPORTED_LANGUAGES.to_vec()
+}
+
+#[tauri::command]
+pub async fn structural_search(
+    content: String,
+    path: Option<String>,
+    language: Option<String>,
+    query: structural::StructuralQuery,
+) -> Result<Vec<structural::StructuralMatch>, String> {
+    let language = structural_language(path, language)?;
+    structural::structural_search(&content, &language, &query).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn structural_replace(
+    content: String,
+    path: Option<String>,
+    language: Option<String>,
+    query: structural::StructuralQuery,
+) -> Result<String, String> {
+    let language = structural_language(path, language)?;
+    structural::structural_replace(&content, &language, &query).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn structural_search_workspace(
+    root: String,
+    language: Option<String>,
+    query: structural::StructuralQuery,
+    limit: Option<usize>,
+) -> Result<Vec<structural::FileStructuralMatches>, String> {
+    tokio::task::spawn_blocking(move || {
+        structural::search_workspace(std::path::Path::new(&root), language.as_deref(), &query, limit.unwrap_or(1000))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
 // Recent Files
 #[tauri::command]
 pub async fn get_recent_files() -> Result<Vec<String>, String> {
-    settings::get_recent_files().await.map_err(|e| e.to_string())
+    recent::get_recent_entries(Some(recent::RecentKind::File))
+        .await
+        .map(|entries| entries.into_iter().map(|e| e.path).collect())
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn add_recent_file(path: String) -> Result<(), String> {
-    settings::add_recent_file(&path).await.map_err(|e| e.to_string())
+    recent::add_recent_entry(&path, recent::RecentKind::File).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn clear_recent_files() -> Result<(), String> {
-    settings::clear_recent_files().await.map_err(|e| e.to_string())
+    recent::clear_recent_entries(Some(recent::RecentKind::File)).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_recent_entries(
+    kind: Option<recent::RecentKind>,
+) -> Result<Vec<recent::RecentEntry>, String> {
+    recent::get_recent_entries(kind).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_recent_entry(path: String, kind: recent::RecentKind) -> Result<(), String> {
+    recent::add_recent_entry(&path, kind).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_recent_entry(path: String, kind: recent::RecentKind) -> Result<(), String> {
+    recent::remove_recent_entry(&path, kind).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_recent_pinned(
+    path: String,
+    kind: recent::RecentKind,
+    pinned: bool,
+) -> Result<(), String> {
+    recent::set_recent_pinned(&path, kind, pinned).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_recent_entries(kind: Option<recent::RecentKind>) -> Result<(), String> {
+    recent::clear_recent_entries(kind).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn prune_recent_entries() -> Result<usize, String> {
+    recent::prune_recent_entries().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn filter_recent_entries(
+    query: String,
+    kind: Option<recent::RecentKind>,
+    limit: Option<usize>,
+) -> Result<Vec<recent::RecentMatch>, String> {
+    recent::filter_recent_entries(&query, kind, limit).await.map_err(|e| e.to_string())
 }
 
 // Encoding
@@ -396,100 +768,368 @@ pub struct TerminalOutput {
     pub stdout: String,
     pub stderr: String,
     pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub cancelled: bool,
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_terminal_command(
+    app: AppHandle,
     command: String,
     cwd: Option<String>,
     terminal_type: String,
+    command_id: Option<String>,
+    timeout_ms: Option<u64>,
+    env: Option<HashMap<String, String>>,
+    stdin: Option<String>,
+    problem_matchers: Option<Vec<problems::ProblemMatcherRef>>,
 ) -> Result<TerminalOutput, String> {
-    use std::process::{Command, Stdio};
-    
-    #[cfg(windows)]
-    use std::os::windows::process::CommandExt;
-    #[cfg(windows)]
-    const CREATE_NO_WINDOW: u32 = 0x08000000;
-    
-    let (shell, shell_arg) = match terminal_type.as_str() {
-        "cmd" => ("cmd", "/C"),
-        "pwsh" => ("pwsh", "-Command"),
-        "wsl" => ("wsl", "-e"),
-        "gitbash" => {
-            // Try common Git Bash paths
-            let git_bash_paths = [
-                "C:\\Program Files\\Git\\bin\\bash.exe",
-                "C:\\Program Files (x86)\\Git\\bin\\bash.exe",
-            ];
-            let bash_path = git_bash_paths.iter()
-                .find(|p| std::path::Path::new(p).exists())
-                .map(|s| *s)
-                .unwrap_or("bash");
-            (bash_path, "-c")
-        },
-        _ => ("powershell", "-Command"), // default to powershell
+    let options = terminal::CommandOptions {
+        command_id,
+        timeout_ms,
+        env: env.unwrap_or_default(),
+        stdin,
     };
-    
-    let mut cmd = Command::new(shell);
-    cmd.arg(shell_arg)
-        .arg(&command)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
-    
-    if let Some(dir) = &cwd {
-        cmd.current_dir(dir);
+    let output = terminal::execute_command(&command, cwd.as_deref(), &terminal_type, options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Err(e) =
+        terminal_history::add_history_entry(cwd.as_deref().unwrap_or(""), &command, output.exit_code)
+            .await
+    {
+        log::warn!("Failed to save terminal history: {}", e);
     }
-    
-    #[cfg(windows)]
-    cmd.creation_flags(CREATE_NO_WINDOW);
-    
-    let output = cmd.output().map_err(|e| e.to_string())?;
-    
-    Ok(TerminalOutput {
-        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-        exit_code: output.status.code(),
-    })
+
+    // 终端命令的问题统一归到 "terminal" 来源，每次运行替换上一次的结果
+    if let Err(e) = problems::publish_from_output(
+        &app,
+        "terminal",
+        &[&output.stdout, &output.stderr],
+        cwd.as_deref(),
+        Some(&command),
+        problem_matchers.as_deref(),
+    )
+    .await
+    {
+        log::warn!("Failed to parse problems: {}", e);
+    }
+    Ok(output)
+}
+
+#[tauri::command]
+pub fn cancel_terminal_command(command_id: String) -> bool {
+    terminal::cancel_command(&command_id)
 }
 
 #[tauri::command]
 pub fn get_available_terminals() -> Vec<String> {
-    let mut terminals = vec!["powershell".to_string()];
-    
-    #[cfg(windows)]
-    {
-        // Check for cmd
-        terminals.push("cmd".to_string());
-        
-        // Check for PowerShell Core (pwsh)
-        if std::process::Command::new("pwsh")
-            .arg("--version")
-            .output()
-            .is_ok()
-        {
-            terminals.push("pwsh".to_string());
-        }
-        
-        // Check for WSL
-        if std::process::Command::new("wsl")
-            .arg("--status")
-            .output()
-            .is_ok()
-        {
-            terminals.push("wsl".to_string());
-        }
-        
-        // Check for Git Bash
-        let git_bash_paths = [
-            "C:\\Program Files\\Git\\bin\\bash.exe",
-            "C:\\Program Files (x86)\\Git\\bin\\bash.exe",
-        ];
-        if git_bash_paths.iter().any(|p| std::path::Path::new(p).exists()) {
-            terminals.push("gitbash".to_string());
-        }
-    }
-    
-    terminals
+    terminal::get_available_terminals()
+}
+
+#[tauri::command]
+pub fn create_terminal_session(
+    app: AppHandle,
+    terminal_type: String,
+    cwd: Option<String>,
+    env: Option<HashMap<String, String>>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<terminal::TerminalSessionInfo, String> {
+    terminal::create_session(
+        &app,
+        &terminal_type,
+        cwd.as_deref(),
+        env,
+        cols.unwrap_or(80),
+        rows.unwrap_or(24),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn write_terminal_session(session_id: String, data: String) -> Result<(), String> {
+    terminal::write_session(&session_id, &data).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn resize_terminal_session(session_id: String, cols: u16, rows: u16) -> Result<(), String> {
+    terminal::resize_session(&session_id, cols, rows).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn kill_terminal_session(session_id: String) -> Result<(), String> {
+    terminal::kill_session(&session_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_terminal_sessions() -> Vec<terminal::TerminalSessionInfo> {
+    terminal::list_sessions()
+}
+
+#[tauri::command]
+pub async fn get_terminal_history(
+    workspace: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<terminal_history::HistoryEntry>, String> {
+    terminal_history::get_history(workspace.as_deref().unwrap_or(""), limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn search_terminal_history(
+    workspace: Option<String>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<terminal_history::HistoryEntry>, String> {
+    terminal_history::search_history(workspace.as_deref().unwrap_or(""), &query, limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_terminal_history(
+    workspace: Option<String>,
+    command: String,
+    exit_code: Option<i32>,
+) -> Result<(), String> {
+    terminal_history::add_history_entry(workspace.as_deref().unwrap_or(""), &command, exit_code)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_terminal_history(workspace: Option<String>) -> Result<(), String> {
+    terminal_history::clear_history(workspace.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rerun_terminal_history(session_id: String, command: String) -> Result<(), String> {
+    terminal_history::rerun_in_session(&session_id, &command)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Task Commands
+#[tauri::command]
+pub async fn list_tasks(folder: String) -> Result<Vec<tasks::TaskDefinition>, String> {
+    tasks::load_tasks(&folder).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn run_task(app: AppHandle, folder: String, label: String) -> Result<tasks::TaskRunInfo, String> {
+    tasks::run_task(&app, &folder, &label).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn run_group_task(
+    app: AppHandle,
+    folder: String,
+    group: String,
+) -> Result<tasks::TaskRunInfo, String> {
+    tasks::run_group_task(&app, &folder, &group).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn cancel_task_run(run_id: String) -> bool {
+    tasks::cancel_task_run(&run_id)
+}
+
+// Problem Commands
+
+#[tauri::command]
+pub async fn get_problems() -> Vec<problems::Problem> {
+    problems::get_problems().await
+}
+
+#[tauri::command]
+pub async fn clear_problems(app: AppHandle, source: Option<String>) {
+    problems::clear_problems(&app, source.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn next_problem(
+    path: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    reverse: Option<bool>,
+) -> Option<problems::Problem> {
+    problems::next_problem(
+        path.as_deref(),
+        line.unwrap_or(1),
+        column.unwrap_or(1),
+        reverse.unwrap_or(false),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn list_problem_matchers() -> Result<Vec<problems::ProblemMatcherDefinition>, String> {
+    problems::get_matcher_definitions().await.map_err(|e| e.to_string())
+}
+
+// Formatter Commands
+#[tauri::command]
+pub async fn format_document(
+    content: String,
+    path: Option<String>,
+    language_id: Option<String>,
+    workspace: Option<String>,
+    options: Option<data_format::DataFormatOptions>,
+) -> Result<formatter::FormattedDocument, formatter::FormatError> {
+    formatter::format_document(
+        &content,
+        path.as_deref(),
+        language_id.as_deref(),
+        workspace.as_deref(),
+        &options.unwrap_or_default(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub fn format_data(
+    content: String,
+    format: data_format::DataFormat,
+    options: Option<data_format::DataFormatOptions>,
+) -> Result<data_format::DataFormatResult, String> {
+    data_format::format_data(&content, format, &options.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_formatters(workspace: Option<String>) -> Result<Vec<formatter::FormatterConfig>, String> {
+    formatter::get_formatters(workspace.as_deref()).await.map_err(|e| e.to_string())
+}
+
+/// 转换数据格式，未指定源格式时按路径和语言 ID 检测
+#[tauri::command]
+pub fn convert_data(
+    content: String,
+    from: Option<conversion::ConversionFormat>,
+    to: conversion::ConversionFormat,
+    path: Option<String>,
+    language_id: Option<String>,
+    options: Option<data_format::DataFormatOptions>,
+) -> Result<conversion::ConversionResult, String> {
+    let from = from
+        .or_else(|| conversion::ConversionFormat::detect(path.as_deref(), language_id.as_deref()))
+        .ok_or_else(|| "Cannot determine the source format".to_string())?;
+    conversion::convert_data(&content, from, to, &options.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+// Validation Commands
+
+#[tauri::command]
+pub async fn validate_document(
+    content: String,
+    path: Option<String>,
+    language_id: Option<String>,
+    workspace: Option<String>,
+) -> Result<Vec<problems::Problem>, String> {
+    validation::validate_document(&content, path.as_deref(), language_id.as_deref(), workspace.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Language Server Commands
+#[tauri::command]
+pub async fn lsp_open_document(
+    app: AppHandle,
+    path: String,
+    text: String,
+    language_id: Option<String>,
+    workspace: Option<String>,
+) -> Result<Option<lsp::LanguageServerInfo>, String> {
+    lsp::open_document(&app, &path, &text, language_id.as_deref(), workspace.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_change_document(path: String, text: String) -> Result<(), String> {
+    lsp::change_document(&path, &text).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_save_document(path: String, text: Option<String>) -> Result<(), String> {
+    lsp::save_document(&path, text.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_close_document(path: String) -> Result<(), String> {
+    lsp::close_document(&path).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_completion(
+    path: String,
+    line: u32,
+    character: u32,
+    trigger_character: Option<String>,
+) -> Result<serde_json::Value, String> {
+    lsp::completion(&path, line, character, trigger_character.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_hover(path: String, line: u32, character: u32) -> Result<serde_json::Value, String> {
+    lsp::hover(&path, line, character).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_definition(path: String, line: u32, character: u32) -> Result<serde_json::Value, String> {
+    lsp::definition(&path, line, character).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_references(
+    path: String,
+    line: u32,
+    character: u32,
+    include_declaration: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    lsp::references(&path, line, character, include_declaration.unwrap_or(true))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_rename(
+    path: String,
+    line: u32,
+    character: u32,
+    new_name: String,
+) -> Result<serde_json::Value, String> {
+    lsp::rename(&path, line, character, &new_name).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn lsp_formatting(
+    path: String,
+    tab_size: Option<u32>,
+    insert_spaces: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    lsp::formatting(&path, tab_size.unwrap_or(4), insert_spaces.unwrap_or(true))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_language_servers() -> Vec<lsp::LanguageServerInfo> {
+    lsp::list_servers().await
+}
+
+#[tauri::command]
+pub async fn restart_language_server(
+    server_id: String,
+    root: String,
+) -> Result<lsp::LanguageServerInfo, String> {
+    lsp::restart_server(&server_id, &root).await.map_err(|e| e.to_string())
 }
 
 // Window commands
@@ -501,3 +1141,9 @@ pub fn show_main_window(app: AppHandle) -> Result<(), String> {
     }
     Ok(())
 }
+
+// 前端完成初始化（已监听 open-file 事件）后调用，返回启动期间排队等待打开的文件
+#[tauri::command]
+pub fn frontend_ready() -> Result<Vec<String>, String> {
+    Ok(single_instance::frontend_ready())
+}
@@ -0,0 +1,846 @@
+// Conversion Module
+// 数据格式互转：JSON、YAML、TOML、XML、plist 和 CSV（扁平数组），并报告转换中丢失的信息
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::path::Path;
+
+use crate::data_format::{self, DataFormat, DataFormatOptions, XmlNode};
+use crate::validation;
+
+/// 支持转换的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversionFormat {
+    Json,
+    Yaml,
+    Toml,
+    Xml,
+    Plist,
+    Csv,
+}
+
+impl ConversionFormat {
+    /// 根据语言 ID（detect_language 的结果）确定格式，plist 按扩展名区分
+    pub fn detect(path: Option<&str>, language_id: Option<&str>) -> Option<Self> {
+        let is_plist = path
+            .and_then(|p| Path::new(p).extension())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("plist"));
+        if is_plist {
+            return Some(ConversionFormat::Plist);
+        }
+        match language_id? {
+            "json" | "jsonc" => Some(ConversionFormat::Json),
+            "yaml" => Some(ConversionFormat::Yaml),
+            "toml" => Some(ConversionFormat::Toml),
+            "xml" => Some(ConversionFormat::Xml),
+            "csv" | "tsv" => Some(ConversionFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// 转换结果在编辑器中使用的语言 ID
+    pub fn language_id(self) -> &'static str {
+        match self {
+            ConversionFormat::Json => "json",
+            ConversionFormat::Yaml => "yaml",
+            ConversionFormat::Toml => "toml",
+            ConversionFormat::Xml | ConversionFormat::Plist => "xml",
+            ConversionFormat::Csv => "csv",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConversionFormat::Json => "json",
+            ConversionFormat::Yaml => "yaml",
+            ConversionFormat::Toml => "toml",
+            ConversionFormat::Xml => "xml",
+            ConversionFormat::Plist => "plist",
+            ConversionFormat::Csv => "csv",
+        }
+    }
+}
+
+/// 转换结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionResult {
+    pub text: String,
+    pub language_id: String,
+    pub extension: String,
+    /// 转换中丢失或改变的信息
+    pub warnings: Vec<String>,
+}
+
+/// 汇总有损转换的提示，同类问题合并并列出前几个位置
+#[derive(Default)]
+struct LossReport {
+    entries: Vec<(String, Vec<String>)>,
+}
+
+impl LossReport {
+    const MAX_PATHS: usize = 5;
+
+    fn note(&mut self, message: &str) {
+        if !self.entries.iter().any(|(m, _)| m == message) {
+            self.entries.push((message.to_string(), Vec::new()));
+        }
+    }
+
+    fn at(&mut self, message: &str, path: &str) {
+        let path = if path.is_empty() { "/".to_string() } else { path.to_string() };
+        match self.entries.iter_mut().find(|(m, _)| m == message) {
+            Some((_, paths)) => paths.push(path),
+            None => self.entries.push((message.to_string(), vec![path])),
+        }
+    }
+
+    fn into_warnings(self) -> Vec<String> {
+        self.entries
+            .into_iter()
+            .map(|(message, paths)| {
+                if paths.is_empty() {
+                    return message;
+                }
+                let mut list = paths.iter().take(Self::MAX_PATHS).cloned().collect::<Vec<_>>().join(", ");
+                if paths.len() > Self::MAX_PATHS {
+                    list.push_str(&format!(" and {} more", paths.len() - Self::MAX_PATHS));
+                }
+                format!("{} ({})", message, list)
+            })
+            .collect()
+    }
+}
+
+fn child_path(path: &str, segment: impl std::fmt::Display) -> String {
+    format!("{}/{}", path, segment)
+}
+
+/// 转换数据格式
+pub fn convert_data(
+    content: &str,
+    from: ConversionFormat,
+    to: ConversionFormat,
+    options: &DataFormatOptions,
+) -> Result<ConversionResult> {
+    let mut report = LossReport::default();
+    let value = match from {
+        ConversionFormat::Json => parse_json(content, &mut report)?,
+        ConversionFormat::Yaml => parse_yaml(content, &mut report)?,
+        ConversionFormat::Toml => parse_toml(content, &mut report)?,
+        ConversionFormat::Xml => parse_xml(content, &mut report)?,
+        ConversionFormat::Plist => parse_plist(content, &mut report)?,
+        ConversionFormat::Csv => parse_csv(content)?,
+    };
+
+    let text = match to {
+        ConversionFormat::Json => {
+            // 严格 JSON 不允许尾随逗号
+            let options = DataFormatOptions { trailing_comma: false, ..options.clone() };
+            data_format::format_data(&serde_json::to_string(&value)?, DataFormat::Json, &options)?.text
+        }
+        ConversionFormat::Yaml => {
+            data_format::format_data(&serde_yaml::to_string(&value)?, DataFormat::Yaml, options)?.text
+        }
+        ConversionFormat::Toml => emit_toml(value, options, &mut report)?,
+        ConversionFormat::Xml => emit_xml(&value, options, &mut report)?,
+        ConversionFormat::Plist => emit_plist(&value, options, &mut report)?,
+        ConversionFormat::Csv => emit_csv(&value, &mut report)?,
+    };
+
+    Ok(ConversionResult {
+        text,
+        language_id: to.language_id().to_string(),
+        extension: to.extension().to_string(),
+        warnings: report.into_warnings(),
+    })
+}
+
+// ============================================================================
+// 解析为 JSON 值
+// ============================================================================
+
+fn parse_json(content: &str, report: &mut LossReport) -> Result<Value> {
+    match serde_json::from_str(content) {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            // JSONC：去掉注释和尾随逗号后重试
+            let value = serde_json::from_str(&validation::strip_jsonc(content))
+                .map_err(|_| anyhow!("Invalid JSON: {}", e))?;
+            report.note("Comments are not preserved");
+            Ok(value)
+        }
+    }
+}
+
+fn parse_yaml(content: &str, report: &mut LossReport) -> Result<Value> {
+    if data_format::has_hash_comments(content) {
+        report.note("Comments are not preserved");
+    }
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        documents.push(serde_yaml::Value::deserialize(document).map_err(|e| anyhow!("Invalid YAML: {}", e))?);
+    }
+    Ok(match documents.len() {
+        0 => Value::Null,
+        1 => yaml_to_json(documents.remove(0), "", report),
+        _ => {
+            report.note("Multiple YAML documents are combined into an array");
+            Value::Array(
+                documents
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, document)| yaml_to_json(document, &child_path("", i), report))
+                    .collect(),
+            )
+        }
+    })
+}
+
+fn yaml_to_json(value: serde_yaml::Value, path: &str, report: &mut LossReport) -> Value {
+    use serde_yaml::Value as Yaml;
+
+    match value {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::from(i)
+            } else if let Some(u) = n.as_u64() {
+                Value::from(u)
+            } else {
+                let f = n.as_f64().unwrap_or(f64::NAN);
+                Number::from_f64(f).map(Value::Number).unwrap_or_else(|| {
+                    report.at("NaN and infinite numbers are converted to null", path);
+                    Value::Null
+                })
+            }
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => Value::Array(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| yaml_to_json(item, &child_path(path, i), report))
+                .collect(),
+        ),
+        Yaml::Mapping(mapping) => {
+            let mut object = Map::new();
+            for (key, value) in mapping {
+                let key = match key {
+                    Yaml::String(s) => s,
+                    other => {
+                        report.at("Non-string keys are converted to strings", path);
+                        serde_yaml::to_string(&other).unwrap_or_default().trim_end().to_string()
+                    }
+                };
+                let value = yaml_to_json(value, &child_path(path, &key), report);
+                object.insert(key, value);
+            }
+            Value::Object(object)
+        }
+        Yaml::Tagged(tagged) => {
+            report.at("YAML tags are dropped", path);
+            yaml_to_json(tagged.value, path, report)
+        }
+    }
+}
+
+fn parse_toml(content: &str, report: &mut LossReport) -> Result<Value> {
+    if data_format::has_hash_comments(content) {
+        report.note("Comments are not preserved");
+    }
+    let table: toml::Table = content.parse().map_err(|e| anyhow!("Invalid TOML: {}", e))?;
+    Ok(toml_to_json(toml::Value::Table(table), "", report))
+}
+
+fn toml_to_json(value: toml::Value, path: &str, report: &mut LossReport) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Number::from_f64(f).map(Value::Number).unwrap_or_else(|| {
+            report.at("NaN and infinite numbers are converted to null", path);
+            Value::Null
+        }),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => {
+            report.at("Dates and times are converted to strings", path);
+            Value::String(dt.to_string())
+        }
+        toml::Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| toml_to_json(item, &child_path(path, i), report))
+                .collect(),
+        ),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = toml_to_json(value, &child_path(path, &key), report);
+                    (key, value)
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn unescape_xml(text: &str) -> String {
+    quick_xml::escape::unescape(text).map_or_else(|_| text.to_string(), |t| t.into_owned())
+}
+
+/// 将 XML 转为对象：属性以 `@` 开头，文本为 `#text`，同名子元素合并为数组，
+/// 只有文本的元素直接转为字符串
+fn parse_xml(content: &str, report: &mut LossReport) -> Result<Value> {
+    let nodes = data_format::parse_xml(content)?;
+    let mut object = Map::new();
+    for node in &nodes {
+        match node {
+            XmlNode::Element { name, .. } => {
+                let value = xml_element_to_json(node, &child_path("", name), report);
+                insert_repeated(&mut object, name.clone(), value);
+            }
+            XmlNode::Comment(_) => report.note("Comments are not preserved"),
+            XmlNode::ProcessingInstruction(_) | XmlNode::DocType(_) => {
+                report.note("Processing instructions and DOCTYPE are dropped")
+            }
+            _ => {}
+        }
+    }
+    Ok(Value::Object(object))
+}
+
+fn insert_repeated(object: &mut Map<String, Value>, key: String, value: Value) {
+    match object.get_mut(&key) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            object.insert(key, value);
+        }
+    }
+}
+
+fn xml_element_to_json(node: &XmlNode, path: &str, report: &mut LossReport) -> Value {
+    let XmlNode::Element { attributes, children, .. } = node else {
+        return Value::Null;
+    };
+
+    let mut object = Map::new();
+    for (name, value) in attributes {
+        object.insert(format!("@{}", name), Value::String(unescape_xml(value)));
+    }
+
+    let mut text = String::new();
+    let mut has_elements = false;
+    for child in children {
+        match child {
+            XmlNode::Element { name, .. } => {
+                has_elements = true;
+                let value = xml_element_to_json(child, &child_path(path, name), report);
+                insert_repeated(&mut object, name.clone(), value);
+            }
+            XmlNode::Text(t) => text.push_str(&unescape_xml(t)),
+            XmlNode::CData(t) => text.push_str(t),
+            XmlNode::Comment(_) => report.note("Comments are not preserved"),
+            XmlNode::ProcessingInstruction(_) | XmlNode::DocType(_) => {
+                report.note("Processing instructions and DOCTYPE are dropped")
+            }
+            XmlNode::Declaration(_) => {}
+        }
+    }
+
+    let text = text.trim();
+    if object.is_empty() {
+        return Value::String(text.to_string());
+    }
+    if !text.is_empty() {
+        if has_elements {
+            report.at("Mixed text and elements lose their order", path);
+        }
+        object.insert("#text".to_string(), Value::String(text.to_string()));
+    }
+    Value::Object(object)
+}
+
+fn xml_text(children: &[XmlNode]) -> String {
+    children
+        .iter()
+        .map(|child| match child {
+            XmlNode::Text(t) => unescape_xml(t),
+            XmlNode::CData(t) => t.clone(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+fn parse_plist(content: &str, report: &mut LossReport) -> Result<Value> {
+    let nodes = data_format::parse_xml(content)?;
+    let root = nodes
+        .iter()
+        .find_map(|node| match node {
+            XmlNode::Element { name, children, .. } if name == "plist" => Some(children),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("Invalid plist: missing <plist> element"))?;
+    let mut elements = root.iter().filter(|n| matches!(n, XmlNode::Element { .. }));
+    match elements.next() {
+        Some(node) => plist_to_json(node, "", report),
+        None => Ok(Value::Null),
+    }
+}
+
+fn plist_to_json(node: &XmlNode, path: &str, report: &mut LossReport) -> Result<Value> {
+    let XmlNode::Element { name, children, .. } = node else {
+        return Ok(Value::Null);
+    };
+    let elements = || children.iter().filter(|n| matches!(n, XmlNode::Element { .. }));
+    Ok(match name.as_str() {
+        "dict" => {
+            let mut object = Map::new();
+            let mut key = None;
+            for child in elements() {
+                match (child, key.take()) {
+                    (XmlNode::Element { name, children, .. }, None) if name == "key" => {
+                        key = Some(xml_text(children));
+                    }
+                    (value, Some(key)) => {
+                        let value = plist_to_json(value, &child_path(path, &key), report)?;
+                        object.insert(key, value);
+                    }
+                    _ => anyhow::bail!("Invalid plist: expected <key> in <dict> at {}", path),
+                }
+            }
+            Value::Object(object)
+        }
+        "array" => Value::Array(
+            elements()
+                .enumerate()
+                .map(|(i, child)| plist_to_json(child, &child_path(path, i), report))
+                .collect::<Result<_>>()?,
+        ),
+        "string" => Value::String(xml_text(children)),
+        "integer" => {
+            let text = xml_text(children);
+            let text = text.trim();
+            text.parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| text.parse::<u64>().map(Value::from))
+                .map_err(|_| anyhow!("Invalid plist integer '{}' at {}", text, path))?
+        }
+        "real" => {
+            let text = xml_text(children);
+            let f: f64 = text.trim().parse().map_err(|_| anyhow!("Invalid plist real '{}' at {}", text, path))?;
+            Number::from_f64(f).map(Value::Number).unwrap_or_else(|| {
+                report.at("NaN and infinite numbers are converted to null", path);
+                Value::Null
+            })
+        }
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        "date" => {
+            report.at("Dates and times are converted to strings", path);
+            Value::String(xml_text(children).trim().to_string())
+        }
+        "data" => {
+            report.at("Binary data is kept as a base64 string", path);
+            Value::String(xml_text(children).split_whitespace().collect())
+        }
+        other => anyhow::bail!("Invalid plist: unknown element <{}> at {}", other, path),
+    })
+}
+
+/// 按分隔符推断：首行含制表符而不含逗号时视为 TSV
+fn csv_delimiter(content: &str) -> u8 {
+    let first = content.lines().next().unwrap_or_default();
+    if first.contains('\t') && !first.contains(',') {
+        b'\t'
+    } else {
+        b','
+    }
+}
+
+/// 推断单元格类型，只转换写法完全一致的数字（保留 "007" 这类字符串）
+fn infer_cell(cell: &str) -> Value {
+    match cell {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    if let Ok(i) = cell.parse::<i64>() {
+        if i.to_string() == cell {
+            return Value::from(i);
+        }
+    }
+    if let Ok(f) = cell.parse::<f64>() {
+        if f.is_finite() && f.to_string() == cell {
+            if let Some(n) = Number::from_f64(f) {
+                return Value::Number(n);
+            }
+        }
+    }
+    Value::String(cell.to_string())
+}
+
+/// 首行为表头，每行转为一个对象
+fn parse_csv(content: &str) -> Result<Value> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(csv_delimiter(content))
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers = reader.headers().map_err(|e| anyhow!("Invalid CSV: {}", e))?.clone();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| anyhow!("Invalid CSV: {}", e))?;
+        let mut row = Map::new();
+        for (i, cell) in record.iter().enumerate() {
+            let key = headers.get(i).map_or_else(|| format!("column{}", i + 1), str::to_string);
+            row.insert(key, infer_cell(cell));
+        }
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
+
+// ============================================================================
+// 输出目标格式
+// ============================================================================
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "table",
+    }
+}
+
+/// TOML 没有 null，移除 null 值并检查混合类型数组
+fn sanitize_toml(value: Value, path: &str, report: &mut LossReport) -> Option<Value> {
+    match value {
+        Value::Null => {
+            report.at("TOML has no null; null values are omitted", path);
+            None
+        }
+        Value::Array(items) => {
+            let items: Vec<Value> = items
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, item)| sanitize_toml(item, &child_path(path, i), report))
+                .collect();
+            if items.windows(2).any(|pair| json_type(&pair[0]) != json_type(&pair[1])) {
+                report.at("Mixed-type arrays require TOML 1.0 and are rejected by older parsers", path);
+            }
+            Some(Value::Array(items))
+        }
+        Value::Object(object) => Some(Value::Object(
+            object
+                .into_iter()
+                .filter_map(|(key, value)| {
+                    let value = sanitize_toml(value, &child_path(path, &key), report)?;
+                    Some((key, value))
+                })
+                .collect(),
+        )),
+        other => Some(other),
+    }
+}
+
+fn emit_toml(value: Value, options: &DataFormatOptions, report: &mut LossReport) -> Result<String> {
+    if !value.is_object() {
+        anyhow::bail!("TOML requires a table at the top level; wrap the value in an object first");
+    }
+    let value = sanitize_toml(value, "", report).unwrap_or_default();
+    let text = toml::to_string(&value).map_err(|e| anyhow!("Cannot convert to TOML: {}", e))?;
+    Ok(data_format::format_data(&text, DataFormat::Toml, options)?.text)
+}
+
+fn is_xml_name_char(c: char, first: bool) -> bool {
+    c.is_alphabetic() || c == '_' || (!first && (c.is_ascii_digit() || matches!(c, '-' | '.' | ':')))
+}
+
+/// 将键名转换为合法的 XML 元素名
+fn xml_name(key: &str, path: &str, report: &mut LossReport) -> String {
+    let valid = key.chars().enumerate().all(|(i, c)| is_xml_name_char(c, i == 0));
+    if valid && !key.is_empty() {
+        return key.to_string();
+    }
+    report.at("Keys that are not valid XML names are renamed", path);
+    let name: String = key.chars().map(|c| if is_xml_name_char(c, false) { c } else { '_' }).collect();
+    if name.chars().next().is_some_and(|c| is_xml_name_char(c, true)) {
+        name
+    } else {
+        format!("_{}", name)
+    }
+}
+
+fn xml_scalar(value: &Value, path: &str, report: &mut LossReport) -> String {
+    match value {
+        Value::String(s) => quick_xml::escape::escape(s.as_str()).into_owned(),
+        Value::Null => {
+            report.at("null values become empty elements", path);
+            String::new()
+        }
+        other => {
+            report.note("XML has no value types; numbers and booleans become text");
+            other.to_string()
+        }
+    }
+}
+
+fn write_xml_element(name: &str, value: &Value, path: &str, out: &mut String, report: &mut LossReport) {
+    let name = xml_name(name, path, report);
+    match value {
+        Value::Object(object) => {
+            out.push('<');
+            out.push_str(&name);
+            for (key, value) in object {
+                if let Some(attribute) = key.strip_prefix('@') {
+                    let attribute = xml_name(attribute, &child_path(path, key), report);
+                    let value = match value {
+                        Value::String(s) => quick_xml::escape::escape(s.as_str()).into_owned(),
+                        other => xml_scalar(other, &child_path(path, key), report),
+                    };
+                    out.push_str(&format!(" {}=\"{}\"", attribute, value.replace('"', "&quot;")));
+                }
+            }
+            out.push('>');
+            for (key, value) in object {
+                if key.starts_with('@') {
+                    continue;
+                }
+                let child = child_path(path, key);
+                if key == "#text" {
+                    out.push_str(&xml_scalar(value, &child, report));
+                    continue;
+                }
+                match value {
+                    // 数组展开为同名的重复元素
+                    Value::Array(items) => {
+                        for (i, item) in items.iter().enumerate() {
+                            write_xml_element(key, item, &child_path(&child, i), out, report);
+                        }
+                    }
+                    _ => write_xml_element(key, value, &child, out, report),
+                }
+            }
+        }
+        Value::Array(items) => {
+            out.push('<');
+            out.push_str(&name);
+            out.push('>');
+            for (i, item) in items.iter().enumerate() {
+                write_xml_element("item", item, &child_path(path, i), out, report);
+            }
+        }
+        scalar => {
+            out.push('<');
+            out.push_str(&name);
+            out.push('>');
+            out.push_str(&xml_scalar(scalar, path, report));
+        }
+    }
+    out.push_str("</");
+    out.push_str(&name);
+    out.push('>');
+}
+
+fn emit_xml(value: &Value, options: &DataFormatOptions, report: &mut LossReport) -> Result<String> {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    // 只有一个键（且不是数组）的对象作为根元素
+    let single = value.as_object().filter(|object| object.len() == 1).and_then(|object| object.iter().next());
+    match single {
+        Some((name, value)) if !value.is_array() && !name.starts_with(['@', '#']) => {
+            write_xml_element(name, value, &child_path("", name), &mut out, report);
+        }
+        _ => {
+            report.note("Values without a single top-level key are wrapped in a <root> element");
+            write_xml_element("root", value, "", &mut out, report);
+        }
+    }
+    Ok(data_format::format_data(&out, DataFormat::Xml, options)?.text)
+}
+
+fn write_plist_value(value: &Value, path: &str, out: &mut String, report: &mut LossReport) {
+    match value {
+        Value::Null => {}
+        Value::Bool(true) => out.push_str("<true/>"),
+        Value::Bool(false) => out.push_str("<false/>"),
+        Value::Number(n) if n.is_f64() => out.push_str(&format!("<real>{}</real>", n)),
+        Value::Number(n) => out.push_str(&format!("<integer>{}</integer>", n)),
+        Value::String(s) => {
+            out.push_str(&format!("<string>{}</string>", quick_xml::escape::escape(s.as_str())))
+        }
+        Value::Array(items) => {
+            out.push_str("<array>");
+            for (i, item) in items.iter().enumerate() {
+                let item_path = child_path(path, i);
+                if item.is_null() {
+                    report.at("plist has no null; null values are omitted", &item_path);
+                }
+                write_plist_value(item, &item_path, out, report);
+            }
+            out.push_str("</array>");
+        }
+        Value::Object(object) => {
+            out.push_str("<dict>");
+            for (key, value) in object {
+                let value_path = child_path(path, key);
+                if value.is_null() {
+                    report.at("plist has no null; null values are omitted", &value_path);
+                    continue;
+                }
+                out.push_str(&format!("<key>{}</key>", quick_xml::escape::escape(key.as_str())));
+                write_plist_value(value, &value_path, out, report);
+            }
+            out.push_str("</dict>");
+        }
+    }
+}
+
+fn emit_plist(value: &Value, options: &DataFormatOptions, report: &mut LossReport) -> Result<String> {
+    let mut out = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+        "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">",
+        "<plist version=\"1.0\">",
+    ));
+    if value.is_null() {
+        report.at("plist has no null; null values are omitted", "");
+    }
+    write_plist_value(value, "", &mut out, report);
+    out.push_str("</plist>");
+    Ok(data_format::format_data(&out, DataFormat::Xml, options)?.text)
+}
+
+fn csv_cell(value: &Value, path: &str, report: &mut LossReport) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(_) | Value::Object(_) => {
+            report.at("Nested values are written as JSON strings", path);
+            value.to_string()
+        }
+        other => other.to_string(),
+    }
+}
+
+/// 对象数组以键名为表头（按首次出现的顺序合并），数组的数组按行输出
+fn emit_csv(value: &Value, report: &mut LossReport) -> Result<String> {
+    let Value::Array(rows) = value else {
+        anyhow::bail!("CSV requires an array of records at the top level");
+    };
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(Vec::new());
+
+    if rows.iter().all(Value::is_object) {
+        let mut headers: Vec<&String> = Vec::new();
+        for row in rows.iter().filter_map(Value::as_object) {
+            for key in row.keys() {
+                if !headers.contains(&key) {
+                    headers.push(key);
+                }
+            }
+        }
+        writer.write_record(&headers)?;
+        for (i, row) in rows.iter().filter_map(Value::as_object).enumerate() {
+            let path = child_path("", i);
+            let record: Vec<String> = headers
+                .iter()
+                .map(|key| row.get(*key).map_or_else(String::new, |v| csv_cell(v, &child_path(&path, key), report)))
+                .collect();
+            writer.write_record(&record)?;
+        }
+    } else if rows.iter().all(Value::is_array) {
+        for (i, row) in rows.iter().filter_map(Value::as_array).enumerate() {
+            let path = child_path("", i);
+            let record: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(j, v)| csv_cell(v, &child_path(&path, j), report))
+                .collect();
+            writer.write_record(&record)?;
+        }
+    } else if rows.iter().all(|v| !v.is_object() && !v.is_array()) {
+        writer.write_record(["value"])?;
+        for (i, row) in rows.iter().enumerate() {
+            writer.write_record([csv_cell(row, &child_path("", i), report)])?;
+        }
+    } else {
+        anyhow::bail!("CSV requires an array whose items are all objects, all arrays or all scalars");
+    }
+
+    let bytes = writer.into_inner().map_err(|e| anyhow!("Cannot write CSV: {}", e))?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(content: &str, from: ConversionFormat, to: ConversionFormat) -> ConversionResult {
+        convert_data(content, from, to, &DataFormatOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trips() {
+        use ConversionFormat::*;
+
+        let json = "{\n  \"name\": \"demo\",\n  \"tags\": [\n    \"a\",\n    \"b\"\n  ],\n  \"server\": {\n    \"port\": 8080\n  }\n}\n";
+        for format in [Yaml, Toml, Plist] {
+            let converted = convert(json, Json, format);
+            assert!(converted.warnings.is_empty(), "{:?}: {:?}", format, converted.warnings);
+            assert_eq!(convert(&converted.text, format, Json).text, json, "{:?}", format);
+        }
+
+        let yaml = convert(json, Json, Yaml).text;
+        assert_eq!(yaml, "name: demo\ntags:\n  - a\n  - b\nserver:\n  port: 8080\n");
+
+        let xml = convert("<config id=\"1\"><item>a</item><item>b</item><name>x &amp; y</name></config>", Xml, Json);
+        let value: Value = serde_json::from_str(&xml.text).unwrap();
+        assert_eq!(value["config"]["@id"], "1");
+        assert_eq!(value["config"]["item"], serde_json::json!(["a", "b"]));
+        assert_eq!(value["config"]["name"], "x & y");
+        let back = convert(&xml.text, Json, Xml).text;
+        assert!(back.contains("<config id=\"1\">"));
+        assert!(back.contains("<name>x &amp; y</name>"));
+
+        let csv = "id,name,zip\n1,Ann,007\n2,\"Lee, B\",10001\n";
+        let value: Value = serde_json::from_str(&convert(csv, Csv, Json).text).unwrap();
+        assert_eq!(value[1]["id"], 2);
+        assert_eq!(value[0]["zip"], "007");
+        assert_eq!(convert(&convert(csv, Csv, Json).text, Json, Csv).text, csv);
+    }
+
+    #[test]
+    fn test_lossy_reports() {
+        use ConversionFormat::*;
+
+        let result = convert("{\"a\": null, \"b\": [1, \"x\", null]}", Json, Toml);
+        assert_eq!(result.text, "b = [1, \"x\"]\n");
+        assert_eq!(
+            result.warnings,
+            vec![
+                "TOML has no null; null values are omitted (/a, /b/2)".to_string(),
+                "Mixed-type arrays require TOML 1.0 and are rejected by older parsers (/b)".to_string(),
+            ]
+        );
+
+        let result = convert("# comment\nwhen = 1979-05-27T07:32:00Z\n", Toml, Json);
+        assert_eq!(result.warnings.len(), 2);
+
+        let result = convert("[{\"a\": {\"b\": 1}}]", Json, Csv);
+        assert_eq!(result.text, "a\n\"{\"\"b\"\":1}\"\n");
+        assert_eq!(result.warnings, vec!["Nested values are written as JSON strings (/0/a)".to_string()]);
+
+        assert!(convert_data("[1, 2]", Json, Toml, &DataFormatOptions::default()).is_err());
+        assert!(convert_data("{\"a\": 1}", Json, Csv, &DataFormatOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,1103 @@
+// Data Format Module
+// 内置的 JSON、TOML、YAML、XML 格式化：美化、压缩和规范化，无需安装外部工具
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::validation;
+
+/// 支持的数据格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    Json,
+    /// 允许注释和尾随逗号的 JSON，格式化时保留注释
+    Jsonc,
+    Toml,
+    Yaml,
+    Xml,
+}
+
+impl DataFormat {
+    /// 根据语言 ID（detect_language 的结果）确定数据格式
+    pub fn from_language(language: &str) -> Option<Self> {
+        match language {
+            "json" => Some(DataFormat::Json),
+            "jsonc" => Some(DataFormat::Jsonc),
+            "toml" => Some(DataFormat::Toml),
+            "yaml" => Some(DataFormat::Yaml),
+            "xml" => Some(DataFormat::Xml),
+            _ => None,
+        }
+    }
+
+    /// 根据语言 ID 和文件路径确定数据格式，约定使用 JSONC 的 .json 文件（tsconfig.json 等）按 JSONC 处理
+    pub fn detect(language: &str, path: Option<&str>) -> Option<Self> {
+        match Self::from_language(language) {
+            Some(DataFormat::Json) if path.is_some_and(validation::is_jsonc_file) => Some(DataFormat::Jsonc),
+            format => format,
+        }
+    }
+}
+
+/// 格式化方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FormatStyle {
+    /// 美化（缩进、换行）
+    #[default]
+    Pretty,
+    /// 压缩（移除多余空白）
+    Minify,
+    /// 规范化（键排序、统一写法），用于比较和签名
+    Canonical,
+}
+
+/// 格式化选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DataFormatOptions {
+    pub style: FormatStyle,
+    /// 缩进宽度（空格数）
+    pub indent: usize,
+    /// 按键名排序（XML 中为属性排序）
+    pub sort_keys: bool,
+    /// 多行数组/对象末尾添加逗号（仅 JSONC 和 TOML，严格 JSON 不允许）
+    pub trailing_comma: bool,
+}
+
+impl Default for DataFormatOptions {
+    fn default() -> Self {
+        Self { style: FormatStyle::Pretty, indent: 2, sort_keys: false, trailing_comma: false }
+    }
+}
+
+impl DataFormatOptions {
+    fn sort(&self) -> bool {
+        self.sort_keys || self.style == FormatStyle::Canonical
+    }
+
+    fn indent_unit(&self) -> String {
+        " ".repeat(self.indent.clamp(1, 16))
+    }
+}
+
+/// 格式化结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataFormatResult {
+    pub text: String,
+    /// 格式化过程中丢失的信息（如注释）
+    pub warnings: Vec<String>,
+}
+
+/// 格式化数据文件内容
+pub fn format_data(
+    content: &str,
+    format: DataFormat,
+    options: &DataFormatOptions,
+) -> Result<DataFormatResult> {
+    let mut warnings = Vec::new();
+    let text = match format {
+        DataFormat::Json => format_json(content, options, false, &mut warnings)?,
+        DataFormat::Jsonc => format_json(content, options, true, &mut warnings)?,
+        DataFormat::Toml => {
+            if has_hash_comments(content) {
+                warnings.push("Comments are not preserved when formatting TOML".to_string());
+            }
+            format_toml(content, options)?
+        }
+        DataFormat::Yaml => {
+            if has_hash_comments(content) {
+                warnings.push("Comments are not preserved when formatting YAML".to_string());
+            }
+            format_yaml(content, options)?
+        }
+        DataFormat::Xml => format_xml(content, options)?,
+    };
+    Ok(DataFormatResult { text, warnings })
+}
+
+/// 粗略检测 `#` 注释（忽略引号内的内容），用于提示注释会丢失
+pub(crate) fn has_hash_comments(content: &str) -> bool {
+    content.lines().any(|line| {
+        let mut quote: Option<char> = None;
+        let mut escaped = false;
+        let mut prev = ' ';
+        for c in line.chars() {
+            match quote {
+                Some(q) => {
+                    if escaped {
+                        escaped = false;
+                    } else if c == '\\' && q == '"' {
+                        escaped = true;
+                    } else if c == q {
+                        quote = None;
+                    }
+                }
+                None => match c {
+                    '"' | '\'' => quote = Some(c),
+                    '#' if prev.is_whitespace() => return true,
+                    _ => {}
+                },
+            }
+            prev = c;
+        }
+        false
+    })
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+/// 解析后的 JSON 节点，标量和键保留原文，数字不经过 f64 转换，不丢失精度
+enum JsonNode<'a> {
+    Scalar(&'a str),
+    /// 条目和右括号前的注释（JSONC）
+    Array(Vec<JsonEntry<'a>>, Vec<&'a str>),
+    Object(Vec<JsonEntry<'a>>, Vec<&'a str>),
+}
+
+/// 数组元素或对象成员
+struct JsonEntry<'a> {
+    /// 条目前的注释
+    comments: Vec<&'a str>,
+    key: Option<&'a str>,
+    value: JsonNode<'a>,
+    /// 与条目在同一行的行尾注释
+    trailing: Option<&'a str>,
+}
+
+/// 在已校验过的 JSON/JSONC 文本上构建节点树，注释附加到相邻的条目上
+struct JsonParser<'a> {
+    text: &'a str,
+    pos: usize,
+    /// 遇到的注释数量
+    comments: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> u8 {
+        self.text.as_bytes().get(self.pos).copied().unwrap_or(0)
+    }
+
+    fn at_comment(&self) -> bool {
+        let rest = &self.text[self.pos..];
+        rest.starts_with("//") || rest.starts_with("/*")
+    }
+
+    /// 读取当前位置的注释，行注释不含换行符
+    fn comment(&mut self) -> &'a str {
+        let rest = &self.text[self.pos..];
+        let len = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else {
+            rest[2..].find("*/").map_or(rest.len(), |p| p + 4)
+        };
+        self.pos += len;
+        self.comments += 1;
+        rest[..len].trim_end()
+    }
+
+    /// 跳过空白并收集其间的注释
+    fn trivia(&mut self) -> Vec<&'a str> {
+        let mut comments = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if !self.at_comment() {
+                return comments;
+            }
+            comments.push(self.comment());
+        }
+    }
+
+    /// 读取值之后同一行的注释（可以在逗号之后）
+    fn trailing_comment(&mut self) -> Option<&'a str> {
+        let bytes = self.text.as_bytes();
+        let skip_blank = |mut i: usize| {
+            while matches!(bytes.get(i), Some(b' ' | b'\t')) {
+                i += 1;
+            }
+            i
+        };
+        let mut pos = skip_blank(self.pos);
+        if bytes.get(pos) == Some(&b',') {
+            pos = skip_blank(pos + 1);
+        }
+        let saved = self.pos;
+        self.pos = pos;
+        if self.at_comment() {
+            Some(self.comment())
+        } else {
+            self.pos = saved;
+            None
+        }
+    }
+
+    fn string(&mut self) -> &'a str {
+        let bytes = self.text.as_bytes();
+        let start = self.pos;
+        self.pos += 1;
+        while self.pos < bytes.len() {
+            match bytes[self.pos] {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => self.pos += 1,
+            }
+        }
+        &self.text[start..self.pos]
+    }
+
+    /// 读取数组或对象的条目，返回条目和右括号前的注释
+    fn entries(&mut self, close: u8, object: bool) -> (Vec<JsonEntry<'a>>, Vec<&'a str>) {
+        self.pos += 1;
+        let mut entries = Vec::new();
+        let mut comments = Vec::new();
+        loop {
+            comments.extend(self.trivia());
+            match self.peek() {
+                0 => break,
+                b',' => self.pos += 1,
+                b if b == close => break,
+                _ => {
+                    let key = if object {
+                        let key = self.string();
+                        comments.extend(self.trivia());
+                        self.pos += 1; // ':'
+                        comments.extend(self.trivia());
+                        Some(key)
+                    } else {
+                        None
+                    };
+                    let value = self.value();
+                    let trailing = self.trailing_comment();
+                    entries.push(JsonEntry { comments: std::mem::take(&mut comments), key, value, trailing });
+                }
+            }
+        }
+        self.pos += 1;
+        (entries, comments)
+    }
+
+    /// 读取当前位置的值（调用前已跳过空白和注释）
+    fn value(&mut self) -> JsonNode<'a> {
+        match self.peek() {
+            b'{' => {
+                let (entries, comments) = self.entries(b'}', true);
+                JsonNode::Object(entries, comments)
+            }
+            b'[' => {
+                let (entries, comments) = self.entries(b']', false);
+                JsonNode::Array(entries, comments)
+            }
+            b'"' => JsonNode::Scalar(self.string()),
+            _ => {
+                let rest = &self.text[self.pos..];
+                let len = rest
+                    .find(|c: char| matches!(c, ',' | ']' | '}' | '/') || c.is_whitespace())
+                    .unwrap_or(rest.len());
+                self.pos += len;
+                JsonNode::Scalar(&rest[..len])
+            }
+        }
+    }
+}
+
+/// 规范化字符串的转义写法，非字符串原样返回
+fn canonical_json_string(raw: &str) -> String {
+    match serde_json::from_str::<String>(raw) {
+        Ok(decoded) => serde_json::Value::String(decoded).to_string(),
+        Err(_) => raw.to_string(),
+    }
+}
+
+/// 格式化 JSON；`jsonc` 为 true 时允许注释和尾随逗号，美化时保留注释
+fn format_json(
+    content: &str,
+    options: &DataFormatOptions,
+    jsonc: bool,
+    warnings: &mut Vec<String>,
+) -> Result<String> {
+    if !jsonc && options.trailing_comma {
+        return Err(anyhow!("Trailing commas are not allowed in JSON"));
+    }
+    // 先用 serde_json 校验以获得准确的错误位置（JSONC 的注释和尾随逗号替换为等长空白），
+    // 再按原文构建节点树
+    let checked = if jsonc {
+        serde_json::from_str::<serde::de::IgnoredAny>(&validation::strip_jsonc(content))
+    } else {
+        serde_json::from_str::<serde::de::IgnoredAny>(content)
+    };
+    checked.map_err(|e| anyhow!("Invalid JSON: {}", e))?;
+
+    let mut parser = JsonParser { text: content, pos: 0, comments: 0 };
+    let leading = parser.trivia();
+    let node = parser.value();
+    let trailing = parser.trivia();
+
+    let mut out = String::new();
+    let pretty = options.style == FormatStyle::Pretty;
+    if pretty {
+        for comment in &leading {
+            out.push_str(comment);
+            out.push('\n');
+        }
+    } else if parser.comments > 0 {
+        warnings.push("Comments are not preserved when minifying JSONC".to_string());
+    }
+    write_json(&node, options, pretty, 0, &mut out);
+    if pretty {
+        for comment in &trailing {
+            out.push('\n');
+            out.push_str(comment);
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn write_json(node: &JsonNode, options: &DataFormatOptions, pretty: bool, level: usize, out: &mut String) {
+    let canonical = options.style == FormatStyle::Canonical;
+    let (open, close, entries, comments): (char, char, Vec<&JsonEntry>, &[&str]) = match node {
+        JsonNode::Object(entries, comments) => {
+            let mut entries: Vec<_> = entries.iter().collect();
+            if options.sort() {
+                entries.sort_by_cached_key(|e| {
+                    serde_json::from_str::<String>(e.key.unwrap_or_default()).unwrap_or_default()
+                });
+            }
+            ('{', '}', entries, comments)
+        }
+        JsonNode::Array(entries, comments) => ('[', ']', entries.iter().collect(), comments),
+        JsonNode::Scalar(raw) => {
+            if canonical {
+                out.push_str(&canonical_json_string(raw));
+            } else {
+                out.push_str(raw);
+            }
+            return;
+        }
+    };
+    // 注释只在美化输出中保留
+    let comments = if pretty { comments } else { &[] };
+
+    out.push(open);
+    if entries.is_empty() && comments.is_empty() {
+        out.push(close);
+        return;
+    }
+
+    let indent = options.indent_unit();
+    let count = entries.len();
+    for (i, entry) in entries.into_iter().enumerate() {
+        if pretty {
+            for comment in &entry.comments {
+                out.push('\n');
+                out.push_str(&indent.repeat(level + 1));
+                out.push_str(comment);
+            }
+            out.push('\n');
+            out.push_str(&indent.repeat(level + 1));
+        }
+        if let Some(key) = entry.key {
+            if canonical {
+                out.push_str(&canonical_json_string(key));
+            } else {
+                out.push_str(key);
+            }
+            out.push_str(if pretty { ": " } else { ":" });
+        }
+        write_json(&entry.value, options, pretty, level + 1, out);
+        if i + 1 < count || (pretty && options.trailing_comma) {
+            out.push(',');
+        }
+        if let (true, Some(comment)) = (pretty, entry.trailing) {
+            out.push(' ');
+            out.push_str(comment);
+        }
+    }
+    for comment in comments {
+        out.push('\n');
+        out.push_str(&indent.repeat(level + 1));
+        out.push_str(comment);
+    }
+    if pretty {
+        out.push('\n');
+        out.push_str(&indent.repeat(level));
+    }
+    out.push(close);
+}
+
+// ============================================================================
+// TOML
+// ============================================================================
+
+/// 行内数组超过该宽度时拆分为多行
+const TOML_ARRAY_WIDTH: usize = 80;
+
+fn format_toml(content: &str, options: &DataFormatOptions) -> Result<String> {
+    let table: toml::Table = content.parse().map_err(|e| anyhow!("Invalid TOML: {}", e))?;
+    let mut out = String::new();
+    write_toml_table(&table, &[], options, &mut out);
+    Ok(out.trim_start_matches('\n').to_string())
+}
+
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+fn is_array_of_tables(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::Array(items) => {
+            !items.is_empty() && items.iter().all(|v| matches!(v, toml::Value::Table(_)))
+        }
+        _ => false,
+    }
+}
+
+fn sorted_entries(table: &toml::Table, sort: bool) -> Vec<(&String, &toml::Value)> {
+    let mut entries: Vec<_> = table.iter().collect();
+    if sort {
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+    }
+    entries
+}
+
+/// 先输出普通键值，再输出子表和表数组，保证键值属于正确的表
+fn write_toml_table(
+    table: &toml::Table,
+    path: &[String],
+    options: &DataFormatOptions,
+    out: &mut String,
+) {
+    let entries = sorted_entries(table, options.sort());
+    let pretty = options.style != FormatStyle::Minify;
+
+    for (key, value) in &entries {
+        if matches!(value, toml::Value::Table(_)) || is_array_of_tables(value) {
+            continue;
+        }
+        out.push_str(&toml_key(key));
+        out.push_str(" = ");
+        write_toml_value(value, options, out);
+        out.push('\n');
+    }
+
+    for (key, value) in &entries {
+        let mut child_path = path.to_vec();
+        child_path.push(toml_key(key));
+        let header = child_path.join(".");
+
+        match value {
+            toml::Value::Table(child) => {
+                // 只包含子表的表可以省略表头
+                let has_values = child.is_empty()
+                    || child
+                        .values()
+                        .any(|v| !matches!(v, toml::Value::Table(_)) && !is_array_of_tables(v));
+                if has_values {
+                    if pretty {
+                        out.push('\n');
+                    }
+                    out.push_str(&format!("[{}]\n", header));
+                }
+                write_toml_table(child, &child_path, options, out);
+            }
+            toml::Value::Array(items) if is_array_of_tables(value) => {
+                for item in items {
+                    if pretty {
+                        out.push('\n');
+                    }
+                    out.push_str(&format!("[[{}]]\n", header));
+                    if let toml::Value::Table(child) = item {
+                        write_toml_table(child, &child_path, options, out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_toml_value(value: &toml::Value, options: &DataFormatOptions, out: &mut String) {
+    match value {
+        toml::Value::Array(items) => {
+            let inline: Vec<String> = items
+                .iter()
+                .map(|item| {
+                    let mut s = String::new();
+                    write_toml_value(item, options, &mut s);
+                    s
+                })
+                .collect();
+            let single_line = format!("[{}]", inline.join(", "));
+            if options.style != FormatStyle::Pretty
+                || single_line.len() <= TOML_ARRAY_WIDTH
+                || inline.iter().any(|s| s.contains('\n'))
+            {
+                out.push_str(&single_line);
+                return;
+            }
+
+            let indent = options.indent_unit();
+            out.push_str("[\n");
+            for (i, item) in inline.iter().enumerate() {
+                out.push_str(&indent);
+                out.push_str(item);
+                if i + 1 < inline.len() || options.trailing_comma {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            out.push(']');
+        }
+        toml::Value::Table(table) => {
+            let entries: Vec<String> = sorted_entries(table, options.sort())
+                .into_iter()
+                .map(|(k, v)| {
+                    let mut s = format!("{} = ", toml_key(k));
+                    write_toml_value(v, options, &mut s);
+                    s
+                })
+                .collect();
+            if entries.is_empty() {
+                out.push_str("{}");
+            } else {
+                out.push_str(&format!("{{ {} }}", entries.join(", ")));
+            }
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+// ============================================================================
+// YAML
+// ============================================================================
+
+fn format_yaml(content: &str, options: &DataFormatOptions) -> Result<String> {
+    // 序列条目 "- " 至少占两列
+    let options = &DataFormatOptions { indent: options.indent.max(2), ..options.clone() };
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value = serde_yaml::Value::deserialize(document)
+            .map_err(|e| anyhow!("Invalid YAML: {}", e))?;
+        documents.push(value);
+    }
+
+    let mut out = String::new();
+    for (i, document) in documents.iter().enumerate() {
+        if i > 0 || documents.len() > 1 {
+            out.push_str("---\n");
+        }
+        if options.style == FormatStyle::Minify {
+            write_yaml_flow(document, options, &mut out)?;
+            out.push('\n');
+        } else {
+            write_yaml_block(document, options, 0, &mut out)?;
+        }
+    }
+    Ok(out)
+}
+
+/// 序列化单个标量，多行字符串会得到块标量（`|-` 加缩进的内容行）
+fn yaml_scalar(value: &serde_yaml::Value) -> Result<String> {
+    Ok(serde_yaml::to_string(value)?.trim_end_matches('\n').to_string())
+}
+
+fn yaml_is_collection(value: &serde_yaml::Value) -> bool {
+    match value {
+        serde_yaml::Value::Mapping(m) => !m.is_empty(),
+        serde_yaml::Value::Sequence(s) => !s.is_empty(),
+        _ => false,
+    }
+}
+
+fn yaml_key(key: &serde_yaml::Value) -> Result<String> {
+    if yaml_is_collection(key) {
+        anyhow::bail!("Complex mapping keys are not supported");
+    }
+    let key = yaml_scalar(key)?;
+    if key.contains('\n') {
+        anyhow::bail!("Multi-line mapping keys are not supported");
+    }
+    Ok(key)
+}
+
+/// 以 `prefix` 开头写入标量或嵌套集合，`level` 为嵌套内容的缩进层级
+fn write_yaml_entry_value(
+    value: &serde_yaml::Value,
+    options: &DataFormatOptions,
+    level: usize,
+    out: &mut String,
+) -> Result<()> {
+    let indent = options.indent_unit();
+    match value {
+        serde_yaml::Value::Tagged(tagged) if yaml_is_collection(&tagged.value) => {
+            out.push_str(&format!(" {}\n", tagged.tag));
+            write_yaml_block(&tagged.value, options, level, out)
+        }
+        v if yaml_is_collection(v) => {
+            out.push('\n');
+            write_yaml_block(v, options, level, out)
+        }
+        scalar => {
+            let rendered = yaml_scalar(scalar)?;
+            let mut lines = rendered.lines();
+            out.push(' ');
+            out.push_str(lines.next().unwrap_or_default());
+            out.push('\n');
+            // 块标量的内容行需要按当前层级重新缩进
+            for line in lines {
+                let line = line.strip_prefix("  ").unwrap_or(line);
+                if !line.is_empty() {
+                    out.push_str(&indent.repeat(level));
+                    out.push_str(line);
+                }
+                out.push('\n');
+            }
+            Ok(())
+        }
+    }
+}
+
+fn write_yaml_block(
+    value: &serde_yaml::Value,
+    options: &DataFormatOptions,
+    level: usize,
+    out: &mut String,
+) -> Result<()> {
+    let indent = options.indent_unit();
+    let pad = indent.repeat(level);
+
+    match value {
+        serde_yaml::Value::Mapping(mapping) if !mapping.is_empty() => {
+            let mut entries: Vec<_> = mapping
+                .iter()
+                .map(|(k, v)| Ok((yaml_key(k)?, v)))
+                .collect::<Result<_>>()?;
+            if options.sort() {
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+            for (key, value) in entries {
+                out.push_str(&pad);
+                out.push_str(&key);
+                out.push(':');
+                write_yaml_entry_value(value, options, level + 1, out)?;
+            }
+            Ok(())
+        }
+        serde_yaml::Value::Sequence(items) if !items.is_empty() => {
+            // "-" 后补齐空格，使条目内容与下一层缩进对齐
+            let dash = format!("-{}", " ".repeat(indent.len().max(2) - 1));
+            for item in items {
+                out.push_str(&pad);
+                if yaml_is_collection(item) {
+                    let mut nested = String::new();
+                    write_yaml_block(item, options, level + 1, &mut nested)?;
+                    let child_pad = indent.repeat(level + 1);
+                    let nested = nested.strip_prefix(&child_pad).unwrap_or(&nested);
+                    out.push_str(&dash);
+                    out.push_str(nested);
+                } else {
+                    out.push('-');
+                    write_yaml_entry_value(item, options, level + 1, out)?;
+                }
+            }
+            Ok(())
+        }
+        scalar => {
+            let rendered = yaml_scalar(scalar)?;
+            out.push_str(&pad);
+            out.push_str(&rendered);
+            out.push('\n');
+            Ok(())
+        }
+    }
+}
+
+/// 流式写法（单行），字符串统一使用双引号以避免与流式语法冲突
+fn write_yaml_flow(
+    value: &serde_yaml::Value,
+    options: &DataFormatOptions,
+    out: &mut String,
+) -> Result<()> {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut entries = Vec::new();
+            for (k, v) in mapping {
+                let mut key = String::new();
+                write_yaml_flow(k, options, &mut key)?;
+                let mut value = String::new();
+                write_yaml_flow(v, options, &mut value)?;
+                entries.push((key, value));
+            }
+            if options.sort() {
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+            let entries: Vec<String> = entries.into_iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+            out.push_str(&format!("{{{}}}", entries.join(", ")));
+        }
+        serde_yaml::Value::Sequence(items) => {
+            let mut rendered = Vec::new();
+            for item in items {
+                let mut s = String::new();
+                write_yaml_flow(item, options, &mut s)?;
+                rendered.push(s);
+            }
+            out.push_str(&format!("[{}]", rendered.join(", ")));
+        }
+        serde_yaml::Value::String(s) => out.push_str(&serde_json::Value::String(s.clone()).to_string()),
+        serde_yaml::Value::Tagged(tagged) => {
+            out.push_str(&format!("{} ", tagged.tag));
+            write_yaml_flow(&tagged.value, options, out)?;
+        }
+        scalar => out.push_str(&yaml_scalar(scalar)?),
+    }
+    Ok(())
+}
+
+// ============================================================================
+// XML
+// ============================================================================
+
+pub(crate) enum XmlNode {
+    Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        children: Vec<XmlNode>,
+    },
+    /// 保持原始（已转义）文本
+    Text(String),
+    CData(String),
+    Comment(String),
+    ProcessingInstruction(String),
+    Declaration(String),
+    DocType(String),
+}
+
+impl XmlNode {
+    fn is_whitespace_text(&self) -> bool {
+        matches!(self, XmlNode::Text(t) if t.trim().is_empty())
+    }
+
+    fn is_text_like(&self) -> bool {
+        matches!(self, XmlNode::Text(_) | XmlNode::CData(_))
+    }
+}
+
+pub(crate) fn parse_xml(content: &str) -> Result<Vec<XmlNode>> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(content);
+    reader.config_mut().trim_text(false);
+
+    let utf8 = |bytes: &[u8]| String::from_utf8_lossy(bytes).to_string();
+    let element = |e: &quick_xml::events::BytesStart| -> Result<XmlNode> {
+        let mut attributes = Vec::new();
+        for attr in e.attributes() {
+            let attr = attr.map_err(|err| anyhow!("Invalid XML attribute: {}", err))?;
+            attributes.push((utf8(attr.key.as_ref()), utf8(&attr.value)));
+        }
+        Ok(XmlNode::Element { name: utf8(e.name().as_ref()), attributes, children: Vec::new() })
+    };
+
+    // 栈底为文档根节点列表
+    let mut stack: Vec<Vec<XmlNode>> = vec![Vec::new()];
+    let mut open: Vec<XmlNode> = Vec::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            anyhow!("Invalid XML at position {}: {}", reader.buffer_position(), e)
+        })?;
+        let node = match event {
+            Event::Start(e) => {
+                open.push(element(&e)?);
+                stack.push(Vec::new());
+                continue;
+            }
+            Event::End(_) => {
+                let children = stack.pop().unwrap_or_default();
+                match open.pop() {
+                    Some(XmlNode::Element { name, attributes, .. }) => {
+                        XmlNode::Element { name, attributes, children }
+                    }
+                    _ => anyhow::bail!("Unexpected closing tag"),
+                }
+            }
+            Event::Empty(e) => element(&e)?,
+            Event::Text(t) => XmlNode::Text(utf8(&t)),
+            Event::CData(t) => XmlNode::CData(utf8(&t)),
+            Event::Comment(t) => XmlNode::Comment(utf8(&t)),
+            Event::PI(t) => XmlNode::ProcessingInstruction(utf8(&t)),
+            Event::Decl(t) => XmlNode::Declaration(utf8(&t)),
+            Event::DocType(t) => XmlNode::DocType(utf8(&t)),
+            Event::Eof => break,
+        };
+        if let Some(siblings) = stack.last_mut() {
+            siblings.push(node);
+        }
+    }
+
+    if !open.is_empty() {
+        anyhow::bail!("Unclosed XML element");
+    }
+    Ok(stack.pop().unwrap_or_default())
+}
+
+fn format_xml(content: &str, options: &DataFormatOptions) -> Result<String> {
+    let nodes = parse_xml(content)?;
+    let mut out = String::new();
+
+    for node in &nodes {
+        match options.style {
+            FormatStyle::Canonical => {
+                // 规范化：去掉 XML 声明，保留原有空白
+                if !matches!(node, XmlNode::Declaration(_)) {
+                    write_xml_inline(node, options, &mut out);
+                }
+            }
+            FormatStyle::Minify => {
+                if !node.is_whitespace_text() {
+                    write_xml_minified(node, options, &mut out);
+                }
+            }
+            FormatStyle::Pretty => {
+                if !node.is_whitespace_text() {
+                    write_xml_pretty(node, options, 0, &mut out);
+                }
+            }
+        }
+    }
+    if options.style == FormatStyle::Canonical {
+        return Ok(out.trim().to_string());
+    }
+    Ok(out)
+}
+
+fn write_xml_start(
+    name: &str,
+    attributes: &[(String, String)],
+    options: &DataFormatOptions,
+    out: &mut String,
+) {
+    let mut attributes: Vec<_> = attributes.iter().collect();
+    if options.sort() {
+        // 命名空间声明在前，其余按名称排序
+        attributes.sort_by_key(|(k, _)| (!(k == "xmlns" || k.starts_with("xmlns:")), k.clone()));
+    }
+    out.push('<');
+    out.push_str(name);
+    for (key, value) in attributes {
+        out.push_str(&format!(" {}=\"{}\"", key, value.replace('"', "&quot;")));
+    }
+}
+
+fn write_xml_leaf(node: &XmlNode, out: &mut String) {
+    match node {
+        XmlNode::Text(text) => out.push_str(text),
+        XmlNode::CData(text) => out.push_str(&format!("<![CDATA[{}]]>", text)),
+        XmlNode::Comment(text) => out.push_str(&format!("<!--{}-->", text)),
+        XmlNode::ProcessingInstruction(text) => out.push_str(&format!("<?{}?>", text)),
+        XmlNode::Declaration(text) => out.push_str(&format!("<?{}?>", text)),
+        XmlNode::DocType(text) => out.push_str(&format!("<!DOCTYPE {}>", text.trim())),
+        XmlNode::Element { .. } => {}
+    }
+}
+
+/// 原样输出（不增删空白），规范化和混合内容使用
+fn write_xml_inline(node: &XmlNode, options: &DataFormatOptions, out: &mut String) {
+    match node {
+        XmlNode::Element { name, attributes, children } => {
+            write_xml_start(name, attributes, options, out);
+            if children.is_empty() && options.style != FormatStyle::Canonical {
+                out.push_str("/>");
+                return;
+            }
+            out.push('>');
+            for child in children {
+                write_xml_inline(child, options, out);
+            }
+            out.push_str(&format!("</{}>", name));
+        }
+        leaf => write_xml_leaf(leaf, out),
+    }
+}
+
+/// 包含非空白文本的元素视为混合内容，其中的空白有意义
+fn has_mixed_content(children: &[XmlNode]) -> bool {
+    children.iter().any(|c| c.is_text_like() && !c.is_whitespace_text())
+}
+
+fn write_xml_minified(node: &XmlNode, options: &DataFormatOptions, out: &mut String) {
+    match node {
+        XmlNode::Element { name, attributes, children } if !has_mixed_content(children) => {
+            write_xml_start(name, attributes, options, out);
+            let children: Vec<_> = children.iter().filter(|c| !c.is_whitespace_text()).collect();
+            if children.is_empty() {
+                out.push_str("/>");
+                return;
+            }
+            out.push('>');
+            for child in children {
+                write_xml_minified(child, options, out);
+            }
+            out.push_str(&format!("</{}>", name));
+        }
+        other => write_xml_inline(other, options, out),
+    }
+}
+
+fn write_xml_pretty(node: &XmlNode, options: &DataFormatOptions, level: usize, out: &mut String) {
+    let pad = options.indent_unit().repeat(level);
+    out.push_str(&pad);
+
+    match node {
+        XmlNode::Element { name, attributes, children } => {
+            let only_text = children.iter().all(XmlNode::is_text_like);
+            if children.is_empty() || only_text || has_mixed_content(children) {
+                write_xml_inline(node, options, out);
+                out.push('\n');
+                return;
+            }
+
+            write_xml_start(name, attributes, options, out);
+            out.push_str(">\n");
+            for child in children.iter().filter(|c| !c.is_whitespace_text()) {
+                write_xml_pretty(child, options, level + 1, out);
+            }
+            out.push_str(&pad);
+            out.push_str(&format!("</{}>\n", name));
+        }
+        leaf => {
+            write_xml_leaf(leaf, out);
+            out.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(style: FormatStyle) -> DataFormatOptions {
+        DataFormatOptions { style, ..Default::default() }
+    }
+
+    #[test]
+    fn test_json() {
+        let input = r#"{"b":[1,2],"a":{"c":null},"e":[]}"#;
+        let pretty = format_data(input, DataFormat::Json, &options(FormatStyle::Pretty)).unwrap();
+        assert_eq!(pretty.text, "{\n  \"b\": [\n    1,\n    2\n  ],\n  \"a\": {\n    \"c\": null\n  },\n  \"e\": []\n}\n");
+
+        let canonical = format_data(input, DataFormat::Json, &options(FormatStyle::Canonical)).unwrap();
+        assert_eq!(canonical.text, r#"{"a":{"c":null},"b":[1,2],"e":[]}"#);
+
+        // 尾随逗号只用于 JSONC
+        let opts = DataFormatOptions { indent: 4, trailing_comma: true, ..Default::default() };
+        assert!(format_data(r#"{"a":1}"#, DataFormat::Json, &opts).is_err());
+        let text = format_data(r#"{"a":1}"#, DataFormat::Jsonc, &opts).unwrap().text;
+        assert_eq!(text, "{\n    \"a\": 1,\n}\n");
+
+        // 数字按原文保留，不经过 f64 转换
+        let input = r#"{"big": 123456789012345678901234567890, "f": 1.10, "e": 1E+2, "s": "a\u00e9"}"#;
+        let minified = format_data(input, DataFormat::Json, &options(FormatStyle::Minify)).unwrap();
+        assert_eq!(minified.text, r#"{"big":123456789012345678901234567890,"f":1.10,"e":1E+2,"s":"a\u00e9"}"#);
+        let canonical = format_data(input, DataFormat::Json, &options(FormatStyle::Canonical)).unwrap();
+        assert_eq!(canonical.text, r#"{"big":123456789012345678901234567890,"e":1E+2,"f":1.10,"s":"aé"}"#);
+    }
+
+    #[test]
+    fn test_jsonc() {
+        let input = "// settings\n{\n  // editor\n  \"a\": 1, // inline\n  \"b\": [1, /* two */ 2,],\n  \"c\": {\n    // empty\n  },\n}\n";
+        let pretty = options(FormatStyle::Pretty);
+        let result = format_data(input, DataFormat::Jsonc, &pretty).unwrap();
+        assert_eq!(
+            result.text,
+            "// settings\n{\n  // editor\n  \"a\": 1, // inline\n  \"b\": [\n    1, /* two */\n    2\n  ],\n  \"c\": {\n    // empty\n  }\n}\n"
+        );
+        assert!(result.warnings.is_empty());
+        assert!(format_data(input, DataFormat::Json, &pretty).is_err());
+
+        // 格式化结果再次格式化保持不变，包括尾随逗号
+        for opts in [pretty, DataFormatOptions { trailing_comma: true, ..Default::default() }] {
+            let once = format_data(input, DataFormat::Jsonc, &opts).unwrap().text;
+            let twice = format_data(&once, DataFormat::Jsonc, &opts).unwrap().text;
+            assert_eq!(once, twice);
+        }
+
+        let minified = format_data(input, DataFormat::Jsonc, &options(FormatStyle::Minify)).unwrap();
+        assert_eq!(minified.text, r#"{"a":1,"b":[1,2],"c":{}}"#);
+        assert_eq!(minified.warnings.len(), 1);
+
+        assert_eq!(DataFormat::detect("json", Some("/p/tsconfig.json")), Some(DataFormat::Jsonc));
+        assert_eq!(DataFormat::detect("json", Some("/p/package.json")), Some(DataFormat::Json));
+    }
+
+    #[test]
+    fn test_toml() {
+        let input = "title = \"x\"\n[server]\nport = 80\n[server.tls]\ncert = \"a\"\n[[bin]]\nname = \"a\"\n[[bin]]\nname = \"b\"\n# note\n";
+        let result = format_data(input, DataFormat::Toml, &options(FormatStyle::Pretty)).unwrap();
+        assert_eq!(
+            result.text,
+            "title = \"x\"\n\n[server]\nport = 80\n\n[server.tls]\ncert = \"a\"\n\n[[bin]]\nname = \"a\"\n\n[[bin]]\nname = \"b\"\n"
+        );
+        assert_eq!(result.warnings.len(), 1);
+        // 格式化结果可以被重新解析为相同的数据
+        assert_eq!(input.parse::<toml::Table>().unwrap(), result.text.parse::<toml::Table>().unwrap());
+    }
+
+    #[test]
+    fn test_yaml() {
+        let input = "b:\n  - x: 1\n    y: [1, 2]\n  - plain\na: \"multi\\nline\"\n";
+        let result = format_data(input, DataFormat::Yaml, &options(FormatStyle::Pretty)).unwrap();
+        let reparsed: serde_yaml::Value = serde_yaml::from_str(&result.text).unwrap();
+        assert_eq!(reparsed, serde_yaml::from_str::<serde_yaml::Value>(input).unwrap());
+
+        let minified = format_data(input, DataFormat::Yaml, &options(FormatStyle::Canonical)).unwrap();
+        assert!(minified.text.starts_with("a: "));
+
+        let opts = DataFormatOptions { indent: 4, ..Default::default() };
+        let wide = format_data(input, DataFormat::Yaml, &opts).unwrap();
+        let reparsed: serde_yaml::Value = serde_yaml::from_str(&wide.text).unwrap();
+        assert_eq!(reparsed, serde_yaml::from_str::<serde_yaml::Value>(input).unwrap());
+
+        let flow = format_data(input, DataFormat::Yaml, &options(FormatStyle::Minify)).unwrap();
+        let reparsed: serde_yaml::Value = serde_yaml::from_str(&flow.text).unwrap();
+        assert_eq!(reparsed, serde_yaml::from_str::<serde_yaml::Value>(input).unwrap());
+    }
+
+    #[test]
+    fn test_xml() {
+        let input = "<?xml version=\"1.0\"?>\n<root b='2' a=\"1\"><item>text &amp; more</item><empty/><p>mixed <b>bold</b> text</p></root>";
+        let pretty = format_data(input, DataFormat::Xml, &options(FormatStyle::Pretty)).unwrap();
+        assert_eq!(
+            pretty.text,
+            "<?xml version=\"1.0\"?>\n<root b=\"2\" a=\"1\">\n  <item>text &amp; more</item>\n  <empty/>\n  <p>mixed <b>bold</b> text</p>\n</root>\n"
+        );
+
+        let minified = format_data(&pretty.text, DataFormat::Xml, &options(FormatStyle::Minify)).unwrap();
+        assert_eq!(
+            minified.text,
+            "<?xml version=\"1.0\"?><root b=\"2\" a=\"1\"><item>text &amp; more</item><empty/><p>mixed <b>bold</b> text</p></root>"
+        );
+
+        let canonical = format_data(input, DataFormat::Xml, &options(FormatStyle::Canonical)).unwrap();
+        assert!(canonical.text.starts_with("<root a=\"1\" b=\"2\"><item>"));
+        assert!(canonical.text.contains("<empty></empty>"));
+    }
+}
@@ -0,0 +1,235 @@
+// Document Store Module
+//
+// 后端文档模型：按标签页 ID 保存 rope，接收 Monaco 的增量编辑，
+// 搜索、替换、统计和语法操作直接使用后端保存的文本，不再每次通过 IPC 传输整个缓冲区
+
+use crate::commands::SearchResult;
+use crate::{editor, structural, syntax};
+use anyhow::{anyhow, bail, Result};
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+static DOCUMENTS: OnceLock<Mutex<HashMap<String, Arc<Mutex<Document>>>>> = OnceLock::new();
+
+/// Monaco 的内容变化（`IModelContentChange`），偏移和长度为 UTF-16 码元。
+/// 同一事件中的多个变化按顺序依次应用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentChange {
+    pub range_offset: usize,
+    pub range_length: usize,
+    pub text: String,
+}
+
+/// 文档统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStats {
+    pub version: i64,
+    pub lines: usize,
+    pub words: usize,
+    /// UTF-16 码元数，与前端 `content.length` 一致
+    pub chars: usize,
+    pub bytes: usize,
+}
+
+pub struct Document {
+    rope: Rope,
+    /// 最后一次同步的 Monaco 模型版本号
+    version: i64,
+    path: Option<String>,
+    language: Option<String>,
+    /// 连续文本缓存，供正则搜索等需要 `&str` 的操作使用，编辑后失效
+    text: Option<String>,
+}
+
+impl Document {
+    fn new(content: &str, version: i64, path: Option<String>, language: Option<String>) -> Self {
+        Self { rope: Rope::from_str(content), version, path, language, text: None }
+    }
+
+    /// 应用一次编辑事件；任一变化越界时整个事件不生效
+    fn apply(&mut self, base_version: i64, version: i64, changes: &[ContentChange]) -> Result<()> {
+        if self.version != base_version {
+            bail!("Document out of sync: expected version {}, got {}", self.version, base_version);
+        }
+        let mut rope = self.rope.clone();
+        for change in changes {
+            let end = change.range_offset + change.range_length;
+            if end > rope.len_utf16_cu() {
+                bail!("Change out of range: {}..{}", change.range_offset, end);
+            }
+            let start = rope.utf16_cu_to_char(change.range_offset);
+            let end = rope.utf16_cu_to_char(end);
+            rope.remove(start..end);
+            rope.insert(start, &change.text);
+        }
+        self.rope = rope;
+        self.version = version;
+        self.text = None;
+        Ok(())
+    }
+
+    pub fn text(&mut self) -> &str {
+        let rope = &self.rope;
+        self.text.get_or_insert_with(|| rope.to_string())
+    }
+
+    /// 行数（按 `\n` 计）和词数（以空白分隔）与状态栏的统计方式一致
+    pub fn stats(&self) -> DocumentStats {
+        let mut lines = 1;
+        let mut words = 0;
+        let mut in_word = false;
+        for chunk in self.rope.chunks() {
+            lines += chunk.bytes().filter(|&b| b == b'\n').count();
+            for c in chunk.chars() {
+                let word_char = !c.is_whitespace();
+                if word_char && !in_word {
+                    words += 1;
+                }
+                in_word = word_char;
+            }
+        }
+        DocumentStats {
+            version: self.version,
+            lines,
+            words,
+            chars: self.rope.len_utf16_cu(),
+            bytes: self.rope.len_bytes(),
+        }
+    }
+
+    /// 文档语言：打开时指定的语言，否则根据路径检测
+    fn language(&self) -> Option<String> {
+        self.language
+            .clone()
+            .or_else(|| self.path.as_deref().map(|path| syntax::detect_language(path, None)))
+    }
+}
+
+fn documents() -> &'static Mutex<HashMap<String, Arc<Mutex<Document>>>> {
+    DOCUMENTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn with_document<T>(tab_id: &str, f: impl FnOnce(&mut Document) -> Result<T>) -> Result<T> {
+    let document = documents()
+        .lock()
+        .unwrap()
+        .get(tab_id)
+        .cloned()
+        .ok_or_else(|| anyhow!("Document not open: {}", tab_id))?;
+    let mut document = document.lock().unwrap();
+    f(&mut document)
+}
+
+/// 打开（或以全文重新同步）文档
+pub fn open_document(
+    tab_id: &str,
+    content: &str,
+    version: i64,
+    path: Option<String>,
+    language: Option<String>,
+) -> DocumentStats {
+    let document = Document::new(content, version, path, language);
+    let stats = document.stats();
+    documents()
+        .lock()
+        .unwrap()
+        .insert(tab_id.to_string(), Arc::new(Mutex::new(document)));
+    stats
+}
+
+pub fn close_document(tab_id: &str) {
+    documents().lock().unwrap().remove(tab_id);
+}
+
+/// 应用增量编辑；`base_version` 与后端版本不一致时返回错误，前端应重新发送全文
+pub fn apply_changes(tab_id: &str, base_version: i64, version: i64, changes: &[ContentChange]) -> Result<()> {
+    with_document(tab_id, |document| document.apply(base_version, version, changes))
+}
+
+pub fn document_text(tab_id: &str) -> Result<String> {
+    with_document(tab_id, |document| Ok(document.text().to_string()))
+}
+
+pub fn document_stats(tab_id: &str) -> Result<DocumentStats> {
+    with_document(tab_id, |document| Ok(document.stats()))
+}
+
+pub fn search(tab_id: &str, query: &str, options: &editor::SearchOptions) -> Result<Vec<SearchResult>> {
+    with_document(tab_id, |document| editor::search_in_content(document.text(), query, options))
+}
+
+/// 计算替换结果；前端把返回的范围作为编辑应用到 Monaco，变化再以增量同步回来
+pub fn preview_replace(
+    tab_id: &str,
+    search: &str,
+    replace: &str,
+    options: &editor::ReplaceOptions,
+) -> Result<Vec<editor::ReplacePreview>> {
+    with_document(tab_id, |document| editor::preview_replace(document.text(), search, replace, options))
+}
+
+pub fn highlight(tab_id: &str, language: Option<&str>) -> Result<Vec<syntax::HighlightToken>> {
+    with_document(tab_id, |document| {
+        let language = language.map(str::to_string).or_else(|| document.language()).unwrap_or_default();
+        syntax::highlight_content(document.text(), &language)
+    })
+}
+
+pub fn structural_search(
+    tab_id: &str,
+    language: Option<&str>,
+    query: &structural::StructuralQuery,
+) -> Result<Vec<structural::StructuralMatch>> {
+    with_document(tab_id, |document| {
+        let language = language
+            .map(str::to_string)
+            .or_else(|| document.language())
+            .ok_or_else(|| anyhow!("Document language is unknown"))?;
+        structural::structural_search(document.text(), &language, query)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(range_offset: usize, range_length: usize, text: &str) -> ContentChange {
+        ContentChange { range_offset, range_length, text: text.to_string() }
+    }
+
+    #[test]
+    fn test_apply_utf16_changes() {
+        let mut document = Document::new("héllo 😀 world\nline 2", 1, None, None);
+        // 😀 占两个 UTF-16 码元，"world" 从偏移 9 开始
+        document.apply(1, 2, &[change(9, 5, "there"), change(0, 1, "H")]).unwrap();
+        assert_eq!(document.text(), "Héllo 😀 there\nline 2");
+
+        // 版本不连续或越界时拒绝，文档保持不变
+        assert!(document.apply(1, 3, &[change(0, 0, "x")]).is_err());
+        assert!(document.apply(2, 3, &[change(0, 0, "x"), change(100, 1, "")]).is_err());
+        assert_eq!(document.text(), "Héllo 😀 there\nline 2");
+        assert_eq!(document.version, 2);
+        assert_eq!((document.stats().chars, document.stats().bytes), (21, 24));
+    }
+
+    #[test]
+    fn test_document_operations() {
+        let tab_id = "document-test-tab";
+        let stats = open_document(tab_id, "foo bar\nfoo", 1, Some("notes.txt".to_string()), None);
+        assert_eq!((stats.lines, stats.words, stats.chars), (2, 3, 11));
+
+        apply_changes(tab_id, 1, 2, &[change(11, 0, " baz\n")]).unwrap();
+        let results = search(tab_id, "foo", &editor::SearchOptions::default()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].line, 2);
+        let stats = document_stats(tab_id).unwrap();
+        assert_eq!((stats.version, stats.lines, stats.words), (2, 3, 4));
+
+        close_document(tab_id);
+        assert!(document_text(tab_id).is_err());
+    }
+}
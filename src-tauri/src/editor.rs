@@ -2,113 +2,405 @@
 
 use crate::commands::SearchResult;
 use anyhow::Result;
-use regex::RegexBuilder;
+use caseless::{default_case_fold_str, Caseless};
+use regex::{Captures, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
-pub fn search_in_content(
+/// 搜索选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub use_regex: bool,
+    /// 匹配的前后须为单词边界（Unicode 字母、数字和下划线视为单词字符）
+    pub whole_word: bool,
+    /// 正则中 `^`/`$` 匹配每行的开头和结尾
+    pub multiline: bool,
+    /// 正则中 `.` 也匹配换行符
+    pub dot_all: bool,
+    /// 只在这些范围内搜索，为空时搜索全文
+    pub selections: Vec<SearchRange>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: false,
+            use_regex: false,
+            whole_word: false,
+            multiline: true,
+            dot_all: false,
+            selections: Vec::new(),
+        }
+    }
+}
+
+/// 文本范围（行、列均从 1 开始，列为 UTF-16 码元，与 Monaco 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchRange {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// 行起始偏移，用于字节偏移和行列位置之间的转换
+struct Lines<'a> {
+    content: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(content: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { content, starts }
+    }
+
+    /// 行内容（不含换行符）
+    fn text(&self, line: usize) -> &'a str {
+        let start = self.starts[line];
+        let end = self.starts.get(line + 1).map_or(self.content.len(), |&next| next - 1);
+        let text = &self.content[start..end];
+        text.strip_suffix('\r').unwrap_or(text)
+    }
+
+    /// 字节偏移所在的行（从 0 开始）
+    fn line_of(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// 行列位置（列为 UTF-16）对应的字节偏移，超出范围时取最近的有效位置
+    fn offset(&self, line: usize, column: usize) -> usize {
+        let Some(&start) = self.starts.get(line.saturating_sub(1)) else {
+            return self.content.len();
+        };
+        let text = self.text(line.saturating_sub(1));
+        let mut units = 0;
+        for (i, c) in text.char_indices() {
+            if units >= column.saturating_sub(1) {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        start + text.len()
+    }
+}
+
+/// 匹配位置：行从 0 开始，列为 UTF-16 码元（从 0 开始），以及全文中的字符偏移
+#[derive(Clone, Copy)]
+struct Position {
+    line: usize,
+    column: usize,
+    char_offset: usize,
+}
+
+/// 按递增顺序把字节偏移转换为位置，只计数上一个位置之后的文本
+struct PositionCursor<'a> {
+    lines: &'a Lines<'a>,
+    byte: usize,
+    position: Position,
+}
+
+impl<'a> PositionCursor<'a> {
+    fn new(lines: &'a Lines<'a>) -> Self {
+        Self { lines, byte: 0, position: Position { line: 0, column: 0, char_offset: 0 } }
+    }
+
+    fn advance(&mut self, offset: usize) -> Position {
+        let content = self.lines.content;
+        let passed = &content[self.byte..offset];
+        let line = self.lines.line_of(offset);
+        self.position.char_offset += passed.chars().count();
+        self.position.column = if line == self.position.line {
+            self.position.column + passed.encode_utf16().count()
+        } else {
+            content[self.lines.starts[line]..offset].encode_utf16().count()
+        };
+        self.position.line = line;
+        self.byte = offset;
+        self.position
+    }
+
+    /// 生成 `[start, end)` 的搜索结果，`start` 不能早于上一次的结束位置
+    fn result(&mut self, start: usize, end: usize) -> SearchResult {
+        let content = self.lines.content;
+        let start_position = self.advance(start);
+        let end_position = self.advance(end);
+        SearchResult {
+            line: start_position.line + 1,
+            column: start_position.column + 1,
+            end_line: end_position.line + 1,
+            end_column: end_position.column + 1,
+            length: content[start..end].encode_utf16().count(),
+            byte_start: start,
+            byte_end: end,
+            char_start: start_position.char_offset,
+            char_end: end_position.char_offset,
+            text: content[start..end].to_string(),
+            context: self.lines.text(start_position.line).to_string(),
+        }
+    }
+}
+
+/// 把按起点递增、互不重叠的字节区间转换为搜索结果
+pub(crate) fn results_for_ranges(content: &str, ranges: &[Range<usize>]) -> Vec<SearchResult> {
+    let lines = Lines::new(content);
+    let mut cursor = PositionCursor::new(&lines);
+    ranges.iter().map(|range| cursor.result(range.start, range.end)).collect()
+}
+
+/// 全量大小写折叠（Unicode CaseFolding 的 C+F 映射，如 ß → ss）后的文本，以及与原文偏移的对应关系
+struct Folded {
+    text: String,
+    /// 折叠后字节长度变化或展开为多个字符的位置：(折叠后范围, 原文范围)
+    spans: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl Folded {
+    fn new(content: &str) -> Self {
+        let mut text = String::with_capacity(content.len());
+        let mut spans = Vec::new();
+        for (start, c) in content.char_indices() {
+            let folded_start = text.len();
+            let mut count = 0;
+            for folded in std::iter::once(c).default_case_fold() {
+                text.push(folded);
+                count += 1;
+            }
+            if count != 1 || text.len() - folded_start != c.len_utf8() {
+                spans.push((folded_start..text.len(), start..start + c.len_utf8()));
+            }
+        }
+        Self { text, spans }
+    }
+
+    /// 折叠后偏移对应的原文偏移；位于某个字符折叠结果的中间时返回 None
+    fn original(&self, offset: usize) -> Option<usize> {
+        let index = self.spans.partition_point(|(folded, _)| folded.start <= offset);
+        let Some((folded, original)) = index.checked_sub(1).map(|i| &self.spans[i]) else {
+            return Some(offset);
+        };
+        if offset == folded.start {
+            Some(original.start)
+        } else if offset < folded.end {
+            None
+        } else {
+            Some(original.end + offset - folded.end)
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// 匹配两端是否为单词边界；以非单词字符开头或结尾的一端不作要求
+fn is_whole_word(content: &str, start: usize, end: usize) -> bool {
+    let matched = &content[start..end];
+    let before = content[..start].chars().next_back();
+    let after = content[end..].chars().next();
+    let start_ok = !before.is_some_and(is_word_char) || matched.chars().next().is_some_and(|c| !is_word_char(c));
+    let end_ok = !after.is_some_and(is_word_char) || matched.chars().next_back().is_some_and(|c| !is_word_char(c));
+    start_ok && end_ok
+}
+
+fn next_char_boundary(content: &str, offset: usize) -> usize {
+    offset + content[offset..].chars().next().map_or(1, char::len_utf8)
+}
+
+fn build_search_regex(query: &str, options: &SearchOptions) -> Result<Regex> {
+    let pattern = if options.use_regex { query.to_string() } else { regex::escape(query) };
+    Ok(RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(options.multiline)
+        .dot_matches_new_line(options.dot_all)
+        .crlf(true)
+        .build()?)
+}
+
+/// 依次访问每个匹配，正则搜索时附带捕获组；`visit` 返回 false 时停止
+fn visit_matches(
     content: &str,
     query: &str,
-    case_sensitive: bool,
-    use_regex: bool,
-) -> Result<Vec<SearchResult>> {
-    let mut results = Vec::new();
-    
+    options: &SearchOptions,
+    mut visit: impl FnMut(SearchResult, Option<&Captures>) -> bool,
+) -> Result<()> {
     if query.is_empty() {
-        return Ok(results);
+        return Ok(());
     }
     
-    let lines: Vec<&str> = content.lines().collect();
-    
-    if use_regex {
-        let regex = RegexBuilder::new(query)
-            .case_insensitive(!case_sensitive)
-            .build()?;
-        
-        for (line_idx, line) in lines.iter().enumerate() {
-            for mat in regex.find_iter(line) {
-                results.push(SearchResult {
-                    line: line_idx + 1,
-                    column: mat.start() + 1,
-                    length: mat.len(),
-                    text: mat.as_str().to_string(),
-                    context: line.to_string(),
-                });
-            }
+    // 纯 ASCII 时简单折叠与全量折叠结果相同，无需转换文本
+    let ascii = content.is_ascii() && query.is_ascii();
+    let folded = (!options.case_sensitive && !options.use_regex && !ascii).then(|| Folded::new(content));
+    let (haystack, regex) = match &folded {
+        Some(folded) => {
+            let options = SearchOptions { case_sensitive: true, ..options.clone() };
+            (folded.text.as_str(), build_search_regex(&default_case_fold_str(query), &options)?)
         }
-    } else {
-        let search_query = if case_sensitive {
-            query.to_string()
+        None => (content, build_search_regex(query, options)?),
+    };
+    let to_original = |offset: usize| folded.as_ref().map_or(Some(offset), |f| f.original(offset));
+    
+    let lines = Lines::new(content);
+    let ranges: Vec<(usize, usize)> = options
+        .selections
+        .iter()
+        .map(|r| (lines.offset(r.start_line, r.start_column), lines.offset(r.end_line, r.end_column)))
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+    let in_ranges = |start: usize, end: usize| {
+        ranges.is_empty() || ranges.iter().any(|&(a, b)| a <= start && end <= b)
+    };
+    
+    let mut cursor = PositionCursor::new(&lines);
+    let mut pos = 0;
+    while pos <= haystack.len() {
+        // 只有正则搜索需要捕获组，此时 haystack 即原文
+        let (mat, captures) = if options.use_regex {
+            let Some(captures) = regex.captures_at(haystack, pos) else {
+                break;
+            };
+            (captures.get(0).unwrap(), Some(captures))
         } else {
-            query.to_lowercase()
-        };
-        
-        for (line_idx, line) in lines.iter().enumerate() {
-            let search_line = if case_sensitive {
-                line.to_string()
-            } else {
-                line.to_lowercase()
+            let Some(mat) = regex.find_at(haystack, pos) else {
+                break;
             };
-            
-            let mut start = 0;
-            while let Some(pos) = search_line[start..].find(&search_query) {
-                let actual_pos = start + pos;
-                results.push(SearchResult {
-                    line: line_idx + 1,
-                    column: actual_pos + 1,
-                    length: query.len(),
-                    text: line[actual_pos..actual_pos + query.len()].to_string(),
-                    context: line.to_string(),
-                });
-                start = actual_pos + 1;
-            }
+            (mat, None)
+        };
+        // 不在字符边界、超出选区或不满足全词匹配时，从下一个字符继续查找
+        let bounds = to_original(mat.start()).zip(to_original(mat.end()));
+        let Some((start, end)) = bounds.filter(|&(start, end)| {
+            in_ranges(start, end) && (!options.whole_word || is_whole_word(content, start, end))
+        }) else {
+            pos = next_char_boundary(haystack, mat.start());
+            continue;
+        };
+        if !visit(cursor.result(start, end), captures.as_ref()) {
+            break;
         }
+        pos = if mat.is_empty() { next_char_boundary(haystack, mat.end()) } else { mat.end() };
     }
     
+    Ok(())
+}
+
+/// 在全文（或选区）中搜索，正则可以跨行匹配。
+/// 不区分大小写的普通搜索使用全量大小写折叠，正则使用 Unicode 简单折叠
+pub fn search_in_content(content: &str, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+    let mut results = Vec::new();
+    visit_matches(content, query, options, |result, _| {
+        results.push(result);
+        true
+    })?;
     Ok(results)
 }
 
-pub fn search_and_replace(
+/// 替换选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReplaceOptions {
+    #[serde(flatten)]
+    pub search: SearchOptions,
+    /// 按匹配文本的大小写调整替换文本（foo → bar 时 Foo → Bar、FOO → BAR）
+    pub preserve_case: bool,
+    pub replace_all: bool,
+}
+
+/// 替换预览：匹配位置和计算出的替换文本
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacePreview {
+    #[serde(flatten)]
+    pub result: SearchResult,
+    pub replacement: String,
+}
+
+/// 处理替换文本中的 `\n`、`\t` 和 `\\` 转义，其余反斜杠原样保留
+fn unescape_replacement(replace: &str) -> String {
+    let mut result = String::with_capacity(replace.len());
+    let mut chars = replace.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// 按匹配文本的大小写形式调整替换文本：全大写、全小写或首字母大写
+fn preserve_case(matched: &str, replacement: &str) -> String {
+    let has_upper = matched.chars().any(char::is_uppercase);
+    let has_lower = matched.chars().any(char::is_lowercase);
+    if has_upper && !has_lower && matched.chars().filter(|c| c.is_alphabetic()).count() > 1 {
+        return replacement.to_uppercase();
+    }
+    if has_lower && !has_upper {
+        return replacement.to_lowercase();
+    }
+    if matched.chars().find(|c| c.is_alphabetic()).is_some_and(char::is_uppercase) {
+        let mut chars = replacement.chars();
+        return match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+    }
+    replacement.to_string()
+}
+
+/// 计算每个匹配的替换文本（正则支持 `$1`、`${name}` 和 `$$`），`replace_all` 为 false 时只处理第一个匹配。
+/// 普通搜索处理 `\n`、`\t` 转义；正则模式下替换文本原样交给捕获组展开，反斜杠不做处理
+pub fn preview_replace(
     content: &str,
     search: &str,
     replace: &str,
-    case_sensitive: bool,
-    use_regex: bool,
-    replace_all: bool,
-) -> Result<String> {
-    if search.is_empty() {
-        return Ok(content.to_string());
-    }
-    
-    if use_regex {
-        let regex = RegexBuilder::new(search)
-            .case_insensitive(!case_sensitive)
-            .build()?;
-        
-        if replace_all {
-            Ok(regex.replace_all(content, replace).to_string())
-        } else {
-            Ok(regex.replace(content, replace).to_string())
-        }
+    options: &ReplaceOptions,
+) -> Result<Vec<ReplacePreview>> {
+    let replace = if options.search.use_regex {
+        replace.to_string()
     } else {
-        if replace_all {
-            if case_sensitive {
-                Ok(content.replace(search, replace))
-            } else {
-                let regex = RegexBuilder::new(&regex::escape(search))
-                    .case_insensitive(true)
-                    .build()?;
-                Ok(regex.replace_all(content, replace).to_string())
-            }
-        } else {
-            if case_sensitive {
-                Ok(content.replacen(search, replace, 1))
-            } else {
-                let regex = RegexBuilder::new(&regex::escape(search))
-                    .case_insensitive(true)
-                    .build()?;
-                Ok(regex.replace(content, replace).to_string())
-            }
+        unescape_replacement(replace)
+    };
+    let mut previews = Vec::new();
+    visit_matches(content, search, &options.search, |result, captures| {
+        let mut replacement = String::new();
+        match captures {
+            Some(captures) => captures.expand(&replace, &mut replacement),
+            None => replacement.push_str(&replace),
+        }
+        if options.preserve_case {
+            replacement = preserve_case(&result.text, &replacement);
         }
+        previews.push(ReplacePreview { result, replacement });
+        options.replace_all
+    })?;
+    Ok(previews)
+}
+
+pub fn search_and_replace(content: &str, search: &str, replace: &str, options: &ReplaceOptions) -> Result<String> {
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for preview in preview_replace(content, search, replace, options)? {
+        result.push_str(&content[last..preview.result.byte_start]);
+        result.push_str(&preview.replacement);
+        last = preview.result.byte_end;
     }
+    result.push_str(&content[last..]);
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -118,7 +410,8 @@ mod tests {
     #[test]
     fn test_search_basic() {
         let content = "Hello World\nHello Rust\nGoodbye World";
-        let results = search_in_content(content, "Hello", true, false).unwrap();
+        let options = SearchOptions { case_sensitive: true, ..Default::default() };
+        let results = search_in_content(content, "Hello", &options).unwrap();
         assert_eq!(results.len(), 2);
         assert_eq!(results[0].line, 1);
         assert_eq!(results[1].line, 2);
@@ -127,14 +420,97 @@ mod tests {
     #[test]
     fn test_search_case_insensitive() {
         let content = "Hello World\nhello rust";
-        let results = search_in_content(content, "hello", false, false).unwrap();
+        let results = search_in_content(content, "hello", &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 2);
     }
     
+    #[test]
+    fn test_search_whole_word_multiline_and_selection() {
+        let content = "foo food foo_bar\r\nbar.foo\nfoo\nend";
+        let options = SearchOptions { whole_word: true, ..Default::default() };
+        let results = search_in_content(content, "foo", &options).unwrap();
+        let positions: Vec<_> = results.iter().map(|r| (r.line, r.column)).collect();
+        assert_eq!(positions, vec![(1, 1), (2, 5), (3, 1)]);
+        assert_eq!(results[1].context, "bar.foo");
+        
+        let options = SearchOptions { use_regex: true, ..Default::default() };
+        let results = search_in_content(content, r"foo\nfoo$", &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].line, results[0].column, results[0].end_line, results[0].end_column), (2, 5, 3, 4));
+        
+        let options = SearchOptions { use_regex: true, dot_all: true, ..Default::default() };
+        let results = search_in_content(content, r"bar\..*end", &options).unwrap();
+        assert_eq!((results[0].line, results[0].end_line), (2, 4));
+        
+        let selection = SearchRange { start_line: 1, start_column: 5, end_line: 2, end_column: 4 };
+        let options = SearchOptions { selections: vec![selection], ..Default::default() };
+        let results = search_in_content(content, "foo", &options).unwrap();
+        let positions: Vec<_> = results.iter().map(|r| (r.line, r.column)).collect();
+        assert_eq!(positions, vec![(1, 5), (1, 10)]);
+    }
+    
+    #[test]
+    fn test_search_unicode_positions_and_case_folding() {
+        let content = "中文 İstanbul STRASSE straße\n😀foo Foo";
+        let results = search_in_content(content, "Straße", &SearchOptions::default()).unwrap();
+        let positions: Vec<_> = results.iter().map(|r| (r.line, r.column, r.end_column, r.length)).collect();
+        assert_eq!(positions, vec![(1, 13, 20, 7), (1, 21, 27, 6)]);
+        assert_eq!((results[0].byte_start, results[0].char_start), (17, 12));
+        assert_eq!(&content[results[1].byte_start..results[1].byte_end], "straße");
+        
+        // 不匹配一个字符折叠结果的一部分
+        assert!(search_in_content("ß", "s", &SearchOptions::default()).unwrap().is_empty());
+        
+        let results = search_in_content(content, "foo", &SearchOptions::default()).unwrap();
+        let positions: Vec<_> = results.iter().map(|r| (r.line, r.column, r.char_start)).collect();
+        assert_eq!(positions, vec![(2, 3, 28), (2, 7, 32)]);
+        
+        let selection = SearchRange { start_line: 2, start_column: 3, end_line: 2, end_column: 6 };
+        let options = SearchOptions { selections: vec![selection], ..Default::default() };
+        assert_eq!(search_in_content(content, "foo", &options).unwrap().len(), 1);
+    }
+    
     #[test]
     fn test_replace_all() {
         let content = "foo bar foo baz foo";
-        let result = search_and_replace(content, "foo", "qux", true, false, true).unwrap();
+        let options = ReplaceOptions {
+            search: SearchOptions { case_sensitive: true, ..Default::default() },
+            replace_all: true,
+            ..Default::default()
+        };
+        let result = search_and_replace(content, "foo", "qux", &options).unwrap();
         assert_eq!(result, "qux bar qux baz qux");
     }
+    
+    #[test]
+    fn test_replace_preview_and_preserve_case() {
+        let content = "name: Alice\nname: Bob";
+        let options = ReplaceOptions {
+            search: SearchOptions { use_regex: true, ..Default::default() },
+            replace_all: true,
+            ..Default::default()
+        };
+        let previews = preview_replace(content, r"name: (?<who>\w+)", r"${who}=$1", &options).unwrap();
+        let replacements: Vec<_> = previews.iter().map(|p| p.replacement.as_str()).collect();
+        assert_eq!(replacements, vec!["Alice=Alice", "Bob=Bob"]);
+        assert_eq!((previews[1].result.line, previews[1].result.end_column), (2, 10));
+        
+        let options = ReplaceOptions { preserve_case: true, replace_all: true, ..Default::default() };
+        let result = search_and_replace("foo Foo FOO", "foo", "bar", &options).unwrap();
+        assert_eq!(result, "bar Bar BAR");
+        
+        let options = ReplaceOptions::default();
+        assert_eq!(search_and_replace("a, b, c", ", ", r"\n", &options).unwrap(), "a\nb, c");
+    }
+
+    #[test]
+    fn test_regex_replace_keeps_backslashes() {
+        let options = ReplaceOptions {
+            search: SearchOptions { use_regex: true, ..Default::default() },
+            replace_all: true,
+            ..Default::default()
+        };
+        let result = search_and_replace(r"C:\dir\file", r"\\(\w+)", r"\\[$1]\n", &options).unwrap();
+        assert_eq!(result, r"C:\\[dir]\n\\[file]\n");
+    }
 }
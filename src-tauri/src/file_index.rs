@@ -0,0 +1,634 @@
+// File Index Module
+// 工作区文件索引：遵循 .gitignore 的并行遍历，文件监视器增量更新，供“转到文件”（Ctrl+P）模糊搜索
+
+use anyhow::{anyhow, Result};
+use ignore::{WalkBuilder, WalkState};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Config, Matcher, Utf32String};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// 索引构建完成或文件变化后发送
+pub const FILE_INDEX_EVENT: &str = "file-index-updated";
+
+/// 合并文件系统事件的等待时间
+const DEBOUNCE: Duration = Duration::from_millis(150);
+/// 事件持续到达时，最迟在该时间后处理已收集的变化
+const MAX_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// 每个线程至少处理的路径数量，路径较少时不拆分线程
+const PATHS_PER_THREAD: usize = 8192;
+
+/// 一批文件变化：刷新过的目录（相对路径）及是否完整遍历，上层目录在前
+pub(crate) type DirtyDirs = Vec<(String, bool)>;
+
+static INDEXES: OnceLock<Mutex<HashMap<PathBuf, Arc<FileIndex>>>> = OnceLock::new();
+
+/// 索引状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileIndexStatus {
+    pub root: String,
+    pub file_count: usize,
+    /// 首次遍历尚未完成
+    pub indexing: bool,
+}
+
+/// 模糊匹配结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMatch {
+    /// 相对工作区的路径（使用 `/` 分隔）
+    pub path: String,
+    pub score: u32,
+    /// 匹配字符在相对路径中的位置（字符索引）
+    pub positions: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchResult {
+    pub matches: Vec<FileMatch>,
+    /// 匹配的文件总数（结果按 limit 截断前）
+    pub total: usize,
+    pub indexing: bool,
+}
+
+/// 预处理的路径，匹配时无需再转换编码
+pub(crate) struct IndexedPath {
+    path: String,
+    haystack: Utf32String,
+    /// 路径中出现的字符集合，用于快速排除不可能匹配的路径
+    mask: u64,
+}
+
+impl IndexedPath {
+    pub(crate) fn new(path: &str) -> Self {
+        // 含非 ASCII 字符时查询可能经过 Unicode 规范化匹配，不参与过滤
+        let mask = if path.is_ascii() { char_mask(path) } else { u64::MAX };
+        Self { path: path.to_string(), haystack: Utf32String::from(path), mask }
+    }
+}
+
+fn char_bit(c: char) -> u64 {
+    match c.to_ascii_lowercase() {
+        c @ 'a'..='z' => 1 << (c as u32 - 'a' as u32),
+        c @ '0'..='9' => 1 << (26 + c as u32 - '0' as u32),
+        '.' => 1 << 36,
+        '_' => 1 << 37,
+        '-' => 1 << 38,
+        '/' => 1 << 39,
+        _ => 0,
+    }
+}
+
+fn char_mask(text: &str) -> u64 {
+    text.chars().fold(0, |mask, c| mask | char_bit(c))
+}
+
+/// 查询中必须出现的字符（忽略取反的词和 `'`、`^`、`$` 语法）
+fn query_mask(query: &str) -> u64 {
+    query
+        .split_whitespace()
+        .filter(|atom| !atom.starts_with('!'))
+        .map(|atom| char_mask(atom.trim_start_matches(['\'', '^']).trim_end_matches('$')))
+        .fold(0, |mask, atom| mask | atom)
+}
+
+#[derive(Default)]
+struct IndexState {
+    files: BTreeSet<String>,
+    dirs: BTreeSet<String>,
+    /// 用于匹配的文件列表，文件变化后重建
+    snapshot: Option<Arc<Vec<IndexedPath>>>,
+    indexing: bool,
+    /// 首次遍历期间收到的变化，遍历完成后重新处理
+    pending: HashMap<String, bool>,
+}
+
+impl IndexState {
+    fn snapshot(&mut self) -> Arc<Vec<IndexedPath>> {
+        self.snapshot
+            .get_or_insert_with(|| Arc::new(self.files.iter().map(|path| IndexedPath::new(path)).collect()))
+            .clone()
+    }
+
+    fn insert(&mut self, files: Vec<String>, dirs: Vec<String>) {
+        self.files.extend(files);
+        self.dirs.extend(dirs);
+        self.snapshot = None;
+    }
+
+    /// 移除目录下的所有文件和子目录（不含目录本身）
+    fn remove_subtree(&mut self, dir: &str) {
+        for path in descendants(&self.files, dir) {
+            self.files.remove(&path);
+        }
+        for path in descendants(&self.dirs, dir) {
+            self.dirs.remove(&path);
+        }
+        self.snapshot = None;
+    }
+}
+
+/// 目录下的所有路径，利用有序集合按前缀范围查找
+fn descendants(set: &BTreeSet<String>, dir: &str) -> Vec<String> {
+    if dir.is_empty() {
+        return set.iter().cloned().collect();
+    }
+    // '0' 是 '/' 的下一个字符
+    set.range(format!("{}/", dir)..format!("{}0", dir)).cloned().collect()
+}
+
+/// 目录的直接子项
+fn children(set: &BTreeSet<String>, dir: &str) -> HashSet<String> {
+    let prefix_len = if dir.is_empty() { 0 } else { dir.len() + 1 };
+    descendants(set, dir)
+        .into_iter()
+        .filter(|path| !path[prefix_len..].contains('/'))
+        .collect()
+}
+
+struct FileIndex {
+    root: PathBuf,
+    state: RwLock<IndexState>,
+    /// 释放监视器即停止后台更新线程
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    /// 共用本索引监视器的订阅者（如全文索引），接收已过滤掉被忽略目录的变化
+    listeners: Mutex<Vec<mpsc::Sender<DirtyDirs>>>,
+}
+
+impl FileIndex {
+    fn status(&self) -> FileIndexStatus {
+        let state = self.state.read().unwrap();
+        FileIndexStatus {
+            root: self.root.to_string_lossy().to_string(),
+            file_count: state.files.len(),
+            indexing: state.indexing,
+        }
+    }
+
+    fn relative(&self, path: &Path) -> Option<String> {
+        relative_path(&self.root, path)
+    }
+
+    /// 并行遍历目录，返回相对根目录的文件和目录
+    fn walk(&self, start: &Path, max_depth: Option<usize>) -> (Vec<String>, Vec<String>) {
+        let (tx, rx) = mpsc::channel();
+        WalkBuilder::new(start)
+            .hidden(false)
+            .require_git(false)
+            .max_depth(max_depth)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build_parallel()
+            .run(|| {
+                let tx = tx.clone();
+                Box::new(move |entry| {
+                    if let Ok(entry) = entry {
+                        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                        let _ = tx.send((entry.into_path(), is_dir));
+                    }
+                    WalkState::Continue
+                })
+            });
+        drop(tx);
+
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        for (path, is_dir) in rx {
+            if let Some(relative) = self.relative(&path) {
+                if is_dir {
+                    dirs.push(relative);
+                } else {
+                    files.push(relative);
+                }
+            }
+        }
+        (files, dirs)
+    }
+
+    /// 首次遍历，返回遍历期间排队的变化
+    fn build(&self) -> HashMap<String, bool> {
+        let (files, dirs) = self.walk(&self.root, None);
+        self.commit_build(files, dirs)
+    }
+
+    /// 写入首次遍历的结果并结束索引状态，取出遍历期间排队的变化
+    fn commit_build(&self, files: Vec<String>, dirs: Vec<String>) -> HashMap<String, bool> {
+        let mut state = self.state.write().unwrap();
+        state.files.clear();
+        state.dirs.clear();
+        state.insert(files, dirs);
+        state.indexing = false;
+        std::mem::take(&mut state.pending)
+    }
+
+    /// 重新读取目录的直接子项；新出现的子目录完整遍历，消失的子目录整体移除。
+    /// `full` 为 true 时（如 .gitignore 变化）重新遍历整个子树。
+    /// 首次遍历期间的变化排队等待遍历完成，目录未被索引时忽略，两种情况都返回 false
+    fn refresh_dir(&self, dir: &str, full: bool) -> bool {
+        {
+            let mut state = self.state.write().unwrap();
+            if state.indexing {
+                *state.pending.entry(dir.to_string()).or_insert(false) |= full;
+                return false;
+            }
+            // 只刷新已索引的目录：被忽略的目录不在索引中，从其内部遍历会绕过上层的忽略规则
+            if !dir.is_empty() && !state.dirs.contains(dir) {
+                return false;
+            }
+        }
+        let path = self.root.join(dir);
+        if !path.is_dir() {
+            let mut state = self.state.write().unwrap();
+            state.remove_subtree(dir);
+            state.dirs.remove(dir);
+            return true;
+        }
+        if full {
+            let (files, dirs) = self.walk(&path, None);
+            let mut state = self.state.write().unwrap();
+            state.remove_subtree(dir);
+            state.insert(files, dirs);
+            return true;
+        }
+
+        let (files, dirs) = self.walk(&path, Some(1));
+        let files: HashSet<String> = files.into_iter().collect();
+        let dirs: HashSet<String> = dirs.into_iter().filter(|d| d != dir).collect();
+
+        let (removed_files, removed_dirs, added_dirs) = {
+            let state = self.state.read().unwrap();
+            let old_files = children(&state.files, dir);
+            let old_dirs = children(&state.dirs, dir);
+            (
+                old_files.difference(&files).cloned().collect::<Vec<_>>(),
+                old_dirs.difference(&dirs).cloned().collect::<Vec<_>>(),
+                dirs.difference(&old_dirs).cloned().collect::<Vec<_>>(),
+            )
+        };
+        let mut added_files: Vec<String> = files.into_iter().collect();
+        let mut nested_dirs = Vec::new();
+        for added in &added_dirs {
+            let (files, dirs) = self.walk(&self.root.join(added), None);
+            added_files.extend(files);
+            nested_dirs.extend(dirs);
+        }
+
+        let mut state = self.state.write().unwrap();
+        for file in removed_files {
+            state.files.remove(&file);
+        }
+        for removed in removed_dirs {
+            state.remove_subtree(&removed);
+            state.dirs.remove(&removed);
+        }
+        nested_dirs.extend(added_dirs);
+        nested_dirs.push(dir.to_string());
+        state.insert(added_files, nested_dirs);
+        true
+    }
+
+    /// 刷新一批变化，返回实际刷新的目录
+    fn refresh_dirs(&self, dirty: HashMap<String, bool>) -> DirtyDirs {
+        // 先处理上层目录，子目录可能已被上层的完整遍历覆盖，新建的子目录也会先被登记
+        let mut dirs: Vec<_> = dirty.into_iter().collect();
+        dirs.sort();
+        dirs.into_iter().filter(|(dir, full)| self.refresh_dir(dir, *full)).collect()
+    }
+
+    /// 将刷新过的目录发送给订阅者，订阅者已关闭时发送失败，随之移除
+    fn notify(&self, refreshed: DirtyDirs) {
+        if !refreshed.is_empty() {
+            self.listeners.lock().unwrap().retain(|tx| tx.send(refreshed.clone()).is_ok());
+        }
+    }
+}
+
+fn indexes() -> &'static Mutex<HashMap<PathBuf, Arc<FileIndex>>> {
+    INDEXES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_index(root: &str) -> Option<Arc<FileIndex>> {
+    indexes().lock().unwrap().get(Path::new(root)).cloned()
+}
+
+/// 相对根目录的路径（使用 `/` 分隔）
+pub(crate) fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// 将文件系统事件转换为需要刷新的目录（相对路径）及是否需要完整遍历
+fn dirty_dirs(root: &Path, event: &notify::Event, dirty: &mut HashMap<String, bool>) {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    for path in &event.paths {
+        let Some(relative) = relative_path(root, path) else {
+            continue;
+        };
+        if relative.is_empty() || relative == ".git" || relative.starts_with(".git/") || relative.contains("/.git/") {
+            continue;
+        }
+        let parent = relative.rsplit_once('/').map_or("", |(parent, _)| parent).to_string();
+        let is_ignore_file = path
+            .file_name()
+            .is_some_and(|name| name == ".gitignore" || name == ".ignore");
+        let full = dirty.entry(parent).or_insert(false);
+        *full |= is_ignore_file;
+    }
+}
+
+/// 从第一个事件开始收集一批变化：静默 `DEBOUNCE` 后结束，持续有事件时最多等待 `MAX_DEBOUNCE`
+fn collect_dirty(
+    root: &Path,
+    rx: &mpsc::Receiver<notify::Event>,
+    first: notify::Event,
+) -> HashMap<String, bool> {
+    let mut dirty = HashMap::new();
+    dirty_dirs(root, &first, &mut dirty);
+    let deadline = Instant::now() + MAX_DEBOUNCE;
+    loop {
+        let timeout = DEBOUNCE.min(deadline.saturating_duration_since(Instant::now()));
+        if timeout.is_zero() {
+            break;
+        }
+        match rx.recv_timeout(timeout) {
+            Ok(event) => dirty_dirs(root, &event, &mut dirty),
+            Err(_) => break,
+        }
+    }
+    dirty
+}
+
+fn start_watcher(app: AppHandle, index: Arc<FileIndex>) -> Result<()> {
+    let (tx, rx) = mpsc::channel::<notify::Event>();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = tx.send(event);
+        }
+    })?;
+    watcher.watch(&index.root, RecursiveMode::Recursive)?;
+    *index.watcher.lock().unwrap() = Some(watcher);
+
+    let index = Arc::downgrade(&index);
+    std::thread::spawn(move || {
+        // 监视器被释放后通道断开，线程退出
+        while let Ok(event) = rx.recv() {
+            let Some(index) = index.upgrade() else {
+                break;
+            };
+            let dirty = collect_dirty(&index.root, &rx, event);
+            if dirty.is_empty() {
+                continue;
+            }
+            let refreshed = index.refresh_dirs(dirty);
+            let _ = app.emit(FILE_INDEX_EVENT, index.status());
+            index.notify(refreshed);
+        }
+    });
+    Ok(())
+}
+
+/// 为工作区构建文件索引（已存在时直接返回状态），首次遍历在后台进行，完成后发送事件
+pub fn open_index(app: AppHandle, root: &str) -> Result<FileIndexStatus> {
+    let root_path = PathBuf::from(root);
+    if !root_path.is_dir() {
+        return Err(anyhow!("Not a directory: {}", root));
+    }
+    let index = {
+        let mut indexes = indexes().lock().unwrap();
+        if let Some(index) = indexes.get(&root_path) {
+            return Ok(index.status());
+        }
+        let index = Arc::new(FileIndex {
+            root: root_path.clone(),
+            state: RwLock::new(IndexState { indexing: true, ..Default::default() }),
+            watcher: Mutex::new(None),
+            listeners: Mutex::new(Vec::new()),
+        });
+        indexes.insert(root_path, index.clone());
+        index
+    };
+
+    // 先启动监视器，避免遗漏遍历期间的变化
+    if let Err(e) = start_watcher(app.clone(), index.clone()) {
+        log::warn!("File watcher unavailable for {}: {}", root, e);
+    }
+    let status = index.status();
+    std::thread::spawn(move || {
+        let pending = index.build();
+        // 遍历期间的变化可能发生在已遍历过的目录中，遍历完成后重新处理
+        let refreshed = index.refresh_dirs(pending);
+        let _ = app.emit(FILE_INDEX_EVENT, index.status());
+        index.notify(refreshed);
+    });
+    Ok(status)
+}
+
+/// 订阅工作区的文件变化，与文件索引共用一个监视器（索引未打开时先打开）。
+/// 接收端释放后订阅自动取消
+pub(crate) fn subscribe(app: AppHandle, root: &str) -> Result<mpsc::Receiver<DirtyDirs>> {
+    open_index(app, root)?;
+    let index = get_index(root).ok_or_else(|| anyhow!("File index not opened: {}", root))?;
+    let (tx, rx) = mpsc::channel();
+    index.listeners.lock().unwrap().push(tx);
+    Ok(rx)
+}
+
+/// 关闭工作区索引并停止监视
+pub fn close_index(root: &str) {
+    if let Some(index) = indexes().lock().unwrap().remove(Path::new(root)) {
+        index.watcher.lock().unwrap().take();
+    }
+}
+
+pub fn index_status(root: &str) -> Option<FileIndexStatus> {
+    get_index(root).map(|index| index.status())
+}
+
+/// 模糊查找文件，按分数排序（分数相同时路径短的优先）
+pub fn find_files(root: &str, query: &str, limit: usize) -> Result<FileSearchResult> {
+    let index = get_index(root).ok_or_else(|| anyhow!("File index not opened: {}", root))?;
+    let (snapshot, indexing) = {
+        let mut state = index.state.write().unwrap();
+        (state.snapshot(), state.indexing)
+    };
+    let (matches, total) = match_paths(&snapshot, query, limit);
+    Ok(FileSearchResult { matches, total, indexing })
+}
+
+/// 排序键：分数高、路径短、索引靠前的优先
+type RankKey = (u32, Reverse<usize>, Reverse<usize>);
+
+pub(crate) fn match_paths(paths: &[IndexedPath], query: &str, limit: usize) -> (Vec<FileMatch>, usize) {
+    if query.trim().is_empty() {
+        let matches = paths
+            .iter()
+            .take(limit)
+            .map(|p| FileMatch { path: p.path.clone(), score: 0, positions: Vec::new() })
+            .collect();
+        return (matches, paths.len());
+    }
+    let pattern = Pattern::parse(query, CaseMatching::Smart, Normalization::Smart);
+    let required = query_mask(query);
+
+    // 各线程只保留自己的前 limit 个结果，最后合并
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(paths.len() / PATHS_PER_THREAD + 1);
+    let chunk_size = paths.len().div_ceil(threads).max(1);
+    let partials: Vec<(Vec<RankKey>, usize)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                let pattern = &pattern;
+                scope.spawn(move || {
+                    let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+                    let mut top: BinaryHeap<Reverse<RankKey>> = BinaryHeap::with_capacity(limit + 1);
+                    let mut count = 0;
+                    for (i, path) in chunk.iter().enumerate() {
+                        if path.mask & required != required {
+                            continue;
+                        }
+                        if let Some(score) = pattern.score(path.haystack.slice(..), &mut matcher) {
+                            count += 1;
+                            top.push(Reverse((score, Reverse(path.path.len()), Reverse(chunk_index * chunk_size + i))));
+                            if top.len() > limit {
+                                top.pop();
+                            }
+                        }
+                    }
+                    (top.into_iter().map(|Reverse(key)| key).collect(), count)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap_or_default()).collect()
+    });
+
+    let total = partials.iter().map(|(_, count)| count).sum();
+    let mut ranked: Vec<RankKey> = partials.into_iter().flat_map(|(keys, _)| keys).collect();
+    ranked.sort_unstable_by(|a, b| b.cmp(a));
+    ranked.truncate(limit);
+
+    // 只为最终结果计算高亮位置
+    let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+    let matches = ranked
+        .into_iter()
+        .map(|(score, _, Reverse(i))| {
+            let path = &paths[i];
+            let mut positions = Vec::new();
+            pattern.indices(path.haystack.slice(..), &mut matcher, &mut positions);
+            positions.sort_unstable();
+            positions.dedup();
+            FileMatch { path: path.path.clone(), score, positions }
+        })
+        .collect();
+    (matches, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_paths_ranking() {
+        let paths: Vec<IndexedPath> = ["src/main.rs", "src/components/Editor.tsx", "docs/main/README.md", "src/store/index.ts"]
+            .iter()
+            .map(|s| IndexedPath::new(s))
+            .collect();
+        let (matches, total) = match_paths(&paths, "main", 10);
+        assert_eq!(total, 2);
+        assert_eq!(matches[0].path, "src/main.rs");
+        assert_eq!(matches[0].positions, vec![4, 5, 6, 7]);
+
+        let (matches, _) = match_paths(&paths, "edtsx", 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, "src/components/Editor.tsx");
+
+        let (matches, total) = match_paths(&paths, "", 2);
+        assert_eq!((matches.len(), total), (2, 4));
+    }
+
+    #[test]
+    fn test_walk_and_refresh() {
+        let root = std::env::temp_dir().join(format!("kaironotes-index-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("src/nested/lib.rs"), "").unwrap();
+        std::fs::write(root.join("target/out.bin"), "").unwrap();
+        std::fs::write(root.join("debug.log"), "").unwrap();
+
+        let index = FileIndex {
+            root: root.clone(),
+            state: RwLock::default(),
+            watcher: Mutex::new(None),
+            listeners: Mutex::new(Vec::new()),
+        };
+        index.build();
+        let files = |index: &FileIndex| index.state.read().unwrap().files.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(files(&index), vec![".gitignore", "src/main.rs", "src/nested/lib.rs"]);
+
+        // 新增目录、删除文件
+        std::fs::create_dir_all(root.join("src/new/deep")).unwrap();
+        std::fs::write(root.join("src/new/deep/a.rs"), "").unwrap();
+        std::fs::write(root.join("src/skip.log"), "").unwrap();
+        std::fs::remove_file(root.join("src/main.rs")).unwrap();
+        index.refresh_dir("src", false);
+        assert_eq!(files(&index), vec![".gitignore", "src/nested/lib.rs", "src/new/deep/a.rs"]);
+
+        // 被忽略目录中的变化不会从目录内部遍历
+        std::fs::write(root.join("target/new.bin"), "").unwrap();
+        assert!(!index.refresh_dir("target", false));
+        assert_eq!(files(&index), vec![".gitignore", "src/nested/lib.rs", "src/new/deep/a.rs"]);
+
+        // 删除目录
+        std::fs::remove_dir_all(root.join("src/nested")).unwrap();
+        index.refresh_dir("src/nested", false);
+        assert_eq!(files(&index), vec![".gitignore", "src/new/deep/a.rs"]);
+
+        // .gitignore 变化后重新遍历
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        index.refresh_dir("", true);
+        assert_eq!(files(&index), vec![".gitignore", "src/new/deep/a.rs", "target/new.bin", "target/out.bin"]);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_changes_during_build() {
+        let root = std::env::temp_dir().join(format!("kaironotes-index-build-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src/sub")).unwrap();
+        std::fs::write(root.join("src/sub/a.rs"), "").unwrap();
+
+        let index = FileIndex {
+            root: root.clone(),
+            state: RwLock::new(IndexState { indexing: true, ..Default::default() }),
+            watcher: Mutex::new(None),
+            listeners: Mutex::new(Vec::new()),
+        };
+        // 子目录已被遍历后才创建文件，事件在遍历完成前到达
+        let (files, dirs) = index.walk(&root, None);
+        std::fs::write(root.join("src/sub/b.rs"), "").unwrap();
+        assert!(!index.refresh_dir("src/sub", false));
+
+        let pending = index.commit_build(files, dirs);
+        assert_eq!(index.refresh_dirs(pending), vec![("src/sub".to_string(), false)]);
+        let files = index.state.read().unwrap().files.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(files, vec!["src/sub/a.rs", "src/sub/b.rs"]);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -1,16 +1,21 @@
 // File Operations Module
 
 use crate::commands::{DirectoryEntry, FileInfo};
-use crate::syntax;
-use anyhow::{Context, Result};
+use crate::{hex, large_file, syntax};
+use anyhow::{bail, Context, Result};
 use encoding_rs::*;
 use std::path::Path;
 use chrono::{DateTime, Local};
+use tokio::io::AsyncReadExt;
 
 pub async fn read_file_content(path: &str) -> Result<String> {
     let bytes = tokio::fs::read(path)
         .await
         .with_context(|| format!("Failed to read file: {}", path))?;
+    // 二进制文件按文本解码会得到乱码，保存后会损坏文件，应使用十六进制编辑器打开
+    if hex::is_binary(&bytes[..bytes.len().min(ENCODING_SAMPLE_SIZE)]) {
+        bail!("Binary file cannot be opened as text: {}", path);
+    }
     
     // Try to detect encoding and decode
     let (content, _, _) = UTF_8.decode(&bytes);
@@ -57,7 +62,8 @@ pub async fn get_file_info(path: &str) -> Result<FileInfo> {
         datetime.format("%Y-%m-%d %H:%M:%S").to_string()
     });
     
-    let encoding = detect_encoding(path).await.unwrap_or_else(|_| "UTF-8".to_string());
+    let sample = if metadata.is_file() { read_sample(path).await.unwrap_or_default() } else { Vec::new() };
+    let encoding = detect_encoding_from_sample(&sample, sample.len() < ENCODING_SAMPLE_SIZE).to_string();
     let language = syntax::detect_language(path, None);
     
     Ok(FileInfo {
@@ -71,6 +77,8 @@ pub async fn get_file_info(path: &str) -> Result<FileInfo> {
         created,
         encoding,
         language,
+        is_large: metadata.is_file() && metadata.len() >= large_file::LARGE_FILE_THRESHOLD,
+        is_binary: hex::is_binary(&sample),
     })
 }
 
@@ -148,40 +156,63 @@ pub async fn list_directory(path: &str) -> Result<Vec<DirectoryEntry>> {
     Ok(entries)
 }
 
+/// 编码检测只读取文件开头的样本
+pub const ENCODING_SAMPLE_SIZE: usize = 64 * 1024;
+
 pub async fn detect_encoding(path: &str) -> Result<String> {
-    let bytes = tokio::fs::read(path).await?;
-    
-    // Check BOM
-    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
-        return Ok("UTF-8".to_string());
+    let sample = read_sample(path).await?;
+    Ok(detect_encoding_from_sample(&sample, sample.len() < ENCODING_SAMPLE_SIZE).to_string())
+}
+
+/// 读取文件开头最多 `ENCODING_SAMPLE_SIZE` 字节
+async fn read_sample(path: &str) -> Result<Vec<u8>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut sample = vec![0; ENCODING_SAMPLE_SIZE];
+    let mut len = 0;
+    while len < sample.len() {
+        let read = file.read(&mut sample[len..]).await?;
+        if read == 0 {
+            break;
+        }
+        len += read;
     }
-    if bytes.starts_with(&[0xFF, 0xFE]) {
-        return Ok("UTF-16LE".to_string());
+    sample.truncate(len);
+    Ok(sample)
+}
+
+/// 从文件开头的样本检测编码；`complete` 为 false 时允许样本末尾有被截断的多字节字符
+pub fn detect_encoding_from_sample(sample: &[u8], complete: bool) -> &'static str {
+    // Check BOM
+    if sample.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return "UTF-8";
     }
-    if bytes.starts_with(&[0xFE, 0xFF]) {
-        return Ok("UTF-16BE".to_string());
+    if sample.starts_with(&[0xFF, 0xFE]) {
+        return "UTF-16LE";
     }
-    
-    // Try UTF-8 first
-    if String::from_utf8(bytes.clone()).is_ok() {
-        return Ok("UTF-8".to_string());
+    if sample.starts_with(&[0xFE, 0xFF]) {
+        return "UTF-16BE";
     }
     
-    // Try other encodings
-    for (encoding, name) in &[
-        (GBK as &'static Encoding, "GBK"),
+    for (encoding, name) in [
+        (UTF_8, "UTF-8"),
+        (GBK, "GBK"),
         (GB18030, "GB18030"),
         (SHIFT_JIS, "Shift_JIS"),
         (EUC_KR, "EUC-KR"),
         (WINDOWS_1252, "Windows-1252"),
     ] {
-        let (_, _, had_errors) = encoding.decode(&bytes);
-        if !had_errors {
-            return Ok(name.to_string());
+        let mut decoder = encoding.new_decoder_without_bom_handling();
+        let capacity = decoder
+            .max_utf8_buffer_length_without_replacement(sample.len())
+            .unwrap_or(sample.len() * 3);
+        let mut decoded = String::with_capacity(capacity);
+        let (result, _) = decoder.decode_to_string_without_replacement(sample, &mut decoded, complete);
+        if result == DecoderResult::InputEmpty {
+            return name;
         }
     }
     
-    Ok("UTF-8".to_string())
+    "UTF-8"
 }
 
 pub fn convert_encoding(content: &str, _from: &str, to: &str) -> Result<String> {
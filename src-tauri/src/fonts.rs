@@ -44,7 +44,7 @@ fn get_system_font_dirs() -> Vec<PathBuf> {
         dirs.push(PathBuf::from("/usr/share/fonts"));
         dirs.push(PathBuf::from("/usr/local/share/fonts"));
         if let Ok(home) = std::env::var("HOME") {
-            dirs.push(PathBuf::from(home).join(".fonts"));
+            dirs.push(PathBuf::from(&home).join(".fonts"));
             dirs.push(PathBuf::from(home).join(".local/share/fonts"));
         }
     }
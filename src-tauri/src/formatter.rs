@@ -0,0 +1,559 @@
+// Formatter Module
+// 外部格式化工具注册表：按语言选择格式化程序，通过标准输入输出格式化文档内容
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::data_format::{self, DataFormat, DataFormatOptions};
+use crate::syntax;
+use crate::system_integration;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// 默认格式化超时时间
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// 内置格式化程序（JSON、TOML、YAML、XML）的 ID，可在工作区语言映射中使用
+pub const BUILTIN_FORMATTER_ID: &str = "builtin";
+
+/// 外部格式化程序配置（内置或用户配置目录下的 formatters.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatterConfig {
+    pub id: String,
+    pub command: String,
+    /// 参数中可使用 `${file}`（文件路径，未保存的文档为按语言生成的占位文件名）和 `${workspaceFolder}`
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 支持的语言 ID（与 detect_language 的结果一致）
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// 用户格式化配置（配置目录下的 formatters.json），可以添加新的外部程序
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFormatters {
+    /// 额外的格式化程序，同 ID 时覆盖内置配置
+    #[serde(default)]
+    pub formatters: Vec<FormatterConfig>,
+    /// 语言到格式化程序 ID 的映射，值为 null 时禁用该语言的格式化
+    #[serde(default)]
+    pub languages: HashMap<String, Option<String>>,
+}
+
+/// 工作区对已有格式化程序的调整，不能指定命令
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatterOverride {
+    pub id: String,
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    #[serde(default)]
+    pub languages: Option<Vec<String>>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// 工作区格式化配置（<文件夹>/.kaironotes/formatters.json）。
+/// 工作区来自克隆的仓库等不受信任的来源，只能调整内置和用户配置中已有的格式化程序，
+/// 其中的 `command` 字段会被忽略
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFormatters {
+    /// 按 ID 调整已有格式化程序的参数、语言和超时时间，未知 ID 被忽略
+    #[serde(default)]
+    pub formatters: Vec<FormatterOverride>,
+    /// 语言到格式化程序 ID 的映射，覆盖用户映射，值为 null 时禁用该语言的格式化
+    #[serde(default)]
+    pub languages: HashMap<String, Option<String>>,
+}
+
+/// 格式化结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattedDocument {
+    pub text: String,
+    pub formatter: String,
+    /// 内容是否发生变化
+    pub changed: bool,
+    /// 格式化过程中丢失的信息（如注释）
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// 格式化错误，序列化后前端可以根据 kind 区分处理
+#[derive(Debug, Clone, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FormatError {
+    #[error("No formatter configured for {language}")]
+    NoFormatter { language: String },
+    #[error("Formatter {formatter} is not installed: {message}")]
+    #[serde(rename_all = "camelCase")]
+    NotFound { formatter: String, message: String },
+    #[error("Formatter {formatter} timed out after {timeout_ms} ms")]
+    #[serde(rename_all = "camelCase")]
+    Timeout { formatter: String, timeout_ms: u64 },
+    #[error("Formatter {formatter} failed: {stderr}")]
+    #[serde(rename_all = "camelCase")]
+    Failed { formatter: String, exit_code: Option<i32>, stderr: String },
+    #[error("{message}")]
+    Other { message: String },
+}
+
+impl FormatError {
+    fn other(error: impl std::fmt::Display) -> Self {
+        FormatError::Other { message: error.to_string() }
+    }
+}
+
+/// 内置格式化程序配置，需要用户自行安装对应工具
+pub fn builtin_formatters() -> Vec<FormatterConfig> {
+    let formatter = |id: &str, command: &str, args: &[&str], languages: &[&str]| FormatterConfig {
+        id: id.to_string(),
+        command: command.to_string(),
+        args: args.iter().map(|s| s.to_string()).collect(),
+        languages: languages.iter().map(|s| s.to_string()).collect(),
+        timeout_ms: None,
+    };
+
+    vec![
+        formatter("rustfmt", "rustfmt", &["--edition", "2021"], &["rust"]),
+        formatter(
+            "prettier",
+            "prettier",
+            &["--stdin-filepath", "${file}"],
+            &[
+                "javascript",
+                "typescript",
+                "javascriptreact",
+                "typescriptreact",
+                "css",
+                "scss",
+                "less",
+                "html",
+                "vue",
+                "markdown",
+                "graphql",
+                "json",
+                "yaml",
+            ],
+        ),
+        formatter("black", "black", &["--quiet", "-"], &["python"]),
+        formatter("clang-format", "clang-format", &["--assume-filename=${file}"], &["c", "cpp"]),
+        formatter("shfmt", "shfmt", &["-filename", "${file}"], &["shell"]),
+        formatter("taplo", "taplo", &["format", "-"], &["toml"]),
+    ]
+}
+
+fn get_user_formatters_path() -> anyhow::Result<PathBuf> {
+    let config_dir = system_integration::get_config_dir()?;
+    Ok(config_dir.join("formatters.json"))
+}
+
+/// 读取用户格式化配置，文件不存在时返回默认值
+pub async fn load_user_formatters() -> anyhow::Result<UserFormatters> {
+    let path = get_user_formatters_path()?;
+    if !path.exists() {
+        return Ok(UserFormatters::default());
+    }
+    let content = tokio::fs::read_to_string(&path).await?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid formatter config {}: {}", path.display(), e))
+}
+
+fn get_workspace_formatters_path(folder: &str) -> PathBuf {
+    Path::new(folder).join(".kaironotes").join("formatters.json")
+}
+
+/// 读取工作区格式化配置，文件不存在时返回默认值
+pub async fn load_workspace_formatters(folder: &str) -> anyhow::Result<WorkspaceFormatters> {
+    let path = get_workspace_formatters_path(folder);
+    if !path.exists() {
+        return Ok(WorkspaceFormatters::default());
+    }
+    let content = tokio::fs::read_to_string(&path).await?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid formatter config {}: {}", path.display(), e))
+}
+
+/// 合并格式化配置：用户配置中的格式化程序优先于内置配置，工作区只调整已有程序的参数
+fn merge_formatters(user: Vec<FormatterConfig>, overrides: &[FormatterOverride]) -> Vec<FormatterConfig> {
+    let mut formatters = user;
+    for builtin in builtin_formatters() {
+        if !formatters.iter().any(|f| f.id == builtin.id) {
+            formatters.push(builtin);
+        }
+    }
+    for item in overrides {
+        let Some(formatter) = formatters.iter_mut().find(|f| f.id == item.id) else {
+            continue;
+        };
+        if let Some(args) = &item.args {
+            formatter.args = args.clone();
+        }
+        if let Some(languages) = &item.languages {
+            formatter.languages = languages.clone();
+        }
+        if item.timeout_ms.is_some() {
+            formatter.timeout_ms = item.timeout_ms;
+        }
+    }
+    formatters
+}
+
+/// 合并后的格式化程序和语言映射
+struct ResolvedFormatters {
+    formatters: Vec<FormatterConfig>,
+    languages: HashMap<String, Option<String>>,
+}
+
+async fn resolve_formatters(workspace: Option<&str>) -> anyhow::Result<ResolvedFormatters> {
+    let user = load_user_formatters().await?;
+    let config = match workspace {
+        Some(folder) => load_workspace_formatters(folder).await?,
+        None => WorkspaceFormatters::default(),
+    };
+    let mut languages = user.languages;
+    languages.extend(config.languages);
+    Ok(ResolvedFormatters {
+        formatters: merge_formatters(user.formatters, &config.formatters),
+        languages,
+    })
+}
+
+/// 获取可用的格式化程序（用户配置 + 内置，应用工作区调整）
+pub async fn get_formatters(workspace: Option<&str>) -> anyhow::Result<Vec<FormatterConfig>> {
+    Ok(resolve_formatters(workspace).await?.formatters)
+}
+
+/// 语言对应的格式化方式
+#[derive(Debug, Clone)]
+enum Selection {
+    External(FormatterConfig),
+    Builtin(DataFormat),
+    Disabled,
+}
+
+/// 选择语言对应的格式化程序：优先使用用户和工作区中的语言映射，其次是第一个支持该语言的外部程序，
+/// 最后是内置格式化
+fn select_formatter(
+    formatters: &[FormatterConfig],
+    mapping: &HashMap<String, Option<String>>,
+    language: &str,
+    builtin: Option<DataFormat>,
+) -> Selection {
+    match mapping.get(language) {
+        Some(Some(id)) if id == BUILTIN_FORMATTER_ID => {
+            builtin.map_or(Selection::Disabled, Selection::Builtin)
+        }
+        Some(Some(id)) => formatters
+            .iter()
+            .find(|f| &f.id == id)
+            .cloned()
+            .map_or(Selection::Disabled, Selection::External),
+        Some(None) => Selection::Disabled,
+        None => match formatters.iter().find(|f| f.languages.iter().any(|l| l == language)) {
+            Some(formatter) => Selection::External(formatter.clone()),
+            None => builtin.map_or(Selection::Disabled, Selection::Builtin),
+        },
+    }
+}
+
+async fn find_formatter(
+    language: &str,
+    builtin: Option<DataFormat>,
+    workspace: Option<&str>,
+) -> anyhow::Result<Selection> {
+    let resolved = resolve_formatters(workspace).await?;
+    Ok(select_formatter(&resolved.formatters, &resolved.languages, language, builtin))
+}
+
+/// 未保存文档的占位文件名，格式化程序根据扩展名推断语法
+fn placeholder_file(language: &str) -> String {
+    let extension = match language {
+        "javascript" => "js",
+        "typescript" => "ts",
+        "javascriptreact" => "jsx",
+        "typescriptreact" => "tsx",
+        "markdown" => "md",
+        "python" => "py",
+        "rust" => "rs",
+        "shell" => "sh",
+        "plaintext" => "txt",
+        other => other,
+    };
+    format!("untitled.{}", extension)
+}
+
+fn expand_args(args: &[String], file: Option<&str>, language: &str, workspace: &str) -> Vec<String> {
+    let file = file.map_or_else(|| placeholder_file(language), str::to_string);
+    args.iter()
+        .map(|arg| arg.replace("${file}", &file).replace("${workspaceFolder}", workspace))
+        .collect()
+}
+
+/// Windows 上 npm 安装的工具是 .cmd 脚本，需要补全扩展名才能直接启动
+#[cfg(windows)]
+fn resolve_program(command: &str) -> PathBuf {
+    let path = Path::new(command);
+    if path.extension().is_some() || path.components().count() > 1 {
+        return path.to_path_buf();
+    }
+    let extensions = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    if let Some(paths) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&paths) {
+            for ext in extensions.split(';').filter(|e| !e.is_empty()) {
+                let candidate = dir.join(format!("{}{}", command, ext.to_lowercase()));
+                if candidate.is_file() {
+                    return candidate;
+                }
+            }
+        }
+    }
+    path.to_path_buf()
+}
+
+#[cfg(not(windows))]
+fn resolve_program(command: &str) -> PathBuf {
+    PathBuf::from(command)
+}
+
+/// 使用外部格式化程序格式化内容：内容通过标准输入传入，从标准输出读取结果
+pub async fn run_formatter(
+    formatter: &FormatterConfig,
+    content: &str,
+    path: Option<&str>,
+    language: &str,
+    workspace: Option<&str>,
+) -> Result<FormattedDocument, FormatError> {
+    let args = expand_args(&formatter.args, path, language, workspace.unwrap_or_default());
+
+    let mut cmd = tokio::process::Command::new(resolve_program(&formatter.command));
+    cmd.args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    // 在文件所在目录运行，使格式化程序能找到项目中的配置文件
+    let dir = path
+        .and_then(|p| Path::new(p).parent())
+        .filter(|p| p.is_dir())
+        .or(workspace.map(Path::new));
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    let mut child = cmd.spawn().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => FormatError::NotFound {
+            formatter: formatter.id.clone(),
+            message: e.to_string(),
+        },
+        _ => FormatError::other(e),
+    })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = content.to_string();
+        tokio::spawn(async move {
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let timeout_ms = formatter.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    // 超时后 child 被丢弃，kill_on_drop 会结束进程
+    let output = tokio::time::timeout(Duration::from_millis(timeout_ms), child.wait_with_output())
+        .await
+        .map_err(|_| FormatError::Timeout { formatter: formatter.id.clone(), timeout_ms })?
+        .map_err(FormatError::other)?;
+
+    if !output.status.success() {
+        return Err(FormatError::Failed {
+            formatter: formatter.id.clone(),
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    let text = String::from_utf8(output.stdout).map_err(FormatError::other)?;
+    Ok(FormattedDocument {
+        changed: text != content,
+        text,
+        formatter: formatter.id.clone(),
+        warnings: Vec::new(),
+    })
+}
+
+fn run_builtin(
+    format: DataFormat,
+    content: &str,
+    options: &DataFormatOptions,
+) -> Result<FormattedDocument, FormatError> {
+    let result = data_format::format_data(content, format, options).map_err(FormatError::other)?;
+    Ok(FormattedDocument {
+        changed: result.text != content,
+        text: result.text,
+        formatter: BUILTIN_FORMATTER_ID.to_string(),
+        warnings: result.warnings,
+    })
+}
+
+/// 格式化文档：根据语言（未指定时按路径检测）选择格式化程序。
+/// 数据格式的外部程序未安装时回退到内置格式化，`options` 仅用于内置格式化
+pub async fn format_document(
+    content: &str,
+    path: Option<&str>,
+    language_id: Option<&str>,
+    workspace: Option<&str>,
+    options: &DataFormatOptions,
+) -> Result<FormattedDocument, FormatError> {
+    let language = match language_id {
+        Some(language) => language.to_string(),
+        None => syntax::detect_language(path.unwrap_or_default(), Some(content)),
+    };
+
+    let builtin = DataFormat::detect(&language, path);
+
+    match find_formatter(&language, builtin, workspace).await.map_err(FormatError::other)? {
+        Selection::External(formatter) => {
+            let result = run_formatter(&formatter, content, path, &language, workspace).await;
+            match (result, builtin) {
+                (Err(FormatError::NotFound { .. }), Some(format)) => {
+                    run_builtin(format, content, options)
+                }
+                (result, _) => result,
+            }
+        }
+        Selection::Builtin(format) => run_builtin(format, content, options),
+        Selection::Disabled => Err(FormatError::NoFormatter { language }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_formatter() {
+        let formatters = builtin_formatters();
+        let mut mapping = HashMap::new();
+        let select = |mapping: &HashMap<String, Option<String>>, language: &str| {
+            match select_formatter(&formatters, mapping, language, DataFormat::from_language(language)) {
+                Selection::External(f) => f.id,
+                Selection::Builtin(_) => BUILTIN_FORMATTER_ID.to_string(),
+                Selection::Disabled => String::new(),
+            }
+        };
+        assert_eq!(select(&mapping, "rust"), "rustfmt");
+        assert_eq!(select(&mapping, "xml"), "builtin");
+        assert_eq!(select(&mapping, "plaintext"), "");
+
+        mapping.insert("rust".to_string(), None);
+        mapping.insert("json".to_string(), Some("taplo".to_string()));
+        mapping.insert("yaml".to_string(), Some("builtin".to_string()));
+        assert_eq!(select(&mapping, "rust"), "");
+        assert_eq!(select(&mapping, "json"), "taplo");
+        assert_eq!(select(&mapping, "yaml"), "builtin");
+    }
+
+    #[test]
+    fn test_expand_args_without_path() {
+        let args: Vec<String> = ["--stdin-filepath", "${file}", "--root=${workspaceFolder}"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            expand_args(&args, None, "typescript", "/ws"),
+            ["--stdin-filepath", "untitled.ts", "--root=/ws"]
+        );
+        assert_eq!(expand_args(&args[..2], None, "cpp", ""), ["--stdin-filepath", "untitled.cpp"]);
+        assert_eq!(
+            expand_args(&args[..2], Some("/ws/src/main.rs"), "rust", ""),
+            ["--stdin-filepath", "/ws/src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn test_workspace_overrides_cannot_add_commands() {
+        let overrides: Vec<FormatterOverride> = serde_json::from_str(
+            r#"[
+                {"id": "evil", "command": "sh", "languages": ["plaintext"]},
+                {"id": "black", "command": "sh", "args": ["-q", "-"], "timeoutMs": 1000}
+            ]"#,
+        )
+        .unwrap();
+        let formatters = merge_formatters(Vec::new(), &overrides);
+        assert!(formatters.iter().all(|f| f.id != "evil"));
+        let black = formatters.iter().find(|f| f.id == "black").unwrap();
+        assert_eq!(black.command, "black");
+        assert_eq!(black.args, ["-q", "-"]);
+        assert_eq!(black.timeout_ms, Some(1000));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_formatter() {
+        let formatter = |command: &str, args: &[&str]| FormatterConfig {
+            id: command.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|s| s.to_string()).collect(),
+            languages: Vec::new(),
+            timeout_ms: Some(500),
+        };
+
+        let result = run_formatter(&formatter("tr", &["a-z", "A-Z"]), "abc", None, "plaintext", None).await;
+        assert_eq!(result.unwrap().text, "ABC");
+
+        let result = run_formatter(&formatter("sh", &["-c", "echo oops >&2; exit 3"]), "", None, "plaintext", None).await;
+        assert!(matches!(result, Err(FormatError::Failed { exit_code: Some(3), ref stderr, .. }) if stderr == "oops"));
+
+        let result = run_formatter(&formatter("sleep", &["5"]), "", None, "plaintext", None).await;
+        assert!(matches!(result, Err(FormatError::Timeout { .. })));
+
+        let result = run_formatter(&formatter("kairo-missing-formatter", &[]), "", None, "plaintext", None).await;
+        assert!(matches!(result, Err(FormatError::NotFound { .. })));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_untrusted_workspace_command_not_spawned() {
+        let dir = std::env::temp_dir().join(format!("kairo-formatter-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(".kaironotes")).unwrap();
+        let marker = dir.join("spawned");
+        let config = serde_json::json!({
+            "formatters": [
+                {
+                    "id": "evil",
+                    "command": "sh",
+                    "args": ["-c", format!("touch {}", marker.display())],
+                    "languages": ["plaintext"]
+                },
+                {"id": "rustfmt", "command": "sh", "args": ["-c", format!("touch {}", marker.display())]}
+            ],
+            "languages": {"plaintext": "evil"}
+        });
+        std::fs::write(dir.join(".kaironotes").join("formatters.json"), config.to_string()).unwrap();
+        let workspace = dir.to_string_lossy().to_string();
+
+        let options = DataFormatOptions::default();
+        let result = format_document("text", None, Some("plaintext"), Some(&workspace), &options).await;
+        assert!(matches!(result, Err(FormatError::NoFormatter { .. })));
+        // rustfmt 的参数被工作区替换，但仍然运行内置命令而不是 sh
+        let _ = format_document("fn main() {}", None, Some("rust"), Some(&workspace), &options).await;
+        assert!(!marker.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,908 @@
+// Full Text Module
+// 笔记库全文索引：基于 tantivy 的磁盘索引（位于缓存目录），支持中日文分词、短语/布尔/前缀查询，
+// 与文件索引共用文件监视器增量更新
+
+use crate::file_index::{self, relative_path};
+use anyhow::{anyhow, Result};
+use ignore::{WalkBuilder, WalkState};
+use jieba_rs::{Jieba, TokenizeMode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, UNIX_EPOCH};
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::{
+    AllQuery, BooleanQuery, BoostQuery, Occur, PhrasePrefixQuery, PhraseQuery, Query, RegexQuery, TermQuery,
+};
+use tantivy::schema::{
+    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED, STRING,
+};
+use tantivy::tokenizer::{RemoveLongFilter, TextAnalyzer, Token, TokenStream, Tokenizer};
+use tantivy::directory::error::OpenReadError;
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, TantivyError, Term};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 索引构建完成或文件变化后发送
+pub const FULL_TEXT_EVENT: &str = "full-text-index-updated";
+
+/// 索引格式变化时递增，旧索引会被重建
+const INDEX_VERSION: u32 = 1;
+const TOKENIZER: &str = "kairo_cjk";
+const WRITER_MEMORY: usize = 50_000_000;
+/// 索引目录被锁定（如刚关闭的索引仍在释放写入器）时的重试次数和间隔
+const LOCK_RETRIES: usize = 10;
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// 超过此大小的文件不建立索引
+const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+/// 建立索引的笔记和文本文件扩展名（小写）
+const NOTE_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "txt", "text", "org", "rst", "adoc", "asciidoc", "wiki"];
+/// 首次构建时每索引多少个文件提交一次，使结果尽早可搜索
+const COMMIT_INTERVAL: usize = 500;
+const MAX_SNIPPETS: usize = 3;
+const SNIPPET_CHARS: usize = 160;
+/// 长行截取片段时保留在第一个匹配前的字符数
+const SNIPPET_CONTEXT: usize = 40;
+
+static JIEBA: OnceLock<Jieba> = OnceLock::new();
+static INDEXES: OnceLock<Mutex<HashMap<PathBuf, Arc<FullTextIndex>>>> = OnceLock::new();
+
+/// 索引状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullTextStatus {
+    pub root: String,
+    pub document_count: usize,
+    /// 首次构建尚未完成
+    pub indexing: bool,
+}
+
+/// 匹配所在行的片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSnippet {
+    /// 行号（从 1 开始）
+    pub line: u32,
+    /// 第一个匹配的列（从 1 开始，UTF-16）
+    pub column: u32,
+    pub text: String,
+    /// 高亮范围 [start, end)，为 text 内的 UTF-16 偏移
+    pub highlights: Vec<[u32; 2]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteHit {
+    /// 相对根目录的路径（使用 `/` 分隔）
+    pub path: String,
+    pub score: f32,
+    /// 按匹配词数量排序
+    pub snippets: Vec<NoteSnippet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSearchResult {
+    pub hits: Vec<NoteHit>,
+    /// 匹配的文档总数（结果按 limit 截断前）
+    pub total: usize,
+    pub indexing: bool,
+}
+
+// ============================================================================
+// 分词
+// ============================================================================
+
+fn jieba() -> &'static Jieba {
+    JIEBA.get_or_init(Jieba::new)
+}
+
+/// 汉字和日文假名需要分词，其余文字（含韩文）按空白和标点切分
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
+
+/// 将文本切分为小写词元：字母数字连续段为一个词，中日文段由 jieba 分词。
+/// `Search` 模式（建立索引时）额外输出长词中的短词，与长词位于同一位置，
+/// 使“全文”能匹配到“全文索引”而不影响短语查询
+fn tokenize(text: &str, mode: TokenizeMode) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let cjk = is_cjk(c);
+        if !cjk && !c.is_alphanumeric() {
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, next)) = chars.peek() {
+            if is_cjk(next) != cjk || !next.is_alphanumeric() {
+                break;
+            }
+            end = i + next.len_utf8();
+            chars.next();
+        }
+        let run = &text[start..end];
+        if cjk {
+            // jieba 返回字符偏移，转换为字节偏移
+            let offsets: Vec<usize> = run.char_indices().map(|(i, _)| i).chain([run.len()]).collect();
+            let words = jieba().tokenize(run, TokenizeMode::Default, true);
+            let mut word = 0;
+            for token in jieba().tokenize(run, mode, true) {
+                while token.start >= words[word].end {
+                    word += 1;
+                }
+                tokens.push(Token {
+                    offset_from: start + offsets[token.start],
+                    offset_to: start + offsets[token.end],
+                    position: position + word,
+                    text: token.word.to_string(),
+                    position_length: 1,
+                });
+            }
+            position += words.len();
+        } else {
+            tokens.push(Token {
+                offset_from: start,
+                offset_to: end,
+                position,
+                text: run.to_lowercase(),
+                position_length: 1,
+            });
+            position += 1;
+        }
+    }
+    tokens
+}
+
+#[derive(Clone)]
+struct CjkTokenizer;
+
+struct VecTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Tokenizer for CjkTokenizer {
+    type TokenStream<'a> = VecTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> VecTokenStream {
+        VecTokenStream { tokens: tokenize(text, TokenizeMode::Search), index: 0 }
+    }
+}
+
+impl TokenStream for VecTokenStream {
+    fn advance(&mut self) -> bool {
+        self.index += 1;
+        self.index <= self.tokens.len()
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
+
+// ============================================================================
+// 查询
+// ============================================================================
+
+/// 查询中的一项：单词或引号短语，`*` 结尾表示前缀，`-` 或 `NOT` 表示排除
+#[derive(Debug, Default, PartialEq)]
+struct Clause {
+    text: String,
+    prefix: bool,
+    negated: bool,
+}
+
+/// 解析查询：各项默认为 AND 关系，`OR` 分隔多组
+fn parse_query(input: &str) -> Vec<Vec<Clause>> {
+    let mut groups = vec![Vec::new()];
+    let mut chars = input.chars().peekable();
+    let mut negate_next = false;
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut clause = Clause { negated: std::mem::take(&mut negate_next), ..Default::default() };
+        if c == '-' {
+            clause.negated = true;
+            chars.next();
+        }
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                clause.text.push(c);
+            }
+            if chars.peek() == Some(&'*') {
+                clause.prefix = true;
+                chars.next();
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                clause.text.push(c);
+                chars.next();
+            }
+            if !clause.negated {
+                match clause.text.as_str() {
+                    "OR" => {
+                        if groups.last().is_some_and(|g| !g.is_empty()) {
+                            groups.push(Vec::new());
+                        }
+                        continue;
+                    }
+                    "AND" => continue,
+                    "NOT" => {
+                        negate_next = true;
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(text) = clause.text.strip_suffix('*') {
+                clause.text = text.to_string();
+                clause.prefix = true;
+            }
+        }
+        groups.last_mut().unwrap().push(clause);
+    }
+    groups.retain(|g| !g.is_empty());
+    groups
+}
+
+fn field_query(field: Field, terms: &[String], prefix: bool) -> Result<Box<dyn Query>> {
+    let to_terms = || terms.iter().map(|t| Term::from_field_text(field, t)).collect::<Vec<_>>();
+    Ok(match (terms.len(), prefix) {
+        (1, false) => Box::new(TermQuery::new(Term::from_field_text(field, &terms[0]), IndexRecordOption::WithFreqs)),
+        (1, true) => Box::new(RegexQuery::from_pattern(&format!("{}.*", regex::escape(&terms[0])), field)?),
+        (_, false) => Box::new(PhraseQuery::new(to_terms())),
+        (_, true) => Box::new(PhrasePrefixQuery::new(to_terms())),
+    })
+}
+
+/// 用于片段高亮的词：完整匹配的词和前缀
+#[derive(Default)]
+struct Highlighter {
+    words: HashSet<String>,
+    prefixes: Vec<String>,
+}
+
+impl Highlighter {
+    fn matches(&self, token: &str) -> bool {
+        self.words.contains(token) || self.prefixes.iter().any(|p| token.starts_with(p.as_str()))
+    }
+}
+
+fn build_query(fields: &Fields, input: &str) -> Result<(Box<dyn Query>, Highlighter)> {
+    let mut highlighter = Highlighter::default();
+    let mut groups: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for group in parse_query(input) {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for clause in group {
+            let terms: Vec<String> = tokenize(&clause.text, TokenizeMode::Default).into_iter().map(|t| t.text).collect();
+            if terms.is_empty() {
+                continue;
+            }
+            let query: Box<dyn Query> = Box::new(BooleanQuery::new(vec![
+                (Occur::Should, Box::new(BoostQuery::new(field_query(fields.title, &terms, clause.prefix)?, 2.0))),
+                (Occur::Should, field_query(fields.body, &terms, clause.prefix)?),
+            ]));
+            if clause.negated {
+                clauses.push((Occur::MustNot, query));
+                continue;
+            }
+            let (last, rest) = terms.split_last().unwrap();
+            highlighter.words.extend(rest.iter().cloned());
+            if clause.prefix {
+                highlighter.prefixes.push(last.clone());
+            } else {
+                highlighter.words.insert(last.clone());
+            }
+            clauses.push((Occur::Must, query));
+        }
+        if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+            if clauses.is_empty() {
+                continue;
+            }
+            clauses.push((Occur::Must, Box::new(AllQuery)));
+        }
+        groups.push((Occur::Should, Box::new(BooleanQuery::new(clauses))));
+    }
+    if groups.is_empty() {
+        return Err(anyhow!("Empty query"));
+    }
+    Ok((Box::new(BooleanQuery::new(groups)), highlighter))
+}
+
+// ============================================================================
+// 片段
+// ============================================================================
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+/// 选出匹配词最多的几行作为片段，长行截取第一个匹配附近的部分
+fn snippets(body: &str, highlighter: &Highlighter) -> Vec<NoteSnippet> {
+    // (不同匹配词数, 匹配次数, 行号, 行, 匹配范围)
+    let mut candidates = Vec::new();
+    for (index, line) in body.lines().enumerate() {
+        let mut ranges = Vec::new();
+        let mut distinct = HashSet::new();
+        for token in tokenize(line, TokenizeMode::Search) {
+            if highlighter.matches(&token.text) {
+                ranges.push(token.offset_from..token.offset_to);
+                distinct.insert(token.text);
+            }
+        }
+        // 长词与其中的短词可能同时匹配，合并重叠的范围
+        ranges.sort_by_key(|r| (r.start, r.end));
+        ranges.dedup_by(|next, prev| {
+            let overlaps = next.start < prev.end;
+            if overlaps {
+                prev.end = prev.end.max(next.end);
+            }
+            overlaps
+        });
+        if !ranges.is_empty() {
+            candidates.push((distinct.len(), ranges.len(), index, line, ranges));
+        }
+    }
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+
+    candidates
+        .into_iter()
+        .take(MAX_SNIPPETS)
+        .map(|(_, _, index, line, ranges)| {
+            let first = ranges[0].start;
+            let start = if line.chars().count() > SNIPPET_CHARS {
+                let before: Vec<usize> = line[..first].char_indices().map(|(i, _)| i).collect();
+                before.len().checked_sub(SNIPPET_CONTEXT).map_or(0, |i| before[i])
+            } else {
+                line.len() - line.trim_start().len()
+            };
+            let end = line[start..].char_indices().nth(SNIPPET_CHARS).map_or(line.len(), |(i, _)| start + i);
+            let text = &line[start..end];
+            let highlights = ranges
+                .iter()
+                .filter(|r| r.start >= start && r.end <= end)
+                .map(|r| [utf16_len(&line[start..r.start]), utf16_len(&line[start..r.end])])
+                .collect();
+            NoteSnippet {
+                line: index as u32 + 1,
+                column: utf16_len(&line[..first]) + 1,
+                text: text.to_string(),
+                highlights,
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// 索引
+// ============================================================================
+
+#[derive(Clone, Copy)]
+struct Fields {
+    path: Field,
+    title: Field,
+    body: Field,
+}
+
+fn build_schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let indexing = TextFieldIndexing::default()
+        .set_tokenizer(TOKENIZER)
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+    let path = builder.add_text_field("path", STRING | STORED);
+    let title = builder.add_text_field("title", TextOptions::default().set_indexing_options(indexing.clone()));
+    let body = builder.add_text_field("body", TextOptions::default().set_indexing_options(indexing).set_stored());
+    (builder.build(), Fields { path, title, body })
+}
+
+/// 已处理文件（含未建立索引的二进制文件）的修改时间，与索引一起保存，重新打开时据此增量更新
+type Manifest = BTreeMap<String, u64>;
+
+struct IndexState {
+    manifest: Manifest,
+    indexing: bool,
+}
+
+struct FullTextIndex {
+    root: PathBuf,
+    fields: Fields,
+    reader: IndexReader,
+    /// 同一时间只有一个线程更新索引；关闭后为 None，释放索引目录的锁
+    writer: Mutex<Option<IndexWriter>>,
+    state: RwLock<IndexState>,
+    /// 已关闭，进行中的更新在下一个文件处停止
+    closed: AtomicBool,
+}
+
+/// 是否为需要建立索引的笔记文件
+fn is_note(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.')
+        .is_some_and(|(stem, ext)| !stem.is_empty() && NOTE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// 打开失败时是否应重建索引：Schema 不兼容或数据损坏。其他错误（权限、IO、锁）直接返回，不删除索引
+fn should_rebuild(error: &TantivyError) -> bool {
+    matches!(
+        error,
+        TantivyError::SchemaError(_)
+            | TantivyError::DataCorruption(_)
+            | TantivyError::IncompatibleIndex(_)
+            | TantivyError::OpenReadError(OpenReadError::FileDoesNotExist(_) | OpenReadError::IncompatibleIndex(_))
+    )
+}
+
+/// 创建写入器；索引目录仍被锁定时稍后重试，超过重试次数后返回锁错误
+fn open_writer(index: &Index) -> tantivy::Result<IndexWriter> {
+    let mut attempts = 0;
+    loop {
+        match index.writer(WRITER_MEMORY) {
+            Err(TantivyError::LockFailure(..)) if attempts < LOCK_RETRIES => {
+                attempts += 1;
+                std::thread::sleep(LOCK_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// 读取可索引的文本文件，二进制和过大的文件返回 None
+fn read_text(path: &Path) -> Option<String> {
+    if std::fs::metadata(path).ok()?.len() > MAX_FILE_SIZE {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if bytes[..bytes.len().min(8000)].contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn modified_millis(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// 目录下的所有条目（`dir` 为空表示根目录）
+fn subtree<'a>(manifest: &'a Manifest, dir: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+    let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+    manifest.range(prefix.clone()..).map(|(path, _)| path).take_while(move |path| path.starts_with(&prefix))
+}
+
+impl FullTextIndex {
+    fn open(root: PathBuf, dir: PathBuf) -> Result<Self> {
+        let (schema, fields) = build_schema();
+        std::fs::create_dir_all(&dir)?;
+        let recreate = |reason: &dyn std::fmt::Display| -> Result<Index> {
+            log::warn!("Rebuilding full text index {}: {}", dir.display(), reason);
+            std::fs::remove_dir_all(&dir)?;
+            std::fs::create_dir_all(&dir)?;
+            Ok(Index::create_in_dir(&dir, schema.clone())?)
+        };
+        let index = if !dir.join("meta.json").exists() {
+            Index::create_in_dir(&dir, schema.clone())?
+        } else {
+            match Index::open_in_dir(&dir) {
+                Ok(index) if index.schema() == schema => index,
+                Ok(_) => recreate(&"schema changed")?,
+                Err(e) if should_rebuild(&e) => recreate(&e)?,
+                Err(e) => return Err(e.into()),
+            }
+        };
+        index
+            .tokenizers()
+            .register(TOKENIZER, TextAnalyzer::builder(CjkTokenizer).filter(RemoveLongFilter::limit(64)).build());
+        let writer = open_writer(&index)?;
+        let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+
+        // 文件修改时间记录保存在最近一次提交的附加数据中（旧版本单独保存的 manifest.json 不再使用）
+        let _ = std::fs::remove_file(dir.join("manifest.json"));
+        let manifest: Option<Manifest> = index
+            .load_metas()?
+            .payload
+            .and_then(|payload| serde_json::from_str(&payload).ok());
+        // 没有记录时无法判断索引中的文档是否过期，全部重建
+        let manifest = match manifest {
+            Some(manifest) => manifest,
+            None => {
+                writer.delete_all_documents()?;
+                Manifest::new()
+            }
+        };
+
+        Ok(Self {
+            root,
+            fields,
+            reader,
+            writer: Mutex::new(Some(writer)),
+            state: RwLock::new(IndexState { manifest, indexing: true }),
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    /// 停止更新并释放写入器。进行中的构建或更新提交已处理的文件后结束，
+    /// 后台线程仍持有实例时也不会阻止重新打开同一索引目录
+    fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        self.writer.lock().unwrap().take();
+    }
+
+    fn status(&self) -> FullTextStatus {
+        FullTextStatus {
+            root: self.root.to_string_lossy().to_string(),
+            document_count: self.reader.searcher().num_docs() as usize,
+            indexing: self.state.read().unwrap().indexing,
+        }
+    }
+
+    /// 遍历目录（遵循 .gitignore），返回笔记文件的修改时间和子目录
+    fn walk(&self, start: &Path, max_depth: Option<usize>) -> (HashMap<String, u64>, Vec<String>) {
+        let (tx, rx) = mpsc::channel();
+        WalkBuilder::new(start)
+            .hidden(false)
+            .require_git(false)
+            .max_depth(max_depth)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build_parallel()
+            .run(|| {
+                let tx = tx.clone();
+                Box::new(move |entry| {
+                    if let Ok(entry) = entry {
+                        if let Ok(metadata) = entry.metadata() {
+                            let _ = tx.send((entry.into_path(), metadata.is_dir(), modified_millis(&metadata)));
+                        }
+                    }
+                    WalkState::Continue
+                })
+            });
+        drop(tx);
+
+        let mut files = HashMap::new();
+        let mut dirs = Vec::new();
+        for (path, is_dir, mtime) in rx {
+            if let Some(relative) = relative_path(&self.root, &path) {
+                if is_dir {
+                    dirs.push(relative);
+                } else if is_note(&relative) {
+                    files.insert(relative, mtime);
+                }
+            }
+        }
+        (files, dirs)
+    }
+
+    /// 提交索引，文件修改时间记录作为提交的附加数据与索引内容一起原子地保存
+    fn commit(&self, writer: &mut IndexWriter) -> Result<()> {
+        let manifest = serde_json::to_string(&self.state.read().unwrap().manifest)?;
+        let mut prepared = writer.prepare_commit()?;
+        prepared.set_payload(&manifest);
+        prepared.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    fn remove(&self, writer: &IndexWriter, path: &str) {
+        writer.delete_term(Term::from_field_text(self.fields.path, path));
+        self.state.write().unwrap().manifest.remove(path);
+    }
+
+    /// 重新索引文件；不是文本文件时只记录修改时间
+    fn update(&self, writer: &IndexWriter, path: &str, mtime: u64) -> Result<()> {
+        writer.delete_term(Term::from_field_text(self.fields.path, path));
+        if let Some(text) = read_text(&self.root.join(path)) {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            let title = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+            writer.add_document(doc!(
+                self.fields.path => path,
+                self.fields.title => title,
+                self.fields.body => text,
+            ))?;
+        }
+        self.state.write().unwrap().manifest.insert(path.to_string(), mtime);
+        Ok(())
+    }
+
+    /// 使目录下的索引与磁盘一致。`max_depth` 为 1 时只比较直接子项，
+    /// 新出现的子目录完整遍历，消失的子目录整体移除
+    fn sync_dir(&self, dir: &str, max_depth: Option<usize>) -> Result<()> {
+        let mut guard = self.writer.lock().unwrap();
+        let Some(writer) = guard.as_mut() else {
+            return Ok(());
+        };
+        let path = self.root.join(dir);
+        let stale: Vec<String> = {
+            let state = self.state.read().unwrap();
+            subtree(&state.manifest, dir).cloned().collect()
+        };
+        if !path.is_dir() {
+            for file in &stale {
+                self.remove(writer, file);
+            }
+            return self.commit(writer);
+        }
+
+        let (mut files, dirs) = self.walk(&path, max_depth);
+        if max_depth.is_some() {
+            let depth = if dir.is_empty() { 0 } else { dir.matches('/').count() + 1 };
+            let child_dir = |file: &str| {
+                let segments: Vec<&str> = file.splitn(depth + 2, '/').collect();
+                (segments.len() == depth + 2).then(|| segments[..=depth].join("/"))
+            };
+            let old_dirs: HashSet<String> = stale.iter().filter_map(|f| child_dir(f)).collect();
+            let new_dirs: HashSet<String> = dirs.into_iter().filter(|d| d != dir).collect();
+            for file in stale.iter().filter(|f| f.matches('/').count() == depth && !files.contains_key(*f)) {
+                self.remove(writer, file);
+            }
+            for removed in old_dirs.difference(&new_dirs) {
+                for file in stale.iter().filter(|f| f.starts_with(&format!("{}/", removed))) {
+                    self.remove(writer, file);
+                }
+            }
+            for added in new_dirs.difference(&old_dirs) {
+                files.extend(self.walk(&self.root.join(added), None).0);
+            }
+        } else {
+            for file in stale.iter().filter(|f| !files.contains_key(*f)) {
+                self.remove(writer, file);
+            }
+        }
+
+        let changed: Vec<(String, u64)> = {
+            let state = self.state.read().unwrap();
+            files.into_iter().filter(|(file, mtime)| state.manifest.get(file) != Some(mtime)).collect()
+        };
+        for (i, (file, mtime)) in changed.iter().enumerate() {
+            if self.closed.load(Ordering::Relaxed) {
+                break;
+            }
+            self.update(writer, file, *mtime)?;
+            if (i + 1) % COMMIT_INTERVAL == 0 {
+                self.commit(writer)?;
+            }
+        }
+        self.commit(writer)
+    }
+
+    fn build(&self) -> Result<()> {
+        let result = self.sync_dir("", None);
+        self.state.write().unwrap().indexing = false;
+        result
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<NoteSearchResult> {
+        let (query, highlighter) = build_query(&self.fields, query)?;
+        let searcher = self.reader.searcher();
+        let (top, total) = searcher.search(&*query, &(TopDocs::with_limit(limit.max(1)), Count))?;
+        let mut hits = Vec::with_capacity(top.len());
+        for (score, address) in top {
+            let doc: TantivyDocument = searcher.doc(address)?;
+            let text = |field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            hits.push(NoteHit {
+                path: text(self.fields.path),
+                score,
+                snippets: snippets(&text(self.fields.body), &highlighter),
+            });
+        }
+        Ok(NoteSearchResult { hits, total, indexing: self.state.read().unwrap().indexing })
+    }
+}
+
+fn indexes() -> &'static Mutex<HashMap<PathBuf, Arc<FullTextIndex>>> {
+    INDEXES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_index(root: &str) -> Option<Arc<FullTextIndex>> {
+    indexes().lock().unwrap().get(Path::new(root)).cloned()
+}
+
+/// 索引目录：缓存目录下按根路径哈希（FNV-1a）区分
+fn index_dir(app: &AppHandle, root: &Path) -> Result<PathBuf> {
+    let hash = root
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3));
+    Ok(app
+        .path()
+        .app_cache_dir()?
+        .join(format!("fulltext-v{}", INDEX_VERSION))
+        .join(format!("{:016x}", hash)))
+}
+
+/// 订阅文件索引的变化（已排除被忽略的目录），同步到全文索引
+fn start_updates(app: AppHandle, index: Arc<FullTextIndex>) -> Result<()> {
+    let rx = file_index::subscribe(app.clone(), &index.root.to_string_lossy())?;
+    let index = Arc::downgrade(&index);
+    std::thread::spawn(move || {
+        // 全文索引关闭后线程退出，释放接收端即取消订阅
+        while let Ok(dirs) = rx.recv() {
+            let Some(index) = index.upgrade() else {
+                break;
+            };
+            for (dir, full) in dirs {
+                let max_depth = if full { None } else { Some(1) };
+                if let Err(e) = index.sync_dir(&dir, max_depth) {
+                    log::warn!("Failed to update full text index for {}: {}", dir, e);
+                }
+            }
+            let _ = app.emit(FULL_TEXT_EVENT, index.status());
+        }
+    });
+    Ok(())
+}
+
+/// 打开（或创建）工作区的全文索引，已有索引增量更新，首次构建在后台进行，完成后发送事件
+pub fn open_index(app: AppHandle, root: &str) -> Result<FullTextStatus> {
+    let root_path = PathBuf::from(root);
+    if !root_path.is_dir() {
+        return Err(anyhow!("Not a directory: {}", root));
+    }
+    let index = {
+        let mut indexes = indexes().lock().unwrap();
+        if let Some(index) = indexes.get(&root_path) {
+            return Ok(index.status());
+        }
+        let index = Arc::new(FullTextIndex::open(root_path.clone(), index_dir(&app, &root_path)?)?);
+        indexes.insert(root_path, index.clone());
+        index
+    };
+
+    if let Err(e) = start_updates(app.clone(), index.clone()) {
+        log::warn!("File watcher unavailable for {}: {}", root, e);
+    }
+    let status = index.status();
+    std::thread::spawn(move || {
+        if let Err(e) = index.build() {
+            log::error!("Failed to build full text index for {}: {}", index.root.display(), e);
+        }
+        let _ = app.emit(FULL_TEXT_EVENT, index.status());
+    });
+    Ok(status)
+}
+
+/// 关闭工作区索引并停止更新（磁盘上的索引保留，下次打开时增量更新）。
+/// 等待进行中的更新结束并释放写入器，之后可以立即重新打开
+pub fn close_index(root: &str) {
+    let index = indexes().lock().unwrap().remove(Path::new(root));
+    if let Some(index) = index {
+        index.close();
+    }
+}
+
+pub fn index_status(root: &str) -> Option<FullTextStatus> {
+    get_index(root).map(|index| index.status())
+}
+
+/// 全文搜索，按相关度排序，每个结果附带匹配行的片段
+pub async fn search(root: &str, query: &str, limit: usize) -> Result<NoteSearchResult> {
+    let index = get_index(root).ok_or_else(|| anyhow!("Full text index not opened: {}", root))?;
+    let query = query.to_string();
+    tokio::task::spawn_blocking(move || index.search(&query, limit))
+        .await
+        .map_err(|e| anyhow!("Search task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_and_parse_query() {
+        let texts: Vec<String> = tokenize("Hello世界, rust_lang 我们正在测试中文分词", TokenizeMode::Default)
+            .into_iter()
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(&texts[..4], ["hello", "世界", "rust", "lang"]);
+        assert!(texts.contains(&"中文".to_string()));
+        assert!(texts.contains(&"分词".to_string()));
+
+        let groups = parse_query(r#"note "exact phrase" -draft tok* OR NOT 中文"#);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].len(), 4);
+        assert_eq!(groups[0][1], Clause { text: "exact phrase".into(), prefix: false, negated: false });
+        assert!(groups[0][2].negated);
+        assert!(groups[0][3].prefix);
+        assert_eq!(groups[1], vec![Clause { text: "中文".into(), prefix: false, negated: true }]);
+    }
+
+    #[test]
+    fn test_index_and_search() {
+        let base = std::env::temp_dir().join(format!("kaironotes-fulltext-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let root = base.join("vault");
+        std::fs::create_dir_all(root.join("diary")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(root.join("diary/day1.md"), "# 日记\n今天学习了中文分词和全文索引。\n").unwrap();
+        std::fs::write(root.join("rust.md"), "Tokenizers split text.\nThe quick brown fox\n").unwrap();
+        std::fs::write(root.join("debug.log"), "中文分词").unwrap();
+
+        let index = FullTextIndex::open(root.clone(), base.join("index")).unwrap();
+        index.build().unwrap();
+        let paths = |query: &str| {
+            let mut paths: Vec<String> = index.search(query, 10).unwrap().hits.into_iter().map(|h| h.path).collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(paths("分词"), vec!["diary/day1.md"]);
+        assert_eq!(paths("token*"), vec!["rust.md"]);
+        assert_eq!(paths("\"quick brown\""), vec!["rust.md"]);
+        assert!(paths("\"brown quick\"").is_empty());
+        assert_eq!(paths("fox OR 日记"), vec!["diary/day1.md", "rust.md"]);
+        // 只索引笔记文件，.gitignore 等不参与搜索
+        assert_eq!(paths("-fox"), vec!["diary/day1.md"]);
+
+        let result = index.search("全文 索引", 10).unwrap();
+        let snippet = &result.hits[0].snippets[0];
+        assert_eq!((snippet.line, snippet.text.as_str()), (2, "今天学习了中文分词和全文索引。"));
+        assert_eq!(snippet.highlights, vec![[10, 12], [12, 14]]);
+
+        // 修改、删除文件后增量更新
+        std::fs::write(root.join("rust.md"), "Nothing here").unwrap();
+        std::fs::remove_dir_all(root.join("diary")).unwrap();
+        index.sync_dir("", Some(1)).unwrap();
+        assert!(paths("fox").is_empty());
+        assert!(paths("分词").is_empty());
+        assert_eq!(paths("nothing"), vec!["rust.md"]);
+        assert_eq!(index.status().document_count, 1);
+
+        // 重新打开时从提交的附加数据恢复修改时间记录，未变化的文件不再重建
+        drop(index);
+        let index = FullTextIndex::open(root.clone(), base.join("index")).unwrap();
+        assert_eq!(index.state.read().unwrap().manifest.keys().collect::<Vec<_>>(), vec!["rust.md"]);
+        assert_eq!(index.status().document_count, 1);
+
+        std::fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn test_close_and_reopen() {
+        let base = std::env::temp_dir().join(format!("kaironotes-fulltext-reopen-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let root = base.join("vault");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("note.md"), "hello").unwrap();
+
+        // 关闭后其他线程仍持有实例，也可以立即重新打开
+        let first = Arc::new(FullTextIndex::open(root.clone(), base.join("index")).unwrap());
+        first.close();
+        first.build().unwrap();
+        let second = FullTextIndex::open(root.clone(), base.join("index")).unwrap();
+        second.build().unwrap();
+        assert_eq!(second.status().document_count, 1);
+
+        // 写入器仍被持有时重试，直到对方释放
+        let closer = std::thread::spawn(move || {
+            std::thread::sleep(LOCK_RETRY_DELAY * 3);
+            second.close();
+        });
+        let third = FullTextIndex::open(root.clone(), base.join("index")).unwrap();
+        closer.join().unwrap();
+        assert_eq!(third.status().document_count, 1);
+        drop((first, third));
+
+        std::fs::remove_dir_all(&base).ok();
+    }
+}
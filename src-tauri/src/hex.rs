@@ -0,0 +1,571 @@
+// Hex Editor Module
+//
+// 二进制文件的十六进制查看和编辑：原始文件按需分页读取，修改记录在片段表中，
+// 不改变长度的修改可以原地写回，插入和删除需要另存为
+
+use anyhow::{anyhow, bail, Context, Result};
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 单次请求最多读取的字节数
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+
+/// 搜索和另存为时每次处理的字节数
+const CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// 未指定时最多返回的搜索结果数
+const DEFAULT_SEARCH_LIMIT: usize = 1000;
+
+static DOCUMENTS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<HexDocument>>>>> = OnceLock::new();
+
+/// 根据文件开头的样本判断是否为二进制文件：
+/// 带 UTF-16 BOM 的视为文本，否则含有 NUL 或控制字符超过 10% 时视为二进制
+pub fn is_binary(sample: &[u8]) -> bool {
+    if sample.starts_with(&[0xFF, 0xFE]) || sample.starts_with(&[0xFE, 0xFF]) {
+        return false;
+    }
+    if memchr::memchr(0, sample).is_some() {
+        return true;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B))
+        .count();
+    control * 10 > sample.len()
+}
+
+/// 十六进制文档状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HexStatus {
+    pub path: String,
+    pub size: u64,
+    pub original_size: u64,
+    pub modified: bool,
+    /// 插入或删除过字节，只能另存为
+    pub requires_save_as: bool,
+}
+
+/// 一段字节
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HexRange {
+    pub offset: u64,
+    pub bytes: Vec<u8>,
+    /// 范围内被修改过的区间 `[start, end)`（文件偏移）
+    pub modified: Vec<(u64, u64)>,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HexSearchMode {
+    /// 十六进制字节序列，`??` 匹配任意字节，如 `4D 5A ?? 00`
+    #[default]
+    Hex,
+    /// 按指定编码编码后的文本
+    Text,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HexSearchQuery {
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: HexSearchMode,
+    /// 文本模式使用的编码，默认 UTF-8
+    pub encoding: Option<String>,
+    /// 文本模式下是否区分 ASCII 字母大小写
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HexSearchResult {
+    pub offsets: Vec<u64>,
+    /// 匹配的字节数
+    pub length: u64,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Original,
+    Added,
+}
+
+/// 片段表中的一段：原始文件或新增缓冲区中的 `[start, start + len)`
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    source: Source,
+    start: u64,
+    len: u64,
+}
+
+pub struct HexDocument {
+    path: PathBuf,
+    file: File,
+    original_size: u64,
+    added: Vec<u8>,
+    pieces: Vec<Piece>,
+}
+
+impl HexDocument {
+    fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let original_size = file.metadata()?.len();
+        let pieces = if original_size > 0 {
+            vec![Piece { source: Source::Original, start: 0, len: original_size }]
+        } else {
+            Vec::new()
+        };
+        Ok(Self { path: path.to_path_buf(), file, original_size, added: Vec::new(), pieces })
+    }
+
+    pub fn size(&self) -> u64 {
+        self.pieces.iter().map(|piece| piece.len).sum()
+    }
+
+    fn modified(&self) -> bool {
+        self.pieces.iter().any(|piece| piece.source == Source::Added) || self.size() != self.original_size
+    }
+
+    /// 所有原始片段都还在原来的偏移上时，修改可以原地写回
+    fn in_place(&self) -> bool {
+        let mut pos = 0;
+        for piece in &self.pieces {
+            if piece.source == Source::Original && piece.start != pos {
+                return false;
+            }
+            pos += piece.len;
+        }
+        pos == self.original_size
+    }
+
+    fn status(&self) -> HexStatus {
+        HexStatus {
+            path: self.path.to_string_lossy().to_string(),
+            size: self.size(),
+            original_size: self.original_size,
+            modified: self.modified(),
+            requires_save_as: !self.in_place(),
+        }
+    }
+
+    /// 确保 `offset` 处是片段边界，返回从该处开始的片段索引
+    fn split(&mut self, offset: u64) -> usize {
+        let mut pos = 0;
+        for i in 0..self.pieces.len() {
+            let piece = self.pieces[i];
+            if offset == pos {
+                return i;
+            }
+            if offset < pos + piece.len {
+                let head = offset - pos;
+                self.pieces[i].len = head;
+                self.pieces.insert(i + 1, Piece { source: piece.source, start: piece.start + head, len: piece.len - head });
+                return i + 1;
+            }
+            pos += piece.len;
+        }
+        self.pieces.len()
+    }
+
+    fn insert(&mut self, offset: u64, bytes: &[u8]) -> Result<()> {
+        if offset > self.size() {
+            bail!("Offset out of range: {}", offset);
+        }
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let index = self.split(offset);
+        let start = self.added.len() as u64;
+        self.added.extend_from_slice(bytes);
+        // 连续输入时与前一个新增片段合并，避免片段数量增长过快
+        if let Some(prev) = index.checked_sub(1).map(|i| &mut self.pieces[i]) {
+            if prev.source == Source::Added && prev.start + prev.len == start {
+                prev.len += bytes.len() as u64;
+                return Ok(());
+            }
+        }
+        self.pieces.insert(index, Piece { source: Source::Added, start, len: bytes.len() as u64 });
+        Ok(())
+    }
+
+    fn delete(&mut self, offset: u64, len: u64) -> Result<()> {
+        if offset.checked_add(len).map_or(true, |end| end > self.size()) {
+            bail!("Range out of bounds: {}+{}", offset, len);
+        }
+        let start = self.split(offset);
+        let end = self.split(offset + len);
+        self.pieces.drain(start..end);
+        Ok(())
+    }
+
+    /// 覆盖写入，不改变长度
+    fn overwrite(&mut self, offset: u64, bytes: &[u8]) -> Result<()> {
+        self.delete(offset, bytes.len() as u64)?;
+        self.insert(offset, bytes)
+    }
+
+    fn read(&mut self, offset: u64, len: u64) -> Result<HexRange> {
+        self.read_range(offset, len.min(MAX_READ_BYTES))
+    }
+
+    /// 读取 `[offset, offset + len)`，不受单次请求大小的限制
+    fn read_range(&mut self, offset: u64, len: u64) -> Result<HexRange> {
+        let size = self.size();
+        let end = offset.saturating_add(len).min(size);
+        let mut bytes = Vec::with_capacity(end.saturating_sub(offset) as usize);
+        let mut modified: Vec<(u64, u64)> = Vec::new();
+        let mut pos = 0;
+        for piece in &self.pieces {
+            let piece_end = pos + piece.len;
+            if piece_end > offset && pos < end {
+                let from = offset.max(pos);
+                let to = end.min(piece_end);
+                let start = piece.start + (from - pos);
+                let count = (to - from) as usize;
+                match piece.source {
+                    Source::Original => {
+                        let at = bytes.len();
+                        bytes.resize(at + count, 0);
+                        self.file.seek(SeekFrom::Start(start))?;
+                        self.file.read_exact(&mut bytes[at..])?;
+                    }
+                    Source::Added => {
+                        bytes.extend_from_slice(&self.added[start as usize..start as usize + count]);
+                        match modified.last_mut() {
+                            Some(last) if last.1 == from => last.1 = to,
+                            _ => modified.push((from, to)),
+                        }
+                    }
+                }
+            }
+            pos = piece_end;
+            if pos >= end {
+                break;
+            }
+        }
+        Ok(HexRange { offset, bytes, modified, size })
+    }
+
+    /// 原地写回修改过的字节
+    fn save(&mut self) -> Result<()> {
+        if !self.in_place() {
+            bail!("Inserted or deleted bytes require Save As");
+        }
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {} for writing", self.path.display()))?;
+        let mut pos = 0;
+        for piece in &self.pieces {
+            if piece.source == Source::Added {
+                file.seek(SeekFrom::Start(pos))?;
+                file.write_all(&self.added[piece.start as usize..(piece.start + piece.len) as usize])?;
+            }
+            pos += piece.len;
+        }
+        file.sync_all()?;
+        self.reload(&self.path.clone())
+    }
+
+    /// 写入新文件（先写临时文件再替换，目标可以是原文件），之后文档切换到新文件
+    fn save_as(&mut self, target: &Path) -> Result<()> {
+        let name = target.file_name().ok_or_else(|| anyhow!("Invalid path: {}", target.display()))?;
+        let temp = target.with_file_name(format!(".{}.kairo-tmp", name.to_string_lossy()));
+        let result = (|| -> Result<()> {
+            let mut writer = BufWriter::new(File::create(&temp)?);
+            let size = self.size();
+            let mut offset = 0;
+            while offset < size {
+                let range = self.read(offset, CHUNK_SIZE)?;
+                writer.write_all(&range.bytes)?;
+                offset += range.bytes.len() as u64;
+            }
+            writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+            std::fs::rename(&temp, target)?;
+            Ok(())
+        })();
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e.context(format!("Failed to save {}", target.display())));
+        }
+        self.reload(target)
+    }
+
+    fn reload(&mut self, path: &Path) -> Result<()> {
+        *self = Self::open(path)?;
+        Ok(())
+    }
+
+    fn search(&mut self, pattern: &[(u8, u8)], start: u64, limit: usize) -> Result<HexSearchResult> {
+        let mut offsets = Vec::new();
+        let length = pattern.len() as u64;
+        let size = self.size();
+        let exact: Option<Vec<u8>> =
+            pattern.iter().all(|&(_, mask)| mask == 0xFF).then(|| pattern.iter().map(|&(value, _)| value).collect());
+        let finder = exact.as_deref().map(memchr::memmem::Finder::new);
+
+        let mut pos = start;
+        while pos + length <= size {
+            // 相邻块重叠 length - 1 字节，跨块的匹配不会遗漏
+            let data = self.read_range(pos, CHUNK_SIZE + length - 1)?.bytes;
+            let last = (data.len() as u64 + 1).saturating_sub(length).min(CHUNK_SIZE) as usize;
+            let mut found = |i: usize| -> bool {
+                offsets.push(pos + i as u64);
+                offsets.len() > limit
+            };
+            let done = match &finder {
+                Some(finder) => finder.find_iter(&data).take_while(|&i| i < last).any(&mut found),
+                None => (0..last)
+                    .filter(|&i| pattern.iter().zip(&data[i..]).all(|(&(value, mask), &b)| b & mask == value))
+                    .any(&mut found),
+            };
+            if done {
+                offsets.truncate(limit);
+                return Ok(HexSearchResult { offsets, length, truncated: true });
+            }
+            pos += CHUNK_SIZE;
+        }
+        Ok(HexSearchResult { offsets, length, truncated: false })
+    }
+}
+
+/// 把搜索条件编译为 (值, 掩码) 序列，按 `byte & mask == value` 比较
+fn compile_pattern(query: &HexSearchQuery) -> Result<Vec<(u8, u8)>> {
+    let pattern = match query.mode {
+        HexSearchMode::Hex => {
+            let digits: Vec<char> = query.pattern.chars().filter(|c| !c.is_whitespace()).collect();
+            if digits.len() % 2 != 0 {
+                bail!("Invalid hex pattern: {}", query.pattern);
+            }
+            digits
+                .chunks(2)
+                .map(|pair| {
+                    if pair == ['?', '?'] {
+                        return Ok((0, 0));
+                    }
+                    let text: String = pair.iter().collect();
+                    u8::from_str_radix(&text, 16)
+                        .map(|value| (value, 0xFF))
+                        .map_err(|_| anyhow!("Invalid hex pattern: {}", query.pattern))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        HexSearchMode::Text => {
+            let label = query.encoding.as_deref().unwrap_or("UTF-8");
+            let mut pattern = Vec::new();
+            let mut buf = [0u8; 4];
+            // 逐字符编码，只对 ASCII 字母忽略大小写，多字节字符的组成字节始终精确比较
+            for c in query.pattern.chars() {
+                let mask = if !query.case_sensitive && c.is_ascii_alphabetic() { 0xDF } else { 0xFF };
+                for b in encode_char(c, label, &mut buf)? {
+                    let byte_mask = if mask == 0xDF && b.is_ascii_alphabetic() { 0xDF } else { 0xFF };
+                    pattern.push((b & byte_mask, byte_mask));
+                }
+            }
+            pattern
+        }
+    };
+    if pattern.is_empty() {
+        bail!("Search pattern is empty");
+    }
+    Ok(pattern)
+}
+
+fn encode_char(c: char, label: &str, buf: &mut [u8; 4]) -> Result<Vec<u8>> {
+    // encoding_rs 的 UTF-16 编码器按 WHATWG 规范输出 UTF-8，需要单独处理
+    let units = c.encode_utf16(&mut [0u16; 2]).to_vec();
+    match label.to_ascii_uppercase().as_str() {
+        "UTF-16LE" | "UTF-16" => Ok(units.iter().flat_map(|unit| unit.to_le_bytes()).collect()),
+        "UTF-16BE" => Ok(units.iter().flat_map(|unit| unit.to_be_bytes()).collect()),
+        _ => {
+            let encoding = Encoding::for_label(label.as_bytes())
+                .ok_or_else(|| anyhow!("Unsupported encoding: {}", label))?;
+            let (encoded, _, unmappable) = encoding.encode(c.encode_utf8(buf));
+            if unmappable {
+                bail!("Character {:?} cannot be encoded in {}", c, label);
+            }
+            Ok(encoded.into_owned())
+        }
+    }
+}
+
+fn documents() -> &'static Mutex<HashMap<PathBuf, Arc<Mutex<HexDocument>>>> {
+    DOCUMENTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn with_document<T>(path: &str, f: impl FnOnce(&mut HexDocument) -> Result<T>) -> Result<T> {
+    let document = documents()
+        .lock()
+        .unwrap()
+        .get(Path::new(path))
+        .cloned()
+        .ok_or_else(|| anyhow!("Hex document not open: {}", path))?;
+    let mut document = document.lock().unwrap();
+    f(&mut document)
+}
+
+/// 打开十六进制文档；已打开时返回现有文档的状态
+pub fn open_document(path: &str) -> Result<HexStatus> {
+    let mut documents = documents().lock().unwrap();
+    if let Some(document) = documents.get(Path::new(path)) {
+        return Ok(document.lock().unwrap().status());
+    }
+    let document = HexDocument::open(Path::new(path))?;
+    let status = document.status();
+    documents.insert(PathBuf::from(path), Arc::new(Mutex::new(document)));
+    Ok(status)
+}
+
+pub fn close_document(path: &str) {
+    documents().lock().unwrap().remove(Path::new(path));
+}
+
+pub fn read_bytes(path: &str, offset: u64, length: u64) -> Result<HexRange> {
+    with_document(path, |document| document.read(offset, length))
+}
+
+pub fn write_bytes(path: &str, offset: u64, bytes: &[u8]) -> Result<HexStatus> {
+    with_document(path, |document| {
+        document.overwrite(offset, bytes)?;
+        Ok(document.status())
+    })
+}
+
+pub fn insert_bytes(path: &str, offset: u64, bytes: &[u8]) -> Result<HexStatus> {
+    with_document(path, |document| {
+        document.insert(offset, bytes)?;
+        Ok(document.status())
+    })
+}
+
+pub fn delete_bytes(path: &str, offset: u64, length: u64) -> Result<HexStatus> {
+    with_document(path, |document| {
+        document.delete(offset, length)?;
+        Ok(document.status())
+    })
+}
+
+pub fn save(path: &str) -> Result<HexStatus> {
+    with_document(path, |document| {
+        document.save()?;
+        Ok(document.status())
+    })
+}
+
+/// 另存为后文档以新路径登记
+pub fn save_as(path: &str, target: &str) -> Result<HexStatus> {
+    let status = with_document(path, |document| {
+        document.save_as(Path::new(target))?;
+        Ok(document.status())
+    })?;
+    let mut documents = documents().lock().unwrap();
+    if let Some(document) = documents.remove(Path::new(path)) {
+        documents.insert(PathBuf::from(target), document);
+    }
+    Ok(status)
+}
+
+pub fn search(path: &str, query: &HexSearchQuery, start: u64, limit: Option<usize>) -> Result<HexSearchResult> {
+    let pattern = compile_pattern(query)?;
+    with_document(path, |document| document.search(&pattern, start, limit.unwrap_or(DEFAULT_SEARCH_LIMIT)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("kairo-hex-{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_edit_and_save() {
+        assert!(is_binary(b"MZ\x90\x00\x03\x00"));
+        assert!(!is_binary(b"plain text\r\n\ttabs"));
+        assert!(!is_binary(b"\xFF\xFEh\x00i\x00"));
+
+        let path = temp_file("edit.bin", &[0, 1, 2, 3, 4, 5, 6, 7]);
+        let mut document = HexDocument::open(&path).unwrap();
+        document.overwrite(2, &[0xAA, 0xBB]).unwrap();
+        let range = document.read(0, 8).unwrap();
+        assert_eq!(range.bytes, [0, 1, 0xAA, 0xBB, 4, 5, 6, 7]);
+        assert_eq!(range.modified, [(2, 4)]);
+        assert!(!document.status().requires_save_as);
+        document.save().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), [0, 1, 0xAA, 0xBB, 4, 5, 6, 7]);
+        assert!(!document.status().modified);
+
+        // 插入和删除改变了偏移，只能另存为
+        document.insert(8, &[8, 9]).unwrap();
+        document.delete(0, 2).unwrap();
+        assert!(document.status().requires_save_as);
+        assert!(document.save().is_err());
+        let target = path.with_extension("out");
+        document.save_as(&target).unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), [0xAA, 0xBB, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(document.status().path, target.to_string_lossy());
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&target).unwrap();
+    }
+
+    #[test]
+    fn test_search_patterns() {
+        let mut content = b"\x00\x00Hello".to_vec();
+        content.extend("hello".encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        content.extend_from_slice(b"\x4D\x5A\x90\x00");
+        let path = temp_file("search.bin", &content);
+        let mut document = HexDocument::open(&path).unwrap();
+
+        let query = |pattern: &str, mode, encoding: Option<&str>, case_sensitive| HexSearchQuery {
+            pattern: pattern.to_string(),
+            mode,
+            encoding: encoding.map(str::to_string),
+            case_sensitive,
+        };
+        let mut find = |query: HexSearchQuery| document.search(&compile_pattern(&query).unwrap(), 0, 10).unwrap().offsets;
+
+        assert_eq!(find(query("4d 5a ?? 00", HexSearchMode::Hex, None, true)), [17]);
+        assert_eq!(find(query("HELLO", HexSearchMode::Text, None, false)), [2]);
+        assert_eq!(find(query("HELLO", HexSearchMode::Text, Some("UTF-16LE"), false)), [7]);
+        assert!(find(query("HELLO", HexSearchMode::Text, None, true)).is_empty());
+        assert!(compile_pattern(&query("4D 5", HexSearchMode::Hex, None, true)).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_search_across_chunks() {
+        let mut content = vec![0u8; CHUNK_SIZE as usize + 16];
+        let at = CHUNK_SIZE as usize - 2;
+        content[at..at + 4].copy_from_slice(b"\xDE\xAD\xBE\xEF");
+        let path = temp_file("chunks.bin", &content);
+        let mut document = HexDocument::open(&path).unwrap();
+
+        let query = HexSearchQuery {
+            pattern: "de ad be ef".to_string(),
+            mode: HexSearchMode::Hex,
+            encoding: None,
+            case_sensitive: true,
+        };
+        let result = document.search(&compile_pattern(&query).unwrap(), 0, 10).unwrap();
+        assert_eq!(result.offsets, [at as u64]);
+        let masked = HexSearchQuery { pattern: "de ?? be ef".to_string(), ..query };
+        let result = document.search(&compile_pattern(&masked).unwrap(), 0, 10).unwrap();
+        assert_eq!(result.offsets, [at as u64]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,488 @@
+// Large File Module
+//
+// 大文件模式：超过阈值的文件不整体读入内存，从开头的样本检测编码，
+// 在后台建立行偏移索引，按偏移读取并解码行范围供前端虚拟滚动显示（只读或仅追加）
+
+use crate::file_ops;
+use anyhow::{anyhow, Context, Result};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use tauri::{AppHandle, Emitter};
+
+/// 超过该大小的文件使用大文件模式打开
+pub const LARGE_FILE_THRESHOLD: u64 = 32 * 1024 * 1024;
+
+/// 行索引进度事件，每扫描一块发送一次
+pub const LARGE_FILE_EVENT: &str = "large-file-index-progress";
+
+/// 每次扫描并发布的字节数
+const INDEX_CHUNK: usize = 16 * 1024 * 1024;
+
+/// 每隔多少行记录一个行起始偏移，读取时从最近的记录向后扫描
+const CHECKPOINT_INTERVAL: usize = 64;
+
+/// 单行最多返回的字节数，超出部分截断（如压缩成一行的 JSON）
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// 单次请求最多返回的行数
+const MAX_LINES_PER_REQUEST: usize = 10_000;
+
+/// 读取行时每次从文件读取的字节数（偶数，保持 UTF-16 对齐）
+const READ_BLOCK: usize = 256 * 1024;
+
+static FILES: OnceLock<Mutex<HashMap<PathBuf, Arc<LargeFile>>>> = OnceLock::new();
+
+/// 大文件状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeFileStatus {
+    pub path: String,
+    pub size: u64,
+    pub encoding: String,
+    /// 已索引的行数，索引完成前只包含已扫描的部分
+    pub line_count: usize,
+    pub indexed_bytes: u64,
+    pub indexing: bool,
+}
+
+/// 一段行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineRange {
+    /// 第一行的行号（从 0 开始）
+    pub start: usize,
+    /// 不含行尾换行符
+    pub lines: Vec<String>,
+    /// 超过长度限制被截断的行号
+    pub truncated: Vec<usize>,
+    pub line_count: usize,
+    pub indexing: bool,
+}
+
+/// 换行符的编码方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    /// UTF-8 及 GBK 等多字节编码，`\n` 不会出现在多字节字符内部
+    Byte,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Unit {
+    fn width(self) -> usize {
+        match self {
+            Unit::Byte => 1,
+            Unit::Utf16Le | Unit::Utf16Be => 2,
+        }
+    }
+
+    /// 查找 `[from, end)` 中的下一个换行符，返回下一行的起始偏移；`origin` 为文本起点（BOM 之后）
+    fn next_line(self, bytes: &[u8], mut from: usize, end: usize, origin: usize) -> Option<usize> {
+        loop {
+            let found = from + memchr::memchr(b'\n', &bytes[from..end])?;
+            match self {
+                Unit::Byte => return Some(found + 1),
+                Unit::Utf16Le if (found - origin) % 2 == 0 && bytes.get(found + 1) == Some(&0) => {
+                    return Some(found + 2)
+                }
+                Unit::Utf16Be if (found - origin) % 2 == 1 && bytes[found - 1] == 0 => return Some(found + 1),
+                _ => from = found + 1,
+            }
+        }
+    }
+}
+
+/// 从 `offset` 读取最多 `len` 字节，文件被截断时返回的数据较少。
+/// 使用按偏移读取而不是内存映射，文件被其他进程截断时不会因访问映射区域出错
+fn read_at(file: &File, offset: usize, len: usize) -> io::Result<Vec<u8>> {
+    #[cfg(unix)]
+    use std::os::unix::fs::FileExt;
+    #[cfg(windows)]
+    use std::os::windows::fs::FileExt;
+
+    let mut buf = vec![0; len];
+    let mut filled = 0;
+    while filled < len {
+        let pos = (offset + filled) as u64;
+        #[cfg(unix)]
+        let read = file.read_at(&mut buf[filled..], pos);
+        #[cfg(windows)]
+        let read = file.seek_read(&mut buf[filled..], pos);
+        match read {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    buf.truncate(filled);
+    Ok(buf)
+}
+
+/// 顺序读取文件时缓存一块数据
+struct BlockReader<'a> {
+    file: &'a File,
+    start: usize,
+    buf: Vec<u8>,
+}
+
+impl<'a> BlockReader<'a> {
+    fn new(file: &'a File) -> Self {
+        Self { file, start: 0, buf: Vec::new() }
+    }
+
+    /// 返回从 `pos` 开始、不超过 `limit` 的数据，`pos` 不在缓存中时读取一块；文件变短时可能为空
+    fn window(&mut self, pos: usize, limit: usize) -> io::Result<&[u8]> {
+        if pos < self.start || pos >= self.start + self.buf.len() {
+            self.start = pos;
+            self.buf = read_at(self.file, pos, READ_BLOCK.min(limit - pos))?;
+        }
+        let end = (self.start + self.buf.len()).min(limit);
+        Ok(&self.buf[pos - self.start..end.max(pos) - self.start])
+    }
+}
+
+/// 行索引：每 `CHECKPOINT_INTERVAL` 行的起始偏移
+struct LineIndex {
+    file: Arc<File>,
+    /// 建立索引时的文件长度
+    len: usize,
+    checkpoints: Vec<usize>,
+    line_count: usize,
+    /// 已扫描到的字节位置
+    scanned: usize,
+    /// 文件被截断后重建索引时递增，使正在进行的扫描结果作废
+    generation: u64,
+}
+
+impl LineIndex {
+    fn new(file: Arc<File>, len: usize, origin: usize) -> Self {
+        Self { file, len, checkpoints: vec![origin], line_count: 1, scanned: origin.min(len), generation: 0 }
+    }
+}
+
+pub struct LargeFile {
+    path: PathBuf,
+    encoding_name: String,
+    encoding: &'static Encoding,
+    unit: Unit,
+    /// BOM 之后的文本起点
+    origin: usize,
+    index: RwLock<LineIndex>,
+    indexing: AtomicBool,
+    closed: AtomicBool,
+}
+
+/// 打开文件并返回当前长度
+fn open_file_len(path: &Path) -> Result<(File, usize)> {
+    let file = File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+    let len = file.metadata()?.len() as usize;
+    Ok((file, len))
+}
+
+impl LargeFile {
+    fn open(path: &Path) -> Result<Self> {
+        let (file, len) = open_file_len(path)?;
+        let sample = read_at(&file, 0, len.min(file_ops::ENCODING_SAMPLE_SIZE))?;
+        let encoding_name = file_ops::detect_encoding_from_sample(&sample, sample.len() == len);
+        let encoding = Encoding::for_label(encoding_name.as_bytes()).unwrap_or(UTF_8);
+        let (unit, bom) = if encoding == UTF_16LE {
+            (Unit::Utf16Le, if sample.starts_with(&[0xFF, 0xFE]) { 2 } else { 0 })
+        } else if encoding == UTF_16BE {
+            (Unit::Utf16Be, if sample.starts_with(&[0xFE, 0xFF]) { 2 } else { 0 })
+        } else {
+            (Unit::Byte, if sample.starts_with(&[0xEF, 0xBB, 0xBF]) { 3 } else { 0 })
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            encoding_name: encoding_name.to_string(),
+            encoding,
+            unit,
+            origin: bom,
+            index: RwLock::new(LineIndex::new(Arc::new(file), len, bom)),
+            indexing: AtomicBool::new(true),
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    pub fn status(&self) -> LargeFileStatus {
+        let index = self.index.read().unwrap();
+        LargeFileStatus {
+            path: self.path.to_string_lossy().to_string(),
+            size: index.len as u64,
+            encoding: self.encoding_name.clone(),
+            line_count: index.line_count,
+            indexed_bytes: index.scanned as u64,
+            indexing: self.indexing.load(Ordering::SeqCst),
+        }
+    }
+
+    /// 分块扫描尚未索引的部分，每块完成后发布并回调进度；全部扫描完或文件关闭时返回
+    fn build_index(&self, mut progress: impl FnMut(LargeFileStatus)) {
+        while !self.closed.load(Ordering::SeqCst) {
+            let (file, len, from, mut line_count, generation) = {
+                let index = self.index.read().unwrap();
+                if index.scanned >= index.len {
+                    // 在持有锁时清除标志，保证 reopen 能看到并重新启动索引
+                    self.indexing.store(false, Ordering::SeqCst);
+                    break;
+                }
+                (index.file.clone(), index.len, index.scanned, index.line_count, index.generation)
+            };
+
+            // 块边界与编码单元对齐，避免拆开 UTF-16 换行符
+            let mut end = (from + INDEX_CHUNK).min(len);
+            if end < len {
+                end -= (end - self.origin) % self.unit.width();
+            }
+            // 文件被截断时读到的数据较少：停止索引，由 refresh 检查新的长度后重建或继续
+            let bytes = read_at(&file, from, end - from).unwrap_or_default();
+            if bytes.len() < end - from {
+                let mut index = self.index.write().unwrap();
+                if index.generation == generation {
+                    index.len = from;
+                }
+                continue;
+            }
+            // 起点与编码单元对齐，块内偏移的奇偶性与相对文本起点的一致
+            let mut checkpoints = Vec::new();
+            let mut pos = 0;
+            while let Some(next) = self.unit.next_line(&bytes, pos, bytes.len(), 0) {
+                if line_count % CHECKPOINT_INTERVAL == 0 {
+                    checkpoints.push(from + next);
+                }
+                line_count += 1;
+                pos = next;
+            }
+
+            {
+                let mut index = self.index.write().unwrap();
+                if index.generation != generation {
+                    continue;
+                }
+                index.checkpoints.extend(checkpoints);
+                index.line_count = line_count;
+                index.scanned = end;
+            }
+            progress(self.status());
+        }
+        progress(self.status());
+    }
+
+    /// 重新打开文件并检查长度：之后的按偏移读取使用新的句柄，文件增长时（仅追加的文件）
+    /// 继续从已扫描位置索引，变短时重建索引。返回是否需要启动索引线程
+    fn reopen(&self) -> Result<bool> {
+        let (file, len) = open_file_len(&self.path)?;
+        let mut index = self.index.write().unwrap();
+        if len == index.len {
+            return Ok(false);
+        }
+        let file = Arc::new(file);
+        if len < index.scanned {
+            let generation = index.generation + 1;
+            *index = LineIndex::new(file, len, self.origin);
+            index.generation = generation;
+        } else {
+            index.file = file;
+            index.len = len;
+        }
+        Ok(!self.indexing.swap(true, Ordering::SeqCst))
+    }
+
+    /// 读取从行起始偏移 `pos` 开始的一行，返回下一行的起始偏移和行内容（最多 `MAX_LINE_BYTES` 字节）。
+    /// 块与行起点都与编码单元对齐，块内偏移的奇偶性与相对文本起点的一致
+    fn read_line(&self, reader: &mut BlockReader, pos: usize, limit: usize) -> io::Result<(usize, Vec<u8>)> {
+        let mut text = Vec::new();
+        let mut from = pos;
+        while from < limit {
+            let window = reader.window(from, limit)?;
+            if window.is_empty() {
+                break;
+            }
+            let found = self.unit.next_line(window, 0, window.len(), 0);
+            let end = found.unwrap_or(window.len());
+            let keep = end.min(MAX_LINE_BYTES.saturating_sub(text.len()));
+            text.extend_from_slice(&window[..keep]);
+            from += end;
+            if found.is_some() {
+                return Ok((from, text));
+            }
+        }
+        Ok((limit, text))
+    }
+
+    /// 读取从 `start` 开始的最多 `count` 行，只返回已索引的行
+    pub fn read_lines(&self, start: usize, count: usize) -> Result<LineRange> {
+        let index = self.index.read().unwrap();
+        let limit = index.scanned;
+        let end_line = start.saturating_add(count.min(MAX_LINES_PER_REQUEST)).min(index.line_count);
+        let mut lines = Vec::new();
+        let mut truncated = Vec::new();
+
+        if start < end_line {
+            let mut reader = BlockReader::new(&index.file);
+            let mut pos = index.checkpoints[start / CHECKPOINT_INTERVAL];
+            for _ in 0..start % CHECKPOINT_INTERVAL {
+                pos = self.read_line(&mut reader, pos, limit)?.0;
+            }
+            for line in start..end_line {
+                let (next, bytes) = self.read_line(&mut reader, pos, limit)?;
+                if next - pos > MAX_LINE_BYTES {
+                    truncated.push(line);
+                }
+                let (text, _) = self.encoding.decode_without_bom_handling(&bytes);
+                let text = text.strip_suffix('\n').unwrap_or(&text);
+                lines.push(text.strip_suffix('\r').unwrap_or(text).to_string());
+                pos = next;
+            }
+        }
+
+        Ok(LineRange {
+            start,
+            lines,
+            truncated,
+            line_count: index.line_count,
+            indexing: self.indexing.load(Ordering::SeqCst),
+        })
+    }
+
+    /// 以文件的编码在末尾追加文本
+    fn append(&self, text: &str) -> Result<()> {
+        let bytes: Vec<u8> = match self.unit {
+            Unit::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            Unit::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            Unit::Byte => self.encoding.encode(text).0.into_owned(),
+        };
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open file: {}", self.path.display()))?;
+        file.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+fn files() -> &'static Mutex<HashMap<PathBuf, Arc<LargeFile>>> {
+    FILES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_file(path: &str) -> Result<Arc<LargeFile>> {
+    files()
+        .lock()
+        .unwrap()
+        .get(Path::new(path))
+        .cloned()
+        .ok_or_else(|| anyhow!("Large file not opened: {}", path))
+}
+
+fn spawn_indexer(app: AppHandle, file: Arc<LargeFile>) {
+    std::thread::spawn(move || {
+        file.build_index(|status| {
+            let _ = app.emit(LARGE_FILE_EVENT, status);
+        });
+    });
+}
+
+/// 打开大文件：只读取文件长度后立即返回，内容按偏移读取，行索引在后台建立。已打开时检查文件是否增长
+pub fn open_file(app: AppHandle, path: &str) -> Result<LargeFileStatus> {
+    if let Ok(file) = get_file(path) {
+        return refresh(app, path).map(|_| file.status());
+    }
+    let file = Arc::new(LargeFile::open(Path::new(path))?);
+    files().lock().unwrap().insert(PathBuf::from(path), file.clone());
+    let status = file.status();
+    spawn_indexer(app, file);
+    Ok(status)
+}
+
+pub fn close_file(path: &str) {
+    if let Some(file) = files().lock().unwrap().remove(Path::new(path)) {
+        file.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+pub fn file_status(path: &str) -> Option<LargeFileStatus> {
+    get_file(path).ok().map(|file| file.status())
+}
+
+pub fn read_lines(path: &str, start: usize, count: usize) -> Result<LineRange> {
+    get_file(path)?.read_lines(start, count)
+}
+
+/// 重新检查文件大小，索引新追加的内容，文件变短时重建索引
+pub fn refresh(app: AppHandle, path: &str) -> Result<LargeFileStatus> {
+    let file = get_file(path)?;
+    if file.reopen()? {
+        spawn_indexer(app, file.clone());
+    }
+    Ok(file.status())
+}
+
+/// 在文件末尾追加文本（大文件模式只支持追加编辑）
+pub fn append(app: AppHandle, path: &str, text: &str) -> Result<LargeFileStatus> {
+    get_file(path)?.append(text)?;
+    refresh(app, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("kairo-large-{}-{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_lines_across_checkpoints() {
+        let text: String = (0..200).map(|i| format!("line {}\r\n", i)).collect();
+        let path = temp_file("utf8", text.as_bytes());
+        let file = LargeFile::open(&path).unwrap();
+        file.build_index(|_| {});
+
+        let status = file.status();
+        assert!(!status.indexing);
+        assert_eq!(status.line_count, 201);
+        let range = file.read_lines(63, 3).unwrap();
+        assert_eq!(range.lines, ["line 63", "line 64", "line 65"]);
+        assert_eq!(file.read_lines(199, 10).unwrap().lines, ["line 199", ""]);
+        assert!(file.read_lines(500, 10).unwrap().lines.is_empty());
+
+        // 追加后继续索引新内容
+        file.append("tail\n").unwrap();
+        assert!(file.reopen().unwrap());
+        file.build_index(|_| {});
+        assert_eq!(file.read_lines(200, 2).unwrap().lines, ["tail", ""]);
+
+        // 被其他进程截断后读取不会出错，刷新时重建索引
+        std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(14).unwrap();
+        assert!(file.read_lines(190, 5).is_ok());
+        assert!(file.reopen().unwrap());
+        file.build_index(|_| {});
+        assert_eq!(file.read_lines(0, 10).unwrap().lines, ["line 0", "line 1"]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_utf16_lines() {
+        let mut bytes = vec![0xFF, 0xFE];
+        // U+0A0A 的两个字节都是 0x0A，不应被当作换行
+        for c in "第一行\n\u{0A0A}二\n三".encode_utf16() {
+            bytes.extend_from_slice(&c.to_le_bytes());
+        }
+        let path = temp_file("utf16", &bytes);
+        let file = LargeFile::open(&path).unwrap();
+        file.build_index(|_| {});
+
+        assert_eq!(file.status().encoding, "UTF-16LE");
+        assert_eq!(file.read_lines(0, 10).unwrap().lines, ["第一行", "\u{0A0A}二", "三"]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
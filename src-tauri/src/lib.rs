@@ -2,17 +2,36 @@
 // Cross-platform document editor built with Tauri
 
 mod commands;
+mod conversion;
+mod data_format;
+mod document;
 mod editor;
 mod encoding;
+mod file_index;
 mod file_ops;
 mod fonts;
+mod formatter;
+mod full_text;
+mod hex;
+mod large_file;
+mod log_follow;
+mod lsp;
 mod plugin;
+mod problems;
+mod protocol;
+mod recent;
 mod settings;
+mod single_instance;
+mod structural;
 mod syntax;
 mod system_integration;
+mod tasks;
+mod terminal;
+mod terminal_history;
+mod validation;
 
 use std::path::PathBuf;
-use tauri::{Manager, Emitter};
+use tauri::Manager;
 use tauri_plugin_window_state::StateFlags;
 
 /// 获取前端资源目录路径
@@ -23,32 +42,19 @@ fn get_gui_path() -> PathBuf {
     exe_dir.join("gui")
 }
 
-/// 根据文件扩展名获取 MIME 类型
-fn get_mime_type(path: &std::path::Path) -> &'static str {
-    match path.extension().and_then(|e| e.to_str()) {
-        Some("html") => "text/html; charset=utf-8",
-        Some("js") => "application/javascript; charset=utf-8",
-        Some("mjs") => "application/javascript; charset=utf-8",
-        Some("css") => "text/css; charset=utf-8",
-        Some("json") => "application/json; charset=utf-8",
-        Some("svg") => "image/svg+xml",
-        Some("png") => "image/png",
-        Some("jpg") | Some("jpeg") => "image/jpeg",
-        Some("gif") => "image/gif",
-        Some("ico") => "image/x-icon",
-        Some("woff") => "font/woff",
-        Some("woff2") => "font/woff2",
-        Some("ttf") => "font/ttf",
-        Some("otf") => "font/otf",
-        Some("eot") => "application/vnd.ms-fontobject",
-        _ => "application/octet-stream",
-    }
-}
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 已有实例在运行时，把要打开的文件转发给它后退出
+    let startup_paths = single_instance::startup_paths();
+    let instance_listener = match single_instance::acquire(&startup_paths) {
+        single_instance::Instance::Primary(listener) => Some(listener),
+        single_instance::Instance::Forwarded => return,
+        single_instance::Instance::Standalone => None,
+    };
+
     let gui_path = get_gui_path();
-    let gui_path_clone = gui_path.clone();
+    // 协议处理按规范化后的根目录限制请求路径
+    let gui_root = gui_path.canonicalize().unwrap_or_else(|_| gui_path.clone());
     
     tauri::Builder::default()
         .plugin(tauri_plugin_log::Builder::default().build())
@@ -65,39 +71,11 @@ pub fn run() {
         .plugin(tauri_plugin_process::init())
         // 注册自定义协议从本地 gui 文件夹加载前端资源
         .register_uri_scheme_protocol("kairogui", move |_ctx, request| {
-            let uri = request.uri();
-            let mut path = uri.path().to_string();
-            
-            // URL 解码路径
-            if let Ok(decoded) = urlencoding::decode(&path) {
-                path = decoded.into_owned();
-            }
-            
-            // 移除开头的斜杠并处理路径
-            let file_path = if path == "/" || path.is_empty() {
-                gui_path_clone.join("index.html")
-            } else {
-                let clean_path = path.trim_start_matches('/');
-                gui_path_clone.join(clean_path)
-            };
-            
-            match std::fs::read(&file_path) {
-                Ok(content) => {
-                    tauri::http::Response::builder()
-                        .status(200)
-                        .header("Content-Type", get_mime_type(&file_path))
-                        .header("Access-Control-Allow-Origin", "*")
-                        .body(content)
-                        .unwrap()
-                }
-                Err(_) => {
-                    tauri::http::Response::builder()
-                        .status(404)
-                        .header("Content-Type", "text/plain")
-                        .body(format!("File not found: {:?}", file_path).into_bytes())
-                        .unwrap()
-                }
-            }
+            protocol::handle_gui_request(&gui_root, &request)
+        })
+        // 预览中的本地附件（图片、PDF、音视频），只允许访问登记的目录
+        .register_uri_scheme_protocol("kairofile", |_ctx, request| {
+            protocol::handle_file_request(&request)
         })
         .invoke_handler(tauri::generate_handler![
             commands::read_file,
@@ -105,9 +83,30 @@ pub fn run() {
             commands::get_file_info,
             commands::search_in_file,
             commands::search_and_replace,
+            commands::preview_replace,
+            commands::open_document,
+            commands::close_document,
+            commands::apply_document_changes,
+            commands::get_document_text,
+            commands::get_document_stats,
+            commands::search_document,
+            commands::preview_document_replace,
+            commands::get_document_highlight,
+            commands::structural_search_document,
+            commands::get_structural_languages,
+            commands::structural_search,
+            commands::structural_replace,
+            commands::structural_search_workspace,
             commands::get_recent_files,
             commands::add_recent_file,
             commands::clear_recent_files,
+            commands::get_recent_entries,
+            commands::add_recent_entry,
+            commands::remove_recent_entry,
+            commands::set_recent_pinned,
+            commands::clear_recent_entries,
+            commands::prune_recent_entries,
+            commands::filter_recent_entries,
             commands::detect_encoding,
             commands::convert_encoding,
             commands::get_syntax_highlight,
@@ -115,6 +114,34 @@ pub fn run() {
             commands::get_settings,
             commands::save_settings,
             commands::list_directory,
+            commands::open_large_file,
+            commands::close_large_file,
+            commands::get_large_file_status,
+            commands::read_large_file_lines,
+            commands::refresh_large_file,
+            commands::append_to_large_file,
+            commands::open_hex_document,
+            commands::close_hex_document,
+            commands::read_hex_bytes,
+            commands::write_hex_bytes,
+            commands::insert_hex_bytes,
+            commands::delete_hex_bytes,
+            commands::save_hex_document,
+            commands::save_hex_document_as,
+            commands::search_hex,
+            commands::set_file_protocol_roots,
+            commands::start_log_follow,
+            commands::stop_log_follow,
+            commands::update_log_follow_filter,
+            commands::detect_log_levels,
+            commands::open_file_index,
+            commands::close_file_index,
+            commands::get_file_index_status,
+            commands::find_files,
+            commands::open_full_text_index,
+            commands::close_full_text_index,
+            commands::get_full_text_index_status,
+            commands::search_full_text,
             commands::create_file,
             commands::create_directory,
             commands::delete_path,
@@ -147,9 +174,51 @@ pub fn run() {
             commands::get_config_directory,
             // Terminal commands
             commands::execute_terminal_command,
+            commands::cancel_terminal_command,
             commands::get_available_terminals,
+            commands::create_terminal_session,
+            commands::write_terminal_session,
+            commands::resize_terminal_session,
+            commands::kill_terminal_session,
+            commands::list_terminal_sessions,
+            commands::get_terminal_history,
+            commands::search_terminal_history,
+            commands::add_terminal_history,
+            commands::clear_terminal_history,
+            commands::rerun_terminal_history,
+            // Task commands
+            commands::list_tasks,
+            commands::run_task,
+            commands::run_group_task,
+            commands::cancel_task_run,
+            // Problem commands
+            commands::get_problems,
+            commands::clear_problems,
+            commands::next_problem,
+            commands::list_problem_matchers,
+            // Formatter commands
+            commands::format_document,
+            commands::format_data,
+            commands::list_formatters,
+            commands::convert_data,
+            // Validation commands
+            commands::validate_document,
+            // Language server commands
+            commands::lsp_open_document,
+            commands::lsp_change_document,
+            commands::lsp_save_document,
+            commands::lsp_close_document,
+            commands::lsp_completion,
+            commands::lsp_hover,
+            commands::lsp_definition,
+            commands::lsp_references,
+            commands::lsp_rename,
+            commands::lsp_formatting,
+            commands::list_language_servers,
+            commands::restart_language_server,
             // Window commands
             commands::show_main_window,
+            commands::frontend_ready,
         ])
         .setup(move |app| {
             use tauri::{WebviewUrl, WebviewWindowBuilder};
@@ -184,16 +253,10 @@ pub fn run() {
             // Log GUI path for debugging
             log::info!("GUI path: {:?}, exists: {}", gui_path, gui_path.exists());
             
-            // 处理命令行参数 - 打开文件
-            let args: Vec<String> = std::env::args().collect();
-            if args.len() > 1 {
-                let file_path = args[1].clone();
-                let window = app.get_webview_window("main").unwrap();
-                // 延迟发送事件，等待前端加载完成
-                tauri::async_runtime::spawn(async move {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-                    let _ = window.emit("open-file", file_path);
-                });
+            // 处理命令行参数 - 打开文件，前端调用 frontend_ready 后取走
+            single_instance::open_paths(app.handle(), startup_paths);
+            if let Some(listener) = instance_listener {
+                single_instance::start_listener(app.handle().clone(), listener);
             }
             
             // Initialize settings
@@ -0,0 +1,605 @@
+// Log Follow Module
+//
+// 跟踪持续追加的日志文件（类似 `tail -F`）：检测日志轮转和截断，
+// 在后端按包含/排除正则和严重级别过滤，新行以事件批量推送给前端
+
+use anyhow::{anyhow, Context, Result};
+use notify::{RecursiveMode, Watcher};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 新日志行事件
+pub const LOG_FOLLOW_EVENT: &str = "log-follow-lines";
+
+/// 文件监听之外的轮询间隔，监听不可用（如网络文件系统）时依靠轮询
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 未指定时开始跟踪前先发送的末尾行数
+const DEFAULT_INITIAL_LINES: usize = 200;
+
+/// 每次读取的字节数，同时也是一个事件最多覆盖的数据量
+const READ_CHUNK: usize = 1024 * 1024;
+
+/// 没有换行符的内容超过该长度时按该长度切分发送，避免不完整行的缓冲无限增长
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// 记录文件开头的字节数，用于识别截断后又写到超过原偏移的情况
+const FINGERPRINT_BYTES: u64 = 256;
+
+/// 只在行首这么多字节内查找级别关键字，避免消息正文中的单词被误认为级别
+const LEVEL_SCAN_BYTES: usize = 128;
+
+static FOLLOWERS: OnceLock<Mutex<HashMap<String, Arc<Follower>>>> = OnceLock::new();
+
+/// 日志严重级别，按严重程度排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    /// 解析级别名称（不区分大小写）
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "trace" | "trc" | "verbose" | "finest" | "finer" => Some(Self::Trace),
+            "debug" | "dbg" | "fine" | "config" => Some(Self::Debug),
+            "info" | "inf" | "information" | "notice" => Some(Self::Info),
+            "warn" | "warning" | "wrn" => Some(Self::Warn),
+            "error" | "err" | "eror" | "severe" => Some(Self::Error),
+            "fatal" | "critical" | "crit" | "alert" | "emerg" | "emergency" | "panic" => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+
+    /// pino / bunyan 的数字级别
+    fn from_number(value: u64) -> Option<Self> {
+        match value {
+            0..=10 => Some(Self::Trace),
+            11..=20 => Some(Self::Debug),
+            21..=30 => Some(Self::Info),
+            31..=40 => Some(Self::Warn),
+            41..=50 => Some(Self::Error),
+            _ => Some(Self::Fatal),
+        }
+    }
+
+    /// syslog 优先级 `<PRI>` 中的 severity（PRI 的低 3 位）
+    fn from_syslog_severity(severity: u64) -> Self {
+        match severity {
+            0..=2 => Self::Fatal,
+            3 => Self::Error,
+            4 => Self::Warn,
+            5 | 6 => Self::Info,
+            _ => Self::Debug,
+        }
+    }
+}
+
+/// 检测一行日志的严重级别，支持 JSON Lines、syslog、log4j 以及 Rust `env_logger` 等常见格式
+pub fn detect_level(line: &str) -> Option<LogLevel> {
+    let line = line.trim_start();
+    if line.starts_with('{') {
+        if let Some(level) = json_level(line) {
+            return Some(level);
+        }
+    }
+    syslog_level(line).or_else(|| keyword_level(line))
+}
+
+fn json_level(line: &str) -> Option<LogLevel> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let object = value.as_object()?;
+    let level = ["level", "severity", "lvl", "loglevel", "levelname", "log.level", "@l"]
+        .iter()
+        .find_map(|key| object.get(*key))
+        // ECS 格式：{"log": {"level": "..."}}
+        .or_else(|| object.get("log")?.get("level"))?;
+    match level {
+        serde_json::Value::String(name) => LogLevel::parse(name),
+        serde_json::Value::Number(number) => number.as_u64().and_then(LogLevel::from_number),
+        _ => None,
+    }
+}
+
+fn syslog_level(line: &str) -> Option<LogLevel> {
+    let rest = line.strip_prefix('<')?;
+    let end = rest.find('>').filter(|&end| (1..=3).contains(&end))?;
+    let priority: u64 = rest[..end].parse().ok()?;
+    Some(LogLevel::from_syslog_severity(priority & 7))
+}
+
+/// 行首附近的级别关键字：
+/// log4j / logback 的 `2024-01-01 12:00:00 ERROR ...`，
+/// env_logger 的 `[2024-01-01T12:00:00Z WARN  app::module] ...`，
+/// 以及 `[error]`、`level=warn` 等形式
+fn keyword_level(line: &str) -> Option<LogLevel> {
+    static KEYWORD: OnceLock<Regex> = OnceLock::new();
+    let regex = KEYWORD.get_or_init(|| {
+        Regex::new(concat!(
+            r"\b(TRACE|DEBUG|INFO|NOTICE|WARN|WARNING|ERROR|ERR|SEVERE|FATAL|CRITICAL|CRIT|ALERT|EMERG|PANIC)\b",
+            r"|\[(?i:(trace|debug|info|notice|warn|warning|error|err|fatal|critical|crit))\]",
+            r#"|(?i:\b(?:level|lvl|severity)=)"?(\w+)"#,
+        ))
+        .unwrap()
+    });
+
+    let mut end = line.len().min(LEVEL_SCAN_BYTES);
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    let captures = regex.captures(&line[..end])?;
+    let name = captures.iter().skip(1).flatten().next()?;
+    LogLevel::parse(name.as_str())
+}
+
+/// 跟踪选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FollowOptions {
+    /// 只保留匹配该正则的行
+    pub include: Option<String>,
+    /// 丢弃匹配该正则的行
+    pub exclude: Option<String>,
+    /// 最低严重级别；没有可识别级别的行沿用前一条日志的级别（如异常堆栈）
+    pub min_level: Option<LogLevel>,
+    pub case_sensitive: bool,
+    /// 开始跟踪时先发送的末尾行数
+    pub initial_lines: Option<usize>,
+}
+
+/// 编译后的过滤条件
+struct LineFilter {
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+    min_level: Option<LogLevel>,
+}
+
+impl LineFilter {
+    fn new(options: &FollowOptions) -> Result<Self> {
+        let compile = |pattern: &Option<String>| -> Result<Option<Regex>> {
+            pattern
+                .as_deref()
+                .filter(|pattern| !pattern.is_empty())
+                .map(|pattern| {
+                    RegexBuilder::new(pattern)
+                        .case_insensitive(!options.case_sensitive)
+                        .build()
+                        .with_context(|| format!("Invalid filter pattern: {}", pattern))
+                })
+                .transpose()
+        };
+        Ok(Self {
+            include: compile(&options.include)?,
+            exclude: compile(&options.exclude)?,
+            min_level: options.min_level,
+        })
+    }
+
+    fn accepts(&self, text: &str, level: Option<LogLevel>) -> bool {
+        self.min_level.map_or(true, |min| level.is_some_and(|level| level >= min))
+            && self.include.as_ref().map_or(true, |regex| regex.is_match(text))
+            && !self.exclude.as_ref().is_some_and(|regex| regex.is_match(text))
+    }
+}
+
+/// 一行日志
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+    pub text: String,
+    pub level: Option<LogLevel>,
+    /// 行在当前文件中的字节偏移
+    pub offset: u64,
+}
+
+/// 文件被替换或截断时重新从头读取
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FollowReset {
+    Rotated,
+    Truncated,
+}
+
+/// 一批新行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFollowBatch {
+    pub id: String,
+    pub lines: Vec<LogLine>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset: Option<FollowReset>,
+    /// 被过滤掉的行数
+    pub filtered: usize,
+}
+
+/// 用于识别文件是否被替换：Unix 上为设备号和 inode，
+/// 其他平台为创建时间（Windows 在 15 秒内以同名重建文件时可能沿用旧的创建时间，
+/// 此时依靠长度变小识别为截断）
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    let created = metadata.created().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some((created.as_secs(), created.subsec_nanos() as u64))
+}
+
+/// 从末尾向前找到倒数第 `lines` 行的起始偏移
+fn tail_offset(file: &mut File, len: u64, lines: usize) -> io::Result<u64> {
+    if lines == 0 {
+        return Ok(len);
+    }
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut pos = len;
+    let mut newlines = 0;
+    while pos > 0 {
+        let size = (buf.len() as u64).min(pos) as usize;
+        pos -= size as u64;
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut buf[..size])?;
+        for i in (0..size).rev() {
+            let offset = pos + i as u64;
+            // 文件末尾的换行符属于最后一行
+            if buf[i] == b'\n' && offset + 1 != len {
+                newlines += 1;
+                if newlines == lines {
+                    return Ok(offset + 1);
+                }
+            }
+        }
+    }
+    Ok(0)
+}
+
+/// 跟踪线程持有的读取状态
+struct FollowState {
+    file: File,
+    identity: Option<(u64, u64)>,
+    /// 已读取到的字节偏移
+    offset: u64,
+    /// 尚未遇到换行符的末尾部分
+    partial: Vec<u8>,
+    /// 最近一条可识别级别的日志级别
+    level: Option<LogLevel>,
+    /// 已读取部分开头的字节（最多 `FINGERPRINT_BYTES`）
+    fingerprint: Vec<u8>,
+}
+
+impl FollowState {
+    fn open(path: &Path, initial_lines: usize) -> Result<Self> {
+        let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let metadata = file.metadata()?;
+        let offset = tail_offset(&mut file, metadata.len(), initial_lines)?;
+        let mut state = Self {
+            file,
+            identity: file_identity(&metadata),
+            offset,
+            partial: Vec::new(),
+            level: None,
+            fingerprint: Vec::new(),
+        };
+        state.update_fingerprint()?;
+        Ok(state)
+    }
+
+    fn read_prefix(&mut self, len: u64) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.file.seek(SeekFrom::Start(0))?;
+        (&mut self.file).take(len).read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// 指纹不足 `FINGERPRINT_BYTES` 时用新读取的内容补足
+    fn update_fingerprint(&mut self) -> io::Result<()> {
+        let len = self.offset.min(FINGERPRINT_BYTES);
+        if (self.fingerprint.len() as u64) < len {
+            self.fingerprint = self.read_prefix(len)?;
+        }
+        Ok(())
+    }
+
+    /// 文件开头与指纹不同时说明文件被截断后重新写入（长度可能已超过原偏移）
+    fn prefix_changed(&mut self) -> io::Result<bool> {
+        if self.fingerprint.is_empty() {
+            return Ok(false);
+        }
+        Ok(self.read_prefix(self.fingerprint.len() as u64)? != self.fingerprint)
+    }
+}
+
+struct Follower {
+    id: String,
+    path: PathBuf,
+    filter: RwLock<LineFilter>,
+    stopped: AtomicBool,
+}
+
+impl Follower {
+    /// 读取新增内容；文件被轮转时先读完旧文件剩余部分，再从新文件开头读取
+    fn poll(&self, state: &mut FollowState, emit: &mut impl FnMut(LogFollowBatch)) -> io::Result<()> {
+        // 轮转过程中文件可能暂时不存在
+        let Ok(metadata) = std::fs::metadata(&self.path) else {
+            return Ok(());
+        };
+
+        let mut reset = None;
+        if file_identity(&metadata) != state.identity {
+            self.read_new(state, None, emit)?;
+            let file = File::open(&self.path)?;
+            state.identity = file_identity(&file.metadata()?);
+            state.file = file;
+            reset = Some(FollowReset::Rotated);
+        } else if metadata.len() < state.offset || state.prefix_changed()? {
+            reset = Some(FollowReset::Truncated);
+        }
+        if reset.is_some() {
+            state.offset = 0;
+            state.partial.clear();
+            state.level = None;
+            state.fingerprint.clear();
+        }
+        self.read_new(state, reset, emit)?;
+        state.update_fingerprint()
+    }
+
+    fn read_new(
+        &self,
+        state: &mut FollowState,
+        mut reset: Option<FollowReset>,
+        emit: &mut impl FnMut(LogFollowBatch),
+    ) -> io::Result<()> {
+        state.file.seek(SeekFrom::Start(state.offset))?;
+        let mut buf = vec![0u8; READ_CHUNK];
+        loop {
+            let read = state.file.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            let line_start = state.offset - state.partial.len() as u64;
+            state.offset += read as u64;
+            state.partial.extend_from_slice(&buf[..read]);
+            let batch = self.split_lines(state, line_start, reset.take());
+            if !batch.lines.is_empty() || batch.filtered > 0 || batch.reset.is_some() {
+                emit(batch);
+            }
+        }
+        if let Some(reset) = reset {
+            emit(LogFollowBatch { id: self.id.clone(), lines: Vec::new(), reset: Some(reset), filtered: 0 });
+        }
+        Ok(())
+    }
+
+    /// 取出 `partial` 中所有完整的行，末尾不完整的部分留到下次（超过 `MAX_LINE_BYTES` 时切分发送）
+    fn split_lines(&self, state: &mut FollowState, mut offset: u64, reset: Option<FollowReset>) -> LogFollowBatch {
+        let filter = self.filter.read().unwrap();
+        let mut lines = Vec::new();
+        let mut filtered = 0;
+        let mut consumed = 0;
+        loop {
+            let rest = &state.partial[consumed..];
+            let end = match memchr::memchr(b'\n', rest) {
+                Some(newline) => consumed + newline + 1,
+                None if rest.len() > MAX_LINE_BYTES => consumed + MAX_LINE_BYTES,
+                None => break,
+            };
+            let raw = &state.partial[consumed..end];
+            let raw = raw.strip_suffix(b"\n").unwrap_or(raw);
+            let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+            let text = String::from_utf8_lossy(raw).into_owned();
+            let level = match detect_level(&text) {
+                Some(level) => {
+                    state.level = Some(level);
+                    Some(level)
+                }
+                None => state.level,
+            };
+            if filter.accepts(&text, level) {
+                lines.push(LogLine { text, level, offset });
+            } else {
+                filtered += 1;
+            }
+            offset += (end - consumed) as u64;
+            consumed = end;
+        }
+        state.partial.drain(..consumed);
+        LogFollowBatch { id: self.id.clone(), lines, reset, filtered }
+    }
+}
+
+fn followers() -> &'static Mutex<HashMap<String, Arc<Follower>>> {
+    FOLLOWERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 开始跟踪日志文件，返回跟踪 ID；先发送末尾的若干行，之后推送新追加的行
+pub fn start(app: AppHandle, path: &str, options: &FollowOptions) -> Result<String> {
+    let path = PathBuf::from(path);
+    let filter = LineFilter::new(options)?;
+    let mut state = FollowState::open(&path, options.initial_lines.unwrap_or(DEFAULT_INITIAL_LINES))?;
+
+    let follower = Arc::new(Follower {
+        id: uuid::Uuid::new_v4().to_string(),
+        path,
+        filter: RwLock::new(filter),
+        stopped: AtomicBool::new(false),
+    });
+    followers().lock().unwrap().insert(follower.id.clone(), follower.clone());
+    let id = follower.id.clone();
+
+    std::thread::spawn(move || {
+        let mut emit = |batch: LogFollowBatch| {
+            let _ = app.emit(LOG_FOLLOW_EVENT, batch);
+        };
+
+        // 监听父目录，这样文件被删除重建后仍能收到通知
+        let (tx, rx) = mpsc::channel::<()>();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if event.is_ok() {
+                let _ = tx.send(());
+            }
+        })
+        .and_then(|mut watcher| {
+            let dir = follower.path.parent().filter(|dir| !dir.as_os_str().is_empty());
+            watcher.watch(dir.unwrap_or(Path::new(".")), RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+        if let Err(e) = &watcher {
+            log::warn!("Log follow falls back to polling for {}: {}", follower.path.display(), e);
+        }
+
+        while !follower.stopped.load(Ordering::Relaxed) {
+            if let Err(e) = follower.poll(&mut state, &mut emit) {
+                log::warn!("Failed to read {}: {}", follower.path.display(), e);
+            }
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(()) => while rx.try_recv().is_ok() {},
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => std::thread::sleep(POLL_INTERVAL),
+            }
+        }
+        drop(watcher);
+    });
+
+    Ok(id)
+}
+
+pub fn stop(id: &str) {
+    if let Some(follower) = followers().lock().unwrap().remove(id) {
+        follower.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 更新过滤条件，只影响之后读取的行
+pub fn update_filter(id: &str, options: &FollowOptions) -> Result<()> {
+    let follower = followers()
+        .lock()
+        .unwrap()
+        .get(id)
+        .cloned()
+        .ok_or_else(|| anyhow!("Log follow not found: {}", id))?;
+    *follower.filter.write().unwrap() = LineFilter::new(options)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_detect_level() {
+        let cases = [
+            (r#"{"level":"warn","msg":"disk almost full"}"#, Some(LogLevel::Warn)),
+            (r#"{"level":50,"msg":"pino error"}"#, Some(LogLevel::Error)),
+            (r#"{"log":{"level":"debug"},"message":"ecs"}"#, Some(LogLevel::Debug)),
+            ("<11>Jan  1 00:00:00 host app: failed", Some(LogLevel::Error)),
+            ("<30>Jan  1 00:00:00 host app: started", Some(LogLevel::Info)),
+            ("2024-01-01 12:00:00,123 FATAL [main] com.example.App - boom", Some(LogLevel::Fatal)),
+            ("[2024-01-01T12:00:00Z WARN  my_app::server] slow request", Some(LogLevel::Warn)),
+            ("[Mon Jan 01 12:00:00 2024] [error] client denied", Some(LogLevel::Error)),
+            ("time=2024-01-01 level=trace msg=\"tick\"", Some(LogLevel::Trace)),
+            ("INFORMATIONAL banner without level", None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(detect_level(line), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_follow_append_truncate_and_filter() {
+        let dir = std::env::temp_dir().join(format!("kairo-log-follow-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        std::fs::write(&path, "old 1\nold 2\nold 3\n").unwrap();
+
+        let options = FollowOptions { min_level: Some(LogLevel::Warn), exclude: Some("ignored".into()), ..Default::default() };
+        let follower = Follower {
+            id: "test".into(),
+            path: path.clone(),
+            filter: RwLock::new(LineFilter::new(&options).unwrap()),
+            stopped: AtomicBool::new(false),
+        };
+        let mut state = FollowState::open(&path, 2).unwrap();
+        assert_eq!(state.offset, 6);
+
+        let mut batches = Vec::new();
+        let mut emit = |batch: LogFollowBatch| batches.push(batch);
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        // 异常堆栈沿用 ERROR 级别，不完整的最后一行留到下次读取
+        write!(file, "INFO started\nERROR failed\n    at main.rs:1\nWARN ignored\nWARN par").unwrap();
+        follower.poll(&mut state, &mut emit).unwrap();
+        writeln!(file, "tial").unwrap();
+        follower.poll(&mut state, &mut emit).unwrap();
+        drop(file);
+
+        std::fs::write(&path, "ERROR again\n").unwrap();
+        follower.poll(&mut state, &mut emit).unwrap();
+
+        let texts: Vec<Vec<&str>> =
+            batches.iter().map(|batch| batch.lines.iter().map(|line| line.text.as_str()).collect()).collect();
+        assert_eq!(texts, vec![vec!["ERROR failed", "    at main.rs:1"], vec!["WARN partial"], vec!["ERROR again"]]);
+        assert_eq!(batches[0].filtered, 4);
+        assert_eq!(batches[0].lines[1].level, Some(LogLevel::Error));
+        assert_eq!(batches[2].reset, Some(FollowReset::Truncated));
+        assert_eq!(batches[2].lines[0].offset, 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_follow_long_line_and_rewrite() {
+        let dir = std::env::temp_dir().join(format!("kairo-log-rewrite-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        std::fs::write(&path, "first\n").unwrap();
+
+        let follower = Follower {
+            id: "test".into(),
+            path: path.clone(),
+            filter: RwLock::new(LineFilter::new(&FollowOptions::default()).unwrap()),
+            stopped: AtomicBool::new(false),
+        };
+        let mut state = FollowState::open(&path, 10).unwrap();
+        let mut batches = Vec::new();
+        let mut emit = |batch: LogFollowBatch| batches.push(batch);
+        follower.poll(&mut state, &mut emit).unwrap();
+
+        // 没有换行符的长内容按长度切分，只保留不足一段的部分
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&vec![b'x'; MAX_LINE_BYTES * 2 + 10]).unwrap();
+        follower.poll(&mut state, &mut emit).unwrap();
+        drop(file);
+        assert_eq!(state.partial.len(), 10);
+
+        // 截断后重新写入超过原偏移的内容，通过开头的指纹识别
+        let rewritten = format!("second\n{}\n", "y".repeat(MAX_LINE_BYTES * 3));
+        std::fs::write(&path, &rewritten).unwrap();
+        follower.poll(&mut state, &mut emit).unwrap();
+
+        let lengths: Vec<Vec<usize>> =
+            batches.iter().map(|batch| batch.lines.iter().map(|line| line.text.len()).collect()).collect();
+        assert_eq!(lengths[0], vec![5]);
+        assert_eq!(lengths[1], vec![MAX_LINE_BYTES, MAX_LINE_BYTES]);
+        assert_eq!(batches[2].reset, Some(FollowReset::Truncated));
+        assert_eq!(batches[2].lines[0].text, "second");
+        assert_eq!(state.offset, rewritten.len() as u64);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1181 @@
+// Language Server Protocol Module
+// 按语言和工作区根目录通过 stdio 启动语言服务器，同步文档并提供补全、悬停、跳转、重命名、格式化和诊断
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, Command};
+use tokio::sync::{oneshot, Mutex, RwLock};
+
+use crate::{problems, syntax, system_integration};
+
+/// 运行中的语言服务器，按服务器 ID 和根目录区分
+static SERVERS: OnceLock<Mutex<HashMap<ServerKey, Arc<LanguageServer>>>> = OnceLock::new();
+/// 已打开文档（URI）所属的语言服务器
+static DOCUMENTS: OnceLock<Mutex<HashMap<String, ServerKey>>> = OnceLock::new();
+
+/// 诊断信息事件（原始 LSP 格式）
+pub const LSP_DIAGNOSTICS_EVENT: &str = "lsp-diagnostics";
+/// 语言服务器状态变化事件
+pub const LSP_STATUS_EVENT: &str = "lsp-status";
+/// 语言服务器消息事件（window/showMessage）
+pub const LSP_MESSAGE_EVENT: &str = "lsp-message";
+
+/// 请求超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// 在重启窗口期内最多自动重启的次数
+const MAX_RESTARTS: usize = 3;
+const RESTART_WINDOW: Duration = Duration::from_secs(120);
+
+/// 语言服务器配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageServerConfig {
+    pub id: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 处理的语言 ID（与 detect_language 的结果一致）
+    pub languages: Vec<String>,
+    /// 用于确定项目根目录的标记文件
+    #[serde(default)]
+    pub root_markers: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub initialization_options: Option<Value>,
+    /// 响应 workspace/configuration 请求的设置
+    #[serde(default)]
+    pub settings: Option<Value>,
+}
+
+/// 语言服务器标识：服务器 ID + 根目录
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerKey {
+    pub server_id: String,
+    pub root: String,
+}
+
+/// 语言服务器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerStatus {
+    Starting,
+    Running,
+    Restarting,
+    Stopped,
+    Crashed,
+}
+
+/// 语言服务器信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageServerInfo {
+    pub server_id: String,
+    pub root: String,
+    pub command: String,
+    pub status: ServerStatus,
+    /// 自动重启次数
+    pub restarts: usize,
+    /// 打开的文档数量
+    pub documents: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusPayload {
+    server_id: String,
+    root: String,
+    status: ServerStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticsPayload {
+    server_id: String,
+    root: String,
+    uri: String,
+    path: Option<String>,
+    diagnostics: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MessagePayload {
+    server_id: String,
+    /// 1 = 错误, 2 = 警告, 3 = 信息, 4 = 日志
+    level: i64,
+    message: String,
+}
+
+/// 内置语言服务器配置，需要用户自行安装对应程序
+pub fn builtin_servers() -> Vec<LanguageServerConfig> {
+    let server = |id: &str, command: &str, args: &[&str], languages: &[&str], markers: &[&str]| {
+        LanguageServerConfig {
+            id: id.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|s| s.to_string()).collect(),
+            languages: languages.iter().map(|s| s.to_string()).collect(),
+            root_markers: markers.iter().map(|s| s.to_string()).collect(),
+            env: HashMap::new(),
+            initialization_options: None,
+            settings: None,
+        }
+    };
+
+    vec![
+        server("rust-analyzer", "rust-analyzer", &[], &["rust"], &["Cargo.toml"]),
+        server(
+            "typescript-language-server",
+            "typescript-language-server",
+            &["--stdio"],
+            &["typescript", "javascript", "typescriptreact", "javascriptreact"],
+            &["tsconfig.json", "jsconfig.json", "package.json"],
+        ),
+        server(
+            "pylsp",
+            "pylsp",
+            &[],
+            &["python"],
+            &["pyproject.toml", "setup.py", "setup.cfg", "requirements.txt"],
+        ),
+        server("gopls", "gopls", &[], &["go"], &["go.mod", "go.work"]),
+        server(
+            "clangd",
+            "clangd",
+            &[],
+            &["c", "cpp"],
+            &["compile_commands.json", "CMakeLists.txt", ".clangd"],
+        ),
+        server(
+            "vscode-json-language-server",
+            "vscode-json-language-server",
+            &["--stdio"],
+            &["json", "jsonc"],
+            &["package.json"],
+        ),
+    ]
+}
+
+fn get_user_servers_path() -> Result<PathBuf> {
+    let config_dir = system_integration::get_config_dir()?;
+    Ok(config_dir.join("language_servers.json"))
+}
+
+/// 获取所有语言服务器配置，用户配置（config/language_servers.json）覆盖同 ID 的内置配置
+pub async fn get_server_configs() -> Result<Vec<LanguageServerConfig>> {
+    let mut configs = builtin_servers();
+    let path = get_user_servers_path()?;
+    if path.exists() {
+        let content = tokio::fs::read_to_string(&path).await?;
+        let user: Vec<LanguageServerConfig> = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Invalid language server config {}: {}", path.display(), e))?;
+        for config in user {
+            configs.retain(|c| c.id != config.id);
+            configs.push(config);
+        }
+    }
+    Ok(configs)
+}
+
+fn servers() -> &'static Mutex<HashMap<ServerKey, Arc<LanguageServer>>> {
+    SERVERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn documents() -> &'static Mutex<HashMap<String, ServerKey>> {
+    DOCUMENTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 将文件路径转换为 file:// URI
+pub fn path_to_uri(path: &str) -> String {
+    let normalized = path.replace('\\', "/");
+    let encoded = normalized
+        .split('/')
+        .map(|segment| {
+            // 保留 Windows 盘符中的冒号
+            let is_drive = segment.len() == 2
+                && segment.ends_with(':')
+                && segment.chars().next().is_some_and(|c| c.is_ascii_alphabetic());
+            if is_drive {
+                segment.to_string()
+            } else {
+                urlencoding::encode(segment).into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if encoded.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        format!("file:///{}", encoded)
+    }
+}
+
+/// 将 file:// URI 转换为文件路径
+pub fn uri_to_path(uri: &str) -> Option<String> {
+    let rest = uri.strip_prefix("file://")?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    let decoded = urlencoding::decode(rest).ok()?.into_owned();
+
+    // Windows: /C:/path -> C:\path
+    let bytes = decoded.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        let path = &decoded[1..];
+        return Some(if cfg!(windows) { path.replace('/', "\\") } else { path.to_string() });
+    }
+    Some(decoded)
+}
+
+/// LSP 语言 ID 与 detect_language 结果不一致的情况
+fn lsp_language_id(language: &str) -> &str {
+    match language {
+        "shell" => "shellscript",
+        "batch" => "bat",
+        other => other,
+    }
+}
+
+/// 确定项目根目录：从文件所在目录向上查找标记文件，不超出工作区根目录；
+/// 找不到时使用包含该文件的工作区根目录，否则使用文件所在目录
+fn find_root(path: &Path, workspace: Option<&Path>, markers: &[String]) -> PathBuf {
+    let parent = path.parent().unwrap_or(path);
+    let workspace = workspace.filter(|w| path.starts_with(w));
+
+    if !markers.is_empty() {
+        for dir in parent.ancestors() {
+            if markers.iter().any(|m| dir.join(m).exists()) {
+                return dir.to_path_buf();
+            }
+            if workspace.is_some_and(|w| dir == w) {
+                break;
+            }
+        }
+    }
+
+    workspace.unwrap_or(parent).to_path_buf()
+}
+
+async fn write_message(stdin: &mut ChildStdin, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    stdin
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    stdin.write_all(&body).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+/// 读取一条 JSON-RPC 消息，流结束时返回 None
+async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse()?);
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+/// 与语言服务器进程的一次连接，重启时整体替换
+struct Connection {
+    generation: u64,
+    stdin: Mutex<ChildStdin>,
+    pending: std::sync::Mutex<HashMap<i64, oneshot::Sender<Result<Value>>>>,
+    next_id: AtomicI64,
+    capabilities: std::sync::RwLock<Value>,
+    kill: std::sync::Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl Connection {
+    async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        write_message(&mut *self.stdin.lock().await, &message).await
+    }
+
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(e) = write_message(&mut *self.stdin.lock().await, &message).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(anyhow!("Language server exited before responding to {}", method)),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                let _ = self.notify("$/cancelRequest", json!({ "id": id })).await;
+                Err(anyhow!("Language server request timed out: {}", method))
+            }
+        }
+    }
+
+    async fn respond(&self, id: Value, result: Result<Value, (i64, String)>) -> Result<()> {
+        let message = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        write_message(&mut *self.stdin.lock().await, &message).await
+    }
+
+    fn fail_pending(&self) {
+        for (_, tx) in self.pending.lock().unwrap().drain() {
+            let _ = tx.send(Err(anyhow!("Language server exited")));
+        }
+    }
+
+    fn kill(&self) {
+        if let Some(kill) = self.kill.lock().unwrap().take() {
+            let _ = kill.send(());
+        }
+    }
+}
+
+struct OpenDocument {
+    language_id: String,
+    version: i64,
+    text: String,
+}
+
+struct LanguageServer {
+    key: ServerKey,
+    config: LanguageServerConfig,
+    app: AppHandle,
+    connection: RwLock<Option<Arc<Connection>>>,
+    /// 启动、停止和重启互斥进行；请求期间持有读锁，重启会等进行中的请求结束，
+    /// 重启期间发出的请求等待新进程就绪
+    lifecycle: RwLock<()>,
+    /// 打开的文档，重启后重新发送给服务器
+    documents: Mutex<HashMap<String, OpenDocument>>,
+    /// 每个文档的诊断，汇总后发布到问题列表
+    diagnostics: std::sync::Mutex<HashMap<String, Vec<problems::Problem>>>,
+    status: std::sync::Mutex<ServerStatus>,
+    restart_times: std::sync::Mutex<Vec<Instant>>,
+    restarts: std::sync::Mutex<usize>,
+    shutting_down: AtomicBool,
+    /// 每次启动递增，用于忽略已被替换的旧进程的退出
+    generation: AtomicU64,
+}
+
+impl LanguageServer {
+    fn new(key: ServerKey, config: LanguageServerConfig, app: AppHandle) -> Self {
+        Self {
+            key,
+            config,
+            app,
+            connection: RwLock::new(None),
+            lifecycle: RwLock::new(()),
+            documents: Mutex::new(HashMap::new()),
+            diagnostics: std::sync::Mutex::new(HashMap::new()),
+            status: std::sync::Mutex::new(ServerStatus::Starting),
+            restart_times: std::sync::Mutex::new(Vec::new()),
+            restarts: std::sync::Mutex::new(0),
+            shutting_down: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    fn problems_source(&self) -> String {
+        format!("lsp:{}:{}", self.key.server_id, self.key.root)
+    }
+
+    fn set_status(&self, status: ServerStatus) {
+        *self.status.lock().unwrap() = status;
+        let _ = self.app.emit(
+            LSP_STATUS_EVENT,
+            StatusPayload {
+                server_id: self.key.server_id.clone(),
+                root: self.key.root.clone(),
+                status,
+            },
+        );
+    }
+
+    async fn info(&self) -> LanguageServerInfo {
+        let documents = self.documents.lock().await.len();
+        LanguageServerInfo {
+            server_id: self.key.server_id.clone(),
+            root: self.key.root.clone(),
+            command: self.config.command.clone(),
+            status: *self.status.lock().unwrap(),
+            restarts: *self.restarts.lock().unwrap(),
+            documents,
+        }
+    }
+
+    async fn connection(&self) -> Result<Arc<Connection>> {
+        self.connection
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow!("Language server {} is not running", self.key.server_id))
+    }
+
+    /// 启动服务器进程并完成 initialize 握手，然后重新打开已有文档
+    async fn start(self: &Arc<Self>) -> Result<()> {
+        self.set_status(ServerStatus::Starting);
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let mut child = Command::new(&self.config.command)
+            .args(&self.config.args)
+            .envs(&self.config.env)
+            .current_dir(&self.key.root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                anyhow!("Failed to start language server {}: {}", self.config.command, e)
+            })?;
+
+        let stdin = child.stdin.take().ok_or_else(|| anyhow!("Failed to open stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| anyhow!("Failed to open stdout"))?;
+        let stderr = child.stderr.take().ok_or_else(|| anyhow!("Failed to open stderr"))?;
+
+        let (kill_tx, kill_rx) = oneshot::channel::<()>();
+        let connection = Arc::new(Connection {
+            generation,
+            stdin: Mutex::new(stdin),
+            pending: std::sync::Mutex::new(HashMap::new()),
+            next_id: AtomicI64::new(1),
+            capabilities: std::sync::RwLock::new(Value::Null),
+            kill: std::sync::Mutex::new(Some(kill_tx)),
+        });
+
+        tauri::async_runtime::spawn(async move {
+            tokio::select! {
+                _ = child.wait() => {}
+                _ = kill_rx => {
+                    let _ = child.kill().await;
+                }
+            }
+        });
+
+        let server_id = self.key.server_id.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                log::debug!("[{}] {}", server_id, line);
+            }
+        });
+
+        {
+            let server = self.clone();
+            let connection = connection.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut reader = BufReader::new(stdout);
+                loop {
+                    match read_message(&mut reader).await {
+                        Ok(Some(message)) => server.handle_message(&connection, message).await,
+                        Ok(None) => break,
+                        Err(e) => {
+                            log::warn!("[{}] Invalid message: {}", server.key.server_id, e);
+                            break;
+                        }
+                    }
+                }
+                connection.fail_pending();
+                connection.kill();
+                server.handle_exit(&connection);
+            });
+        }
+
+        let root_uri = path_to_uri(&self.key.root);
+        let root_name = Path::new(&self.key.root)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.key.root.clone());
+        let params = json!({
+            "processId": std::process::id(),
+            "clientInfo": { "name": "KairoNotes", "version": env!("CARGO_PKG_VERSION") },
+            "rootPath": self.key.root,
+            "rootUri": root_uri,
+            "workspaceFolders": [{ "uri": root_uri, "name": root_name }],
+            "initializationOptions": self.config.initialization_options,
+            "capabilities": client_capabilities(),
+        });
+
+        let result = match connection.request("initialize", params, REQUEST_TIMEOUT).await {
+            Ok(result) => result,
+            Err(e) => {
+                // 忽略该进程的退出，由调用方决定如何处理
+                self.generation.fetch_add(1, Ordering::SeqCst);
+                connection.kill();
+                return Err(e);
+            }
+        };
+        *connection.capabilities.write().unwrap() =
+            result.get("capabilities").cloned().unwrap_or(Value::Null);
+        connection.notify("initialized", json!({})).await?;
+        if let Some(settings) = &self.config.settings {
+            connection
+                .notify("workspace/didChangeConfiguration", json!({ "settings": settings }))
+                .await?;
+        }
+
+        let documents = self.documents.lock().await;
+        for (uri, document) in documents.iter() {
+            connection
+                .notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": {
+                            "uri": uri,
+                            "languageId": document.language_id,
+                            "version": document.version,
+                            "text": document.text,
+                        }
+                    }),
+                )
+                .await?;
+        }
+        *self.connection.write().await = Some(connection);
+        drop(documents);
+
+        self.set_status(ServerStatus::Running);
+        Ok(())
+    }
+
+    /// 进程退出后自动重启，短时间内崩溃次数过多时放弃
+    fn handle_exit(self: &Arc<Self>, connection: &Arc<Connection>) {
+        let server = self.clone();
+        let connection = connection.clone();
+        let task: Pin<Box<dyn Future<Output = ()> + Send>> = Box::pin(async move {
+            {
+                let mut current = server.connection.write().await;
+                if current.as_ref().is_some_and(|c| Arc::ptr_eq(c, &connection)) {
+                    *current = None;
+                }
+            }
+            // 与手动重启、停止互斥，持锁期间的新请求会等待重启完成
+            let _lifecycle = server.lifecycle.write().await;
+            if server.generation.load(Ordering::SeqCst) != connection.generation {
+                return;
+            }
+            if server.shutting_down.load(Ordering::SeqCst) {
+                server.set_status(ServerStatus::Stopped);
+                return;
+            }
+
+            let attempt = {
+                let mut times = server.restart_times.lock().unwrap();
+                times.retain(|t| t.elapsed() < RESTART_WINDOW);
+                if times.len() >= MAX_RESTARTS {
+                    None
+                } else {
+                    times.push(Instant::now());
+                    Some(times.len())
+                }
+            };
+            let Some(attempt) = attempt else {
+                log::error!("Language server {} crashed too often, giving up", server.key.server_id);
+                server.set_status(ServerStatus::Crashed);
+                return;
+            };
+
+            log::warn!(
+                "Language server {} exited, restarting (attempt {})",
+                server.key.server_id,
+                attempt
+            );
+            server.set_status(ServerStatus::Restarting);
+            tokio::time::sleep(Duration::from_millis(500 * (1 << attempt))).await;
+            if server.shutting_down.load(Ordering::SeqCst) {
+                server.set_status(ServerStatus::Stopped);
+                return;
+            }
+            *server.restarts.lock().unwrap() += 1;
+            if let Err(e) = server.start().await {
+                log::error!("Failed to restart language server {}: {}", server.key.server_id, e);
+                server.set_status(ServerStatus::Crashed);
+            }
+        });
+        tauri::async_runtime::spawn(task);
+    }
+
+    async fn handle_message(&self, connection: &Connection, message: Value) {
+        let method = message.get("method").and_then(Value::as_str);
+        let id = message.get("id").cloned();
+
+        match (method, id) {
+            // 响应
+            (None, Some(id)) => {
+                let Some(id) = id.as_i64() else { return };
+                let Some(tx) = connection.pending.lock().unwrap().remove(&id) else { return };
+                let result = match message.get("error") {
+                    Some(error) => Err(anyhow!(
+                        "{}",
+                        error.get("message").and_then(Value::as_str).unwrap_or("Unknown error")
+                    )),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = tx.send(result);
+            }
+            // 服务器发起的请求
+            (Some(method), Some(id)) => {
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                let result = self.handle_server_request(method, &params);
+                if let Err(e) = connection.respond(id, result).await {
+                    log::warn!("[{}] Failed to respond to {}: {}", self.key.server_id, method, e);
+                }
+            }
+            // 通知
+            (Some(method), None) => {
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                self.handle_notification(method, params).await;
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn handle_server_request(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "workspace/configuration" => {
+                let items = params.get("items").and_then(Value::as_array).cloned().unwrap_or_default();
+                let settings = self.config.settings.clone().unwrap_or(Value::Null);
+                Ok(Value::Array(
+                    items
+                        .iter()
+                        .map(|item| match item.get("section").and_then(Value::as_str) {
+                            Some(section) => section
+                                .split('.')
+                                .try_fold(&settings, |value, key| value.get(key))
+                                .cloned()
+                                .unwrap_or(Value::Null),
+                            None => settings.clone(),
+                        })
+                        .collect(),
+                ))
+            }
+            "workspace/workspaceFolders" => {
+                let uri = path_to_uri(&self.key.root);
+                Ok(json!([{ "uri": uri, "name": self.key.root }]))
+            }
+            "client/registerCapability"
+            | "client/unregisterCapability"
+            | "window/workDoneProgress/create"
+            | "window/showMessageRequest" => Ok(Value::Null),
+            // 编辑由前端应用，服务器主动发起的编辑暂不支持
+            "workspace/applyEdit" => Ok(json!({ "applied": false })),
+            _ => Err((-32601, format!("Method not found: {}", method))),
+        }
+    }
+
+    async fn handle_notification(&self, method: &str, params: Value) {
+        match method {
+            "textDocument/publishDiagnostics" => self.publish_diagnostics(params).await,
+            "window/showMessage" => {
+                let _ = self.app.emit(
+                    LSP_MESSAGE_EVENT,
+                    MessagePayload {
+                        server_id: self.key.server_id.clone(),
+                        level: params.get("type").and_then(Value::as_i64).unwrap_or(3),
+                        message: params
+                            .get("message")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                    },
+                );
+            }
+            "window/logMessage" => {
+                if let Some(message) = params.get("message").and_then(Value::as_str) {
+                    log::debug!("[{}] {}", self.key.server_id, message);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 发送诊断事件，并将诊断合并到问题列表中
+    async fn publish_diagnostics(&self, params: Value) {
+        let Some(uri) = params.get("uri").and_then(Value::as_str) else { return };
+        let diagnostics = params.get("diagnostics").cloned().unwrap_or(json!([]));
+        let path = uri_to_path(uri);
+
+        let _ = self.app.emit(
+            LSP_DIAGNOSTICS_EVENT,
+            DiagnosticsPayload {
+                server_id: self.key.server_id.clone(),
+                root: self.key.root.clone(),
+                uri: uri.to_string(),
+                path: path.clone(),
+                diagnostics: diagnostics.clone(),
+            },
+        );
+
+        let Some(path) = path else { return };
+        let converted: Vec<problems::Problem> = diagnostics
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|d| diagnostic_to_problem(&path, d, &self.key.server_id))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let all: Vec<problems::Problem> = {
+            let mut store = self.diagnostics.lock().unwrap();
+            if converted.is_empty() {
+                store.remove(uri);
+            } else {
+                store.insert(uri.to_string(), converted);
+            }
+            store.values().flatten().cloned().collect()
+        };
+        problems::publish_problems(&self.app, &self.problems_source(), all).await;
+    }
+
+    /// 请求服务器关闭，超时后强制结束进程
+    async fn stop(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        if let Some(connection) = self.connection.write().await.take() {
+            let _ = connection
+                .request("shutdown", Value::Null, Duration::from_secs(2))
+                .await;
+            let _ = connection.notify("exit", Value::Null).await;
+            connection.kill();
+        }
+        problems::clear_problems(&self.app, Some(&self.problems_source())).await;
+        self.set_status(ServerStatus::Stopped);
+    }
+}
+
+fn diagnostic_to_problem(path: &str, diagnostic: &Value, server_id: &str) -> Option<problems::Problem> {
+    let position = |key: &str, field: &str| {
+        diagnostic
+            .get("range")?
+            .get(key)?
+            .get(field)?
+            .as_u64()
+            .map(|v| v as usize + 1)
+    };
+    let severity = match diagnostic.get("severity").and_then(Value::as_i64) {
+        Some(2) => problems::Severity::Warning,
+        Some(3) => problems::Severity::Info,
+        Some(4) => problems::Severity::Hint,
+        _ => problems::Severity::Error,
+    };
+    let code = diagnostic.get("code").map(|code| match code {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    });
+
+    Some(problems::Problem {
+        file: path.to_string(),
+        line: position("start", "line")?,
+        column: position("start", "character")?,
+        end_line: position("end", "line"),
+        end_column: position("end", "character"),
+        severity,
+        message: diagnostic.get("message")?.as_str()?.to_string(),
+        code,
+        source: diagnostic
+            .get("source")
+            .and_then(Value::as_str)
+            .unwrap_or(server_id)
+            .to_string(),
+    })
+}
+
+fn client_capabilities() -> Value {
+    json!({
+        "general": { "positionEncodings": ["utf-16"] },
+        "textDocument": {
+            "synchronization": { "didSave": true, "willSave": false, "dynamicRegistration": false },
+            "completion": {
+                "completionItem": {
+                    "snippetSupport": true,
+                    "documentationFormat": ["markdown", "plaintext"],
+                    "insertReplaceSupport": false,
+                },
+                "contextSupport": true,
+            },
+            "hover": { "contentFormat": ["markdown", "plaintext"] },
+            "definition": { "linkSupport": true },
+            "references": {},
+            "rename": { "prepareSupport": false },
+            "formatting": {},
+            "publishDiagnostics": { "relatedInformation": true, "versionSupport": false },
+        },
+        "workspace": {
+            "configuration": true,
+            "workspaceFolders": true,
+            "applyEdit": false,
+        },
+        "window": { "workDoneProgress": false, "showMessage": {} },
+    })
+}
+
+/// 获取文档所属的服务器，文档未打开时返回 None
+async fn server_for_document(uri: &str) -> Option<Arc<LanguageServer>> {
+    let key = documents().lock().await.get(uri).cloned()?;
+    servers().lock().await.get(&key).cloned()
+}
+
+/// 打开文档：按语言选择语言服务器，必要时启动。没有对应语言服务器时返回 None
+pub async fn open_document(
+    app: &AppHandle,
+    path: &str,
+    text: &str,
+    language_id: Option<&str>,
+    workspace: Option<&str>,
+) -> Result<Option<LanguageServerInfo>> {
+    let language = match language_id {
+        Some(language) => language.to_string(),
+        None => syntax::detect_language(path, Some(text)),
+    };
+    let configs = get_server_configs().await?;
+    let Some(config) = configs.into_iter().find(|c| c.languages.contains(&language)) else {
+        return Ok(None);
+    };
+
+    let root = find_root(Path::new(path), workspace.map(Path::new), &config.root_markers);
+    let key = ServerKey {
+        server_id: config.id.clone(),
+        root: root.to_string_lossy().to_string(),
+    };
+
+    // 先登记再启动，启动期间打开的文档会在握手完成后统一发送
+    let (server, created) = {
+        let mut servers = servers().lock().await;
+        match servers.get(&key) {
+            Some(server) => (server.clone(), false),
+            None => {
+                let server = Arc::new(LanguageServer::new(key.clone(), config, app.clone()));
+                servers.insert(key.clone(), server.clone());
+                (server, true)
+            }
+        }
+    };
+    if created {
+        let _lifecycle = server.lifecycle.write().await;
+        if let Err(e) = server.start().await {
+            servers().lock().await.remove(&key);
+            server.set_status(ServerStatus::Crashed);
+            return Err(e);
+        }
+    }
+
+    let uri = path_to_uri(path);
+    let language_id = lsp_language_id(&language).to_string();
+    {
+        let mut open = server.documents.lock().await;
+        if !open.contains_key(&uri) {
+            if let Ok(connection) = server.connection().await {
+                connection
+                    .notify(
+                        "textDocument/didOpen",
+                        json!({
+                            "textDocument": {
+                                "uri": uri,
+                                "languageId": language_id,
+                                "version": 1,
+                                "text": text,
+                            }
+                        }),
+                    )
+                    .await?;
+            }
+            open.insert(uri.clone(), OpenDocument { language_id, version: 1, text: text.to_string() });
+        }
+    }
+    documents().lock().await.insert(uri, key);
+
+    Ok(Some(server.info().await))
+}
+
+/// 文档内容变化（全量同步）
+pub async fn change_document(path: &str, text: &str) -> Result<()> {
+    let uri = path_to_uri(path);
+    let Some(server) = server_for_document(&uri).await else {
+        return Ok(());
+    };
+
+    let mut open = server.documents.lock().await;
+    let Some(document) = open.get_mut(&uri) else {
+        return Ok(());
+    };
+    document.version += 1;
+    document.text = text.to_string();
+
+    if let Ok(connection) = server.connection().await {
+        connection
+            .notify(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": uri, "version": document.version },
+                    "contentChanges": [{ "text": text }],
+                }),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// 文档已保存
+pub async fn save_document(path: &str, text: Option<&str>) -> Result<()> {
+    let uri = path_to_uri(path);
+    let Some(server) = server_for_document(&uri).await else {
+        return Ok(());
+    };
+    let connection = server.connection().await?;
+    let mut params = json!({ "textDocument": { "uri": uri } });
+    if let Some(text) = text {
+        params["text"] = json!(text);
+    }
+    connection.notify("textDocument/didSave", params).await
+}
+
+/// 关闭文档，服务器不再有打开的文档时将其停止
+pub async fn close_document(path: &str) -> Result<()> {
+    let uri = path_to_uri(path);
+    let Some(key) = documents().lock().await.remove(&uri) else {
+        return Ok(());
+    };
+    let Some(server) = servers().lock().await.get(&key).cloned() else {
+        return Ok(());
+    };
+
+    let remaining = {
+        let mut open = server.documents.lock().await;
+        open.remove(&uri);
+        open.len()
+    };
+    if let Ok(connection) = server.connection().await {
+        connection
+            .notify("textDocument/didClose", json!({ "textDocument": { "uri": uri } }))
+            .await?;
+    }
+
+    if remaining == 0 {
+        servers().lock().await.remove(&key);
+        let _lifecycle = server.lifecycle.write().await;
+        server.stop().await;
+    }
+    Ok(())
+}
+
+/// 向文档所属的语言服务器发送请求
+async fn document_request(path: &str, method: &str, mut params: Value) -> Result<Value> {
+    let uri = path_to_uri(path);
+    let server = server_for_document(&uri)
+        .await
+        .ok_or_else(|| anyhow!("No language server for {}", path))?;
+    params["textDocument"] = json!({ "uri": uri });
+    let _lifecycle = server.lifecycle.read().await;
+    server.connection().await?.request(method, params, REQUEST_TIMEOUT).await
+}
+
+fn position(line: u32, character: u32) -> Value {
+    json!({ "line": line, "character": character })
+}
+
+/// 代码补全，行列为 LSP 格式（从 0 开始，列为 UTF-16 偏移）
+pub async fn completion(
+    path: &str,
+    line: u32,
+    character: u32,
+    trigger_character: Option<&str>,
+) -> Result<Value> {
+    let context = match trigger_character {
+        Some(trigger) => json!({ "triggerKind": 2, "triggerCharacter": trigger }),
+        None => json!({ "triggerKind": 1 }),
+    };
+    document_request(
+        path,
+        "textDocument/completion",
+        json!({ "position": position(line, character), "context": context }),
+    )
+    .await
+}
+
+/// 悬停信息
+pub async fn hover(path: &str, line: u32, character: u32) -> Result<Value> {
+    document_request(path, "textDocument/hover", json!({ "position": position(line, character) })).await
+}
+
+/// 跳转到定义，结果中的 URI 附带 path 字段方便前端打开文件
+pub async fn definition(path: &str, line: u32, character: u32) -> Result<Value> {
+    let result = document_request(
+        path,
+        "textDocument/definition",
+        json!({ "position": position(line, character) }),
+    )
+    .await?;
+    Ok(with_paths(result))
+}
+
+/// 查找引用
+pub async fn references(
+    path: &str,
+    line: u32,
+    character: u32,
+    include_declaration: bool,
+) -> Result<Value> {
+    let result = document_request(
+        path,
+        "textDocument/references",
+        json!({
+            "position": position(line, character),
+            "context": { "includeDeclaration": include_declaration },
+        }),
+    )
+    .await?;
+    Ok(with_paths(result))
+}
+
+/// 重命名，返回 WorkspaceEdit 由前端应用
+pub async fn rename(path: &str, line: u32, character: u32, new_name: &str) -> Result<Value> {
+    document_request(
+        path,
+        "textDocument/rename",
+        json!({ "position": position(line, character), "newName": new_name }),
+    )
+    .await
+}
+
+/// 格式化整个文档，返回 TextEdit 列表
+pub async fn formatting(path: &str, tab_size: u32, insert_spaces: bool) -> Result<Value> {
+    document_request(
+        path,
+        "textDocument/formatting",
+        json!({ "options": { "tabSize": tab_size, "insertSpaces": insert_spaces } }),
+    )
+    .await
+}
+
+/// 为 Location / LocationLink 添加本地路径字段
+fn with_paths(mut value: Value) -> Value {
+    fn add_path(location: &mut Value) {
+        let uri = location
+            .get("uri")
+            .or_else(|| location.get("targetUri"))
+            .and_then(Value::as_str)
+            .and_then(uri_to_path);
+        if let (Some(path), Some(object)) = (uri, location.as_object_mut()) {
+            object.insert("path".to_string(), Value::String(path));
+        }
+    }
+
+    match &mut value {
+        Value::Array(items) => items.iter_mut().for_each(add_path),
+        Value::Object(_) => add_path(&mut value),
+        _ => {}
+    }
+    value
+}
+
+/// 列出语言服务器
+pub async fn list_servers() -> Vec<LanguageServerInfo> {
+    let servers: Vec<Arc<LanguageServer>> = servers().lock().await.values().cloned().collect();
+    let mut infos = Vec::with_capacity(servers.len());
+    for server in servers {
+        infos.push(server.info().await);
+    }
+    infos
+}
+
+/// 手动重启语言服务器（包括已放弃自动重启的服务器）
+pub async fn restart_server(server_id: &str, root: &str) -> Result<LanguageServerInfo> {
+    let key = ServerKey { server_id: server_id.to_string(), root: root.to_string() };
+    let server = servers()
+        .lock()
+        .await
+        .get(&key)
+        .cloned()
+        .ok_or_else(|| anyhow!("Language server not found: {}", server_id))?;
+
+    // 等待进行中的请求和自动重启结束，避免两个进程同时启动
+    let _lifecycle = server.lifecycle.write().await;
+    server.stop().await;
+    server.shutting_down.store(false, Ordering::SeqCst);
+    server.restart_times.lock().unwrap().clear();
+    server.diagnostics.lock().unwrap().clear();
+    server.start().await?;
+    Ok(server.info().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_roundtrip() {
+        let uri = path_to_uri("/home/user/my notes/a#b.rs");
+        assert_eq!(uri, "file:///home/user/my%20notes/a%23b.rs");
+        assert_eq!(uri_to_path(&uri).as_deref(), Some("/home/user/my notes/a#b.rs"));
+
+        let uri = path_to_uri(r"C:\Users\me\main.rs");
+        assert_eq!(uri, "file:///C:/Users/me/main.rs");
+        let expected = if cfg!(windows) { r"C:\Users\me\main.rs" } else { "C:/Users/me/main.rs" };
+        assert_eq!(uri_to_path(&uri).as_deref(), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_read_message() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":null}"#;
+        let data = format!(
+            "Content-Length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let mut reader = BufReader::new(data.as_bytes());
+        let message = read_message(&mut reader).await.unwrap().unwrap();
+        assert_eq!(message["id"], 1);
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_diagnostic_to_problem() {
+        let diagnostic = json!({
+            "range": { "start": { "line": 4, "character": 2 }, "end": { "line": 4, "character": 7 } },
+            "severity": 2,
+            "code": 6133,
+            "message": "'x' is declared but never used",
+        });
+        let problem = diagnostic_to_problem("/p/a.ts", &diagnostic, "tsserver").unwrap();
+        assert_eq!((problem.line, problem.column, problem.end_column), (5, 3, Some(8)));
+        assert_eq!(problem.severity, problems::Severity::Warning);
+        assert_eq!(problem.code.as_deref(), Some("6133"));
+        assert_eq!(problem.source, "tsserver");
+    }
+}
@@ -0,0 +1,754 @@
+// Problem Matcher Module
+// 将命令和任务的输出解析为诊断信息（文件、行、列、严重级别），供编辑器显示标记和跳转
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::RwLock;
+
+use crate::system_integration;
+
+/// 各来源（命令、任务）最近一次运行产生的问题
+static PROBLEMS: OnceLock<RwLock<HashMap<String, Vec<Problem>>>> = OnceLock::new();
+
+/// 问题列表更新事件
+pub const PROBLEMS_UPDATED_EVENT: &str = "problems-updated";
+
+/// 严重级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl Severity {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "error" | "fatal" | "fatal error" | "err" => Some(Severity::Error),
+            "warning" | "warn" => Some(Severity::Warning),
+            "info" | "information" | "note" => Some(Severity::Info),
+            "hint" | "help" => Some(Severity::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// 诊断信息，行列均从 1 开始
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Problem {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+    pub code: Option<String>,
+    /// 产生该问题的匹配器名称
+    pub source: String,
+}
+
+/// 单行匹配模式，字段值为正则捕获组序号
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemPattern {
+    pub regexp: String,
+    #[serde(default)]
+    pub file: Option<usize>,
+    #[serde(default)]
+    pub line: Option<usize>,
+    #[serde(default)]
+    pub column: Option<usize>,
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    #[serde(default)]
+    pub severity: Option<usize>,
+    #[serde(default)]
+    pub message: Option<usize>,
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// 最后一个模式可重复匹配（如 eslint 在文件名之后列出多条问题）
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+}
+
+/// 问题匹配器定义，多个模式需依次匹配连续的行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemMatcherDefinition {
+    pub name: String,
+    pub patterns: Vec<ProblemPattern>,
+    /// 未捕获严重级别时使用的默认值
+    #[serde(default)]
+    pub severity: Option<Severity>,
+    /// 相邻模式之间允许跳过的行数（如 Python traceback 中的源码行）
+    #[serde(default)]
+    pub max_gap: usize,
+}
+
+/// 匹配器引用：名称或内联定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ProblemMatcherRef {
+    Name(String),
+    Inline(ProblemMatcherDefinition),
+}
+
+#[derive(Debug, Default, Clone)]
+struct PartialProblem {
+    file: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<Severity>,
+    message: Option<String>,
+    code: Option<String>,
+}
+
+impl PartialProblem {
+    fn merge(&mut self, pattern: &ProblemPattern, caps: &regex::Captures) {
+        let text = |idx: Option<usize>| {
+            idx.and_then(|i| caps.get(i))
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let number = |idx: Option<usize>| text(idx).and_then(|s| s.parse::<usize>().ok());
+
+        if let Some(file) = text(pattern.file) {
+            self.file = Some(file);
+        }
+        if let Some(line) = number(pattern.line) {
+            self.line = Some(line);
+        }
+        if let Some(column) = number(pattern.column) {
+            self.column = Some(column);
+        }
+        if let Some(end_line) = number(pattern.end_line) {
+            self.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(pattern.end_column) {
+            self.end_column = Some(end_column);
+        }
+        if let Some(severity) = text(pattern.severity).and_then(|s| Severity::parse(&s)) {
+            self.severity = Some(severity);
+        }
+        if let Some(message) = text(pattern.message) {
+            self.message = Some(message);
+        }
+        if let Some(code) = text(pattern.code) {
+            self.code = Some(code);
+        }
+    }
+}
+
+/// 编译后的匹配器及其匹配状态
+pub struct ProblemMatcher {
+    definition: ProblemMatcherDefinition,
+    regexes: Vec<Regex>,
+    next: usize,
+    gap: usize,
+    partial: PartialProblem,
+}
+
+impl ProblemMatcher {
+    pub fn new(definition: ProblemMatcherDefinition) -> Result<Self> {
+        if definition.patterns.is_empty() {
+            anyhow::bail!("Problem matcher '{}' has no patterns", definition.name);
+        }
+        let regexes = definition
+            .patterns
+            .iter()
+            .map(|p| {
+                Regex::new(&p.regexp)
+                    .map_err(|e| anyhow!("Invalid pattern in matcher '{}': {}", definition.name, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { definition, regexes, next: 0, gap: 0, partial: PartialProblem::default() })
+    }
+
+    fn reset(&mut self) {
+        self.next = 0;
+        self.gap = 0;
+        self.partial = PartialProblem::default();
+    }
+
+    /// 输入一行输出，匹配完成时返回问题
+    fn feed(&mut self, line: &str, cwd: Option<&Path>) -> Option<Problem> {
+        let last = self.regexes.len() - 1;
+
+        if self.next > 0 {
+            // 循环模式：继续使用最后一个模式匹配，并保留前面模式捕获的字段（如文件名）
+            let index = if self.next > last { last } else { self.next };
+            if let Some(caps) = self.regexes[index].captures(line) {
+                let mut partial = self.partial.clone();
+                partial.merge(&self.definition.patterns[index], &caps);
+                self.gap = 0;
+                if index == last {
+                    let problem = self.build(partial, cwd);
+                    if self.definition.patterns[last].repeat {
+                        self.next = last + 1;
+                    } else {
+                        self.reset();
+                    }
+                    return problem;
+                }
+                self.partial = partial;
+                self.next += 1;
+                return None;
+            }
+
+            // 不匹配时检查是否开始了新的问题，否则在允许范围内跳过该行
+            if !self.regexes[0].is_match(line) {
+                if self.next <= last && self.gap < self.definition.max_gap {
+                    self.gap += 1;
+                    return None;
+                }
+                self.reset();
+                return None;
+            }
+            self.reset();
+        }
+
+        let caps = self.regexes[0].captures(line)?;
+        self.partial.merge(&self.definition.patterns[0], &caps);
+        if last == 0 {
+            let partial = std::mem::take(&mut self.partial);
+            let problem = self.build(partial, cwd);
+            self.reset();
+            return problem;
+        }
+        self.next = 1;
+        None
+    }
+
+    fn build(&self, partial: PartialProblem, cwd: Option<&Path>) -> Option<Problem> {
+        let file = partial.file?;
+        Some(Problem {
+            file: resolve_file(&file, cwd),
+            line: partial.line.unwrap_or(1),
+            column: partial.column.unwrap_or(1),
+            end_line: partial.end_line,
+            end_column: partial.end_column,
+            severity: partial
+                .severity
+                .or(self.definition.severity)
+                .unwrap_or(Severity::Error),
+            message: partial.message.unwrap_or_default(),
+            code: partial.code,
+            source: self.definition.name.clone(),
+        })
+    }
+}
+
+/// 将相对路径解析为基于工作目录的路径，并统一路径分隔符
+fn resolve_file(file: &str, cwd: Option<&Path>) -> String {
+    let path = Path::new(file);
+    let joined = match cwd {
+        Some(cwd) if path.is_relative() => cwd.join(path),
+        _ => path.to_path_buf(),
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized.to_string_lossy().to_string()
+}
+
+/// 逐行收集问题的解析器
+pub struct ProblemCollector {
+    matchers: Vec<ProblemMatcher>,
+    cwd: Option<PathBuf>,
+    problems: Vec<Problem>,
+}
+
+impl ProblemCollector {
+    pub fn new(matchers: Vec<ProblemMatcher>, cwd: Option<&str>) -> Self {
+        Self { matchers, cwd: cwd.map(PathBuf::from), problems: Vec::new() }
+    }
+
+    /// 输入任意输出片段，按行解析。
+    /// 一行只归属于第一个产生问题的匹配器，格式更严格的匹配器排在前面
+    pub fn feed(&mut self, text: &str) {
+        for line in text.lines() {
+            let line = strip_ansi(line);
+            for matcher in &mut self.matchers {
+                if let Some(problem) = matcher.feed(&line, self.cwd.as_deref()) {
+                    self.problems.push(problem);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// 清除未完成的多行匹配状态（切换到另一个输出流时使用）
+    pub fn reset(&mut self) {
+        for matcher in &mut self.matchers {
+            matcher.reset();
+        }
+    }
+
+    /// 取出已收集的问题
+    pub fn take_problems(&mut self) -> Vec<Problem> {
+        std::mem::take(&mut self.problems)
+    }
+}
+
+/// 移除 ANSI 颜色等控制序列
+fn strip_ansi(line: &str) -> String {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    let re = ANSI.get_or_init(|| Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07]*\x07").unwrap());
+    re.replace_all(line, "").trim_end_matches('\r').to_string()
+}
+
+fn pattern(regexp: &str) -> ProblemPattern {
+    ProblemPattern { regexp: regexp.to_string(), ..Default::default() }
+}
+
+/// 内置问题匹配器
+pub fn builtin_matchers() -> Vec<ProblemMatcherDefinition> {
+    vec![
+        // error[E0382]: use of moved value
+        //   --> src/main.rs:48:37
+        ProblemMatcherDefinition {
+            name: "rustc".to_string(),
+            patterns: vec![
+                ProblemPattern {
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..pattern(r"^(error|warning)(?:\[(\w+)\])?: (.*)$")
+                },
+                ProblemPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..pattern(r"^\s*--> (.+?):(\d+):(\d+)$")
+                },
+            ],
+            severity: None,
+            max_gap: 0,
+        },
+        // main.c:10:5: error: expected ';' before '}' token
+        ProblemMatcherDefinition {
+            name: "gcc".to_string(),
+            patterns: vec![ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                ..pattern(r"^(.+?):(\d+):(\d+): (?:fatal )?(error|warning|note): (.*)$")
+            }],
+            severity: None,
+            max_gap: 0,
+        },
+        // src/app.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.
+        ProblemMatcherDefinition {
+            name: "tsc".to_string(),
+            patterns: vec![ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..pattern(r"^(.+?)\((\d+),(\d+)\): (error|warning) (TS\d+): (.*)$")
+            }],
+            severity: None,
+            max_gap: 0,
+        },
+        // src/app.ts:12:5 - error TS2322: Type 'string' is not assignable to type 'number'.
+        ProblemMatcherDefinition {
+            name: "tsc-pretty".to_string(),
+            patterns: vec![ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..pattern(r"^(.+?):(\d+):(\d+) - (error|warning) (TS\d+): (.*)$")
+            }],
+            severity: None,
+            max_gap: 0,
+        },
+        // /path/to/file.js: line 12, col 5, Error - 'foo' is defined but never used. (no-unused-vars)
+        ProblemMatcherDefinition {
+            name: "eslint-compact".to_string(),
+            patterns: vec![ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                code: Some(6),
+                ..pattern(r"^(.+?): line (\d+), col (\d+), (Error|Warning) - (.+?)(?: \((.+)\))?$")
+            }],
+            severity: None,
+            max_gap: 0,
+        },
+        //   File "app.py", line 3, in <module>
+        //     foo()
+        // NameError: name 'foo' is not defined
+        ProblemMatcherDefinition {
+            name: "python".to_string(),
+            patterns: vec![
+                ProblemPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    ..pattern(r#"^\s*File "(.+)", line (\d+)"#)
+                },
+                ProblemPattern {
+                    code: Some(1),
+                    message: Some(2),
+                    ..pattern(r"^(\w+(?:Error|Exception|Warning|Exit|Interrupt)\w*)(?::\s*(.*))?$")
+                },
+            ],
+            severity: Some(Severity::Error),
+            max_gap: 3,
+        },
+        // 宽松的多行格式（任意不缩进的行都可能被当作文件名），只在命令中出现 eslint/lint 时默认启用
+        // /path/to/file.js
+        //   12:5  error  'foo' is defined but never used  no-unused-vars
+        ProblemMatcherDefinition {
+            name: "eslint".to_string(),
+            patterns: vec![
+                ProblemPattern { file: Some(1), ..pattern(r"^([^\s].*)$") },
+                ProblemPattern {
+                    line: Some(1),
+                    column: Some(2),
+                    severity: Some(3),
+                    message: Some(4),
+                    code: Some(5),
+                    repeat: true,
+                    ..pattern(r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.+?)(?:\s\s+(\S+))?$")
+                },
+            ],
+            severity: None,
+            max_gap: 0,
+        },
+    ]
+}
+
+fn get_user_matchers_path() -> Result<PathBuf> {
+    let config_dir = system_integration::get_config_dir()?;
+    Ok(config_dir.join("problem_matchers.json"))
+}
+
+/// 读取用户自定义匹配器（配置目录下的 problem_matchers.json）
+pub async fn load_user_matchers() -> Result<Vec<ProblemMatcherDefinition>> {
+    let path = get_user_matchers_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = tokio::fs::read_to_string(&path).await?;
+    serde_json::from_str(&content).map_err(|e| anyhow!("Invalid problem matchers file: {}", e))
+}
+
+/// 获取所有可用的匹配器定义，用户匹配器可覆盖同名内置匹配器
+pub async fn get_matcher_definitions() -> Result<Vec<ProblemMatcherDefinition>> {
+    let mut definitions = builtin_matchers();
+    for user in load_user_matchers().await? {
+        definitions.retain(|d| d.name != user.name);
+        definitions.push(user);
+    }
+    Ok(definitions)
+}
+
+/// 内置匹配器对应的命令行工具，用于未指定匹配器时按命令选择
+const MATCHER_TOOLS: &[(&str, &[&str])] = &[
+    ("rustc", &["cargo", "rustc", "clippy-driver"]),
+    ("gcc", &["gcc", "g++", "cc", "c++", "clang", "clang++", "make", "cmake", "ninja"]),
+    ("tsc", &["tsc", "vue-tsc"]),
+    ("tsc-pretty", &["tsc", "vue-tsc"]),
+    ("eslint-compact", &["eslint", "lint"]),
+    ("python", &["python", "python3", "py", "pytest"]),
+    ("eslint", &["eslint", "lint"]),
+];
+
+/// 只在命令中明确出现对应工具时才默认启用的宽松匹配器
+const LOOSE_MATCHERS: &[&str] = &["eslint"];
+
+/// 命令中出现的程序名和参数（去掉路径和 .exe 后缀，小写）
+fn command_tools(command: &str) -> Vec<String> {
+    command
+        .split(|c: char| c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | ')' | '"' | '\''))
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let name = token.rsplit(['/', '\\']).next().unwrap_or(token).to_lowercase();
+            name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+        })
+        .collect()
+}
+
+/// 未指定匹配器时按命令选择：命令中出现已知工具时只使用对应的内置匹配器，
+/// 否则使用除宽松匹配器外的全部内置匹配器；用户自定义匹配器总是启用
+fn select_default_definitions(
+    definitions: Vec<ProblemMatcherDefinition>,
+    command: Option<&str>,
+) -> Vec<ProblemMatcherDefinition> {
+    let builtin: Vec<String> = builtin_matchers().into_iter().map(|d| d.name).collect();
+    let tools = command.map(command_tools).unwrap_or_default();
+    let uses = |name: &str| {
+        MATCHER_TOOLS
+            .iter()
+            .find(|(matcher, _)| *matcher == name)
+            .is_some_and(|(_, names)| tools.iter().any(|t| names.contains(&t.as_str())))
+    };
+    let any_known = builtin.iter().any(|name| uses(name));
+
+    definitions
+        .into_iter()
+        .filter(|d| {
+            if !builtin.contains(&d.name) {
+                true
+            } else if any_known {
+                uses(&d.name)
+            } else {
+                !LOOSE_MATCHERS.contains(&d.name.as_str())
+            }
+        })
+        .collect()
+}
+
+/// 根据引用创建匹配器；未指定时按命令选择可用匹配器，空列表表示不解析
+pub async fn resolve_matchers(
+    refs: Option<&[ProblemMatcherRef]>,
+    command: Option<&str>,
+) -> Result<Vec<ProblemMatcher>> {
+    let definitions = get_matcher_definitions().await?;
+
+    let selected = match refs {
+        None => select_default_definitions(definitions, command),
+        Some(refs) => refs
+            .iter()
+            .map(|r| match r {
+                ProblemMatcherRef::Inline(definition) => Ok(definition.clone()),
+                ProblemMatcherRef::Name(name) => {
+                    let name = name.trim_start_matches('$');
+                    definitions
+                        .iter()
+                        .find(|d| d.name == name)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("Unknown problem matcher: {}", name))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?,
+    };
+
+    selected.into_iter().map(ProblemMatcher::new).collect()
+}
+
+fn problems_store() -> &'static RwLock<HashMap<String, Vec<Problem>>> {
+    PROBLEMS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProblemsUpdatedPayload {
+    source: String,
+    problems: Vec<Problem>,
+}
+
+/// 替换某个来源的问题列表并通知前端更新标记
+pub async fn publish_problems(app: &AppHandle, source: &str, problems: Vec<Problem>) {
+    problems_store()
+        .write()
+        .await
+        .insert(source.to_string(), problems.clone());
+    let _ = app.emit(
+        PROBLEMS_UPDATED_EVENT,
+        ProblemsUpdatedPayload { source: source.to_string(), problems },
+    );
+}
+
+/// 获取所有问题，按文件、行、列排序
+pub async fn get_problems() -> Vec<Problem> {
+    let store = problems_store().read().await;
+    let mut problems: Vec<Problem> = store.values().flatten().cloned().collect();
+    problems.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+    problems
+}
+
+/// 清除问题，未指定来源时清除全部
+pub async fn clear_problems(app: &AppHandle, source: Option<&str>) {
+    let sources: Vec<String> = {
+        let mut store = problems_store().write().await;
+        match source {
+            Some(source) => {
+                store.remove(source);
+                vec![source.to_string()]
+            }
+            None => store.drain().map(|(k, _)| k).collect(),
+        }
+    };
+    for source in sources {
+        let _ = app.emit(
+            PROBLEMS_UPDATED_EVENT,
+            ProblemsUpdatedPayload { source, problems: Vec::new() },
+        );
+    }
+}
+
+/// 查找当前位置之后（或之前）的下一个问题，到达末尾时循环
+pub async fn next_problem(
+    file: Option<&str>,
+    line: usize,
+    column: usize,
+    reverse: bool,
+) -> Option<Problem> {
+    let problems = get_problems().await;
+    if problems.is_empty() {
+        return None;
+    }
+
+    let current = file.map(|f| (f.to_string(), line, column));
+    let key = |p: &Problem| (p.file.clone(), p.line, p.column);
+
+    let found = match (&current, reverse) {
+        (None, false) => problems.first(),
+        (None, true) => problems.last(),
+        (Some(current), false) => problems
+            .iter()
+            .find(|p| key(p) > *current)
+            .or(problems.first()),
+        (Some(current), true) => problems
+            .iter()
+            .rev()
+            .find(|p| key(p) < *current)
+            .or(problems.last()),
+    };
+    found.cloned()
+}
+
+/// 解析一次性命令的输出（可包含多个输出流）并发布问题
+pub async fn publish_from_output(
+    app: &AppHandle,
+    source: &str,
+    outputs: &[&str],
+    cwd: Option<&str>,
+    command: Option<&str>,
+    refs: Option<&[ProblemMatcherRef]>,
+) -> Result<()> {
+    let matchers = resolve_matchers(refs, command).await?;
+    if matchers.is_empty() {
+        return Ok(());
+    }
+    let mut collector = ProblemCollector::new(matchers, cwd);
+    for output in outputs {
+        collector.feed(output);
+        collector.reset();
+    }
+    publish_problems(app, source, collector.take_problems()).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(output: &str) -> Vec<Problem> {
+        let matchers = builtin_matchers()
+            .into_iter()
+            .map(|d| ProblemMatcher::new(d).unwrap())
+            .collect();
+        let mut collector = ProblemCollector::new(matchers, Some("/work"));
+        collector.feed(output);
+        collector.take_problems()
+    }
+
+    #[test]
+    fn test_rustc() {
+        let problems = collect(
+            "error[E0382]: use of moved value: `home`\n  --> src/fonts.rs:48:37\n   |\n",
+        );
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].file, resolve_file("src/fonts.rs", Some(Path::new("/work"))));
+        assert_eq!((problems[0].line, problems[0].column), (48, 37));
+        assert_eq!(problems[0].severity, Severity::Error);
+        assert_eq!(problems[0].code.as_deref(), Some("E0382"));
+    }
+
+    #[test]
+    fn test_gcc_and_tsc() {
+        let problems = collect(
+            "main.c:10:5: warning: unused variable 'x'\nsrc/app.ts(3,7): error TS2322: Type mismatch.\n",
+        );
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].source, "gcc");
+        assert_eq!(problems[0].severity, Severity::Warning);
+        assert_eq!(problems[1].source, "tsc");
+        assert_eq!(problems[1].code.as_deref(), Some("TS2322"));
+    }
+
+    #[test]
+    fn test_eslint_loop() {
+        let problems = collect(
+            "/work/a.js\n  1:5  error  'x' is defined but never used  no-unused-vars\n  2:1  warning  Unexpected console statement  no-console\n\n",
+        );
+        let eslint: Vec<_> = problems.iter().filter(|p| p.source == "eslint").collect();
+        assert_eq!(eslint.len(), 2);
+        assert_eq!(eslint[1].line, 2);
+        assert_eq!(eslint[1].severity, Severity::Warning);
+        assert_eq!(eslint[1].code.as_deref(), Some("no-console"));
+    }
+
+    #[test]
+    fn test_default_matchers_follow_command() {
+        let names = |command: &str| -> Vec<String> {
+            select_default_definitions(builtin_matchers(), Some(command))
+                .into_iter()
+                .map(|d| d.name)
+                .collect()
+        };
+        assert_eq!(names("cargo build --release"), vec!["rustc"]);
+        assert_eq!(names("/usr/bin/python3 app.py"), vec!["python"]);
+        assert!(names("npx eslint src").contains(&"eslint".to_string()));
+        // 未识别的命令不启用宽松的 eslint 匹配器
+        let generic = names("npm run build");
+        assert!(!generic.contains(&"eslint".to_string()));
+        assert!(generic.contains(&"gcc".to_string()));
+    }
+
+    #[test]
+    fn test_paths_keep_case() {
+        let problems = collect("src/Parser.rs:3:1: error: Bad token\n");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].file.ends_with("Parser.rs"));
+    }
+
+    #[test]
+    fn test_python_traceback() {
+        let problems = collect(
+            "Traceback (most recent call last):\n  File \"/work/app.py\", line 8, in <module>\n    main()\n  File \"/work/lib.py\", line 3, in main\n    foo()\n    ^^^\nNameError: name 'foo' is not defined\n",
+        );
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, 3);
+        assert_eq!(problems[0].code.as_deref(), Some("NameError"));
+    }
+}
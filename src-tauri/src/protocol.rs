@@ -0,0 +1,594 @@
+// Protocol Module
+//
+// 自定义协议处理：`kairogui://` 从可执行文件旁的 gui 目录加载前端资源，
+// `kairofile://` 为预览提供本地附件（图片、PDF、音视频），只允许访问登记的根目录。
+// 请求路径限制在根目录内（拒绝 `..`、绝对路径和指向外部的符号链接），
+// 支持 ETag/Last-Modified 条件请求、Range 请求以及 .br/.gz 预压缩资源
+
+use crate::hex;
+use chrono::{DateTime, Utc};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::http::{header, HeaderValue, Method, Request, Response, StatusCode};
+
+/// 带哈希的构建产物（Vite 输出到 assets 目录），内容不会改变，可以长期缓存
+const IMMUTABLE_PREFIX: &str = "assets/";
+
+/// 内容类型检测读取的字节数
+const SNIFF_BYTES: usize = 512;
+
+/// 单个响应最多返回的字节数，更长的范围或更大的文件由客户端继续请求（如视频拖动进度）
+const MAX_RANGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// `kairofile://` 响应的内容安全策略：附件中的 SVG、HTML 等不能执行脚本或加载外部资源
+const FILE_CSP: &str = "default-src 'none'; img-src data:; style-src 'unsafe-inline'; sandbox";
+
+/// `kairofile://` 允许访问的根目录（已规范化）
+static FILE_ROOTS: OnceLock<RwLock<Vec<PathBuf>>> = OnceLock::new();
+
+/// 根据文件扩展名获取 MIME 类型
+pub fn mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "application/javascript; charset=utf-8",
+        Some("mjs") => "application/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("json") => "application/json; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("eot") => "application/vnd.ms-fontobject",
+        Some("wasm") => "application/wasm",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("bmp") => "image/bmp",
+        Some("pdf") => "application/pdf",
+        Some("mp3") => "audio/mpeg",
+        Some("m4a") => "audio/mp4",
+        Some("wav") => "audio/wav",
+        Some("flac") => "audio/flac",
+        Some("ogg") | Some("oga") | Some("opus") => "audio/ogg",
+        Some("mp4") | Some("m4v") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mov") => "video/quicktime",
+        Some("txt") | Some("md") | Some("log") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// 把 URL 路径解析为 `root`（已规范化）内的文件；越出根目录时返回 `None`。
+/// 目录请求返回其中的 index.html
+pub fn resolve_gui_path(root: &Path, uri_path: &str) -> Option<PathBuf> {
+    let decoded = urlencoding::decode(uri_path).ok()?;
+    let mut path = root.to_path_buf();
+    // 逐段拼接：解码后的 `\`、盘符和 `..` 都不能用来跳出根目录
+    for segment in decoded.split(['/', '\\']) {
+        match segment {
+            "" | "." => continue,
+            ".." => return None,
+            segment if segment.contains(':') || segment.contains('\0') => return None,
+            segment => path.push(segment),
+        }
+    }
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    // 规范化会解析符号链接，确保最终文件仍在根目录内
+    let canonical = path.canonicalize().ok()?;
+    (canonical.starts_with(root) && canonical.is_file()).then_some(canonical)
+}
+
+/// 根据文件内容检测类型，签名无法识别时参考扩展名。
+/// HTML 按纯文本发送，避免附件中的页面在应用内执行
+pub fn sniff_content_type(head: &[u8], path: &Path) -> &'static str {
+    let at = |offset: usize, signature: &[u8]| head.get(offset..offset + signature.len()) == Some(signature);
+    let contains = |needle: &[u8]| memchr::memmem::find(head, needle).is_some();
+
+    if at(0, b"\x89PNG\r\n\x1a\n") {
+        return "image/png";
+    }
+    if at(0, b"\xFF\xD8\xFF") {
+        return "image/jpeg";
+    }
+    if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        return "image/gif";
+    }
+    if at(0, b"RIFF") {
+        if at(8, b"WEBP") {
+            return "image/webp";
+        }
+        if at(8, b"WAVE") {
+            return "audio/wav";
+        }
+        if at(8, b"AVI ") {
+            return "video/x-msvideo";
+        }
+    }
+    if at(4, b"ftyp") {
+        return match head.get(8..12) {
+            Some(b"avif") | Some(b"avis") => "image/avif",
+            Some(b"heic") | Some(b"heix") | Some(b"mif1") => "image/heic",
+            Some(b"M4A ") => "audio/mp4",
+            Some(b"qt  ") => "video/quicktime",
+            _ => "video/mp4",
+        };
+    }
+    if at(0, b"\x1A\x45\xDF\xA3") {
+        return if contains(b"webm") { "video/webm" } else { "video/x-matroska" };
+    }
+    if at(0, b"OggS") {
+        return if contains(b"theora") { "video/ogg" } else { "audio/ogg" };
+    }
+    if at(0, b"fLaC") {
+        return "audio/flac";
+    }
+    if at(0, b"ID3") {
+        return "audio/mpeg";
+    }
+    // MPEG 音频帧同步字：layer 位为 0 时是 AAC (ADTS)
+    if let [0xFF, second, ..] = head {
+        if second & 0xE0 == 0xE0 {
+            return if second & 0x06 == 0 { "audio/aac" } else { "audio/mpeg" };
+        }
+    }
+    if at(0, b"%PDF-") {
+        return "application/pdf";
+    }
+    if at(0, b"wOFF") {
+        return "font/woff";
+    }
+    if at(0, b"wOF2") {
+        return "font/woff2";
+    }
+    if at(0, b"\0asm") {
+        return "application/wasm";
+    }
+    if at(0, b"\0\0\x01\0") {
+        return "image/x-icon";
+    }
+    if at(0, b"II*\0") || at(0, b"MM\0*") {
+        return "image/tiff";
+    }
+
+    if !hex::is_binary(head) {
+        let text = String::from_utf8_lossy(head);
+        let start = text.trim_start_matches('\u{FEFF}').trim_start().to_ascii_lowercase();
+        if start.starts_with("<svg") || (start.starts_with("<?xml") && start.contains("<svg")) {
+            return "image/svg+xml";
+        }
+        return match mime_type(path) {
+            "text/html; charset=utf-8" | "application/octet-stream" => "text/plain; charset=utf-8",
+            mime => mime,
+        };
+    }
+    if at(0, b"BM") {
+        return "image/bmp";
+    }
+    mime_type(path)
+}
+
+/// 设置 `kairofile://` 允许访问的根目录：工作区、笔记所在目录和设置中额外允许的目录。
+/// 工作区必须是目录且不能是文件系统根目录，笔记必须是已存在的文件；
+/// 返回规范化后实际登记的目录（不存在或不符合要求的被忽略）
+pub fn set_file_roots(workspace: Option<&str>, note: Option<&str>, extra: &[String]) -> Vec<String> {
+    let canonical = |path: &str| Some(path).filter(|path| !path.is_empty()).and_then(|path| Path::new(path).canonicalize().ok());
+    let mut roots: Vec<PathBuf> = Vec::new();
+    roots.extend(workspace.and_then(canonical).filter(|path| path.is_dir() && path.parent().is_some()));
+    roots.extend(
+        note.and_then(canonical)
+            .filter(|path| path.is_file())
+            .and_then(|path| path.parent().map(Path::to_path_buf)),
+    );
+    roots.extend(extra.iter().filter_map(|root| canonical(root)).filter(|root| root.is_dir()));
+    roots.dedup();
+    let accepted = roots.iter().map(|root| root.to_string_lossy().to_string()).collect();
+    *FILE_ROOTS.get_or_init(|| RwLock::new(Vec::new())).write().unwrap() = roots;
+    accepted
+}
+
+/// 把 `kairofile://localhost/<编码后的绝对路径>` 解析为允许的根目录内的文件
+pub fn resolve_file_path(uri_path: &str) -> Option<PathBuf> {
+    let encoded = uri_path.strip_prefix('/').unwrap_or(uri_path);
+    let decoded = urlencoding::decode(encoded).ok()?;
+    let mut path = PathBuf::from(decoded.as_ref());
+    // 未整体编码的 Unix 路径（kairofile://localhost/home/...）会丢失开头的斜杠
+    if !path.is_absolute() {
+        path = Path::new("/").join(path);
+    }
+    if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+        return None;
+    }
+    let canonical = path.canonicalize().ok()?;
+    let roots = FILE_ROOTS.get()?.read().unwrap();
+    (canonical.is_file() && roots.iter().any(|root| canonical.starts_with(root))).then_some(canonical)
+}
+
+/// `kairofile://` 请求处理
+pub fn handle_file_request(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(path) = resolve_file_path(request.uri().path()) else {
+        return status_response(StatusCode::NOT_FOUND);
+    };
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    if let Ok(file) = File::open(&path) {
+        let _ = file.take(SNIFF_BYTES as u64).read_to_end(&mut head);
+    }
+    let content_type = sniff_content_type(&head, &path);
+    let options = ServeOptions { content_type, cache_control: "no-cache", precompressed: false };
+    let mut response = serve_file(request, &path, &options);
+    let headers = response.headers_mut();
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    // 沙箱中的文档无法使用内置 PDF 查看器，PDF 不加 CSP
+    if content_type != "application/pdf" {
+        headers.insert(header::CONTENT_SECURITY_POLICY, HeaderValue::from_static(FILE_CSP));
+    }
+    response
+}
+
+/// 文件响应选项
+pub struct ServeOptions<'a> {
+    pub content_type: &'a str,
+    pub cache_control: &'a str,
+    /// 客户端接受时改为发送同目录下的 `.br` / `.gz` 预压缩文件
+    pub precompressed: bool,
+}
+
+/// `kairogui://` 请求处理
+pub fn handle_gui_request(root: &Path, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(path) = resolve_gui_path(root, request.uri().path()) else {
+        return status_response(StatusCode::NOT_FOUND);
+    };
+    let immutable = path
+        .strip_prefix(root)
+        .is_ok_and(|relative| relative.to_string_lossy().replace('\\', "/").starts_with(IMMUTABLE_PREFIX));
+    let options = ServeOptions {
+        content_type: mime_type(&path),
+        cache_control: if immutable { "public, max-age=31536000, immutable" } else { "no-cache" },
+        precompressed: true,
+    };
+    serve_file(request, &path, &options)
+}
+
+/// 发送文件，处理条件请求和 Range 请求
+pub fn serve_file(request: &Request<Vec<u8>>, path: &Path, options: &ServeOptions) -> Response<Vec<u8>> {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return status_response(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    let range_header = header_str(request, header::RANGE);
+
+    // Range 请求针对未压缩的内容，只在完整请求时使用预压缩文件
+    let (file_path, encoding) = match options.precompressed && range_header.is_none() {
+        true => precompressed_variant(request, path),
+        false => (path.to_path_buf(), None),
+    };
+    let Ok(mut file) = File::open(&file_path) else {
+        return status_response(StatusCode::NOT_FOUND);
+    };
+    let Ok(metadata) = file.metadata() else {
+        return status_response(StatusCode::NOT_FOUND);
+    };
+    let len = metadata.len();
+    let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+    let etag = entity_tag(len, modified, encoding);
+    let last_modified = http_date(modified);
+
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, options.content_type)
+        .header(header::CACHE_CONTROL, options.cache_control)
+        .header(header::ETAG, &etag)
+        .header(header::LAST_MODIFIED, &last_modified)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    if options.precompressed {
+        builder = builder.header(header::VARY, "Accept-Encoding");
+    }
+    if let Some(encoding) = encoding {
+        builder = builder.header(header::CONTENT_ENCODING, encoding);
+    }
+
+    if not_modified(request, &etag, modified) {
+        return builder.status(StatusCode::NOT_MODIFIED).body(Vec::new()).unwrap();
+    }
+
+    // If-Range 不匹配时忽略 Range，返回完整内容
+    let if_range_matches = header_str(request, header::IF_RANGE).map_or(true, |value| value == etag || value == last_modified);
+    let range = match range_header.filter(|_| if_range_matches).map(|value| parse_range(value, len)) {
+        Some(Some(RangeSpec::Satisfiable(start, end))) => Some((start, end)),
+        Some(Some(RangeSpec::Unsatisfiable)) => {
+            return builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                .body(Vec::new())
+                .unwrap();
+        }
+        // 无法解析或多段范围时返回完整内容
+        Some(None) | None => None,
+    };
+
+    let (status, start, count) = match range {
+        Some((start, end)) => {
+            let end = end.min(start + MAX_RANGE_BYTES - 1);
+            builder = builder.header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len));
+            (StatusCode::PARTIAL_CONTENT, start, end - start + 1)
+        }
+        // 完整内容超过上限时只返回开头部分，客户端根据 Content-Range 继续请求其余部分
+        None if len > MAX_RANGE_BYTES => {
+            builder = builder.header(header::CONTENT_RANGE, format!("bytes 0-{}/{}", MAX_RANGE_BYTES - 1, len));
+            (StatusCode::PARTIAL_CONTENT, 0, MAX_RANGE_BYTES)
+        }
+        None => (StatusCode::OK, 0, len),
+    };
+    builder = builder.header(header::CONTENT_LENGTH, count);
+
+    if request.method() == Method::HEAD {
+        return builder.status(status).body(Vec::new()).unwrap();
+    }
+    let mut body = vec![0; count as usize];
+    if file.seek(SeekFrom::Start(start)).and_then(|_| file.read_exact(&mut body)).is_err() {
+        return status_response(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    builder.status(status).body(body).unwrap()
+}
+
+/// 不带路径等内部信息的状态响应
+pub fn status_response(status: StatusCode) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(status.canonical_reason().unwrap_or_default().as_bytes().to_vec())
+        .unwrap()
+}
+
+fn header_str(request: &Request<Vec<u8>>, name: header::HeaderName) -> Option<&str> {
+    request.headers().get(name).and_then(|value| value.to_str().ok())
+}
+
+fn precompressed_variant(request: &Request<Vec<u8>>, path: &Path) -> (PathBuf, Option<&'static str>) {
+    let accepted = header_str(request, header::ACCEPT_ENCODING).unwrap_or_default();
+    for (encoding, extension) in [("br", "br"), ("gzip", "gz")] {
+        if !accepts_encoding(accepted, encoding) {
+            continue;
+        }
+        let mut variant = path.as_os_str().to_owned();
+        variant.push(".");
+        variant.push(extension);
+        let variant = PathBuf::from(variant);
+        // 超过响应上限的文件需要分段发送，而 Range 请求针对未压缩的内容，不使用预压缩版本
+        if variant.metadata().is_ok_and(|m| m.is_file() && m.len() <= MAX_RANGE_BYTES) {
+            return (variant, Some(encoding));
+        }
+    }
+    (path.to_path_buf(), None)
+}
+
+/// `Accept-Encoding` 是否接受该编码（`q=0` 视为拒绝）
+fn accepts_encoding(header: &str, encoding: &str) -> bool {
+    header.split(',').any(|item| {
+        let mut parts = item.split(';').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        let rejected = parts.any(|param| {
+            param
+                .strip_prefix("q=")
+                .and_then(|q| q.parse::<f32>().ok())
+                .is_some_and(|q| q == 0.0)
+        });
+        (name.eq_ignore_ascii_case(encoding) || name == "*") && !rejected
+    })
+}
+
+fn entity_tag(len: u64, modified: SystemTime, encoding: Option<&str>) -> String {
+    let nanos = modified.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    match encoding {
+        Some(encoding) => format!("\"{:x}-{:x}-{}\"", len, nanos, encoding),
+        None => format!("\"{:x}-{:x}\"", len, nanos),
+    }
+}
+
+fn http_date(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// If-None-Match 优先于 If-Modified-Since
+fn not_modified(request: &Request<Vec<u8>>, etag: &str, modified: SystemTime) -> bool {
+    if let Some(value) = header_str(request, header::IF_NONE_MATCH) {
+        return value
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+    }
+    header_str(request, header::IF_MODIFIED_SINCE)
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|since| DateTime::<Utc>::from(modified).timestamp() <= since.timestamp())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RangeSpec {
+    /// 闭区间 `[start, end]`
+    Satisfiable(u64, u64),
+    Unsatisfiable,
+}
+
+/// 解析单段 `bytes=` 范围；格式无效或包含多段时返回 `None`
+fn parse_range(value: &str, len: u64) -> Option<RangeSpec> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    let range = if start.is_empty() {
+        // 后缀范围：最后 N 个字节
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(RangeSpec::Unsatisfiable);
+        }
+        (len.saturating_sub(suffix), len - 1)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() { u64::MAX } else { end.parse().ok()? };
+        if end < start {
+            return None;
+        }
+        if start >= len {
+            return Some(RangeSpec::Unsatisfiable);
+        }
+        (start, end.min(len - 1))
+    };
+    Some(RangeSpec::Satisfiable(range.0, range.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gui_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("kairo-protocol-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(root.join("assets")).unwrap();
+        std::fs::write(root.join("index.html"), "<!doctype html>").unwrap();
+        std::fs::write(root.join("assets/app.js"), "0123456789").unwrap();
+        root.canonicalize().unwrap()
+    }
+
+    fn get(uri: &str, headers: &[(&str, &str)]) -> Request<Vec<u8>> {
+        let mut builder = Request::builder().uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Vec::new()).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_gui_path() {
+        let root = gui_root("resolve");
+        assert_eq!(resolve_gui_path(&root, "/"), Some(root.join("index.html")));
+        assert_eq!(resolve_gui_path(&root, "/assets/app.js"), Some(root.join("assets/app.js")));
+        assert_eq!(resolve_gui_path(&root, "/assets/./x/../app.js"), None);
+        assert_eq!(resolve_gui_path(&root, "/..%2F..%2Fetc%2Fpasswd"), None);
+        assert_eq!(resolve_gui_path(&root, "/..%5C..%5Cwindows%5Cwin.ini"), None);
+        assert_eq!(resolve_gui_path(&root, "/C:%5Cwindows%5Cwin.ini"), None);
+        assert_eq!(resolve_gui_path(&root, "/missing.js"), None);
+
+        // 指向根目录外的符号链接
+        #[cfg(unix)]
+        {
+            let outside = root.with_extension("outside");
+            std::fs::write(&outside, "secret").unwrap();
+            std::os::unix::fs::symlink(&outside, root.join("link.txt")).unwrap();
+            assert_eq!(resolve_gui_path(&root, "/link.txt"), None);
+            std::fs::remove_file(&outside).unwrap();
+        }
+
+        let response = handle_gui_request(&root, &get("kairogui://localhost/..%2Fsecret", &[]));
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(!String::from_utf8_lossy(response.body()).contains(&*root.to_string_lossy()));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_serve_ranges_caching_and_precompressed() {
+        let root = gui_root("serve");
+        let uri = "kairogui://localhost/assets/app.js";
+
+        let response = handle_gui_request(&root, &get(uri, &[]));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), b"0123456789");
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert!(response.headers()[header::CACHE_CONTROL].to_str().unwrap().contains("immutable"));
+
+        let response = handle_gui_request(&root, &get(uri, &[("If-None-Match", &etag)]));
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let response = handle_gui_request(&root, &get(uri, &[("Range", "bytes=2-4")]));
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body(), b"234");
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 2-4/10");
+        let response = handle_gui_request(&root, &get(uri, &[("Range", "bytes=-3")]));
+        assert_eq!(response.body(), b"789");
+        let response = handle_gui_request(&root, &get(uri, &[("Range", "bytes=20-")]));
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        // 过长的范围只返回前 MAX_RANGE_BYTES 字节
+        let size = MAX_RANGE_BYTES + 10;
+        std::fs::write(root.join("assets/video.mp4"), vec![0u8; size as usize]).unwrap();
+        let response = handle_gui_request(&root, &get("kairogui://localhost/assets/video.mp4", &[("Range", "bytes=5-")]));
+        assert_eq!(response.body().len() as u64, MAX_RANGE_BYTES);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], format!("bytes 5-{}/{}", MAX_RANGE_BYTES + 4, size));
+
+        // 没有 Range 的请求同样受上限约束，大文件返回开头部分
+        let response = handle_gui_request(&root, &get("kairogui://localhost/assets/video.mp4", &[]));
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body().len() as u64, MAX_RANGE_BYTES);
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], MAX_RANGE_BYTES.to_string().as_str());
+        assert_eq!(response.headers()[header::CONTENT_RANGE], format!("bytes 0-{}/{}", MAX_RANGE_BYTES - 1, size));
+        assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+
+        std::fs::write(root.join("assets/app.js.br"), "brotli").unwrap();
+        let response = handle_gui_request(&root, &get(uri, &[("Accept-Encoding", "gzip, br")]));
+        assert_eq!(response.body(), b"brotli");
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "br");
+        let response = handle_gui_request(&root, &get(uri, &[("Accept-Encoding", "gzip, br;q=0")]));
+        assert_eq!(response.body(), b"0123456789");
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_sniff_content_type() {
+        let cases: [(&[u8], &str, &str); 9] = [
+            (b"\x89PNG\r\n\x1a\n....", "noext", "image/png"),
+            (b"RIFF\0\0\0\0WEBPVP8 ", "photo.png", "image/webp"),
+            (b"\0\0\0\x20ftypisom\0\0\x02\0", "clip", "video/mp4"),
+            (b"ID3\x04\0\0\0\0", "song.bin", "audio/mpeg"),
+            (b"%PDF-1.7\n%", "doc", "application/pdf"),
+            (b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\">", "icon", "image/svg+xml"),
+            (b"<!doctype html><script>alert(1)</script>", "page.html", "text/plain; charset=utf-8"),
+            (b"body { color: red }", "style.css", "text/css; charset=utf-8"),
+            (b"\x01\x02\x03\0\0", "data.mp3", "audio/mpeg"),
+        ];
+        for (head, name, expected) in cases {
+            assert_eq!(sniff_content_type(head, Path::new(name)), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_file_protocol_roots() {
+        let root = gui_root("files");
+        let outside = root.with_extension("other");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret.txt"), "secret").unwrap();
+        std::fs::write(root.join("note.png"), b"\x89PNG\r\n\x1a\nimage-data").unwrap();
+
+        let root_str = root.to_string_lossy().to_string();
+        // 文件系统根目录不能作为工作区，笔记必须是文件
+        assert!(set_file_roots(Some("/"), Some(&root_str), &[]).is_empty());
+        let note = outside.join("secret.txt").to_string_lossy().to_string();
+        let accepted = set_file_roots(None, Some(&note), &[]);
+        assert_eq!(accepted, [outside.canonicalize().unwrap().to_string_lossy().to_string()]);
+
+        let accepted = set_file_roots(Some(&root_str), None, &["/missing/kairo/root".to_string()]);
+        assert_eq!(accepted, [root_str]);
+
+        let url = |path: &Path| format!("kairofile://localhost/{}", urlencoding::encode(&path.to_string_lossy()));
+        let response = handle_file_request(&get(&url(&root.join("note.png")), &[("Range", "bytes=8-")]));
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body(), b"image-data");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        assert_eq!(response.headers()[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+
+        for path in [outside.join("secret.txt"), root.join("assets/../../").join(outside.file_name().unwrap()).join("secret.txt")] {
+            assert_eq!(handle_file_request(&get(&url(&path), &[])).status(), StatusCode::NOT_FOUND);
+        }
+        set_file_roots(None, None, &[]);
+        assert_eq!(handle_file_request(&get(&url(&root.join("note.png")), &[])).status(), StatusCode::NOT_FOUND);
+
+        std::fs::remove_dir_all(&root).unwrap();
+        std::fs::remove_dir_all(&outside).unwrap();
+    }
+}
@@ -0,0 +1,380 @@
+// Recent Entries Module
+// 最近打开的文件、文件夹和工作区管理，支持固定、时间戳、自动清理和模糊过滤
+
+use anyhow::Result;
+use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Config, Matcher, Utf32Str};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::OnceLock;
+use tokio::sync::RwLock;
+
+use crate::system_integration;
+
+static RECENT: OnceLock<RwLock<RecentStore>> = OnceLock::new();
+
+/// 每种类型最多保留的未固定条目数量
+const MAX_UNPINNED_ENTRIES: usize = 20;
+
+/// 后台自动清理的最小间隔（毫秒）
+const PRUNE_INTERVAL_MS: i64 = 60_000;
+
+/// 上次安排后台清理的时间（Unix 毫秒时间戳）
+static LAST_PRUNE: AtomicI64 = AtomicI64::new(0);
+
+/// 最近条目类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecentKind {
+    File,
+    Folder,
+    Workspace,
+}
+
+/// 最近条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentEntry {
+    pub path: String,
+    pub kind: RecentKind,
+    #[serde(default)]
+    pub pinned: bool,
+    /// 最后打开时间（Unix 毫秒时间戳）
+    pub last_opened: i64,
+}
+
+/// 模糊过滤结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentMatch {
+    pub entry: RecentEntry,
+    pub score: u32,
+    /// 匹配字符在路径中的位置（字符索引）
+    pub positions: Vec<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RecentStore {
+    #[serde(default)]
+    entries: Vec<RecentEntry>,
+}
+
+impl RecentStore {
+    /// 按固定状态和最后打开时间排序，并裁剪每种类型的未固定条目
+    fn normalize(&mut self) {
+        self.entries
+            .sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_opened.cmp(&a.last_opened)));
+
+        let mut counts = [0usize; 3];
+        self.entries.retain(|e| {
+            if e.pinned {
+                return true;
+            }
+            let count = &mut counts[e.kind as usize];
+            *count += 1;
+            *count <= MAX_UNPINNED_ENTRIES
+        });
+    }
+
+    /// 移除 `missing` 中的未固定条目（路径 -> 检查时的最后打开时间），返回被移除的数量。
+    /// 检查之后重新打开过的条目（最后打开时间已变化）保留
+    fn remove_missing(&mut self, missing: &HashMap<String, i64>) -> usize {
+        let total = self.entries.len();
+        self.entries
+            .retain(|e| e.pinned || missing.get(&e.path) != Some(&e.last_opened));
+        total - self.entries.len()
+    }
+}
+
+/// 找出确定已不存在的路径。检查出错（如网络驱动器或移动硬盘未挂载）时无法确定，视为存在。
+/// 检查是同步 I/O（网络路径可能很慢），需要在阻塞线程中调用
+fn missing_paths(entries: Vec<(String, i64)>) -> HashMap<String, i64> {
+    entries
+        .into_iter()
+        .filter(|(path, _)| matches!(Path::new(path).try_exists(), Ok(false)))
+        .collect()
+}
+
+fn get_recent_path() -> Result<PathBuf> {
+    let config_dir = system_integration::get_config_dir()?;
+    Ok(config_dir.join("recent.json"))
+}
+
+/// 旧版本的最近文件列表（纯路径数组）
+fn get_legacy_recent_files_path() -> Result<PathBuf> {
+    let config_dir = system_integration::get_config_dir()?;
+    Ok(config_dir.join("recent_files.json"))
+}
+
+async fn load_store() -> Result<RecentStore> {
+    let recent_path = get_recent_path()?;
+    if recent_path.exists() {
+        let content = tokio::fs::read_to_string(&recent_path).await?;
+        return Ok(serde_json::from_str(&content).unwrap_or_default());
+    }
+
+    // 迁移旧的 recent_files.json，保留原有顺序
+    let legacy_path = get_legacy_recent_files_path()?;
+    let mut store = RecentStore::default();
+    if legacy_path.exists() {
+        let content = tokio::fs::read_to_string(&legacy_path).await?;
+        let paths: Vec<String> = serde_json::from_str(&content).unwrap_or_default();
+        let now = chrono::Utc::now().timestamp_millis();
+        store.entries = paths
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| RecentEntry {
+                path,
+                kind: RecentKind::File,
+                pinned: false,
+                last_opened: now - i as i64,
+            })
+            .collect();
+    }
+    Ok(store)
+}
+
+async fn get_store() -> Result<&'static RwLock<RecentStore>> {
+    if let Some(store) = RECENT.get() {
+        return Ok(store);
+    }
+    let store = load_store().await?;
+    Ok(RECENT.get_or_init(|| RwLock::new(store)))
+}
+
+async fn save_store(store: &RecentStore) -> Result<()> {
+    system_integration::ensure_config_dir().await?;
+    let recent_path = get_recent_path()?;
+    let content = serde_json::to_string_pretty(store)?;
+    tokio::fs::write(&recent_path, content).await?;
+    Ok(())
+}
+
+/// 移除路径已不存在的未固定条目，返回被移除的数量。
+/// 路径检查在阻塞线程中进行且不持有锁，检查期间新增或重新打开的条目不受影响
+pub async fn prune_recent_entries() -> Result<usize> {
+    let store = get_store().await?;
+    let entries: Vec<(String, i64)> = store
+        .read()
+        .await
+        .entries
+        .iter()
+        .filter(|e| !e.pinned)
+        .map(|e| (e.path.clone(), e.last_opened))
+        .collect();
+
+    let missing = tokio::task::spawn_blocking(move || missing_paths(entries)).await?;
+    if missing.is_empty() {
+        return Ok(0);
+    }
+
+    let mut store = store.write().await;
+    let removed = store.remove_missing(&missing);
+    if removed > 0 {
+        save_store(&store).await?;
+    }
+    Ok(removed)
+}
+
+/// 距上次清理超过间隔时在后台清理不存在的路径，不阻塞调用方
+fn schedule_prune() {
+    let now = chrono::Utc::now().timestamp_millis();
+    let last = LAST_PRUNE.load(Ordering::Relaxed);
+    if now - last < PRUNE_INTERVAL_MS
+        || LAST_PRUNE
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+    {
+        return;
+    }
+    tauri::async_runtime::spawn(async {
+        if let Err(e) = prune_recent_entries().await {
+            log::warn!("Failed to prune recent entries: {}", e);
+        }
+    });
+}
+
+/// 获取最近条目，可按类型过滤。不存在的路径由后台任务延迟清理
+pub async fn get_recent_entries(kind: Option<RecentKind>) -> Result<Vec<RecentEntry>> {
+    let store = get_store().await?;
+    let entries = store
+        .read()
+        .await
+        .entries
+        .iter()
+        .filter(|e| kind.map_or(true, |k| e.kind == k))
+        .cloned()
+        .collect();
+    schedule_prune();
+    Ok(entries)
+}
+
+/// 添加或更新最近条目
+pub async fn add_recent_entry(path: &str, kind: RecentKind) -> Result<()> {
+    let store = get_store().await?;
+    let mut store = store.write().await;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    if let Some(entry) = store
+        .entries
+        .iter_mut()
+        .find(|e| e.kind == kind && e.path == path)
+    {
+        entry.last_opened = now;
+    } else {
+        store.entries.push(RecentEntry {
+            path: path.to_string(),
+            kind,
+            pinned: false,
+            last_opened: now,
+        });
+    }
+
+    store.normalize();
+    save_store(&store).await
+}
+
+/// 移除指定的最近条目
+pub async fn remove_recent_entry(path: &str, kind: RecentKind) -> Result<()> {
+    let store = get_store().await?;
+    let mut store = store.write().await;
+    store.entries.retain(|e| !(e.kind == kind && e.path == path));
+    save_store(&store).await
+}
+
+/// 固定或取消固定最近条目
+pub async fn set_recent_pinned(path: &str, kind: RecentKind, pinned: bool) -> Result<()> {
+    let store = get_store().await?;
+    let mut store = store.write().await;
+
+    match store
+        .entries
+        .iter_mut()
+        .find(|e| e.kind == kind && e.path == path)
+    {
+        Some(entry) => entry.pinned = pinned,
+        None => anyhow::bail!("Recent entry not found: {}", path),
+    }
+
+    store.normalize();
+    save_store(&store).await
+}
+
+/// 清除最近条目（保留已固定的条目），可按类型过滤
+pub async fn clear_recent_entries(kind: Option<RecentKind>) -> Result<()> {
+    let store = get_store().await?;
+    let mut store = store.write().await;
+    store
+        .entries
+        .retain(|e| e.pinned || kind.is_some_and(|k| e.kind != k));
+    save_store(&store).await
+}
+
+/// 模糊过滤最近条目，用于欢迎页的搜索框
+pub async fn filter_recent_entries(
+    query: &str,
+    kind: Option<RecentKind>,
+    limit: Option<usize>,
+) -> Result<Vec<RecentMatch>> {
+    let entries = get_recent_entries(kind).await?;
+
+    if query.trim().is_empty() {
+        let mut matches: Vec<RecentMatch> = entries
+            .into_iter()
+            .map(|entry| RecentMatch { entry, score: 0, positions: Vec::new() })
+            .collect();
+        if let Some(limit) = limit {
+            matches.truncate(limit);
+        }
+        return Ok(matches);
+    }
+
+    let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+    let pattern = Pattern::parse(query, CaseMatching::Smart, Normalization::Smart);
+    let mut buf = Vec::new();
+    let mut matches = Vec::new();
+
+    for entry in entries {
+        let mut positions = Vec::new();
+        let haystack = Utf32Str::new(&entry.path, &mut buf);
+        if let Some(score) = pattern.indices(haystack, &mut matcher, &mut positions) {
+            positions.sort_unstable();
+            positions.dedup();
+            matches.push(RecentMatch { entry, score, positions });
+        }
+    }
+
+    // 分数相同时保持固定优先、最近优先的顺序
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(b.entry.pinned.cmp(&a.entry.pinned))
+            .then(b.entry.last_opened.cmp(&a.entry.last_opened))
+    });
+    if let Some(limit) = limit {
+        matches.truncate(limit);
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, kind: RecentKind, pinned: bool, last_opened: i64) -> RecentEntry {
+        RecentEntry { path: path.to_string(), kind, pinned, last_opened }
+    }
+
+    #[test]
+    fn store_round_trips_and_normalizes() {
+        let mut store = RecentStore::default();
+        store.entries.push(entry("/old.md", RecentKind::File, false, 1));
+        store.entries.push(entry("/notes", RecentKind::Folder, true, 2));
+        for i in 0..MAX_UNPINNED_ENTRIES as i64 {
+            store.entries.push(entry(&format!("/f{}.md", i), RecentKind::File, false, 10 + i));
+        }
+        store.normalize();
+
+        // 固定条目排在最前，最旧的未固定文件被裁剪
+        assert_eq!(store.entries[0].path, "/notes");
+        assert!(store.entries.iter().all(|e| e.path != "/old.md"));
+
+        let json = serde_json::to_string(&store).unwrap();
+        assert!(json.contains("\"lastOpened\""));
+        let loaded: RecentStore = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.entries.len(), store.entries.len());
+        assert_eq!(loaded.entries[0].kind, RecentKind::Folder);
+        assert!(loaded.entries[0].pinned);
+    }
+
+    #[test]
+    fn prunes_only_missing_paths() {
+        let dir = std::env::temp_dir().join(format!("kairo-recent-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let existing = dir.join("a.md");
+        std::fs::write(&existing, "a").unwrap();
+        let existing = existing.to_string_lossy().into_owned();
+        let gone = dir.join("gone.md").to_string_lossy().into_owned();
+        let pinned = dir.join("pinned.md").to_string_lossy().into_owned();
+        let reopened = dir.join("reopened.md").to_string_lossy().into_owned();
+
+        let mut store = RecentStore::default();
+        store.entries.push(entry(&existing, RecentKind::File, false, 2));
+        store.entries.push(entry(&gone, RecentKind::File, false, 1));
+        store.entries.push(entry(&pinned, RecentKind::File, true, 1));
+        store.entries.push(entry(&reopened, RecentKind::File, false, 1));
+
+        let snapshot = store.entries.iter().map(|e| (e.path.clone(), e.last_opened)).collect();
+        let missing = missing_paths(snapshot);
+        assert_eq!(missing.len(), 3);
+        // 检查期间重新打开的条目保留，固定的条目即使不存在也保留
+        store.entries[3].last_opened = 5;
+        assert_eq!(store.remove_missing(&missing), 1);
+        let paths: Vec<_> = store.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, [existing.as_str(), pinned.as_str(), reopened.as_str()]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
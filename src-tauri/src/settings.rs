@@ -6,10 +6,9 @@ use std::path::PathBuf;
 use tokio::sync::RwLock;
 use std::sync::OnceLock;
 
-use crate::system_integration;
+use crate::{system_integration, terminal};
 
 static SETTINGS: OnceLock<RwLock<EditorSettings>> = OnceLock::new();
-static RECENT_FILES: OnceLock<RwLock<Vec<String>>> = OnceLock::new();
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -46,6 +45,19 @@ pub struct EditorSettings {
     pub search_case_sensitive: bool,
     pub search_whole_word: bool,
     pub search_regex: bool,
+    #[serde(default)]
+    pub search_preserve_case: bool,
+    #[serde(default)]
+    pub search_structural: bool,
+    /// 结构化搜索使用 tree-sitter 查询表达式而不是代码模式
+    #[serde(default)]
+    pub search_structural_query: bool,
+    /// 正则中 `^`/`$` 匹配每行的开头和结尾
+    #[serde(default = "default_search_multiline")]
+    pub search_multiline: bool,
+    /// 正则中 `.` 也匹配换行符
+    #[serde(default)]
+    pub search_dot_all: bool,
     
     // Window
     pub restore_windows: bool,
@@ -67,7 +79,28 @@ pub struct EditorSettings {
     pub add_to_context_menu: bool,
     
     // Terminal
+    #[serde(default = "terminal::default_terminal_type")]
     pub terminal_type: String,
+
+    // Validation
+    /// 文件名到 JSON Schema 的映射，用于校验未声明 $schema 的 JSON/YAML 文件
+    #[serde(default)]
+    pub json_schemas: Vec<SchemaAssociation>,
+
+    // Preview
+    /// 除当前文件夹和笔记所在目录外，`kairofile://` 协议允许访问的目录
+    #[serde(default)]
+    pub asset_roots: Vec<String>,
+}
+
+/// JSON Schema 关联
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaAssociation {
+    /// 文件匹配模式：不含 `/` 时匹配文件名，否则匹配相对工作区的路径（如 `.github/workflows/*.yml`）
+    pub file_match: Vec<String>,
+    /// Schema 文件路径（相对路径基于工作区）或 file:// URI
+    pub url: String,
 }
 
 impl Default for EditorSettings {
@@ -96,6 +129,11 @@ impl Default for EditorSettings {
             search_case_sensitive: false,
             search_whole_word: false,
             search_regex: false,
+            search_preserve_case: false,
+            search_structural: false,
+            search_structural_query: false,
+            search_multiline: true,
+            search_dot_all: false,
             restore_windows: true,
             show_status_bar: true,
             show_activity_bar: true,
@@ -109,19 +147,33 @@ impl Default for EditorSettings {
             register_as_default_editor: false,
             register_as_path_editor: false,
             add_to_context_menu: false,
-            terminal_type: "powershell".to_string(),
+            terminal_type: terminal::default_terminal_type(),
+            json_schemas: Vec::new(),
+            asset_roots: Vec::new(),
         }
     }
 }
 
-fn get_settings_path() -> Result<PathBuf> {
-    let config_dir = system_integration::get_config_dir()?;
-    Ok(config_dir.join("settings.json"))
+impl EditorSettings {
+    /// 终端类型为空或是仅 Windows 可用的类型（旧版本前端曾把 powershell 写入所有平台的设置）时，
+    /// 改用当前平台的默认终端
+    fn normalize_terminal_type(&mut self) {
+        const WINDOWS_ONLY: [&str; 4] = ["powershell", "cmd", "wsl", "gitbash"];
+
+        let current = self.terminal_type.trim();
+        if current.is_empty() || (!cfg!(windows) && WINDOWS_ONLY.contains(&current)) {
+            self.terminal_type = terminal::default_terminal_type();
+        }
+    }
 }
 
-fn get_recent_files_path() -> Result<PathBuf> {
+fn default_search_multiline() -> bool {
+    true
+}
+
+fn get_settings_path() -> Result<PathBuf> {
     let config_dir = system_integration::get_config_dir()?;
-    Ok(config_dir.join("recent_files.json"))
+    Ok(config_dir.join("settings.json"))
 }
 
 pub async fn init_settings() -> Result<()> {
@@ -133,7 +185,9 @@ pub async fn init_settings() -> Result<()> {
     // Load or create settings
     let settings: EditorSettings = if settings_path.exists() {
         let content = tokio::fs::read_to_string(&settings_path).await?;
-        serde_json::from_str(&content).unwrap_or_default()
+        let mut settings: EditorSettings = serde_json::from_str(&content).unwrap_or_default();
+        settings.normalize_terminal_type();
+        settings
     } else {
         let default_settings = EditorSettings::default();
         let content = serde_json::to_string_pretty(&default_settings)?;
@@ -146,17 +200,6 @@ pub async fn init_settings() -> Result<()> {
     
     let _ = SETTINGS.set(RwLock::new(settings));
     
-    // Load recent files
-    let recent_path = get_recent_files_path()?;
-    let recent_files = if recent_path.exists() {
-        let content = tokio::fs::read_to_string(&recent_path).await?;
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-    
-    let _ = RECENT_FILES.set(RwLock::new(recent_files));
-    
     Ok(())
 }
 
@@ -170,6 +213,10 @@ pub async fn get_settings() -> Result<EditorSettings> {
 }
 
 pub async fn save_settings(new_settings: &EditorSettings) -> Result<()> {
+    let mut new_settings = new_settings.clone();
+    new_settings.normalize_terminal_type();
+    let new_settings = &new_settings;
+
     // Handle system integration changes
     if let Some(settings) = SETTINGS.get() {
         let old_settings = settings.read().await.clone();
@@ -213,52 +260,6 @@ pub async fn save_settings(new_settings: &EditorSettings) -> Result<()> {
     Ok(())
 }
 
-pub async fn get_recent_files() -> Result<Vec<String>> {
-    if let Some(recent) = RECENT_FILES.get() {
-        Ok(recent.read().await.clone())
-    } else {
-        init_settings().await?;
-        Ok(RECENT_FILES.get().unwrap().read().await.clone())
-    }
-}
-
-pub async fn add_recent_file(path: &str) -> Result<()> {
-    if RECENT_FILES.get().is_none() {
-        init_settings().await?;
-    }
-    
-    if let Some(recent) = RECENT_FILES.get() {
-        let mut files = recent.write().await;
-        
-        // Remove if already exists
-        files.retain(|f| f != path);
-        
-        // Add to front
-        files.insert(0, path.to_string());
-        
-        // Keep only last 20
-        files.truncate(20);
-        
-        // Save to file
-        let recent_path = get_recent_files_path()?;
-        let content = serde_json::to_string_pretty(&*files)?;
-        tokio::fs::write(&recent_path, content).await?;
-    }
-    
-    Ok(())
-}
-
-pub async fn clear_recent_files() -> Result<()> {
-    if let Some(recent) = RECENT_FILES.get() {
-        recent.write().await.clear();
-        
-        let recent_path = get_recent_files_path()?;
-        tokio::fs::write(&recent_path, "[]").await?;
-    }
-    
-    Ok(())
-}
-
 /// 获取配置文件目录路径
 pub fn get_config_directory() -> Result<String> {
     let config_dir = system_integration::get_config_dir()?;
@@ -0,0 +1,329 @@
+// Single Instance Module
+//
+// 保证每个用户只运行一个 KairoNotes 实例：后续启动的进程通过本地套接字
+// 把命令行中的文件路径转发给已运行的实例后直接退出。
+// 要打开的文件在前端完成初始化（监听 open-file 事件）之前先排队，
+// 前端调用 frontend_ready 握手后一次性取走
+
+use anyhow::{Context, Result};
+#[cfg(unix)]
+use interprocess::local_socket::GenericFilePath;
+#[cfg(not(unix))]
+use interprocess::local_socket::GenericNamespaced;
+use interprocess::local_socket::{prelude::*, Listener, ListenerOptions, Name, Stream};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 请求前端打开文件的事件，载荷为文件路径
+pub const OPEN_FILE_EVENT: &str = "open-file";
+
+/// 单条转发消息的最大长度，防止异常客户端耗尽内存
+const MAX_MESSAGE_LEN: u64 = 1024 * 1024;
+
+static OPEN_QUEUE: OnceLock<Mutex<OpenQueue>> = OnceLock::new();
+
+/// 后续实例发送给首个实例的消息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ForwardMessage {
+    paths: Vec<String>,
+}
+
+/// 启动时的实例角色
+pub enum Instance {
+    /// 首个实例，持有监听套接字
+    Primary(Listener),
+    /// 已把参数转发给正在运行的实例，当前进程应退出
+    Forwarded,
+    /// 无法建立本地套接字，按普通多实例方式运行
+    Standalone,
+}
+
+/// 待打开文件队列：前端就绪前暂存，就绪后直接派发
+#[derive(Debug, Default)]
+struct OpenQueue {
+    ready: bool,
+    pending: Vec<String>,
+}
+
+impl OpenQueue {
+    /// 加入要打开的路径，前端已就绪时返回需要立即派发的路径
+    fn push(&mut self, paths: Vec<String>) -> Vec<String> {
+        if self.ready {
+            paths
+        } else {
+            for path in paths {
+                if !self.pending.contains(&path) {
+                    self.pending.push(path);
+                }
+            }
+            Vec::new()
+        }
+    }
+
+    /// 标记前端已就绪，取走排队中的路径
+    fn mark_ready(&mut self) -> Vec<String> {
+        self.ready = true;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+fn open_queue() -> &'static Mutex<OpenQueue> {
+    OPEN_QUEUE.get_or_init(|| Mutex::new(OpenQueue::default()))
+}
+
+/// 从命令行参数中提取要打开的文件路径，相对路径按 `cwd` 转为绝对路径。
+/// 第一个参数是程序本身，以 `-` 开头的参数视为选项忽略
+pub fn collect_paths<I>(args: I, cwd: &Path) -> Vec<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    args.into_iter()
+        .skip(1)
+        .filter(|arg| !arg.is_empty() && !arg.starts_with('-'))
+        .map(|arg| {
+            let path = PathBuf::from(&arg);
+            let path = if path.is_absolute() { path } else { cwd.join(path) };
+            path.to_string_lossy().into_owned()
+        })
+        .collect()
+}
+
+/// 当前进程命令行中的文件路径
+pub fn startup_paths() -> Vec<String> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    collect_paths(std::env::args(), &cwd)
+}
+
+/// 按用户区分的套接字名称。Unix 上为当前用户私有目录中的套接字文件（同时返回文件路径），
+/// 不使用抽象命名空间，避免其他用户抢先占用名称；Windows 上为按用户名区分的命名管道，
+/// 取不到用户名时返回错误（按普通多实例方式运行），不退回到共享的名称
+#[cfg(unix)]
+fn socket_name() -> io::Result<(Name<'static>, Option<PathBuf>)> {
+    // SAFETY: getuid 总是成功
+    let uid = unsafe { libc::getuid() };
+    let dir = match dirs::runtime_dir() {
+        Some(runtime) => runtime.join("kaironotes"),
+        None => std::env::temp_dir().join(format!("kaironotes-{}", uid)),
+    };
+    private_dir(&dir, uid)?;
+    let path = dir.join("instance.sock");
+    Ok((path.clone().to_fs_name::<GenericFilePath>()?, Some(path)))
+}
+
+#[cfg(not(unix))]
+fn socket_name() -> io::Result<(Name<'static>, Option<PathBuf>)> {
+    let user: String = std::env::var("USERNAME")
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if user.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "Unknown user name"));
+    }
+    Ok((format!("kaironotes-{}.sock", user).to_ns_name::<GenericNamespaced>()?, None))
+}
+
+/// 创建只有当前用户可以访问的目录（0700）。目录已存在时必须属于 `uid` 且不允许其他用户访问，
+/// 防止其他用户预先创建目录后替换套接字
+#[cfg(unix)]
+fn private_dir(dir: &Path, uid: libc::uid_t) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e),
+    }
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Insecure instance socket directory: {}", dir.display()),
+        ));
+    }
+    Ok(())
+}
+
+/// 启动时调用：成为首个实例，或把 `paths` 转发给已运行的实例
+pub fn acquire(paths: &[String]) -> Instance {
+    match try_acquire(paths) {
+        Ok(instance) => instance,
+        Err(e) => {
+            log::warn!("Single instance lock unavailable: {:#}", e);
+            Instance::Standalone
+        }
+    }
+}
+
+fn try_acquire(paths: &[String]) -> Result<Instance> {
+    let (name, socket_file) = socket_name().context("Invalid socket name")?;
+
+    // 第二次尝试前会清理残留的套接字文件
+    for attempt in 0..2 {
+        match ListenerOptions::new().name(name.clone()).create_sync() {
+            Ok(listener) => return Ok(Instance::Primary(listener)),
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {}
+            Err(e) => return Err(e).context("Failed to create instance socket"),
+        }
+
+        match forward(&name, paths) {
+            Ok(()) => return Ok(Instance::Forwarded),
+            // 名称被占用但没有进程在监听：上次异常退出遗留的套接字文件。
+            // 其他错误（如已运行的实例没有及时确认）不删除，避免抢走正常实例的套接字
+            Err(e)
+                if attempt == 0
+                    && socket_file.is_some()
+                    && matches!(e.kind(), io::ErrorKind::ConnectionRefused | io::ErrorKind::NotFound) =>
+            {
+                log::info!("Removing stale instance socket: {}", e);
+                if let Some(path) = &socket_file {
+                    std::fs::remove_file(path).context("Failed to remove stale socket")?;
+                }
+            }
+            Err(e) => return Err(e).context("Failed to reach running instance"),
+        }
+    }
+
+    anyhow::bail!("Instance socket is in use")
+}
+
+/// 把路径发送给已运行的实例，等待其确认
+fn forward(name: &Name<'_>, paths: &[String]) -> io::Result<()> {
+    let mut stream = Stream::connect(name.borrow())?;
+    let message = ForwardMessage { paths: paths.to_vec() };
+    let mut line = serde_json::to_string(&message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    stream.flush()?;
+
+    let mut ack = String::new();
+    BufReader::new(stream).read_line(&mut ack)?;
+    if ack.trim() == "ok" {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidData, "Unexpected acknowledgement"))
+    }
+}
+
+/// 在后台线程接收后续实例转发的路径，每个连接在单独的线程中处理，
+/// 不发送数据的客户端不会阻塞其他实例的转发
+pub fn start_listener(app: AppHandle, listener: Listener) {
+    std::thread::spawn(move || {
+        for connection in listener.incoming() {
+            match connection {
+                Ok(stream) => {
+                    let app = app.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = handle_connection(&app, stream) {
+                            log::warn!("Failed to handle forwarded arguments: {}", e);
+                        }
+                    });
+                }
+                Err(e) => log::warn!("Instance socket accept failed: {}", e),
+            }
+        }
+    });
+}
+
+fn handle_connection(app: &AppHandle, stream: Stream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    io::Read::take(&mut reader, MAX_MESSAGE_LEN).read_line(&mut line)?;
+    let message: ForwardMessage = serde_json::from_str(&line)?;
+
+    // 窗口在前端加载完成后才显示，未就绪时不提前显示空白窗口
+    if open_paths(app, message.paths) {
+        focus_main_window(app);
+    }
+
+    let mut stream = reader.into_inner();
+    stream.write_all(b"ok\n")?;
+    stream.flush()
+}
+
+/// 请求前端打开文件：前端未就绪时排队，已就绪时立即发送 open-file 事件。
+/// 返回前端是否已就绪
+pub fn open_paths(app: &AppHandle, paths: Vec<String>) -> bool {
+    let (ready, paths) = match open_queue().lock() {
+        Ok(mut queue) => {
+            let paths = queue.push(paths);
+            (queue.ready, paths)
+        }
+        Err(_) => return false,
+    };
+    for path in paths {
+        let _ = app.emit(OPEN_FILE_EVENT, path);
+    }
+    ready
+}
+
+/// 前端握手：标记已就绪并返回排队中的路径
+pub fn frontend_ready() -> Vec<String> {
+    open_queue()
+        .lock()
+        .map(|mut queue| queue.mark_ready())
+        .unwrap_or_default()
+}
+
+/// 把主窗口带到前台
+fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_absolute_paths_and_skips_options() {
+        let cwd = std::env::temp_dir().join("kaironotes-cwd");
+        let absolute = cwd.join("other").join("b.txt").to_string_lossy().into_owned();
+        let args = vec![
+            "kaironotes".to_string(),
+            "notes/a.md".to_string(),
+            "--verbose".to_string(),
+            absolute.clone(),
+        ];
+
+        let paths = collect_paths(args, &cwd);
+        assert_eq!(
+            paths,
+            vec![cwd.join("notes/a.md").to_string_lossy().into_owned(), absolute]
+        );
+    }
+
+    #[test]
+    fn queue_holds_paths_until_frontend_ready() {
+        let mut queue = OpenQueue::default();
+        assert!(queue.push(vec!["a.md".into(), "b.md".into()]).is_empty());
+        assert!(queue.push(vec!["a.md".into()]).is_empty());
+
+        assert_eq!(queue.mark_ready(), vec!["a.md".to_string(), "b.md".to_string()]);
+        assert_eq!(queue.push(vec!["c.md".into()]), vec!["c.md".to_string()]);
+        assert!(queue.mark_ready().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_dir_must_be_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("kaironotes-instance-{}", std::process::id()));
+        let uid = unsafe { libc::getuid() };
+        private_dir(&dir, uid).unwrap();
+        assert_eq!(std::fs::metadata(&dir).unwrap().permissions().mode() & 0o777, 0o700);
+
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(private_dir(&dir, uid).is_err());
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        assert!(private_dir(&dir, uid.wrapping_add(1)).is_err());
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
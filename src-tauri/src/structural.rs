@@ -0,0 +1,642 @@
+// Structural Search Module
+//
+// 基于 tree-sitter 语法树的结构化搜索与替换：
+// 代码模式中的 `$name` 匹配任意一个节点，`$$$name` 匹配任意多个相邻节点，
+// 比较时忽略空白和注释，字符串和注释内部的文本不会被当作代码匹配
+
+use crate::commands::SearchResult;
+use crate::{editor, syntax};
+use anyhow::{anyhow, bail, Result};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::Path;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor, Tree};
+
+/// 超过该大小的文件在工作区搜索中跳过
+const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// 查询模式中表示整个匹配范围的捕获名，缺省时取所有捕获的并集
+const MATCH_CAPTURE: &str = "match";
+
+/// 支持结构化搜索的语言 ID（与 `syntax::detect_language` 一致）
+pub const SUPPORTED_LANGUAGES: &[&str] = &[
+    "rust",
+    "javascript",
+    "javascriptreact",
+    "typescript",
+    "typescriptreact",
+    "python",
+    "go",
+    "c",
+    "cpp",
+    "java",
+];
+
+fn grammar(language: &str) -> Option<Language> {
+    let language = match language {
+        "rust" => tree_sitter_rust::LANGUAGE,
+        "javascript" | "javascriptreact" => tree_sitter_javascript::LANGUAGE,
+        "typescript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        "typescriptreact" => tree_sitter_typescript::LANGUAGE_TSX,
+        "python" => tree_sitter_python::LANGUAGE,
+        "go" => tree_sitter_go::LANGUAGE,
+        "c" => tree_sitter_c::LANGUAGE,
+        "cpp" => tree_sitter_cpp::LANGUAGE,
+        "java" => tree_sitter_java::LANGUAGE,
+        _ => return None,
+    };
+    Some(language.into())
+}
+
+/// 代码片段不能单独解析时依次尝试的上下文（前缀、后缀）
+fn pattern_contexts(language: &str) -> &'static [(&'static str, &'static str)] {
+    match language {
+        "rust" => &[
+            ("fn __kairo() {\n", "\n}"),
+            ("", ""),
+            ("impl __Kairo {\n", "\n}"),
+        ],
+        "go" => &[
+            ("package __kairo\nfunc __kairo() {\n", "\n}"),
+            ("package __kairo\n", ""),
+        ],
+        "c" | "cpp" => &[
+            ("void __kairo() {\n", "\n}"),
+            ("void __kairo() {\n", ";\n}"),
+            ("", ""),
+        ],
+        "java" => &[
+            ("class __Kairo { void __kairo() {\n", "\n} }"),
+            ("class __Kairo { void __kairo() {\n", ";\n} }"),
+            ("class __Kairo {\n", "\n}"),
+            ("", ""),
+        ],
+        "javascript" | "javascriptreact" | "typescript" | "typescriptreact" => &[
+            ("", ""),
+            ("class __Kairo {\n", "\n}"),
+        ],
+        _ => &[("", "")],
+    }
+}
+
+fn parse(language: &Language, source: &str) -> Result<Tree> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(language)
+        .map_err(|e| anyhow!("Failed to load grammar: {}", e))?;
+    parser
+        .parse(source, None)
+        .ok_or_else(|| anyhow!("Failed to parse source"))
+}
+
+/// 搜索方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StructuralMode {
+    /// 带占位符的代码模式，如 `foo($a, $b)`
+    #[default]
+    Pattern,
+    /// tree-sitter 查询表达式，如 `(call_expression function: (identifier) @f)`
+    Query,
+}
+
+/// 结构化搜索请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralQuery {
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: StructuralMode,
+    /// 替换模板，其中的 `$name` / `$$$name` 替换为对应占位符或捕获的文本
+    #[serde(default)]
+    pub replacement: Option<String>,
+}
+
+/// 一个结构化匹配：位置、各占位符（或捕获）绑定的文本，以及替换预览
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralMatch {
+    #[serde(flatten)]
+    pub result: SearchResult,
+    pub captures: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+/// 工作区中单个文件的匹配
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStructuralMatches {
+    pub path: String,
+    pub language: String,
+    pub matches: Vec<StructuralMatch>,
+}
+
+/// 占位符绑定的节点；多节点占位符可以绑定零个或多个节点
+type Bindings<'t> = BTreeMap<String, Vec<Node<'t>>>;
+
+/// 单个匹配：整体范围和绑定
+struct RawMatch<'t> {
+    range: Range<usize>,
+    bindings: Bindings<'t>,
+}
+
+#[derive(Debug, Clone)]
+struct Placeholder {
+    name: String,
+    variadic: bool,
+}
+
+impl Placeholder {
+    /// `$_` 开头的占位符只匹配不绑定
+    fn binds(&self) -> bool {
+        !self.name.starts_with('_')
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// 解析 `$name` / `$$$name`，返回（占位符、在 `text` 中占用的字节数）
+fn parse_placeholder(text: &str) -> Option<(Placeholder, usize)> {
+    let (variadic, rest) = match text.strip_prefix("$$$") {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('$')?),
+    };
+    let len = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+    if len == 0 && !variadic {
+        return None;
+    }
+    let name = if len == 0 { "_".to_string() } else { rest[..len].to_string() };
+    let prefix = if variadic { 3 } else { 1 };
+    Some((Placeholder { name, variadic }, prefix + len))
+}
+
+/// 编译后的代码模式
+struct CodePattern {
+    tree: Tree,
+    source: String,
+    range: Range<usize>,
+    /// 替换后的标识符 → 占位符
+    placeholders: HashMap<String, Placeholder>,
+}
+
+impl CodePattern {
+    fn new(language: &str, grammar: &Language, pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            bail!("Pattern is empty");
+        }
+
+        // 占位符替换为合法标识符后再解析
+        let mut code = String::with_capacity(pattern.len());
+        let mut placeholders = HashMap::new();
+        let mut rest = pattern;
+        while let Some(index) = rest.find('$') {
+            code.push_str(&rest[..index]);
+            match parse_placeholder(&rest[index..]) {
+                Some((placeholder, len)) => {
+                    let ident = format!("kairo_ph_{}", placeholders.len());
+                    code.push_str(&ident);
+                    placeholders.insert(ident, placeholder);
+                    rest = &rest[index + len..];
+                }
+                None => {
+                    code.push('$');
+                    rest = &rest[index + 1..];
+                }
+            }
+        }
+        code.push_str(rest);
+
+        for (prefix, suffix) in pattern_contexts(language) {
+            let source = format!("{}{}{}", prefix, code, suffix);
+            let tree = parse(grammar, &source)?;
+            if tree.root_node().has_error() {
+                continue;
+            }
+            let range = prefix.len()..prefix.len() + code.len();
+            let Some(node) = tree.root_node().named_descendant_for_byte_range(range.start, range.end) else {
+                continue;
+            };
+            if node.byte_range() != range {
+                continue;
+            }
+            return Ok(Self { tree, source, range, placeholders });
+        }
+        bail!("Pattern is not valid {} code: {}", language, pattern)
+    }
+
+    fn root(&self) -> Node<'_> {
+        self.tree
+            .root_node()
+            .named_descendant_for_byte_range(self.range.start, self.range.end)
+            .unwrap_or_else(|| self.tree.root_node())
+    }
+
+    fn placeholder(&self, node: Node) -> Option<&Placeholder> {
+        self.placeholders.get(&self.source[node.byte_range()])
+    }
+
+    /// 在目标树中查找所有不重叠的匹配（外层优先）
+    fn find<'t>(&self, tree: &'t Tree, source: &str) -> Vec<RawMatch<'t>> {
+        let root = self.root();
+        let mut matches = Vec::new();
+        let mut cursor = tree.walk();
+        'walk: loop {
+            let node = cursor.node();
+            let mut bindings = Bindings::new();
+            if node.is_named() && !node.is_extra() && self.match_node(root, node, source, &mut bindings) {
+                matches.push(RawMatch { range: node.byte_range(), bindings });
+            } else if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'walk;
+                }
+            }
+        }
+        matches
+    }
+
+    fn match_node<'t>(&self, pattern: Node, target: Node<'t>, source: &str, bindings: &mut Bindings<'t>) -> bool {
+        if let Some(placeholder) = self.placeholder(pattern) {
+            return target.is_named() && bind(placeholder, &[target], source, bindings);
+        }
+        if pattern.kind_id() != target.kind_id() {
+            return false;
+        }
+        let pattern_children = significant_children(pattern);
+        let target_children = significant_children(target);
+        if pattern_children.is_empty() {
+            return target_children.is_empty()
+                && self.source[pattern.byte_range()] == source[target.byte_range()];
+        }
+        self.match_children(&pattern_children, &target_children, source, bindings)
+    }
+
+    fn match_children<'t>(
+        &self,
+        pattern: &[Node],
+        target: &[Node<'t>],
+        source: &str,
+        bindings: &mut Bindings<'t>,
+    ) -> bool {
+        let Some((&first, rest)) = pattern.split_first() else {
+            return target.is_empty();
+        };
+        if let Some(placeholder) = self.placeholder(first).filter(|p| p.variadic) {
+            for count in 0..=target.len() {
+                let saved = bindings.clone();
+                if bind(placeholder, &target[..count], source, bindings)
+                    && self.match_children(rest, &target[count..], source, bindings)
+                {
+                    return true;
+                }
+                *bindings = saved;
+            }
+            return false;
+        }
+        let Some((&head, tail)) = target.split_first() else {
+            return false;
+        };
+        self.match_node(first, head, source, bindings) && self.match_children(rest, tail, source, bindings)
+    }
+}
+
+/// 子节点（包括匿名的标点、关键字），不含注释等 extra 节点
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).filter(|child| !child.is_extra()).collect()
+}
+
+/// 节点的词法单元序列，用于比较同名占位符的绑定是否相同
+fn tokens<'s>(nodes: &[Node], source: &'s str) -> Vec<&'s str> {
+    fn collect<'s>(node: Node, source: &'s str, out: &mut Vec<&'s str>) {
+        let children = significant_children(node);
+        if children.is_empty() {
+            out.push(&source[node.byte_range()]);
+        }
+        for child in children {
+            collect(child, source, out);
+        }
+    }
+    let mut out = Vec::new();
+    for &node in nodes {
+        collect(node, source, &mut out);
+    }
+    out
+}
+
+/// 绑定占位符；同名占位符再次出现时要求代码相同（忽略空白和注释）
+fn bind<'t>(placeholder: &Placeholder, nodes: &[Node<'t>], source: &str, bindings: &mut Bindings<'t>) -> bool {
+    if !placeholder.binds() {
+        return true;
+    }
+    match bindings.get(&placeholder.name) {
+        Some(bound) => tokens(bound, source) == tokens(nodes, source),
+        None => {
+            bindings.insert(placeholder.name.clone(), nodes.to_vec());
+            true
+        }
+    }
+}
+
+/// 绑定节点覆盖的原文（多个节点时包括其间的分隔符）
+fn bound_text<'s>(nodes: &[Node], source: &'s str) -> &'s str {
+    match (nodes.first(), nodes.last()) {
+        (Some(first), Some(last)) => &source[first.start_byte()..last.end_byte()],
+        _ => "",
+    }
+}
+
+/// 展开替换模板中的 `$name` / `$$$name`，未绑定的名称原样保留
+fn expand(template: &str, bindings: &Bindings, source: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(index) = rest.find('$') {
+        out.push_str(&rest[..index]);
+        match parse_placeholder(&rest[index..]) {
+            Some((placeholder, len)) => {
+                match bindings.get(&placeholder.name) {
+                    Some(nodes) => out.push_str(bound_text(nodes, source)),
+                    None => out.push_str(&rest[index..index + len]),
+                }
+                rest = &rest[index + len..];
+            }
+            None => {
+                out.push('$');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 执行 tree-sitter 查询；范围为 `@match` 捕获或所有捕获的并集，重叠的匹配只保留靠前的一个
+fn run_query<'t>(grammar: &Language, pattern: &str, tree: &'t Tree, source: &str) -> Result<Vec<RawMatch<'t>>> {
+    let query = Query::new(grammar, pattern).map_err(|e| anyhow!("Invalid query: {}", e))?;
+    let names = query.capture_names();
+    let mut cursor = QueryCursor::new();
+    let mut found = Vec::new();
+    let mut iter = cursor.matches(&query, tree.root_node(), source.as_bytes());
+    while let Some(m) = iter.next() {
+        let mut bindings = Bindings::new();
+        let mut range: Option<Range<usize>> = None;
+        for capture in m.captures {
+            let name = names[capture.index as usize];
+            bindings.entry(name.to_string()).or_default().push(capture.node);
+            if name != MATCH_CAPTURE {
+                let node = capture.node.byte_range();
+                range = Some(match range {
+                    Some(r) => r.start.min(node.start)..r.end.max(node.end),
+                    None => node,
+                });
+            }
+        }
+        if let Some(whole) = bindings.get(MATCH_CAPTURE) {
+            range = Some(whole[0].byte_range());
+        }
+        if let Some(range) = range {
+            found.push(RawMatch { range, bindings });
+        }
+    }
+
+    found.sort_by_key(|m| (m.range.start, std::cmp::Reverse(m.range.end)));
+    let mut matches: Vec<RawMatch> = Vec::with_capacity(found.len());
+    for m in found {
+        if matches.last().map_or(true, |last| m.range.start >= last.range.end) {
+            matches.push(m);
+        }
+    }
+    Ok(matches)
+}
+
+/// 编译后的搜索，可用于同一语言的多个文件
+struct Matcher {
+    grammar: Language,
+    kind: MatcherKind,
+}
+
+enum MatcherKind {
+    Pattern(CodePattern),
+    Query(String),
+}
+
+impl Matcher {
+    fn new(language: &str, query: &StructuralQuery) -> Result<Self> {
+        let grammar = grammar(language).ok_or_else(|| anyhow!("Structural search does not support {}", language))?;
+        let kind = match query.mode {
+            StructuralMode::Pattern => MatcherKind::Pattern(CodePattern::new(language, &grammar, &query.pattern)?),
+            StructuralMode::Query => {
+                // 先编译一次以尽早报告语法错误
+                Query::new(&grammar, &query.pattern).map_err(|e| anyhow!("Invalid query: {}", e))?;
+                MatcherKind::Query(query.pattern.clone())
+            }
+        };
+        Ok(Self { grammar, kind })
+    }
+
+    fn search(&self, content: &str, replacement: Option<&str>) -> Result<Vec<StructuralMatch>> {
+        let tree = parse(&self.grammar, content)?;
+        let raw = match &self.kind {
+            MatcherKind::Pattern(pattern) => pattern.find(&tree, content),
+            MatcherKind::Query(query) => run_query(&self.grammar, query, &tree, content)?,
+        };
+        let ranges: Vec<Range<usize>> = raw.iter().map(|m| m.range.clone()).collect();
+        let results = editor::results_for_ranges(content, &ranges);
+        Ok(raw
+            .into_iter()
+            .zip(results)
+            .map(|(m, result)| StructuralMatch {
+                result,
+                captures: m
+                    .bindings
+                    .iter()
+                    .map(|(name, nodes)| (name.clone(), bound_text(nodes, content).to_string()))
+                    .collect(),
+                replacement: replacement.map(|template| expand(template, &m.bindings, content)),
+            })
+            .collect())
+    }
+}
+
+fn apply_replacements(content: &str, matches: &[StructuralMatch]) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for m in matches {
+        if let Some(replacement) = &m.replacement {
+            result.push_str(&content[last..m.result.byte_start]);
+            result.push_str(replacement);
+            last = m.result.byte_end;
+        }
+    }
+    result.push_str(&content[last..]);
+    result
+}
+
+/// 在文档中进行结构化搜索，提供替换模板时附带每个匹配的替换预览
+pub fn structural_search(content: &str, language: &str, query: &StructuralQuery) -> Result<Vec<StructuralMatch>> {
+    Matcher::new(language, query)?.search(content, query.replacement.as_deref())
+}
+
+/// 用替换模板替换文档中的所有结构化匹配
+pub fn structural_replace(content: &str, language: &str, query: &StructuralQuery) -> Result<String> {
+    let replacement = query.replacement.as_deref().ok_or_else(|| anyhow!("Replacement is required"))?;
+    let matches = Matcher::new(language, query)?.search(content, Some(replacement))?;
+    Ok(apply_replacements(content, &matches))
+}
+
+/// 遍历工作区（遵循 .gitignore）中受支持语言的文件，`language` 为空时搜索所有支持的语言。
+/// 模式无法按某种语言解析时跳过该语言的文件
+fn visit_workspace(
+    root: &Path,
+    language: Option<&str>,
+    query: &StructuralQuery,
+    mut visit: impl FnMut(&Path, &str, &str, &Matcher) -> Result<bool>,
+) -> Result<()> {
+    if let Some(language) = language {
+        if grammar(language).is_none() {
+            bail!("Structural search does not support {}", language);
+        }
+    }
+    let mut matchers: HashMap<String, Option<Matcher>> = HashMap::new();
+    let mut last_error = None;
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file())
+            || entry.metadata().map_or(true, |m| m.len() > MAX_FILE_SIZE)
+        {
+            continue;
+        }
+        let path = entry.path();
+        let detected = syntax::detect_language(&path.to_string_lossy(), None);
+        if grammar(&detected).is_none() || language.is_some_and(|l| l != detected) {
+            continue;
+        }
+        let matcher = matchers.entry(detected.clone()).or_insert_with(|| {
+            Matcher::new(&detected, query)
+                .map_err(|e| last_error = Some(e))
+                .ok()
+        });
+        let Some(matcher) = matcher else {
+            continue;
+        };
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        if !visit(path, &detected, &content, matcher)? {
+            break;
+        }
+    }
+
+    // 所有遇到的语言都无法编译模式时报告错误
+    match last_error {
+        Some(error) if matchers.values().all(Option::is_none) => Err(error),
+        _ => Ok(()),
+    }
+}
+
+/// 在工作区中结构化搜索，最多返回 `limit` 个匹配
+pub fn search_workspace(
+    root: &Path,
+    language: Option<&str>,
+    query: &StructuralQuery,
+    limit: usize,
+) -> Result<Vec<FileStructuralMatches>> {
+    let mut files = Vec::new();
+    let mut total = 0;
+    visit_workspace(root, language, query, |path, language, content, matcher| {
+        let mut matches = matcher.search(content, query.replacement.as_deref())?;
+        matches.truncate(limit.saturating_sub(total));
+        if matches.is_empty() {
+            return Ok(total < limit);
+        }
+        total += matches.len();
+        files.push(FileStructuralMatches {
+            path: path.to_string_lossy().to_string(),
+            language: language.to_string(),
+            matches,
+        });
+        Ok(total < limit)
+    })?;
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(pattern: &str, replacement: Option<&str>) -> StructuralQuery {
+        StructuralQuery {
+            pattern: pattern.to_string(),
+            mode: StructuralMode::Pattern,
+            replacement: replacement.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_pattern_ignores_whitespace_comments_and_strings() {
+        let content = "fn main() {\n    foo(a, b);\n    foo(\n        x + 1, /* note */ y\n    );\n    // foo(c, d)\n    bar(\"foo(e, f)\");\n    foo(g);\n}\n";
+        let query = pattern("foo($a, $b)", Some("foo($b, $a)"));
+
+        let matches = structural_search(content, "rust", &query).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].result.line, 2);
+        assert_eq!(matches[0].captures["a"], "a");
+        assert_eq!(matches[1].result.line, 3);
+        assert_eq!(matches[1].captures["a"], "x + 1");
+        assert_eq!(matches[1].replacement.as_deref(), Some("foo(y, x + 1)"));
+
+        let replaced = structural_replace(content, "rust", &query).unwrap();
+        assert!(replaced.contains("foo(b, a);"));
+        assert!(replaced.contains("foo(y, x + 1);"));
+        assert!(replaced.contains("// foo(c, d)"));
+        assert!(replaced.contains("bar(\"foo(e, f)\")"));
+
+        // 同名占位符要求相同的代码，`$$$` 匹配任意多个参数
+        let same = structural_search("x = max(a, a)\ny = max(a, b)\n", "python", &pattern("max($x, $x)", None)).unwrap();
+        assert_eq!(same.len(), 1);
+        assert_eq!(same[0].result.line, 1);
+        let variadic = structural_search(
+            "log(1);\nlog();\nlog(1, 2, 3);\n",
+            "javascript",
+            &pattern("log($$$args)", Some("console.log($$$args)")),
+        )
+        .unwrap();
+        let replacements: Vec<_> = variadic.iter().filter_map(|m| m.replacement.as_deref()).collect();
+        assert_eq!(replacements, ["console.log(1)", "console.log()", "console.log(1, 2, 3)"]);
+    }
+
+    #[test]
+    fn test_query_expression() {
+        let content = "function f() { foo(1); bar(2); foo(3); }\n";
+        let query = StructuralQuery {
+            pattern: "((call_expression function: (identifier) @name arguments: (arguments (number) @arg)) @match (#eq? @name \"foo\"))".to_string(),
+            mode: StructuralMode::Query,
+            replacement: Some("baz($arg)".to_string()),
+        };
+
+        let matches = structural_search(content, "javascript", &query).unwrap();
+        let texts: Vec<_> = matches.iter().map(|m| m.result.text.as_str()).collect();
+        assert_eq!(texts, ["foo(1)", "foo(3)"]);
+        assert_eq!(matches[1].result.column, 32);
+        assert_eq!(
+            structural_replace(content, "javascript", &query).unwrap(),
+            "function f() { baz(1); bar(2); baz(3); }\n"
+        );
+
+        let invalid = StructuralQuery { pattern: "(call_expression".to_string(), ..query };
+        assert!(structural_search(content, "javascript", &invalid).is_err());
+    }
+}
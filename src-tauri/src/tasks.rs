@@ -0,0 +1,474 @@
+// Task Runner Module
+// 任务系统：读取 <文件夹>/.kaironotes/tasks.json，通过终端层执行任务并流式推送输出
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+
+use crate::{problems, settings, terminal};
+
+static TASK_RUNS: OnceLock<Mutex<HashMap<String, TaskRunState>>> = OnceLock::new();
+
+/// 任务开始事件
+pub const TASK_STARTED_EVENT: &str = "task-started";
+/// 任务输出事件
+pub const TASK_OUTPUT_EVENT: &str = "task-output";
+/// 单个任务结束事件
+pub const TASK_FINISHED_EVENT: &str = "task-finished";
+/// 整个运行（含依赖任务）结束事件
+pub const TASK_RUN_FINISHED_EVENT: &str = "task-run-finished";
+
+/// 任务定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDefinition {
+    pub label: String,
+    pub command: String,
+    /// 工作目录，相对路径基于打开的文件夹
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// 依赖任务，可以是单个标签或标签数组
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub depends_on: Vec<String>,
+    /// 任务分组，如 build、test；可以按分组运行该组的第一个任务
+    #[serde(default)]
+    pub group: Option<String>,
+    /// 覆盖设置中的终端类型
+    #[serde(default)]
+    pub terminal_type: Option<String>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// 问题匹配器：名称、内联定义或它们的数组；未指定时使用所有可用匹配器，空数组表示不解析
+    #[serde(default, deserialize_with = "deserialize_optional_one_or_many")]
+    pub problem_matcher: Option<Vec<problems::ProblemMatcherRef>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TasksFile {
+    #[serde(default)]
+    tasks: Vec<TaskDefinition>,
+}
+
+/// 任务运行信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRunInfo {
+    pub run_id: String,
+    /// 按执行顺序排列的任务标签（依赖任务在前）
+    pub tasks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskStartedPayload {
+    run_id: String,
+    label: String,
+    command: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskOutputPayload {
+    run_id: String,
+    label: String,
+    stream: terminal::OutputStream,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskFinishedPayload {
+    run_id: String,
+    label: String,
+    exit_code: Option<i32>,
+    success: bool,
+    timed_out: bool,
+    cancelled: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskRunFinishedPayload {
+    run_id: String,
+    success: bool,
+}
+
+#[derive(Debug, Default)]
+struct TaskRunState {
+    current_command_id: Option<String>,
+    cancelled: bool,
+}
+
+fn task_runs() -> &'static Mutex<HashMap<String, TaskRunState>> {
+    TASK_RUNS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> From<OneOrMany<T>> for Vec<T> {
+    fn from(value: OneOrMany<T>) -> Self {
+        match value {
+            OneOrMany::One(item) => vec![item],
+            OneOrMany::Many(items) => items,
+        }
+    }
+}
+
+fn deserialize_one_or_many<'de, D, T>(deserializer: D) -> std::result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(deserialize_optional_one_or_many(deserializer)?.unwrap_or_default())
+}
+
+fn deserialize_optional_one_or_many<'de, D, T>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<OneOrMany<T>>::deserialize(deserializer)?.map(Vec::from))
+}
+
+/// 获取任务文件路径
+pub fn get_tasks_file_path(folder: &str) -> PathBuf {
+    Path::new(folder).join(".kaironotes").join("tasks.json")
+}
+
+/// 读取文件夹中定义的任务，文件不存在时返回空列表
+pub async fn load_tasks(folder: &str) -> Result<Vec<TaskDefinition>> {
+    let tasks_path = get_tasks_file_path(folder);
+    if !tasks_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = tokio::fs::read_to_string(&tasks_path).await?;
+    let file: TasksFile = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Invalid tasks file {}: {}", tasks_path.display(), e))?;
+
+    let mut seen = HashSet::new();
+    for task in &file.tasks {
+        if !seen.insert(task.label.as_str()) {
+            anyhow::bail!("Duplicate task label: {}", task.label);
+        }
+    }
+
+    Ok(file.tasks)
+}
+
+/// 计算执行顺序：依赖任务在前，检测缺失依赖和循环依赖
+fn resolve_execution_order<'a>(
+    tasks: &'a [TaskDefinition],
+    label: &str,
+) -> Result<Vec<&'a TaskDefinition>> {
+    fn visit<'a>(
+        by_label: &HashMap<&str, &'a TaskDefinition>,
+        label: &str,
+        visiting: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        order: &mut Vec<&'a TaskDefinition>,
+    ) -> Result<()> {
+        if done.contains(label) {
+            return Ok(());
+        }
+        if visiting.iter().any(|l| l == label) {
+            visiting.push(label.to_string());
+            anyhow::bail!("Circular task dependency: {}", visiting.join(" -> "));
+        }
+
+        let task = by_label
+            .get(label)
+            .ok_or_else(|| anyhow!("Task not found: {}", label))?;
+
+        visiting.push(label.to_string());
+        for dep in &task.depends_on {
+            visit(by_label, dep, visiting, done, order)?;
+        }
+        visiting.pop();
+
+        done.insert(label.to_string());
+        order.push(task);
+        Ok(())
+    }
+
+    let by_label: HashMap<&str, &TaskDefinition> =
+        tasks.iter().map(|t| (t.label.as_str(), t)).collect();
+    let mut order = Vec::new();
+    visit(&by_label, label, &mut Vec::new(), &mut HashSet::new(), &mut order)?;
+    Ok(order)
+}
+
+/// 查找分组中的任务（分组名不区分大小写），多个任务属于同一分组时取定义顺序中的第一个
+fn find_group_task<'a>(tasks: &'a [TaskDefinition], group: &str) -> Result<&'a TaskDefinition> {
+    tasks
+        .iter()
+        .find(|t| t.group.as_deref().is_some_and(|g| g.eq_ignore_ascii_case(group)))
+        .ok_or_else(|| anyhow!("No task in group: {}", group))
+}
+
+/// 替换任务中的 `${workspaceFolder}` 变量
+fn expand_variables(value: &str, folder: &str) -> String {
+    value.replace("${workspaceFolder}", folder)
+}
+
+/// 运行任务（先运行其依赖任务），返回运行 ID，输出通过事件推送
+pub async fn run_task(app: &AppHandle, folder: &str, label: &str) -> Result<TaskRunInfo> {
+    let tasks = load_tasks(folder).await?;
+    let order: Vec<TaskDefinition> = resolve_execution_order(&tasks, label)?
+        .into_iter()
+        .cloned()
+        .collect();
+
+    let default_terminal = settings::get_settings().await?.terminal_type;
+    let run_id = uuid::Uuid::new_v4().to_string();
+    task_runs()
+        .lock()
+        .unwrap()
+        .insert(run_id.clone(), TaskRunState::default());
+
+    let info = TaskRunInfo {
+        run_id: run_id.clone(),
+        tasks: order.iter().map(|t| t.label.clone()).collect(),
+    };
+
+    let app = app.clone();
+    let folder = folder.to_string();
+    tauri::async_runtime::spawn(async move {
+        let mut success = true;
+        for task in order {
+            if !run_single_task(&app, &run_id, &folder, &task, &default_terminal).await {
+                success = false;
+                break;
+            }
+        }
+        task_runs().lock().unwrap().remove(&run_id);
+        let _ = app.emit(TASK_RUN_FINISHED_EVENT, TaskRunFinishedPayload { run_id, success });
+    });
+
+    Ok(info)
+}
+
+/// 运行分组中的任务（如 build 分组对应“运行构建任务”）
+pub async fn run_group_task(app: &AppHandle, folder: &str, group: &str) -> Result<TaskRunInfo> {
+    let tasks = load_tasks(folder).await?;
+    let label = find_group_task(&tasks, group)?.label.clone();
+    run_task(app, folder, &label).await
+}
+
+/// 运行单个任务，返回是否成功
+async fn run_single_task(
+    app: &AppHandle,
+    run_id: &str,
+    folder: &str,
+    task: &TaskDefinition,
+    default_terminal: &str,
+) -> bool {
+    let command_id = format!("{}:{}", run_id, task.label);
+    {
+        let mut runs = task_runs().lock().unwrap();
+        match runs.get_mut(run_id) {
+            Some(state) if !state.cancelled => {
+                // 先登记取消标记，进程启动前到达的取消同样生效
+                terminal::register_command(&command_id);
+                state.current_command_id = Some(command_id.clone());
+            }
+            _ => return false,
+        }
+    }
+
+    let command = expand_variables(&task.command, folder);
+    let cwd = match &task.cwd {
+        Some(cwd) => Path::new(folder).join(expand_variables(cwd, folder)),
+        None => PathBuf::from(folder),
+    }
+    .to_string_lossy()
+    .to_string();
+    let env = task
+        .env
+        .iter()
+        .map(|(k, v)| (k.clone(), expand_variables(v, folder)))
+        .collect();
+    let terminal_type = task.terminal_type.as_deref().unwrap_or(default_terminal);
+
+    let _ = app.emit(
+        TASK_STARTED_EVENT,
+        TaskStartedPayload {
+            run_id: run_id.to_string(),
+            label: task.label.clone(),
+            command: command.clone(),
+        },
+    );
+
+    let collector = match problems::resolve_matchers(task.problem_matcher.as_deref(), Some(&command)).await {
+        Ok(matchers) => Arc::new(Mutex::new(problems::ProblemCollector::new(
+            matchers,
+            Some(&cwd),
+        ))),
+        Err(e) => {
+            log::warn!("Failed to load problem matchers for task {}: {}", task.label, e);
+            Arc::new(Mutex::new(problems::ProblemCollector::new(Vec::new(), None)))
+        }
+    };
+
+    let sink: terminal::OutputSink = {
+        let app = app.clone();
+        let run_id = run_id.to_string();
+        let label = task.label.clone();
+        let collector = collector.clone();
+        Arc::new(move |stream, data| {
+            collector.lock().unwrap().feed(data);
+            let _ = app.emit(
+                TASK_OUTPUT_EVENT,
+                TaskOutputPayload {
+                    run_id: run_id.clone(),
+                    label: label.clone(),
+                    stream,
+                    data: data.to_string(),
+                },
+            );
+        })
+    };
+
+    let options = terminal::CommandOptions {
+        command_id: Some(command_id),
+        timeout_ms: task.timeout_ms,
+        env,
+        stdin: None,
+    };
+    let result =
+        terminal::execute_command_streaming(&command, Some(&cwd), terminal_type, options, sink)
+            .await;
+
+    let payload = match result {
+        Ok(output) => TaskFinishedPayload {
+            run_id: run_id.to_string(),
+            label: task.label.clone(),
+            exit_code: output.exit_code,
+            success: output.exit_code == Some(0),
+            timed_out: output.timed_out,
+            cancelled: output.cancelled,
+        },
+        Err(e) => {
+            let _ = app.emit(
+                TASK_OUTPUT_EVENT,
+                TaskOutputPayload {
+                    run_id: run_id.to_string(),
+                    label: task.label.clone(),
+                    stream: terminal::OutputStream::Stderr,
+                    data: format!("{}\n", e),
+                },
+            );
+            TaskFinishedPayload {
+                run_id: run_id.to_string(),
+                label: task.label.clone(),
+                exit_code: None,
+                success: false,
+                timed_out: false,
+                cancelled: false,
+            }
+        }
+    };
+
+    let task_problems = collector.lock().unwrap().take_problems();
+    problems::publish_problems(app, &format!("task:{}", task.label), task_problems).await;
+
+    let success = payload.success;
+    let _ = app.emit(TASK_FINISHED_EVENT, payload);
+    success
+}
+
+/// 取消任务运行（包括尚未开始的依赖任务）
+pub fn cancel_task_run(run_id: &str) -> bool {
+    let command_id = {
+        let mut runs = task_runs().lock().unwrap();
+        match runs.get_mut(run_id) {
+            Some(state) => {
+                state.cancelled = true;
+                state.current_command_id.clone()
+            }
+            None => return false,
+        }
+    };
+
+    if let Some(command_id) = command_id {
+        terminal::cancel_command(&command_id);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(label: &str, depends_on: &[&str]) -> TaskDefinition {
+        TaskDefinition {
+            label: label.to_string(),
+            command: format!("echo {}", label),
+            cwd: None,
+            env: HashMap::new(),
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            group: None,
+            terminal_type: None,
+            timeout_ms: None,
+            problem_matcher: None,
+        }
+    }
+
+    #[test]
+    fn test_execution_order() {
+        let tasks = vec![task("publish", &["build", "lint"]), task("build", &["lint"]), task("lint", &[])];
+        let order: Vec<&str> = resolve_execution_order(&tasks, "publish")
+            .unwrap()
+            .iter()
+            .map(|t| t.label.as_str())
+            .collect();
+        assert_eq!(order, vec!["lint", "build", "publish"]);
+    }
+
+    #[test]
+    fn test_find_group_task() {
+        let mut lint = task("lint", &[]);
+        lint.group = Some("test".to_string());
+        let mut build = task("build", &[]);
+        build.group = Some("Build".to_string());
+        let mut release = task("release", &[]);
+        release.group = Some("build".to_string());
+        let tasks = vec![lint, build, release];
+
+        assert_eq!(find_group_task(&tasks, "build").unwrap().label, "build");
+        assert_eq!(find_group_task(&tasks, "test").unwrap().label, "lint");
+        assert!(find_group_task(&tasks, "publish").is_err());
+    }
+
+    #[test]
+    fn test_circular_dependency() {
+        let tasks = vec![task("a", &["b"]), task("b", &["a"])];
+        assert!(resolve_execution_order(&tasks, "a").is_err());
+    }
+
+    #[test]
+    fn test_depends_on_string_or_array() {
+        let file: TasksFile = serde_json::from_str(
+            r#"{"tasks": [
+                {"label": "a", "command": "x", "dependsOn": "b"},
+                {"label": "b", "command": "y", "dependsOn": ["c", "d"]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(file.tasks[0].depends_on, vec!["b"]);
+        assert_eq!(file.tasks[1].depends_on, vec!["c", "d"]);
+        assert!(file.tasks[0].problem_matcher.is_none());
+    }
+}
@@ -0,0 +1,800 @@
+// Terminal Module
+// 终端子系统：基于伪终端（PTY）的交互式会话，以及一次性命令执行
+
+use anyhow::{anyhow, Result};
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use crate::commands::TerminalOutput;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// 会话表使用同步锁：PTY 的读写本身是阻塞操作，且读取线程退出时需要移除会话
+static SESSIONS: OnceLock<Mutex<HashMap<String, TerminalSession>>> = OnceLock::new();
+static RUNNING_COMMANDS: OnceLock<Mutex<HashMap<String, Arc<CancelToken>>>> = OnceLock::new();
+
+const GIT_BASH_PATHS: [&str; 2] = [
+    "C:\\Program Files\\Git\\bin\\bash.exe",
+    "C:\\Program Files (x86)\\Git\\bin\\bash.exe",
+];
+
+/// 进程结束后等待输出管道关闭的最长时间：继承了管道的后台子进程会让管道一直保持打开
+const OUTPUT_DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// 终端输出事件
+pub const TERMINAL_OUTPUT_EVENT: &str = "terminal-output";
+/// 终端会话退出事件
+pub const TERMINAL_EXIT_EVENT: &str = "terminal-exit";
+
+/// Shell 启动方式
+#[derive(Debug, Clone)]
+pub struct ShellSpec {
+    pub program: String,
+    /// 执行单条命令时使用的参数（如 `-Command`、`/C`、`-c`）
+    pub command_arg: String,
+    /// 启动交互式会话时使用的参数
+    pub interactive_args: Vec<String>,
+}
+
+/// 终端会话信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalSessionInfo {
+    pub id: String,
+    pub shell: String,
+    pub cwd: Option<String>,
+    pub pid: Option<u32>,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TerminalOutputPayload {
+    session_id: String,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TerminalExitPayload {
+    session_id: String,
+    exit_code: Option<u32>,
+}
+
+struct TerminalSession {
+    info: TerminalSessionInfo,
+    master: Box<dyn MasterPty + Send>,
+    /// 写入端单独加锁，写入阻塞时不影响会话表和其他会话
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+}
+
+fn sessions() -> &'static Mutex<HashMap<String, TerminalSession>> {
+    SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 当前平台的默认终端类型
+pub fn default_terminal_type() -> String {
+    #[cfg(windows)]
+    {
+        "powershell".to_string()
+    }
+
+    #[cfg(not(windows))]
+    {
+        // 常见 Shell 使用名称，其他 Shell 直接使用 $SHELL 的完整路径作为自定义 Shell
+        std::env::var("SHELL")
+            .ok()
+            .filter(|shell| !shell.is_empty())
+            .map(|shell| {
+                let name = std::path::Path::new(&shell)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                match name.as_str() {
+                    "bash" | "zsh" | "fish" | "sh" | "pwsh" => name,
+                    _ => shell,
+                }
+            })
+            .unwrap_or_else(|| "sh".to_string())
+    }
+}
+
+/// 根据终端类型解析 Shell
+///
+/// `terminal_type` 可以是内置类型（powershell、cmd、pwsh、wsl、gitbash、bash、zsh、fish、sh），
+/// 也可以是自定义 Shell 的路径及参数，例如 `/usr/local/bin/nu --login`。
+/// 为空时使用平台默认终端。
+pub fn resolve_shell(terminal_type: &str) -> ShellSpec {
+    let terminal_type = terminal_type.trim();
+    let spec = |program: &str, command_arg: &str, interactive_args: &[&str]| ShellSpec {
+        program: program.to_string(),
+        command_arg: command_arg.to_string(),
+        interactive_args: interactive_args.iter().map(|s| s.to_string()).collect(),
+    };
+
+    match terminal_type {
+        "" => resolve_shell(&default_terminal_type()),
+        // 仅 Windows 可用的终端类型，在其他平台上回退到默认 Shell（兼容旧配置）
+        "powershell" | "cmd" | "wsl" | "gitbash" if !cfg!(windows) => {
+            log::warn!("Terminal type '{}' is not available on this platform", terminal_type);
+            resolve_shell(&default_terminal_type())
+        }
+        "cmd" => spec("cmd", "/C", &[]),
+        "powershell" => spec("powershell", "-Command", &["-NoLogo"]),
+        "pwsh" => spec("pwsh", "-Command", &["-NoLogo"]),
+        "wsl" => spec("wsl", "-e", &[]),
+        "gitbash" => {
+            let bash_path = GIT_BASH_PATHS
+                .iter()
+                .find(|p| std::path::Path::new(p).exists())
+                .copied()
+                .unwrap_or("bash");
+            spec(bash_path, "-c", &["--login", "-i"])
+        }
+        "bash" | "zsh" | "fish" => {
+            let path = find_unix_shell(terminal_type).unwrap_or_else(|| terminal_type.to_string());
+            spec(&path, "-c", &["-l"])
+        }
+        "sh" => {
+            let path = find_unix_shell("sh").unwrap_or_else(|| "sh".to_string());
+            spec(&path, "-c", &[])
+        }
+        custom => {
+            let mut parts = split_command_line(custom).into_iter();
+            let program = parts.next().unwrap_or_default();
+            let interactive_args: Vec<String> = parts.collect();
+            let name = std::path::Path::new(&program)
+                .file_stem()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let command_arg = match name.as_str() {
+                "cmd" => "/C",
+                "powershell" | "pwsh" => "-Command",
+                _ => "-c",
+            };
+            ShellSpec { program, command_arg: command_arg.to_string(), interactive_args }
+        }
+    }
+}
+
+/// 将自定义 Shell 配置拆分为程序和参数，支持单引号、双引号和反斜杠转义
+fn split_command_line(line: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') if matches!(chars.peek(), Some('"') | Some('\\')) => {
+                current.push(chars.next().unwrap());
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                in_token = true;
+            }
+            // Windows 路径中的反斜杠保持原样，只在转义空格和引号时生效
+            (None, '\\') if matches!(chars.peek(), Some(' ') | Some('"') | Some('\'')) => {
+                current.push(chars.next().unwrap());
+                in_token = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_token {
+                    parts.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+    if in_token {
+        parts.push(current);
+    }
+    parts
+}
+
+/// 读取 /etc/shells 中登记的 Shell 路径
+#[cfg(not(windows))]
+fn read_etc_shells() -> Vec<String> {
+    std::fs::read_to_string("/etc/shells")
+        .map(|content| parse_etc_shells(&content))
+        .unwrap_or_default()
+}
+
+#[cfg(not(windows))]
+fn parse_etc_shells(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// 查找 Unix Shell 的完整路径：优先 $SHELL，其次 /etc/shells，最后 PATH
+#[cfg(not(windows))]
+fn find_unix_shell(name: &str) -> Option<String> {
+    let matches_name = |path: &str| {
+        std::path::Path::new(path)
+            .file_name()
+            .is_some_and(|n| n == name)
+    };
+
+    if let Ok(shell) = std::env::var("SHELL") {
+        if matches_name(&shell) && std::path::Path::new(&shell).exists() {
+            return Some(shell);
+        }
+    }
+
+    if let Some(path) = read_etc_shells()
+        .into_iter()
+        .find(|p| matches_name(p) && std::path::Path::new(p).exists())
+    {
+        return Some(path);
+    }
+
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(name))
+            .find(|p| p.is_file())
+            .map(|p| p.to_string_lossy().to_string())
+    })
+}
+
+#[cfg(windows)]
+fn find_unix_shell(_name: &str) -> Option<String> {
+    None
+}
+
+/// 一次性命令的附加选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandOptions {
+    /// 用于取消命令的 ID，由前端生成
+    pub command_id: Option<String>,
+    /// 超时时间（毫秒），超时后终止进程
+    pub timeout_ms: Option<u64>,
+    /// 额外的环境变量
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// 写入进程标准输入的内容
+    pub stdin: Option<String>,
+}
+
+enum CommandOutcome {
+    Exited(std::process::ExitStatus),
+    TimedOut,
+    Cancelled,
+}
+
+/// 命令的取消标记。可以在命令启动前登记，启动前收到的取消不会丢失
+#[derive(Default)]
+struct CancelToken {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+/// 命令结束（包括启动失败）时注销取消标记
+struct CommandRegistration(String);
+
+impl Drop for CommandRegistration {
+    fn drop(&mut self) {
+        running_commands().lock().unwrap().remove(&self.0);
+    }
+}
+
+fn running_commands() -> &'static Mutex<HashMap<String, Arc<CancelToken>>> {
+    RUNNING_COMMANDS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cancel_token(command_id: &str) -> Arc<CancelToken> {
+    running_commands()
+        .lock()
+        .unwrap()
+        .entry(command_id.to_string())
+        .or_default()
+        .clone()
+}
+
+/// 预先登记命令 ID，之后对该 ID 的取消会在命令启动时生效。
+/// 登记后必须以同一 ID 调用一次命令执行，由执行结束时注销
+pub fn register_command(command_id: &str) {
+    cancel_token(command_id);
+}
+
+/// 输出流类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// 逐行接收命令输出的回调
+pub type OutputSink = Arc<dyn Fn(OutputStream, &str) + Send + Sync>;
+
+/// 执行单条命令并等待其结束，支持超时、取消、环境变量和标准输入
+pub async fn execute_command(
+    command: &str,
+    cwd: Option<&str>,
+    terminal_type: &str,
+    options: CommandOptions,
+) -> Result<TerminalOutput> {
+    run_command(command, cwd, terminal_type, options, None).await
+}
+
+/// 执行单条命令，并在输出产生时逐行回调（用于任务等需要流式输出的场景）
+pub async fn execute_command_streaming(
+    command: &str,
+    cwd: Option<&str>,
+    terminal_type: &str,
+    options: CommandOptions,
+    sink: OutputSink,
+) -> Result<TerminalOutput> {
+    run_command(command, cwd, terminal_type, options, Some(sink)).await
+}
+
+/// 读取输出直到结束，同时将每一行转发给回调。
+/// 输出写入共享缓冲区，读取任务被中止时已读到的内容仍然保留
+async fn collect_output<R>(
+    reader: R,
+    stream: OutputStream,
+    sink: Option<OutputSink>,
+    output: Arc<Mutex<Vec<u8>>>,
+) where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    let mut reader = tokio::io::BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                if let Some(sink) = &sink {
+                    sink(stream, &String::from_utf8_lossy(&line));
+                }
+                output.lock().unwrap().extend_from_slice(&line);
+            }
+        }
+    }
+}
+
+async fn run_command(
+    command: &str,
+    cwd: Option<&str>,
+    terminal_type: &str,
+    options: CommandOptions,
+    sink: Option<OutputSink>,
+) -> Result<TerminalOutput> {
+    use std::process::Stdio;
+    use tokio::io::AsyncWriteExt;
+
+    let shell = resolve_shell(terminal_type);
+
+    let mut cmd = tokio::process::Command::new(&shell.program);
+    cmd.arg(&shell.command_arg)
+        .arg(command)
+        .envs(&options.env)
+        .stdin(if options.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    if let Some(dir) = cwd {
+        cmd.current_dir(dir);
+    }
+
+    // 独立进程组，超时或取消时可以终止 Shell 启动的所有子进程
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    // 在启动进程之前登记取消标记，避免启动期间的取消请求丢失
+    let cancel = options
+        .command_id
+        .as_deref()
+        .map(|id| (cancel_token(id), CommandRegistration(id.to_string())));
+    if cancel
+        .as_ref()
+        .is_some_and(|(token, _)| token.cancelled.load(Ordering::SeqCst))
+    {
+        return Ok(TerminalOutput {
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: None,
+            timed_out: false,
+            cancelled: true,
+        });
+    }
+
+    let mut child = cmd.spawn()?;
+
+    if let (Some(input), Some(mut stdin)) = (options.stdin, child.stdin.take()) {
+        tokio::spawn(async move {
+            let _ = stdin.write_all(input.as_bytes()).await;
+            // stdin 在此处关闭，进程可以读到 EOF
+        });
+    }
+
+    let stdout = child.stdout.take().ok_or_else(|| anyhow!("Failed to capture stdout"))?;
+    let stderr = child.stderr.take().ok_or_else(|| anyhow!("Failed to capture stderr"))?;
+    let stdout_buf = Arc::new(Mutex::new(Vec::new()));
+    let stderr_buf = Arc::new(Mutex::new(Vec::new()));
+    let mut stdout_task = tokio::spawn(collect_output(
+        stdout,
+        OutputStream::Stdout,
+        sink.clone(),
+        stdout_buf.clone(),
+    ));
+    let mut stderr_task =
+        tokio::spawn(collect_output(stderr, OutputStream::Stderr, sink, stderr_buf.clone()));
+
+    let cancelled = async {
+        match &cancel {
+            Some((token, _)) => token.notify.notified().await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let timeout = async {
+        match options.timeout_ms {
+            Some(ms) => tokio::time::sleep(std::time::Duration::from_millis(ms)).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let outcome = tokio::select! {
+        status = child.wait() => CommandOutcome::Exited(status?),
+        _ = timeout => CommandOutcome::TimedOut,
+        _ = cancelled => CommandOutcome::Cancelled,
+    };
+    drop(cancel);
+
+    let exit_code = match outcome {
+        CommandOutcome::Exited(status) => status.code(),
+        CommandOutcome::TimedOut | CommandOutcome::Cancelled => {
+            kill_process_tree(&mut child).await;
+            None
+        }
+    };
+
+    // Shell 已结束，但后台子进程可能仍持有输出管道，限时读完剩余输出后放弃
+    let drain = async {
+        let _ = (&mut stdout_task).await;
+        let _ = (&mut stderr_task).await;
+    };
+    if tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, drain).await.is_err() {
+        log::warn!("Output pipes still open after command exited, detaching: {}", command);
+        stdout_task.abort();
+        stderr_task.abort();
+    }
+
+    let stdout = String::from_utf8_lossy(&stdout_buf.lock().unwrap()).to_string();
+    let stderr = String::from_utf8_lossy(&stderr_buf.lock().unwrap()).to_string();
+    Ok(TerminalOutput {
+        stdout,
+        stderr,
+        exit_code,
+        timed_out: matches!(outcome, CommandOutcome::TimedOut),
+        cancelled: matches!(outcome, CommandOutcome::Cancelled),
+    })
+}
+
+/// 终止 Shell 及其启动的所有子进程
+async fn kill_process_tree(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: 进程以独立进程组启动，组 ID 即 Shell 的 PID
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+    }
+
+    #[cfg(windows)]
+    if let Some(pid) = child.id() {
+        let _ = tokio::process::Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .creation_flags(CREATE_NO_WINDOW)
+            .status()
+            .await;
+    }
+
+    let _ = child.kill().await;
+}
+
+/// 取消正在执行或已登记的命令，返回是否找到该命令
+pub fn cancel_command(command_id: &str) -> bool {
+    match running_commands().lock().unwrap().get(command_id) {
+        Some(token) => {
+            token.cancelled.store(true, Ordering::SeqCst);
+            // notify_one 在没有等待者时保留许可，之后的等待会立即完成
+            token.notify.notify_one();
+            true
+        }
+        None => false,
+    }
+}
+
+/// 获取可用的终端类型
+#[cfg(windows)]
+pub fn get_available_terminals() -> Vec<String> {
+    let mut terminals = vec!["powershell".to_string()];
+
+    // Check for cmd
+    terminals.push("cmd".to_string());
+
+    // Check for PowerShell Core (pwsh)
+    if std::process::Command::new("pwsh")
+        .arg("--version")
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .is_ok()
+    {
+        terminals.push("pwsh".to_string());
+    }
+
+    // Check for WSL
+    if std::process::Command::new("wsl")
+        .arg("--status")
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .is_ok()
+    {
+        terminals.push("wsl".to_string());
+    }
+
+    // Check for Git Bash
+    if GIT_BASH_PATHS.iter().any(|p| std::path::Path::new(p).exists()) {
+        terminals.push("gitbash".to_string());
+    }
+
+    terminals
+}
+
+/// 获取可用的终端类型：$SHELL 优先，然后是 /etc/shells 中登记的常见 Shell
+#[cfg(not(windows))]
+pub fn get_available_terminals() -> Vec<String> {
+    const KNOWN_SHELLS: [&str; 4] = ["bash", "zsh", "fish", "sh"];
+
+    let mut terminals = vec![default_terminal_type()];
+    for name in KNOWN_SHELLS {
+        if !terminals.iter().any(|t| t == name) && find_unix_shell(name).is_some() {
+            terminals.push(name.to_string());
+        }
+    }
+
+    // PowerShell Core 在 Linux/macOS 上也可能安装
+    if find_unix_shell("pwsh").is_some() {
+        terminals.push("pwsh".to_string());
+    }
+
+    terminals
+}
+
+/// 创建 PTY 终端会话，输出通过 `terminal-output` 事件推送
+pub fn create_session(
+    app: &AppHandle,
+    terminal_type: &str,
+    cwd: Option<&str>,
+    env: Option<HashMap<String, String>>,
+    cols: u16,
+    rows: u16,
+) -> Result<TerminalSessionInfo> {
+    let shell = resolve_shell(terminal_type);
+    let size = PtySize { rows, cols, pixel_width: 0, pixel_height: 0 };
+    let pair = native_pty_system().openpty(size)?;
+
+    let mut cmd = CommandBuilder::new(&shell.program);
+    cmd.args(&shell.interactive_args);
+    if let Some(dir) = cwd {
+        cmd.cwd(dir);
+    }
+    cmd.env("TERM", "xterm-256color");
+    cmd.env("COLORTERM", "truecolor");
+    for (key, value) in env.unwrap_or_default() {
+        cmd.env(key, value);
+    }
+
+    let mut child = pair.slave.spawn_command(cmd)?;
+    // 子进程已持有 slave 端，父进程需关闭自己的副本，否则子进程退出后读取端收不到 EOF
+    drop(pair.slave);
+
+    let reader = pair.master.try_clone_reader()?;
+    let writer = pair.master.take_writer()?;
+    let killer = child.clone_killer();
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let info = TerminalSessionInfo {
+        id: id.clone(),
+        shell: shell.program,
+        cwd: cwd.map(|s| s.to_string()),
+        pid: child.process_id(),
+        cols,
+        rows,
+    };
+
+    sessions().lock().unwrap().insert(
+        id.clone(),
+        TerminalSession {
+            info: info.clone(),
+            master: pair.master,
+            writer: Arc::new(Mutex::new(writer)),
+            killer,
+        },
+    );
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        pump_output(&app, &id, reader);
+
+        let exit_code = child.wait().ok().map(|status| status.exit_code());
+        sessions().lock().unwrap().remove(&id);
+        let _ = app.emit(TERMINAL_EXIT_EVENT, TerminalExitPayload { session_id: id, exit_code });
+    });
+
+    Ok(info)
+}
+
+/// 持续读取 PTY 输出并推送到前端，直到子进程关闭终端
+fn pump_output(app: &AppHandle, session_id: &str, mut reader: Box<dyn Read + Send>) {
+    let mut buf = [0u8; 8192];
+    // 上一次读取末尾不完整的 UTF-8 字节
+    let mut pending: Vec<u8> = Vec::new();
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        pending.extend_from_slice(&buf[..n]);
+
+        let valid_up_to = match std::str::from_utf8(&pending) {
+            Ok(_) => pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => pending.len(),
+        };
+        if valid_up_to == 0 {
+            continue;
+        }
+
+        let data = String::from_utf8_lossy(&pending[..valid_up_to]).into_owned();
+        pending.drain(..valid_up_to);
+        let _ = app.emit(
+            TERMINAL_OUTPUT_EVENT,
+            TerminalOutputPayload { session_id: session_id.to_string(), data },
+        );
+    }
+
+    if !pending.is_empty() {
+        let data = String::from_utf8_lossy(&pending).into_owned();
+        let _ = app.emit(
+            TERMINAL_OUTPUT_EVENT,
+            TerminalOutputPayload { session_id: session_id.to_string(), data },
+        );
+    }
+}
+
+/// 向会话写入输入（按键、粘贴内容等）
+pub fn write_session(session_id: &str, data: &str) -> Result<()> {
+    let writer = sessions()
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .map(|session| session.writer.clone())
+        .ok_or_else(|| anyhow!("Terminal session not found: {}", session_id))?;
+
+    let mut writer = writer.lock().unwrap();
+    writer.write_all(data.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// 调整会话的终端尺寸
+pub fn resize_session(session_id: &str, cols: u16, rows: u16) -> Result<()> {
+    let mut sessions = sessions().lock().unwrap();
+    let session = sessions
+        .get_mut(session_id)
+        .ok_or_else(|| anyhow!("Terminal session not found: {}", session_id))?;
+    session.master.resize(PtySize { rows, cols, pixel_width: 0, pixel_height: 0 })?;
+    session.info.cols = cols;
+    session.info.rows = rows;
+    Ok(())
+}
+
+/// 结束会话，退出事件由读取线程在进程结束后发送
+pub fn kill_session(session_id: &str) -> Result<()> {
+    let mut sessions = sessions().lock().unwrap();
+    let session = sessions
+        .get_mut(session_id)
+        .ok_or_else(|| anyhow!("Terminal session not found: {}", session_id))?;
+    session.killer.kill()?;
+    Ok(())
+}
+
+/// 获取所有活动会话
+pub fn list_sessions() -> Vec<TerminalSessionInfo> {
+    sessions()
+        .lock()
+        .unwrap()
+        .values()
+        .map(|s| s.info.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(split_command_line("/usr/bin/nu --login"), vec!["/usr/bin/nu", "--login"]);
+        assert_eq!(
+            split_command_line(r#""C:\Program Files\Nu\nu.exe" -l"#),
+            vec!["C:\\Program Files\\Nu\\nu.exe", "-l"]
+        );
+        assert_eq!(split_command_line("fish  -C 'set x 1'"), vec!["fish", "-C", "set x 1"]);
+    }
+
+    #[test]
+    fn test_resolve_custom_shell() {
+        let shell = resolve_shell("/opt/bin/xonsh -i");
+        assert_eq!(shell.program, "/opt/bin/xonsh");
+        assert_eq!(shell.command_arg, "-c");
+        assert_eq!(shell.interactive_args, vec!["-i"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_background_child_does_not_block_output() {
+        let started = std::time::Instant::now();
+        let output = execute_command("echo done; sleep 30 &", None, "sh", CommandOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(output.stdout.trim(), "done");
+        assert_eq!(output.exit_code, Some(0));
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_before_start() {
+        register_command("test-cancel-before-start");
+        assert!(cancel_command("test-cancel-before-start"));
+        let options = CommandOptions {
+            command_id: Some("test-cancel-before-start".to_string()),
+            ..Default::default()
+        };
+        let output = execute_command("echo never", None, "sh", options).await.unwrap();
+        assert!(output.cancelled);
+        assert!(output.stdout.is_empty());
+        assert!(!cancel_command("test-cancel-before-start"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_parse_etc_shells() {
+        let content = "# /etc/shells\n/bin/sh\n\n/usr/bin/zsh\n";
+        assert_eq!(parse_etc_shells(content), vec!["/bin/sh", "/usr/bin/zsh"]);
+    }
+}
@@ -0,0 +1,221 @@
+// Terminal History Module
+// 按工作目录持久化终端命令历史，支持去重、时间戳、退出码和反向搜索
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tokio::sync::RwLock;
+
+use crate::{system_integration, terminal};
+
+static HISTORY: OnceLock<RwLock<HistoryStore>> = OnceLock::new();
+
+/// 每个工作目录最多保留的命令数量
+const MAX_ENTRIES_PER_WORKSPACE: usize = 500;
+
+/// 命令历史条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub command: String,
+    /// 最近一次执行的退出码（交互式会话中执行时未知）
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// 最近一次执行时间（Unix 毫秒时间戳）
+    pub timestamp: i64,
+    /// 执行次数
+    #[serde(default = "default_count")]
+    pub count: u32,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// 按工作目录分组的历史记录，条目按执行时间从旧到新排列
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryStore {
+    #[serde(default)]
+    workspaces: HashMap<String, Vec<HistoryEntry>>,
+}
+
+impl HistoryStore {
+    fn record(&mut self, workspace: &str, command: &str, exit_code: Option<i32>) {
+        let entries = self.workspaces.entry(workspace_key(workspace)).or_default();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let count = match entries.iter().position(|e| e.command == command) {
+            Some(index) => entries.remove(index).count + 1,
+            None => 1,
+        };
+        entries.push(HistoryEntry {
+            command: command.to_string(),
+            exit_code,
+            timestamp: now,
+            count,
+        });
+
+        if entries.len() > MAX_ENTRIES_PER_WORKSPACE {
+            let excess = entries.len() - MAX_ENTRIES_PER_WORKSPACE;
+            entries.drain(..excess);
+        }
+    }
+
+    /// 最新的条目在前
+    fn entries(&self, workspace: &str) -> impl Iterator<Item = &HistoryEntry> {
+        self.workspaces
+            .get(&workspace_key(workspace))
+            .into_iter()
+            .flat_map(|entries| entries.iter().rev())
+    }
+}
+
+/// 统一工作目录的写法，未指定工作目录时使用空字符串作为全局历史
+fn workspace_key(workspace: &str) -> String {
+    let trimmed = workspace.trim_end_matches(['/', '\\']);
+    let key = if trimmed.is_empty() { workspace } else { trimmed };
+    if cfg!(windows) {
+        key.replace('/', "\\").to_lowercase()
+    } else {
+        key.to_string()
+    }
+}
+
+fn get_history_path() -> Result<PathBuf> {
+    let config_dir = system_integration::get_config_dir()?;
+    Ok(config_dir.join("terminal_history.json"))
+}
+
+async fn get_store() -> Result<&'static RwLock<HistoryStore>> {
+    if let Some(store) = HISTORY.get() {
+        return Ok(store);
+    }
+    let history_path = get_history_path()?;
+    let store = if history_path.exists() {
+        let content = tokio::fs::read_to_string(&history_path).await?;
+        serde_json::from_str(&content).unwrap_or_default()
+    } else {
+        HistoryStore::default()
+    };
+    Ok(HISTORY.get_or_init(|| RwLock::new(store)))
+}
+
+async fn save_store(store: &HistoryStore) -> Result<()> {
+    system_integration::ensure_config_dir().await?;
+    let history_path = get_history_path()?;
+    let content = serde_json::to_string_pretty(store)?;
+    tokio::fs::write(&history_path, content).await?;
+    Ok(())
+}
+
+/// 记录一条已执行的命令，相同命令只保留最近一次
+pub async fn add_history_entry(workspace: &str, command: &str, exit_code: Option<i32>) -> Result<()> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Ok(());
+    }
+    let store = get_store().await?;
+    let mut store = store.write().await;
+    store.record(workspace, command, exit_code);
+    save_store(&store).await
+}
+
+/// 获取工作目录的命令历史，最新的在前
+pub async fn get_history(workspace: &str, limit: Option<usize>) -> Result<Vec<HistoryEntry>> {
+    let store = get_store().await?;
+    let store = store.read().await;
+    Ok(store
+        .entries(workspace)
+        .take(limit.unwrap_or(usize::MAX))
+        .cloned()
+        .collect())
+}
+
+/// 反向搜索历史（类似 Ctrl+R），按最近执行时间返回包含查询内容的命令
+pub async fn search_history(
+    workspace: &str,
+    query: &str,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>> {
+    let store = get_store().await?;
+    let store = store.read().await;
+    Ok(search_entries(store.entries(workspace), query)
+        .take(limit.unwrap_or(usize::MAX))
+        .cloned()
+        .collect())
+}
+
+/// 包含大写字母时区分大小写，否则忽略大小写
+fn search_entries<'a>(
+    entries: impl Iterator<Item = &'a HistoryEntry>,
+    query: &str,
+) -> impl Iterator<Item = &'a HistoryEntry> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let query = if case_sensitive { query.to_string() } else { query.to_lowercase() };
+    entries.filter(move |e| {
+        if case_sensitive {
+            e.command.contains(&query)
+        } else {
+            e.command.to_lowercase().contains(&query)
+        }
+    })
+}
+
+/// 清除工作目录的命令历史，未指定时清除全部
+pub async fn clear_history(workspace: Option<&str>) -> Result<()> {
+    let store = get_store().await?;
+    let mut store = store.write().await;
+    match workspace {
+        Some(workspace) => {
+            store.workspaces.remove(&workspace_key(workspace));
+        }
+        None => store.workspaces.clear(),
+    }
+    save_store(&store).await
+}
+
+/// 在指定的终端会话中重新执行历史命令，并记录到该会话工作目录的历史中
+pub async fn rerun_in_session(session_id: &str, command: &str) -> Result<()> {
+    let session = terminal::list_sessions()
+        .into_iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| anyhow!("Terminal session not found: {}", session_id))?;
+
+    terminal::write_session(session_id, &format!("{}\r", command.trim_end()))?;
+    add_history_entry(session.cwd.as_deref().unwrap_or(""), command, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_dedupes_and_orders() {
+        let mut store = HistoryStore::default();
+        store.record("/work", "cargo build", Some(1));
+        store.record("/work/", "cargo test", Some(0));
+        store.record("/work", "cargo build", Some(0));
+
+        let entries: Vec<_> = store.entries("/work").collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "cargo build");
+        assert_eq!(entries[0].exit_code, Some(0));
+        assert_eq!(entries[0].count, 2);
+        assert_eq!(entries[1].command, "cargo test");
+        assert_eq!(store.entries("/other").count(), 0);
+    }
+
+    #[test]
+    fn test_search_smart_case() {
+        let mut store = HistoryStore::default();
+        store.record("", "git status", Some(0));
+        store.record("", "Git log", Some(0));
+
+        let found: Vec<_> = search_entries(store.entries(""), "git").map(|e| &e.command).collect();
+        assert_eq!(found, vec!["Git log", "git status"]);
+        let found: Vec<_> = search_entries(store.entries(""), "Git").map(|e| &e.command).collect();
+        assert_eq!(found, vec!["Git log"]);
+    }
+}
@@ -0,0 +1,1099 @@
+// Validation Module
+// 数据与配置文件校验：JSON、JSONC、TOML、YAML、XML、INI、.env 语法检查（精确到行列），
+// JSON/YAML 支持按 $schema 引用或设置中的文件映射进行 JSON Schema 校验
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::problems::{Problem, Severity};
+use crate::settings::SchemaAssociation;
+use crate::{lsp, settings, syntax, system_integration};
+
+/// 语法错误的来源名称
+const SYNTAX_SOURCE: &str = "validation";
+/// Schema 校验问题的来源名称
+const SCHEMA_SOURCE: &str = "json-schema";
+
+/// 已编译的 Schema，按文件修改时间失效（被 $ref 引用的文件修改后需重新打开 Schema 文件）
+type SchemaCache = HashMap<PathBuf, (SystemTime, Arc<jsonschema::Validator>)>;
+static SCHEMAS: OnceLock<Mutex<SchemaCache>> = OnceLock::new();
+
+/// 支持校验的文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationFormat {
+    Json,
+    /// 允许注释和尾随逗号的 JSON（tsconfig.json、.vscode/settings.json 等）
+    Jsonc,
+    Toml,
+    Yaml,
+    Xml,
+    Ini,
+    Env,
+}
+
+impl ValidationFormat {
+    /// 根据语言 ID 或文件路径确定格式，语言 ID 未指定时按路径检测
+    pub fn detect(path: Option<&str>, language_id: Option<&str>, content: &str) -> Option<Self> {
+        let file_name = path
+            .and_then(|p| Path::new(p).file_name())
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if file_name == ".env" || file_name.starts_with(".env.") {
+            return Some(ValidationFormat::Env);
+        }
+
+        let language = match language_id {
+            Some(language) => language.to_string(),
+            None => syntax::detect_language(path?, Some(content)),
+        };
+        match language.as_str() {
+            "json" if path.is_some_and(is_jsonc_file) => Some(ValidationFormat::Jsonc),
+            "json" => Some(ValidationFormat::Json),
+            "jsonc" => Some(ValidationFormat::Jsonc),
+            "toml" => Some(ValidationFormat::Toml),
+            "yaml" => Some(ValidationFormat::Yaml),
+            "xml" => Some(ValidationFormat::Xml),
+            "ini" => Some(ValidationFormat::Ini),
+            "dotenv" => Some(ValidationFormat::Env),
+            _ => None,
+        }
+    }
+}
+
+/// 约定使用 JSONC 的文件
+pub(crate) fn is_jsonc_file(path: &str) -> bool {
+    let normalized = path.replace('\\', "/").to_lowercase();
+    let file_name = normalized.rsplit('/').next().unwrap_or_default();
+    file_name.ends_with(".jsonc")
+        || file_name.starts_with("tsconfig")
+        || file_name.starts_with("jsconfig")
+        || file_name.starts_with(".eslintrc")
+        || file_name == "devcontainer.json"
+        || normalized.contains("/.vscode/")
+}
+
+/// 字节偏移与行列（行号从 1 开始，列号为 UTF-16 偏移加 1，与 Monaco 一致）之间的转换
+struct LineIndex<'a> {
+    text: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, starts }
+    }
+
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = floor_char_boundary(self.text, offset);
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let column = self.text[self.starts[line]..offset].encode_utf16().count() + 1;
+        (line + 1, column)
+    }
+
+    /// 行号从 1 开始，列号为从 1 开始的字节列
+    fn offset(&self, line: usize, byte_column: usize) -> usize {
+        let Some(&start) = self.starts.get(line.saturating_sub(1)) else {
+            return self.text.len();
+        };
+        let end = self.starts.get(line).map_or(self.text.len(), |&next| next - 1);
+        floor_char_boundary(self.text, (start + byte_column.saturating_sub(1)).min(end))
+    }
+
+    /// 行号从 1 开始，列号为从 0 开始的字符列
+    fn char_offset(&self, line: usize, char_column: usize) -> usize {
+        let Some(&start) = self.starts.get(line.saturating_sub(1)) else {
+            return self.text.len();
+        };
+        self.text[start..]
+            .char_indices()
+            .nth(char_column)
+            .map_or(self.text.len(), |(i, _)| start + i)
+    }
+
+    /// 范围截断到起始行末尾，避免整个对象被标记
+    fn first_line(&self, range: Range<usize>) -> Range<usize> {
+        let line_end = self.text[range.start..].find('\n').map_or(self.text.len(), |i| range.start + i);
+        range.start..range.end.min(line_end).max(range.start)
+    }
+}
+
+fn floor_char_boundary(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// 收集诊断信息
+struct Diagnostics<'a> {
+    index: LineIndex<'a>,
+    file: String,
+    problems: Vec<Problem>,
+}
+
+impl<'a> Diagnostics<'a> {
+    fn new(text: &'a str, file: &str) -> Self {
+        Self { index: LineIndex::new(text), file: file.to_string(), problems: Vec::new() }
+    }
+
+    fn push(
+        &mut self,
+        range: Range<usize>,
+        severity: Severity,
+        message: impl Into<String>,
+        code: Option<&str>,
+        source: &str,
+    ) {
+        let (line, column) = self.index.position(range.start);
+        let (end_line, end_column) = self.index.position(range.end);
+        self.problems.push(Problem {
+            file: self.file.clone(),
+            line,
+            column,
+            end_line: Some(end_line),
+            end_column: Some(end_column),
+            severity,
+            message: message.into(),
+            code: code.map(str::to_string),
+            source: source.to_string(),
+        });
+    }
+
+    fn error(&mut self, range: Range<usize>, message: impl Into<String>) {
+        self.push(range, Severity::Error, message, None, SYNTAX_SOURCE);
+    }
+
+    fn warning(&mut self, range: Range<usize>, message: impl Into<String>) {
+        self.push(range, Severity::Warning, message, None, SYNTAX_SOURCE);
+    }
+}
+
+/// 去掉 serde 错误信息末尾的 "at line X column Y"
+fn strip_position(message: &str) -> &str {
+    message.rsplit_once(" at line ").map_or(message, |(message, _)| message)
+}
+
+/// 校验文档，返回语法错误和 Schema 校验问题
+pub async fn validate_document(
+    content: &str,
+    path: Option<&str>,
+    language_id: Option<&str>,
+    workspace: Option<&str>,
+) -> Result<Vec<Problem>> {
+    let Some(format) = ValidationFormat::detect(path, language_id, content) else {
+        return Ok(Vec::new());
+    };
+    let associations = match format {
+        ValidationFormat::Json | ValidationFormat::Jsonc | ValidationFormat::Yaml => {
+            settings::get_settings().await?.json_schemas
+        }
+        _ => Vec::new(),
+    };
+
+    let content = content.to_string();
+    let path = path.map(str::to_string);
+    let workspace = workspace.map(str::to_string);
+    tokio::task::spawn_blocking(move || {
+        validate(&content, format, path.as_deref(), workspace.as_deref(), &associations)
+    })
+    .await
+    .map_err(|e| anyhow!("Validation task failed: {}", e))
+}
+
+fn validate(
+    content: &str,
+    format: ValidationFormat,
+    path: Option<&str>,
+    workspace: Option<&str>,
+    associations: &[SchemaAssociation],
+) -> Vec<Problem> {
+    let mut diagnostics = Diagnostics::new(content, path.unwrap_or_default());
+    match format {
+        ValidationFormat::Json | ValidationFormat::Jsonc => {
+            let stripped;
+            let text = if format == ValidationFormat::Jsonc {
+                stripped = strip_jsonc(content);
+                stripped.as_str()
+            } else {
+                content
+            };
+            if let Some(value) = check_json(text, &mut diagnostics) {
+                if let Some(reference) = find_schema_reference(content, format, &value, path, workspace, associations) {
+                    let mut spans = None;
+                    validate_schema(&reference, &without_schema_key(&value), &mut diagnostics, |pointer| {
+                        let spans = spans.get_or_insert_with(|| JsonWalker::walk(text, true).spans);
+                        spans.get(pointer).cloned()
+                    });
+                }
+            }
+        }
+        ValidationFormat::Yaml => {
+            let documents = check_yaml(content, &mut diagnostics);
+            let Some(first) = documents.first() else {
+                return diagnostics.problems;
+            };
+            if let Some(reference) = find_schema_reference(content, format, first, path, workspace, associations) {
+                let mut spans = None;
+                for (i, document) in documents.iter().enumerate() {
+                    validate_schema(&reference, &without_schema_key(document), &mut diagnostics, |pointer| {
+                        let spans = spans.get_or_insert_with(|| YamlSpans::collect(content));
+                        spans.get(i).and_then(|document| document.get(pointer)).cloned()
+                    });
+                }
+            }
+        }
+        ValidationFormat::Toml => check_toml(content, &mut diagnostics),
+        ValidationFormat::Xml => check_xml(content, &mut diagnostics),
+        ValidationFormat::Ini => check_ini(content, &mut diagnostics),
+        ValidationFormat::Env => check_env(content, &mut diagnostics),
+    }
+    diagnostics.problems
+}
+
+// ---------------------------------------------------------------------------
+// JSON / JSONC
+// ---------------------------------------------------------------------------
+
+fn check_json(text: &str, diagnostics: &mut Diagnostics) -> Option<Value> {
+    match serde_json::from_str::<Value>(text) {
+        Ok(value) => {
+            for (range, key) in JsonWalker::walk(text, false).duplicates {
+                diagnostics.warning(range, format!("Duplicate key '{}'", key));
+            }
+            Some(value)
+        }
+        Err(e) => {
+            let offset = diagnostics.index.offset(e.line(), e.column());
+            let end = diagnostics.index.text[offset..].chars().next().map_or(offset, |c| offset + c.len_utf8());
+            diagnostics.error(offset..end, strip_position(&e.to_string()));
+            None
+        }
+    }
+}
+
+/// 将 JSONC 中的注释和尾随逗号替换为空格，保持字节偏移不变
+pub(crate) fn strip_jsonc(text: &str) -> String {
+    let mut bytes = text.as_bytes().to_vec();
+    let mut i = 0;
+    let mut in_string = false;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if in_string {
+            match b {
+                b'\\' => i += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+        match (b, bytes.get(i + 1)) {
+            (b'"', _) => in_string = true,
+            (b'/', Some(b'/')) => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    bytes[i] = b' ';
+                    i += 1;
+                }
+                continue;
+            }
+            (b'/', Some(b'*')) => {
+                let end = text[i + 2..].find("*/").map_or(bytes.len(), |p| i + 2 + p + 2);
+                for byte in &mut bytes[i..end] {
+                    if !matches!(*byte, b'\n' | b'\r') {
+                        *byte = b' ';
+                    }
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    // 尾随逗号（注释已替换为空格，只需跳过空白）
+    let mut in_string = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => in_string = !in_string,
+            b',' if !in_string => {
+                let next = bytes[i + 1..].iter().find(|b| !b.is_ascii_whitespace());
+                if matches!(next, Some(b'}') | Some(b']')) {
+                    bytes[i] = b' ';
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    // 只替换了 ASCII 字节或完整的多字节字符
+    String::from_utf8(bytes).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+}
+
+fn escape_pointer_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// 遍历语法正确的 JSON 文本，记录每个 JSON Pointer 对应的位置（键名优先）和重复的键
+struct JsonWalker<'a> {
+    text: &'a str,
+    pos: usize,
+    record_spans: bool,
+    spans: HashMap<String, Range<usize>>,
+    duplicates: Vec<(Range<usize>, String)>,
+}
+
+impl<'a> JsonWalker<'a> {
+    fn walk(text: &'a str, record_spans: bool) -> Self {
+        let mut walker = Self { text, pos: 0, record_spans, spans: HashMap::new(), duplicates: Vec::new() };
+        walker.value(&mut String::new(), None);
+        walker
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn string(&mut self) -> Range<usize> {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(b) = self.peek() {
+            self.pos += if b == b'\\' { 2 } else { 1 };
+            if b == b'"' {
+                break;
+            }
+        }
+        start..self.pos.min(self.text.len())
+    }
+
+    fn value(&mut self, pointer: &mut String, key: Option<Range<usize>>) {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut seen = HashSet::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'"') => {
+                            let key_range = self.string();
+                            let name: String = serde_json::from_str(&self.text[key_range.clone()]).unwrap_or_default();
+                            if !seen.insert(name.clone()) {
+                                self.duplicates.push((key_range.clone(), name.clone()));
+                            }
+                            self.skip_whitespace();
+                            if self.peek() == Some(b':') {
+                                self.pos += 1;
+                            }
+                            let len = pointer.len();
+                            pointer.push('/');
+                            pointer.push_str(&escape_pointer_segment(&name));
+                            self.value(pointer, Some(key_range));
+                            pointer.truncate(len);
+                        }
+                        Some(b'}') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut index = 0;
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') | None => {
+                            self.pos += 1;
+                            break;
+                        }
+                        Some(_) => {
+                            let len = pointer.len();
+                            pointer.push_str(&format!("/{}", index));
+                            self.value(pointer, None);
+                            pointer.truncate(len);
+                            index += 1;
+                        }
+                    }
+                }
+            }
+            Some(b'"') => {
+                self.string();
+            }
+            _ => {
+                while self.peek().is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace()) {
+                    self.pos += 1;
+                }
+            }
+        }
+        if self.record_spans {
+            let end = self.pos.min(self.text.len());
+            self.spans.insert(pointer.clone(), key.unwrap_or(start..end));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// YAML
+// ---------------------------------------------------------------------------
+
+/// 解析所有文档并转换为 JSON 值用于 Schema 校验
+fn check_yaml(text: &str, diagnostics: &mut Diagnostics) -> Vec<Value> {
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(text) {
+        match serde_yaml::Value::deserialize(document) {
+            Ok(value) => documents.push(serde_json::to_value(&value).unwrap_or(Value::Null)),
+            Err(e) => {
+                let offset = e.location().map_or(0, |l| l.index());
+                let offset = floor_char_boundary(text, offset);
+                diagnostics.error(offset..offset, strip_position(&e.to_string()));
+                return Vec::new();
+            }
+        }
+    }
+    documents
+}
+
+enum YamlFrame {
+    Mapping { key: Option<(String, Range<usize>)> },
+    Sequence { next: usize },
+}
+
+/// 通过 YAML 事件流记录每个文档中 JSON Pointer 对应的位置
+struct YamlSpans<'a> {
+    index: LineIndex<'a>,
+    documents: Vec<HashMap<String, Range<usize>>>,
+    stack: Vec<YamlFrame>,
+    path: Vec<String>,
+}
+
+impl<'a> YamlSpans<'a> {
+    fn collect(text: &'a str) -> Vec<HashMap<String, Range<usize>>> {
+        let mut spans = Self { index: LineIndex::new(text), documents: Vec::new(), stack: Vec::new(), path: Vec::new() };
+        // 语法已由 serde_yaml 检查，解析失败时保留已记录的位置
+        let _ = yaml_rust2::parser::Parser::new_from_str(text).load(&mut spans, true);
+        spans.documents
+    }
+
+    fn pointer(&self, last: Option<&str>) -> String {
+        self.path
+            .iter()
+            .map(String::as_str)
+            .chain(last)
+            .map(|segment| format!("/{}", escape_pointer_segment(segment)))
+            .collect()
+    }
+
+    /// 记录一个节点，返回该节点在路径中的名称（根节点为 None）
+    fn node(&mut self, range: Range<usize>, scalar: Option<&str>) -> Option<String> {
+        let (segment, span) = match self.stack.last_mut() {
+            None => (None, range),
+            Some(YamlFrame::Sequence { next }) => {
+                *next += 1;
+                (Some((*next - 1).to_string()), range)
+            }
+            Some(YamlFrame::Mapping { key }) => match key.take() {
+                Some((name, key_range)) => (Some(name), key_range),
+                None => {
+                    // 当前节点是键
+                    let name = scalar.unwrap_or_default().to_string();
+                    *key = Some((name, range));
+                    return None;
+                }
+            },
+        };
+        let pointer = self.pointer(segment.as_deref());
+        if let Some(document) = self.documents.last_mut() {
+            document.insert(pointer, span);
+        }
+        segment
+    }
+
+    fn start_collection(&mut self, range: Range<usize>, frame: YamlFrame) {
+        let is_root = self.stack.is_empty();
+        let segment = self.node(range, None);
+        if !is_root {
+            self.path.push(segment.unwrap_or_default());
+        }
+        self.stack.push(frame);
+    }
+
+    fn end_collection(&mut self) {
+        self.stack.pop();
+        if !self.stack.is_empty() {
+            self.path.pop();
+        }
+    }
+}
+
+impl yaml_rust2::parser::MarkedEventReceiver for YamlSpans<'_> {
+    fn on_event(&mut self, event: yaml_rust2::parser::Event, mark: yaml_rust2::scanner::Marker) {
+        use yaml_rust2::parser::Event;
+        use yaml_rust2::scanner::TScalarStyle;
+
+        let start = self.index.char_offset(mark.line(), mark.col());
+        match event {
+            Event::DocumentStart => {
+                self.documents.push(HashMap::new());
+                self.stack.clear();
+                self.path.clear();
+            }
+            Event::Scalar(value, style, ..) => {
+                let len = match style {
+                    TScalarStyle::Plain => value.len(),
+                    TScalarStyle::SingleQuoted | TScalarStyle::DoubleQuoted => value.len() + 2,
+                    _ => 1,
+                };
+                let range = self.index.first_line(start..start + len);
+                self.node(range, Some(&value));
+            }
+            Event::Alias(_) => {
+                self.node(start..start + 1, Some(""));
+            }
+            Event::MappingStart(..) => self.start_collection(start..start + 1, YamlFrame::Mapping { key: None }),
+            Event::SequenceStart(..) => self.start_collection(start..start + 1, YamlFrame::Sequence { next: 0 }),
+            Event::MappingEnd | Event::SequenceEnd => self.end_collection(),
+            _ => {}
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TOML / XML / INI / .env
+// ---------------------------------------------------------------------------
+
+fn check_toml(text: &str, diagnostics: &mut Diagnostics) {
+    if let Err(e) = toml::from_str::<toml::Table>(text) {
+        let range = e.span().unwrap_or(0..0);
+        diagnostics.error(range, e.message().trim_end());
+    }
+}
+
+fn check_xml(text: &str, diagnostics: &mut Diagnostics) {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(text);
+    reader.config_mut().trim_text(false);
+
+    let mut open: Vec<(String, Range<usize>)> = Vec::new();
+    let mut roots = 0;
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(e) => {
+                let position = floor_char_boundary(text, reader.error_position() as usize);
+                diagnostics.error(position..position, e.to_string());
+                return;
+            }
+        };
+        let range = start..reader.buffer_position() as usize;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                if open.is_empty() {
+                    roots += 1;
+                    if roots == 2 {
+                        diagnostics.error(range.clone(), "Multiple root elements");
+                    }
+                }
+                for attr in e.attributes() {
+                    if let Err(err) = attr {
+                        diagnostics.error(range.clone(), err.to_string());
+                        break;
+                    }
+                }
+                if let Event::Start(e) = event {
+                    open.push((String::from_utf8_lossy(e.name().as_ref()).into_owned(), range));
+                }
+            }
+            Event::End(_) => {
+                open.pop();
+            }
+            Event::Text(t) => {
+                if open.is_empty() && !t.iter().all(u8::is_ascii_whitespace) {
+                    diagnostics.error(range, "Text is not allowed outside the root element");
+                } else if let Err(e) = t.unescape() {
+                    diagnostics.error(range, e.to_string());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    for (name, range) in open {
+        diagnostics.error(range, format!("Element <{}> is not closed", name));
+    }
+    if roots == 0 && !text.trim().is_empty() {
+        diagnostics.error(0..0, "Missing root element");
+    }
+}
+
+/// 按行遍历，返回每行的起始偏移和内容（不含换行符）
+fn lines_with_offsets(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line.trim_end_matches(['\n', '\r']))
+        })
+        .collect()
+}
+
+/// `part` 为 `line` 的子切片，返回其去掉首尾空白后在全文中的范围
+fn trimmed_range(line_start: usize, line: &str, part: &str) -> Range<usize> {
+    let leading = part.len() - part.trim_start().len();
+    let start = line_start + (part.as_ptr() as usize - line.as_ptr() as usize) + leading;
+    start..start + part.trim().len()
+}
+
+fn check_ini(text: &str, diagnostics: &mut Diagnostics) {
+    let mut section = String::new();
+    let mut sections = HashSet::new();
+    let mut keys = HashSet::new();
+    let mut in_entry = false;
+
+    for (start, line) in lines_with_offsets(text) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with(';') || trimmed.starts_with('#') {
+            continue;
+        }
+        let line_range = trimmed_range(start, line, line);
+
+        if trimmed.starts_with('[') {
+            in_entry = false;
+            let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) else {
+                diagnostics.error(line_range, "Missing ']' in section header");
+                continue;
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                diagnostics.error(line_range, "Empty section name");
+                continue;
+            }
+            section = name.to_string();
+            if !sections.insert(section.clone()) {
+                diagnostics.warning(line_range, format!("Duplicate section [{}]", section));
+            }
+            continue;
+        }
+
+        // 缩进的行视为上一个值的续行
+        if in_entry && line.starts_with([' ', '\t']) {
+            continue;
+        }
+
+        let Some(separator) = line.find(['=', ':']) else {
+            diagnostics.error(line_range, "Expected 'key = value' or '[section]'");
+            in_entry = false;
+            continue;
+        };
+        let key_part = &line[..separator];
+        let key = key_part.trim();
+        if key.is_empty() {
+            diagnostics.error(line_range, "Missing key name");
+            in_entry = false;
+            continue;
+        }
+        in_entry = true;
+        if !keys.insert((section.clone(), key.to_string())) {
+            let range = trimmed_range(start, line, key_part);
+            if section.is_empty() {
+                diagnostics.warning(range, format!("Duplicate key '{}'", key));
+            } else {
+                diagnostics.warning(range, format!("Duplicate key '{}' in section [{}]", key, section));
+            }
+        }
+    }
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// 查找未转义的结束引号（单引号内不处理转义）
+fn find_closing_quote(value: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i);
+        }
+    }
+    None
+}
+
+fn check_env(text: &str, diagnostics: &mut Diagnostics) {
+    let lines = lines_with_offsets(text);
+    let mut names = HashSet::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let (start, line) = lines[i];
+        i += 1;
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let line_range = trimmed_range(start, line, line);
+
+        let declaration = trimmed.strip_prefix("export ").map_or(trimmed, str::trim_start);
+        let Some(separator) = declaration.find('=') else {
+            diagnostics.error(line_range, "Expected 'KEY=VALUE'");
+            continue;
+        };
+        let name_part = &declaration[..separator];
+        let name = name_part.trim();
+        let name_range = trimmed_range(start, line, name_part);
+        if !is_env_name(name) {
+            diagnostics.error(name_range, format!("Invalid variable name '{}'", name));
+            continue;
+        }
+        if !names.insert(name.to_string()) {
+            diagnostics.warning(name_range.clone(), format!("Duplicate variable '{}'", name));
+        }
+
+        let value = declaration[separator + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|c| matches!(c, '"' | '\'' | '`')) else {
+            continue;
+        };
+        let quote_offset = start + (value.as_ptr() as usize - line.as_ptr() as usize);
+
+        // 引号内的值可以跨行
+        let mut rest = &value[1..];
+        let mut rest_line = (start, line);
+        loop {
+            if let Some(end) = find_closing_quote(rest, quote) {
+                let after = rest[end + 1..].trim();
+                if !after.is_empty() && !after.starts_with('#') {
+                    let (line_start, current) = rest_line;
+                    let range = trimmed_range(line_start, current, &rest[end + 1..]);
+                    diagnostics.error(range, "Unexpected characters after quoted value");
+                }
+                break;
+            }
+            match lines.get(i) {
+                Some(&(next_start, next_line)) => {
+                    i += 1;
+                    rest = next_line;
+                    rest_line = (next_start, next_line);
+                }
+                None => {
+                    diagnostics.error(quote_offset..quote_offset + 1, "Unterminated quoted value");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON Schema
+// ---------------------------------------------------------------------------
+
+/// Schema 引用及其在文档中的位置（用于报告 Schema 加载错误）
+struct SchemaReference {
+    location: String,
+    /// 相对路径的基准目录
+    base: Option<PathBuf>,
+    range: Range<usize>,
+}
+
+fn find_schema_reference(
+    text: &str,
+    format: ValidationFormat,
+    value: &Value,
+    path: Option<&str>,
+    workspace: Option<&str>,
+    associations: &[SchemaAssociation],
+) -> Option<SchemaReference> {
+    let document_dir = path.and_then(|p| Path::new(p).parent()).map(Path::to_path_buf);
+
+    // YAML 注释中的 `# yaml-language-server: $schema=<url>`
+    if format == ValidationFormat::Yaml {
+        const MODELINE: &str = "yaml-language-server:";
+        for (start, line) in lines_with_offsets(text) {
+            let Some(comment) = line.trim_start().strip_prefix('#') else {
+                continue;
+            };
+            let Some(rest) = comment.trim_start().strip_prefix(MODELINE) else {
+                continue;
+            };
+            if let Some(location) = rest.trim().strip_prefix("$schema=") {
+                return Some(SchemaReference {
+                    location: location.trim().to_string(),
+                    base: document_dir,
+                    range: trimmed_range(start, line, line),
+                });
+            }
+        }
+    }
+
+    if let Some(location) = value.get("$schema").and_then(Value::as_str) {
+        let range = text.find("$schema").map_or(0..0, |i| i..i + "$schema".len());
+        return Some(SchemaReference { location: location.to_string(), base: document_dir, range });
+    }
+
+    let path = path?;
+    let location = associations
+        .iter()
+        .find(|association| association.file_match.iter().any(|pattern| matches_file(pattern, path, workspace)))?
+        .url
+        .clone();
+    let base = match workspace {
+        Some(workspace) => Some(PathBuf::from(workspace)),
+        None => system_integration::get_config_dir().ok(),
+    };
+    Some(SchemaReference { location, base, range: 0..0 })
+}
+
+/// 不含 `/` 的模式匹配文件名，否则匹配相对工作区的路径
+fn matches_file(pattern: &str, path: &str, workspace: Option<&str>) -> bool {
+    let Ok(glob) = globset::GlobBuilder::new(pattern.trim_start_matches('/'))
+        .case_insensitive(cfg!(windows))
+        .literal_separator(true)
+        .build()
+    else {
+        return false;
+    };
+    let matcher = glob.compile_matcher();
+    let path = Path::new(path);
+
+    if !pattern.contains('/') {
+        return path.file_name().is_some_and(|name| matcher.is_match(name));
+    }
+    let relative = workspace.and_then(|w| path.strip_prefix(w).ok()).unwrap_or(path);
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    matcher.is_match(relative.trim_start_matches('/'))
+}
+
+/// 去掉顶层的 `$schema` 键：它用于声明 Schema，不属于文档数据，
+/// 否则 `additionalProperties: false` 时会被报告为多余属性
+fn without_schema_key(value: &Value) -> Cow<'_, Value> {
+    match value {
+        Value::Object(map) if map.contains_key("$schema") => {
+            let mut map = map.clone();
+            map.shift_remove("$schema");
+            Cow::Owned(Value::Object(map))
+        }
+        _ => Cow::Borrowed(value),
+    }
+}
+
+fn resolve_schema_path(reference: &SchemaReference) -> Result<PathBuf> {
+    let location = reference.location.as_str();
+    if location.starts_with("http://") || location.starts_with("https://") {
+        anyhow::bail!(
+            "Remote schema '{}' is not downloaded; save it locally and reference it by path",
+            location
+        );
+    }
+    if location.starts_with("file://") {
+        return lsp::uri_to_path(location)
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow!("Invalid schema URI '{}'", location));
+    }
+    let path = Path::new(location);
+    Ok(match &reference.base {
+        Some(base) if path.is_relative() => base.join(path),
+        _ => path.to_path_buf(),
+    })
+}
+
+fn parse_schema_file(path: &Path) -> Result<Value> {
+    let content = std::fs::read_to_string(path)?;
+    let is_yaml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+    if is_yaml {
+        Ok(serde_yaml::from_str(&content)?)
+    } else {
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+/// 从本地文件加载 $ref 引用的 Schema
+struct FileRetriever;
+
+impl jsonschema::Retrieve for FileRetriever {
+    fn retrieve(
+        &self,
+        uri: &jsonschema::Uri<String>,
+    ) -> std::result::Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let path = lsp::uri_to_path(uri.as_str())
+            .ok_or_else(|| format!("Cannot load remote schema '{}'", uri.as_str()))?;
+        Ok(parse_schema_file(Path::new(&path))?)
+    }
+}
+
+fn load_validator(path: &Path) -> Result<Arc<jsonschema::Validator>> {
+    let path = std::fs::canonicalize(path)
+        .map_err(|e| anyhow!("Cannot read schema '{}': {}", path.display(), e))?;
+    let modified = std::fs::metadata(&path)?.modified()?;
+    let cache = SCHEMAS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some((cached_at, validator)) = cache.lock().unwrap().get(&path) {
+        if *cached_at == modified {
+            return Ok(validator.clone());
+        }
+    }
+
+    let schema = parse_schema_file(&path).map_err(|e| anyhow!("Cannot parse schema '{}': {}", path.display(), e))?;
+    let validator = jsonschema::options()
+        .with_base_uri(lsp::path_to_uri(&path.to_string_lossy()))
+        .with_retriever(FileRetriever)
+        .build(&schema)
+        .map_err(|e| anyhow!("Invalid schema '{}': {}", path.display(), e))?;
+    let validator = Arc::new(validator);
+    cache.lock().unwrap().insert(path, (modified, validator.clone()));
+    Ok(validator)
+}
+
+fn validate_schema(
+    reference: &SchemaReference,
+    instance: &Value,
+    diagnostics: &mut Diagnostics,
+    mut locate: impl FnMut(&str) -> Option<Range<usize>>,
+) {
+    let validator = match resolve_schema_path(reference).and_then(|path| load_validator(&path)) {
+        Ok(validator) => validator,
+        Err(e) => {
+            diagnostics.push(reference.range.clone(), Severity::Info, e.to_string(), None, SCHEMA_SOURCE);
+            return;
+        }
+    };
+
+    for error in validator.iter_errors(instance) {
+        let mut pointer = error.instance_path.as_str().to_string();
+        // 多余的属性标记在属性名上
+        if let jsonschema::error::ValidationErrorKind::AdditionalProperties { unexpected } = &error.kind {
+            if let Some(first) = unexpected.first() {
+                pointer = format!("{}/{}", pointer, escape_pointer_segment(first));
+            }
+        }
+        let range = locate(&pointer)
+            .or_else(|| locate(error.instance_path.as_str()))
+            .map(|range| diagnostics.index.first_line(range))
+            .unwrap_or(0..0);
+        let keyword = error.schema_path.as_str().rsplit('/').next().unwrap_or_default().to_string();
+        diagnostics.push(range, Severity::Warning, error.to_string(), Some(&keyword), SCHEMA_SOURCE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(content: &str, format: ValidationFormat) -> Vec<(usize, usize, String)> {
+        validate(content, format, None, None, &[])
+            .into_iter()
+            .map(|p| (p.line, p.column, p.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_syntax_error_positions() {
+        let problems = check("{\n  \"a\": 1,\n  \"b\": ,\n}", ValidationFormat::Json);
+        assert_eq!(problems.len(), 1);
+        assert_eq!((problems[0].0, problems[0].1), (3, 8));
+
+        assert!(check("{\n  // comment\n  \"a\": [1, 2,],\n}\n", ValidationFormat::Jsonc).is_empty());
+        let problems = check("{\"a\": 1, \"a\": 2}", ValidationFormat::Json);
+        assert_eq!(problems, vec![(1, 10, "Duplicate key 'a'".to_string())]);
+
+        let problems = check("[server]\nport = \n", ValidationFormat::Toml);
+        assert_eq!(problems[0].0, 2);
+
+        let problems = check("a: 1\nb:\n  - x\n c: 2\n", ValidationFormat::Yaml);
+        assert_eq!(problems.len(), 1);
+        assert!(!problems[0].2.contains("at line"));
+
+        let problems = check("<root>\n  <a>text</b>\n</root>", ValidationFormat::Xml);
+        assert_eq!(problems[0].0, 2);
+        let problems = check("<root>\n  <a>\n</root>", ValidationFormat::Xml);
+        assert_eq!(problems.len(), 1);
+        let problems = check("<root>\n  <a>", ValidationFormat::Xml);
+        assert_eq!(problems.len(), 2);
+
+        let problems = check("[db\nhost = x\nhost = y\nnonsense\n", ValidationFormat::Ini);
+        assert_eq!(problems.iter().map(|p| p.0).collect::<Vec<_>>(), vec![1, 3, 4]);
+
+        let problems = check("export A=1\n1B=2\nC=\"multi\nline\"\nD='x' y\nA=2\nE=\"open\n", ValidationFormat::Env);
+        assert_eq!(problems.iter().map(|p| (p.0, p.1)).collect::<Vec<_>>(), vec![(2, 1), (5, 7), (6, 1), (7, 3)]);
+    }
+
+    #[test]
+    fn test_schema_validation() {
+        let dir = std::env::temp_dir().join(format!("kaironotes-validation-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("defs.json"), r#"{"type": "integer", "minimum": 1}"#).unwrap();
+        std::fs::write(
+            dir.join("schema.json"),
+            r#"{"type": "object", "properties": {"name": {"type": "string"}, "port": {"$ref": "defs.json"},
+                "items": {"type": "array", "items": {"type": "string"}}}, "additionalProperties": false}"#,
+        )
+        .unwrap();
+        let dir_str = dir.to_string_lossy().to_string();
+        let file = dir.join("config.json").to_string_lossy().to_string();
+
+        let json = "{\n  \"$schema\": \"./schema.json\",\n  \"name\": 1,\n  \"port\": 0,\n  \"extra\": true\n}";
+        let mut problems = validate(json, ValidationFormat::Json, Some(&file), None, &[]);
+        problems.sort_by_key(|p| p.line);
+        // 顶层 $schema 不算额外属性
+        let lines: Vec<_> = problems.iter().map(|p| (p.line, p.column, p.code.clone().unwrap())).collect();
+        assert_eq!(
+            lines,
+            vec![
+                (3, 3, "type".to_string()),
+                (4, 3, "minimum".to_string()),
+                (5, 3, "additionalProperties".to_string()),
+            ]
+        );
+
+        let associations = vec![SchemaAssociation { file_match: vec!["*.app.yaml".to_string()], url: "schema.json".to_string() }];
+        let file = dir.join("deploy.app.yaml").to_string_lossy().to_string();
+        let yaml = "name: ok\nitems:\n  - a\n  - 2\n---\nport: -1\n";
+        let problems = validate(yaml, ValidationFormat::Yaml, Some(&file), Some(&dir_str), &associations);
+        let lines: Vec<_> = problems.iter().map(|p| (p.line, p.column, p.end_column)).collect();
+        assert_eq!(lines, vec![(4, 5, Some(6)), (6, 1, Some(5))]);
+
+        let problems = validate("$schema: https://example.com/s.json\n", ValidationFormat::Yaml, Some(&file), None, &[]);
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(problems[0].severity, Severity::Info));
+
+        assert!(matches_file(".github/workflows/*.yml", "/repo/.github/workflows/ci.yml", Some("/repo")));
+        assert!(!matches_file("*.yml", "/repo/ci.yaml", Some("/repo")));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -37,10 +37,28 @@ function App() {
   const shouldShowSidebar = settings.showSidebar && openFolder && !isExtremeMode;
 
   useEffect(() => {
+    // 监听从命令行/右键菜单/其他实例转发的打开文件事件
+    const unlisten = listen<string>('open-file', (event) => {
+      console.log('Received open-file event:', event.payload);
+      const { openFile } = useStore.getState();
+      openFile(event.payload);
+    });
+
     const init = async () => {
       await loadSettings();
       if (settings.restoreWindows) {
-        restoreSession();
+        await restoreSession();
+      }
+      // 监听就绪后通知后端，取走启动期间排队的文件
+      try {
+        await unlisten;
+        const pending = await invoke<string[]>('frontend_ready');
+        const { openFile } = useStore.getState();
+        for (const path of pending) {
+          await openFile(path);
+        }
+      } catch (e) {
+        console.error('Failed to open pending files:', e);
       }
       // 前端加载完成后，通知后端显示窗口
       try {
@@ -50,16 +68,7 @@ function App() {
       }
     };
     init();
-  }, []);
 
-  // 监听从命令行/右键菜单打开文件的事件
-  useEffect(() => {
-    const unlisten = listen<string>('open-file', (event) => {
-      console.log('Received open-file event:', event.payload);
-      const { openFile } = useStore.getState();
-      openFile(event.payload);
-    });
-    
     return () => {
       unlisten.then(fn => fn());
     };
@@ -92,14 +101,32 @@ function App() {
     return () => window.removeEventListener('beforeunload', handleBeforeUnload);
   }, []);
 
+  // 为打开的文件夹建立文件索引（用于 Ctrl+P 快速打开）和全文索引（用于侧边栏搜索）
+  useEffect(() => {
+    if (!openFolder) return;
+    invoke('open_file_index', { root: openFolder }).catch((error) => {
+      console.error('Failed to open file index:', error);
+    });
+    invoke('open_full_text_index', { root: openFolder }).catch((error) => {
+      console.error('Failed to open full text index:', error);
+    });
+    return () => {
+      invoke('close_file_index', { root: openFolder }).catch(() => {});
+      invoke('close_full_text_index', { root: openFolder }).catch(() => {});
+    };
+  }, [openFolder]);
+
   // Global keyboard shortcuts
   useEffect(() => {
     const handleKeyDown = (e: KeyboardEvent) => {
-      const { toggleCommandPalette, toggleSearch, toggleTerminal, toggleSettings } = useStore.getState();
+      const { toggleCommandPalette, toggleQuickOpen, toggleSearch, toggleTerminal, toggleSettings } = useStore.getState();
       
       if (e.ctrlKey && e.shiftKey && e.key === 'P') {
         e.preventDefault();
         toggleCommandPalette();
+      } else if (e.ctrlKey && !e.shiftKey && e.key === 'p') {
+        e.preventDefault();
+        toggleQuickOpen();
       } else if (e.ctrlKey && e.key === 'f') {
         e.preventDefault();
         toggleSearch();
@@ -222,7 +249,7 @@ function App() {
             </div>
           </div>
           <AnimatePresence>
-            {showTerminal && <Terminal />}
+            <Terminal />
           </AnimatePresence>
         </div>
         <AnimatePresence>
@@ -1,10 +1,11 @@
 import { useState, useEffect, useRef } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
 import { useTranslation } from 'react-i18next';
+import { invoke } from '@tauri-apps/api/core';
 import { useStore } from '../store';
 import { 
   File, FolderOpen, Save, Settings, Search, 
-  Moon, Sun, X
+  Moon, Sun, X, Play, FileText
 } from 'lucide-react';
 import '../styles/CommandPalette.css';
 
@@ -14,20 +15,112 @@ interface Command {
   icon: React.ReactNode;
   action: () => void;
   shortcut?: string;
+  labelNode?: React.ReactNode;
+}
+
+interface TaskDefinition {
+  label: string;
+  group?: string;
+}
+
+interface FileMatch {
+  path: string;
+  score: number;
+  positions: number[];
+}
+
+interface FileSearchResult {
+  matches: FileMatch[];
+  total: number;
+  indexing: boolean;
+}
+
+// 高亮匹配的字符（positions 为 Unicode 字符索引）
+function highlightPath(path: string, positions: number[]) {
+  const marked = new Set(positions);
+  return Array.from(path).map((ch, i) =>
+    marked.has(i) ? <mark key={i} className="command-match">{ch}</mark> : ch
+  );
 }
 
 export function CommandPalette() {
   const { t } = useTranslation();
   const {
-    showCommandPalette, toggleCommandPalette,
+    showCommandPalette, toggleCommandPalette, quickOpen,
     createTab, openFile, saveFile, toggleSearch, toggleSettings,
-    theme, setTheme
+    theme, setTheme, openFolder, showTerminal, toggleTerminal
   } = useStore();
   
   const [query, setQuery] = useState('');
   const [selectedIndex, setSelectedIndex] = useState(0);
+  const [tasks, setTasks] = useState<TaskDefinition[]>([]);
+  const [files, setFiles] = useState<FileMatch[]>([]);
   const inputRef = useRef<HTMLInputElement>(null);
 
+  // 打开命令面板时加载当前文件夹的任务
+  useEffect(() => {
+    if (!showCommandPalette || !openFolder) {
+      setTasks([]);
+      return;
+    }
+    invoke<TaskDefinition[]>('list_tasks', { folder: openFolder })
+      .then(setTasks)
+      .catch((error) => {
+        console.error('Failed to load tasks:', error);
+        setTasks([]);
+      });
+  }, [showCommandPalette, openFolder]);
+
+  // 快速打开模式：输入 '>' 前缀切换到命令
+  const fileMode = quickOpen && !!openFolder && !query.startsWith('>');
+
+  useEffect(() => {
+    if (!showCommandPalette || !fileMode || !openFolder) {
+      setFiles([]);
+      return;
+    }
+    let cancelled = false;
+    invoke<FileSearchResult>('find_files', { root: openFolder, query, limit: 50 })
+      .then((result) => {
+        if (!cancelled) setFiles(result.matches);
+      })
+      .catch((error) => {
+        console.error('Failed to find files:', error);
+        if (!cancelled) setFiles([]);
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [showCommandPalette, fileMode, openFolder, query]);
+
+  const openWorkspaceFile = (path: string) => {
+    if (!openFolder) return;
+    const separator = openFolder.includes('\\') ? '\\' : '/';
+    const root = openFolder.replace(/[/\\]$/, '');
+    openFile(root + separator + path.split('/').join(separator));
+  };
+
+  const runTask = (label: string) => {
+    if (!openFolder) return;
+    if (!showTerminal) toggleTerminal();
+    invoke('run_task', { folder: openFolder, label }).catch((error) => {
+      console.error('Failed to run task:', error);
+    });
+  };
+
+  // 运行分组中的任务（后端取该组定义顺序中的第一个）
+  const runGroupTask = (group: string) => {
+    if (!openFolder) return;
+    if (!useStore.getState().showTerminal) toggleTerminal();
+    invoke('run_group_task', { folder: openFolder, group }).catch((error) => {
+      console.error('Failed to run task group:', error);
+    });
+  };
+
+  const taskGroups = Array.from(
+    new Set(tasks.map(task => task.group?.toLowerCase()).filter((group): group is string => !!group))
+  );
+
   const commands: Command[] = [
     {
       id: 'new-file',
@@ -70,11 +163,33 @@ export function CommandPalette() {
       icon: theme === 'dark' ? <Sun size={16} /> : <Moon size={16} />,
       action: () => setTheme(theme === 'dark' ? 'light' : 'dark'),
     },
+    ...taskGroups.map(group => ({
+      id: `task-group-${group}`,
+      label: t('tasks.runGroup', { group }),
+      icon: <Play size={16} />,
+      action: () => runGroupTask(group),
+      shortcut: group === 'build' ? 'Ctrl+Shift+B' : undefined,
+    })),
+    ...tasks.map(task => ({
+      id: `task-${task.label}`,
+      label: task.group
+        ? `${t('tasks.run')}: ${task.label} (${task.group})`
+        : `${t('tasks.run')}: ${task.label}`,
+      icon: <Play size={16} />,
+      action: () => runTask(task.label),
+    })),
   ];
 
-  const filteredCommands = commands.filter(cmd =>
-    cmd.label.toLowerCase().includes(query.toLowerCase())
-  );
+  const commandQuery = query.replace(/^>\s*/, '').toLowerCase();
+  const filteredCommands: Command[] = fileMode
+    ? files.map(file => ({
+        id: `file-${file.path}`,
+        label: file.path,
+        labelNode: highlightPath(file.path, file.positions),
+        icon: <FileText size={16} />,
+        action: () => openWorkspaceFile(file.path),
+      }))
+    : commands.filter(cmd => cmd.label.toLowerCase().includes(commandQuery));
 
   useEffect(() => {
     if (showCommandPalette && inputRef.current) {
@@ -89,12 +204,16 @@ export function CommandPalette() {
       if ((e.ctrlKey || e.metaKey) && e.shiftKey && e.key === 'p') {
         e.preventDefault();
         toggleCommandPalette();
+      } else if ((e.ctrlKey || e.metaKey) && e.shiftKey && e.key.toLowerCase() === 'b' && openFolder) {
+        // 运行构建任务
+        e.preventDefault();
+        runGroupTask('build');
       }
     };
     
     window.addEventListener('keydown', handleKeyDown);
     return () => window.removeEventListener('keydown', handleKeyDown);
-  }, [toggleCommandPalette]);
+  }, [toggleCommandPalette, openFolder]);
 
   const handleKeyDown = (e: React.KeyboardEvent) => {
     if (e.key === 'Escape') {
@@ -143,7 +262,7 @@ export function CommandPalette() {
                 ref={inputRef}
                 type="text"
                 className="command-input"
-                placeholder={t('view.commandPalette')}
+                placeholder={quickOpen && openFolder ? t('go.goToFile') : t('view.commandPalette')}
                 value={query}
                 onChange={(e) => {
                   setQuery(e.target.value);
@@ -165,7 +284,7 @@ export function CommandPalette() {
                   onMouseEnter={() => setSelectedIndex(index)}
                 >
                   <span className="command-icon">{cmd.icon}</span>
-                  <span className="command-label">{cmd.label}</span>
+                  <span className="command-label">{cmd.labelNode ?? cmd.label}</span>
                   {cmd.shortcut && (
                     <span className="command-shortcut">{cmd.shortcut}</span>
                   )}
@@ -1,7 +1,12 @@
 import { useEffect, useRef, useCallback } from 'react';
 import MonacoEditor, { OnMount, OnChange } from '@monaco-editor/react';
-import type { editor } from 'monaco-editor';
-import { useStore } from '../store';
+import type { editor, IDisposable } from 'monaco-editor';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useStore, syncDocument } from '../store';
+import { LargeFileView } from './LargeFileView';
+import { LogFollowView } from './LogFollowView';
+import { HexView } from './HexView';
 import { useTranslation } from 'react-i18next';
 import '../styles/Editor.css';
 
@@ -144,17 +149,137 @@ function normalizeLanguage(language: string): string {
   return language;
 }
 
+// 后端问题匹配器产生的诊断信息
+interface Problem {
+  file: string;
+  line: number;
+  column: number;
+  endLine?: number;
+  endColumn?: number;
+  severity: 'error' | 'warning' | 'info' | 'hint';
+  message: string;
+  code?: string;
+  source: string;
+}
+
+// 严重级别到 Monaco MarkerSeverity 的映射
+const problemSeverity: Record<Problem['severity'], number> = {
+  error: 8,
+  warning: 4,
+  info: 2,
+  hint: 1,
+};
+
+// 统一路径分隔符；只有 Windows 路径（盘符开头）按不区分大小写比较，其他平台保持原样
+const normalizePath = (path: string) => {
+  const normalized = path.replace(/\\/g, '/');
+  return /^[a-zA-Z]:\//.test(normalized) ? normalized.toLowerCase() : normalized;
+};
+
+// LSP 诊断级别（1 错误 ~ 4 提示）对应的 Monaco 标记级别
+const lspSeverity: Record<number, number> = { 1: 8, 2: 4, 3: 2, 4: 1 };
+
+// LSP CompletionItemKind（从 1 开始）对应的 Monaco 补全类型名称
+const lspCompletionKinds = [
+  'Text', 'Method', 'Function', 'Constructor', 'Field', 'Variable', 'Class', 'Interface',
+  'Module', 'Property', 'Unit', 'Value', 'Enum', 'Keyword', 'Snippet', 'Color', 'File',
+  'Reference', 'Folder', 'EnumMember', 'Constant', 'Struct', 'Event', 'Operator', 'TypeParameter',
+] as const;
+
+// 补全触发字符，服务器不处理的字符只会返回空结果
+const lspTriggerCharacters = ['.', ':', '>', '<', '/', '@', '"', "'"];
+
+// 打开文档失败（服务器未安装或启动失败）后的重试间隔
+const LSP_RETRY_MS = 30_000;
+
+interface LspRange {
+  start: { line: number; character: number };
+  end: { line: number; character: number };
+}
+
+interface LspDiagnostic {
+  range: LspRange;
+  severity?: number;
+  code?: string | number;
+  source?: string;
+  message: string;
+}
+
+interface LspDiagnosticsEvent {
+  serverId: string;
+  path: string | null;
+  diagnostics: LspDiagnostic[];
+}
+
+interface LspCompletionItem {
+  label: string;
+  kind?: number;
+  detail?: string;
+  documentation?: LspMarkup;
+  insertText?: string;
+  insertTextFormat?: number;
+  textEdit?: { newText: string; range?: LspRange; insert?: LspRange };
+  sortText?: string;
+  filterText?: string;
+  preselect?: boolean;
+}
+
+type LspMarkup = string | { kind?: string; language?: string; value: string };
+
+// LSP 位置从 0 开始，列为 UTF-16 偏移，与 Monaco 的列一致
+const toMonacoRange = (range: LspRange) => ({
+  startLineNumber: range.start.line + 1,
+  startColumn: range.start.character + 1,
+  endLineNumber: range.end.line + 1,
+  endColumn: range.end.character + 1,
+});
+
+// MarkedString / MarkupContent 转为 Markdown
+const lspMarkdown = (content: LspMarkup): string => {
+  if (typeof content === 'string') return content;
+  if (content.language) return '```' + content.language + '\n' + content.value + '\n```';
+  return content.value;
+};
+
+// 支持语法和 Schema 校验的语言
+// 编辑器模型与后端文档模型的同步状态
+interface BackendDocument {
+  model: editor.ITextModel;
+  // 最近一次编辑的版本号（按标签页递增，编辑器重新挂载后继续）
+  version: number;
+  // 后端已包含的版本号：发送全文后跳过之前排队的增量编辑
+  synced: number;
+  // 编辑器卸载时的内容，切换回来时用于判断是否需要重新发送全文
+  content?: string;
+}
+
+const validatedLanguages = new Set(['json', 'jsonc', 'toml', 'yaml', 'xml', 'ini', 'dotenv']);
+
 export function Editor() {
   const { t } = useTranslation();
   const { 
     tabs, activeTabId, updateTabContent, updateCursorPosition,
     settings, theme, createTab, openFile, saveFile, toggleSearch,
-    setEditorInstance
+    setEditorInstance, openFolder
   } = useStore();
   
   const editorRef = useRef<editor.IStandaloneCodeEditor | null>(null);
   const monacoRef = useRef<typeof import('monaco-editor') | null>(null);
+  // 已发送给语言服务器的文档：路径 -> 最近一次发送的内容
+  const lspDocumentsRef = useRef<Map<string, string>>(new Map());
+  // 打开失败的文档下次可重试的时间
+  const lspRetryRef = useRef<Map<string, number>>(new Map());
+  // 语言服务器诊断（按规范化路径）
+  const lspDiagnosticsRef = useRef<Map<string, LspDiagnostic[]>>(new Map());
+  const lspProvidersRef = useRef<IDisposable[] | null>(null);
+  const activePathRef = useRef<string | null>(null);
+  // 已在后端打开文档模型的标签页
+  const documentsRef = useRef<Map<string, BackendDocument>>(new Map());
+  // 以大文件模式打开的文件
+  const largeFilesRef = useRef<Set<string>>(new Set());
+  const hexDocumentsRef = useRef<Set<string>>(new Set());
   const activeTab = tabs.find(tab => tab.id === activeTabId);
+  activePathRef.current = activeTab?.path ?? null;
 
 
 
@@ -182,6 +307,103 @@ export function Editor() {
   // 调试日志
   console.log('Editor language:', currentLanguage, 'for file:', activeTab?.name, 'backend:', activeTab?.language);
 
+  // 将当前文件的语言服务器诊断显示为标记
+  const applyLspMarkers = () => {
+    const model = editorRef.current?.getModel();
+    const monaco = monacoRef.current;
+    if (!model || !monaco) return;
+    const path = activePathRef.current;
+    const diagnostics = path ? lspDiagnosticsRef.current.get(normalizePath(path)) ?? [] : [];
+    monaco.editor.setModelMarkers(model, 'lsp', diagnostics.map(d => ({
+      ...toMonacoRange(d.range),
+      severity: lspSeverity[d.severity ?? 1] ?? 8,
+      message: d.message,
+      code: d.code === undefined ? undefined : String(d.code),
+      source: d.source,
+    })));
+  };
+
+  // 补全和悬停前先发送防抖中尚未同步的内容，返回可查询的文档路径
+  const syncLspDocument = async (model: editor.ITextModel): Promise<string | null> => {
+    const path = activePathRef.current;
+    if (!path || editorRef.current?.getModel() !== model) return null;
+    const sent = lspDocumentsRef.current.get(path);
+    if (sent === undefined) return null;
+    const text = model.getValue();
+    if (sent !== text) {
+      lspDocumentsRef.current.set(path, text);
+      await invoke('lsp_change_document', { path, text }).catch(() => {});
+    }
+    return path;
+  };
+
+  // 注册由语言服务器提供的补全和悬停，所有标签页共用
+  const registerLspProviders = (monaco: typeof import('monaco-editor')) => {
+    if (lspProvidersRef.current) return;
+    lspProvidersRef.current = [
+      monaco.languages.registerCompletionItemProvider('*', {
+        triggerCharacters: lspTriggerCharacters,
+        provideCompletionItems: async (model, position, context) => {
+          const path = await syncLspDocument(model);
+          if (!path) return undefined;
+          const result = await invoke<LspCompletionItem[] | { isIncomplete?: boolean; items?: LspCompletionItem[] } | null>('lsp_completion', {
+            path,
+            line: position.lineNumber - 1,
+            character: position.column - 1,
+            triggerCharacter: context.triggerCharacter ?? null,
+          }).catch(() => null);
+          if (!result) return undefined;
+          const items = Array.isArray(result) ? result : result.items ?? [];
+          const word = model.getWordUntilPosition(position);
+          const wordRange = {
+            startLineNumber: position.lineNumber,
+            startColumn: word.startColumn,
+            endLineNumber: position.lineNumber,
+            endColumn: word.endColumn,
+          };
+          return {
+            incomplete: !Array.isArray(result) && !!result.isIncomplete,
+            suggestions: items.map((item) => {
+              const edit = item.textEdit;
+              const editRange: LspRange | undefined = edit?.range ?? edit?.insert;
+              const kindName = lspCompletionKinds[(item.kind ?? 1) - 1] ?? 'Text';
+              return {
+                label: item.label,
+                kind: monaco.languages.CompletionItemKind[kindName],
+                detail: item.detail,
+                documentation: item.documentation ? { value: lspMarkdown(item.documentation) } : undefined,
+                insertText: edit?.newText ?? item.insertText ?? item.label,
+                insertTextRules: item.insertTextFormat === 2
+                  ? monaco.languages.CompletionItemInsertTextRule.InsertAsSnippet
+                  : undefined,
+                range: editRange ? toMonacoRange(editRange) : wordRange,
+                sortText: item.sortText,
+                filterText: item.filterText,
+                preselect: item.preselect,
+              };
+            }),
+          };
+        },
+      }),
+      monaco.languages.registerHoverProvider('*', {
+        provideHover: async (model, position) => {
+          const path = await syncLspDocument(model);
+          if (!path) return undefined;
+          const result = await invoke<{ contents?: LspMarkup | LspMarkup[]; range?: LspRange } | null>(
+            'lsp_hover',
+            { path, line: position.lineNumber - 1, character: position.column - 1 },
+          ).catch(() => null);
+          if (!result?.contents) return undefined;
+          const contents = (Array.isArray(result.contents) ? result.contents : [result.contents])
+            .map(content => ({ value: lspMarkdown(content) }))
+            .filter(content => content.value.trim());
+          if (contents.length === 0) return undefined;
+          return { contents, range: result.range ? toMonacoRange(result.range) : undefined };
+        },
+      }),
+    ];
+  };
+
   const handleEditorMount: OnMount = useCallback((editor, monaco) => {
     editorRef.current = editor;
     monacoRef.current = monaco;
@@ -199,6 +421,52 @@ export function Editor() {
       console.log('Editor mounted with language:', lang);
     }
     
+    // 后端文档模型按标签页保留：首次挂载或切换回来时内容有变化才发送全文，之后只发送增量编辑；
+    // 版本不一致时重新发送全文
+    if (model && activeTabId) {
+      const tabId = activeTabId;
+      const existing = documentsRef.current.get(tabId);
+      const backend: BackendDocument = existing ?? { model, version: 0, synced: 0 };
+      const unchanged = existing?.content !== undefined && existing.content === model.getValue();
+      backend.model = model;
+      backend.content = undefined;
+      documentsRef.current.set(tabId, backend);
+      const openDocument = () => {
+        if (documentsRef.current.get(tabId) !== backend) return Promise.resolve();
+        if (backend.model.isDisposed()) {
+          // 切换回来时再发送全文
+          backend.content = undefined;
+          return Promise.resolve();
+        }
+        backend.synced = backend.version;
+        return invoke('open_document', {
+          tabId,
+          content: backend.model.getValue(),
+          version: backend.version,
+          path: activeTab?.path ?? null,
+          language: activeTab?.path ? null : activeTab?.language ?? null,
+        });
+      };
+      if (!unchanged) {
+        syncDocument(tabId, openDocument);
+      }
+      model.onDidChangeContent((e) => {
+        const baseVersion = backend.version;
+        const version = ++backend.version;
+        syncDocument(tabId, () => {
+          // 已包含在之后发送的全文中
+          if (version <= backend.synced) return Promise.resolve();
+          return invoke('apply_document_changes', { tabId, baseVersion, version, changes: e.changes })
+            .catch(openDocument);
+        });
+      });
+      model.onWillDispose(() => {
+        if (backend.model === model) {
+          backend.content = model.getValue();
+        }
+      });
+    }
+    
     // 强制布局更新
     setTimeout(() => {
       editor.layout();
@@ -221,6 +489,34 @@ export function Editor() {
       toggleSearch();
     });
     
+    // F8 / Shift+F8 跳转到下一个 / 上一个问题
+    const gotoProblem = async (reverse: boolean) => {
+      const state = useStore.getState();
+      const tab = state.tabs.find(t => t.id === state.activeTabId);
+      const position = editor.getPosition();
+      const problem = await invoke<Problem | null>('next_problem', {
+        path: tab?.path ?? null,
+        line: position?.lineNumber ?? 1,
+        column: position?.column ?? 1,
+        reverse,
+      });
+      if (!problem) return;
+      if (!tab?.path || normalizePath(tab.path) !== normalizePath(problem.file)) {
+        await state.openFile(problem.file);
+      }
+      const target = editorRef.current;
+      if (target) {
+        target.setPosition({ lineNumber: problem.line, column: problem.column });
+        target.revealLineInCenter(problem.line);
+        target.focus();
+      }
+    };
+    editor.addCommand(monaco.KeyCode.F8, () => { gotoProblem(false); });
+    editor.addCommand(monaco.KeyMod.Shift | monaco.KeyCode.F8, () => { gotoProblem(true); });
+    
+    registerLspProviders(monaco);
+    applyLspMarkers();
+
     // Track cursor position
     editor.onDidChangeCursorPosition((e) => {
       if (activeTabId) {
@@ -253,6 +549,163 @@ export function Editor() {
     }
   }, [activeTabId, activeTab, activeTab?.language]);
 
+  // 与语言服务器同步文档：首次打开时启动对应服务器，之后防抖发送内容变化
+  useEffect(() => {
+    const path = activeTab?.path;
+    if (!path || activeTab.largeFile || activeTab.logFollow || activeTab.hexMode) return;
+    const text = activeTab.content;
+    const timer = setTimeout(() => {
+      const opened = lspDocumentsRef.current;
+      if (opened.has(path)) {
+        if (opened.get(path) === text) return;
+        opened.set(path, text);
+        invoke('lsp_change_document', { path, text }).catch(() => {});
+        return;
+      }
+      if ((lspRetryRef.current.get(path) ?? 0) > Date.now()) return;
+      opened.set(path, text);
+      invoke('lsp_open_document', {
+        path,
+        text,
+        languageId: activeTab.language || null,
+        workspace: openFolder || null,
+      }).catch(err => {
+        // didOpen 未送达：移除记录，之后的编辑在重试间隔后重新打开
+        opened.delete(path);
+        lspRetryRef.current.set(path, Date.now() + LSP_RETRY_MS);
+        console.debug('Language server unavailable:', err);
+      });
+    }, 300);
+    return () => clearTimeout(timer);
+  }, [activeTab?.path, activeTab?.content]);
+
+  // 标签页关闭后通知语言服务器
+  useEffect(() => {
+    const paths = new Set(tabs.map(tab => tab.path).filter(Boolean));
+    for (const path of Array.from(lspDocumentsRef.current.keys())) {
+      if (!paths.has(path)) {
+        lspDocumentsRef.current.delete(path);
+        lspRetryRef.current.delete(path);
+        lspDiagnosticsRef.current.delete(normalizePath(path));
+        invoke('lsp_close_document', { path }).catch(() => {});
+      }
+    }
+    const largePaths = new Set(tabs.filter(tab => tab.largeFile).map(tab => tab.path));
+    for (const path of Array.from(largeFilesRef.current)) {
+      if (!largePaths.has(path)) {
+        largeFilesRef.current.delete(path);
+        invoke('close_large_file', { path }).catch(() => {});
+      }
+    }
+    largePaths.forEach(path => path && largeFilesRef.current.add(path));
+    const hexPaths = new Set(tabs.filter(tab => tab.hexMode).map(tab => tab.path));
+    for (const path of Array.from(hexDocumentsRef.current)) {
+      if (!hexPaths.has(path)) {
+        hexDocumentsRef.current.delete(path);
+        invoke('close_hex_document', { path }).catch(() => {});
+      }
+    }
+    hexPaths.forEach(path => path && hexDocumentsRef.current.add(path));
+    const tabIds = new Set(tabs.map(tab => tab.id));
+    for (const tabId of Array.from(documentsRef.current.keys())) {
+      if (!tabIds.has(tabId)) {
+        documentsRef.current.delete(tabId);
+        syncDocument(tabId, () => invoke('close_document', { tabId }));
+      }
+    }
+  }, [tabs]);
+
+  // 将问题匹配器的结果显示为当前文件的标记
+  useEffect(() => {
+    const applyMarkers = async () => {
+      const model = editorRef.current?.getModel();
+      const monaco = monacoRef.current;
+      if (!model || !monaco) return;
+      const problems = activeTab?.path ? await invoke<Problem[]>('get_problems') : [];
+      const path = activeTab?.path ? normalizePath(activeTab.path) : '';
+      // 语言服务器的诊断也会进入问题列表，由 lsp 标记单独显示，这里跳过
+      const lspKeys = new Set((lspDiagnosticsRef.current.get(path) ?? []).map(
+        d => `${d.range.start.line + 1}:${d.range.start.character + 1}:${d.message}`,
+      ));
+      const markers = problems
+        .filter(p => normalizePath(p.file) === path && !lspKeys.has(`${p.line}:${p.column}:${p.message}`))
+        .map(p => ({
+          severity: problemSeverity[p.severity],
+          message: p.message,
+          code: p.code,
+          source: p.source,
+          startLineNumber: p.line,
+          startColumn: p.column,
+          endLineNumber: p.endLine ?? p.line,
+          endColumn: p.endColumn ?? model.getLineMaxColumn(Math.min(p.endLine ?? p.line, model.getLineCount())),
+        }));
+      monaco.editor.setModelMarkers(model, 'problems', markers);
+    };
+
+    applyMarkers();
+    const unlisten = listen('problems-updated', () => { applyMarkers(); });
+    return () => { unlisten.then(fn => fn()); };
+  }, [activeTabId, activeTab?.path]);
+
+  // 语言服务器诊断：一直监听，切换标签页时重新应用
+  useEffect(() => {
+    const unlisten = listen<LspDiagnosticsEvent>('lsp-diagnostics', (event) => {
+      const { path, diagnostics } = event.payload;
+      if (!path) return;
+      if (diagnostics.length === 0) {
+        lspDiagnosticsRef.current.delete(normalizePath(path));
+      } else {
+        lspDiagnosticsRef.current.set(normalizePath(path), diagnostics);
+      }
+      applyLspMarkers();
+    });
+    return () => {
+      unlisten.then(fn => fn());
+      lspProvidersRef.current?.forEach(provider => provider.dispose());
+      lspProvidersRef.current = null;
+    };
+  }, []);
+
+  useEffect(() => {
+    applyLspMarkers();
+  }, [activeTabId, activeTab?.path]);
+
+  // 数据和配置文件校验：防抖后将语法错误和 Schema 问题显示为标记
+  useEffect(() => {
+    const model = editorRef.current?.getModel();
+    const monaco = monacoRef.current;
+    if (!model || !monaco || !activeTab) return;
+    const supported = validatedLanguages.has(activeTab.language) || activeTab.name.toLowerCase().startsWith('.env');
+    if (!supported) {
+      monaco.editor.setModelMarkers(model, 'validation', []);
+      return;
+    }
+    const timer = setTimeout(async () => {
+      try {
+        const problems = await invoke<Problem[]>('validate_document', {
+          content: activeTab.content,
+          path: activeTab.path || null,
+          languageId: activeTab.language || null,
+          workspace: openFolder || null,
+        });
+        if (model.isDisposed()) return;
+        monaco.editor.setModelMarkers(model, 'validation', problems.map(p => ({
+          severity: problemSeverity[p.severity],
+          message: p.message,
+          code: p.code,
+          source: p.source,
+          startLineNumber: p.line,
+          startColumn: p.column,
+          endLineNumber: p.endLine ?? p.line,
+          endColumn: p.endColumn ?? p.column,
+        })));
+      } catch (err) {
+        console.debug('Validation failed:', err);
+      }
+    }, 500);
+    return () => clearTimeout(timer);
+  }, [activeTabId, activeTab?.content, activeTab?.language]);
+
   // 监听窗口大小变化，强制更新布局
   useEffect(() => {
     const handleResize = () => {
@@ -295,6 +748,30 @@ export function Editor() {
     );
   }
 
+  if (activeTab.hexMode && activeTab.path) {
+    return (
+      <div className="editor-container">
+        <HexView tabId={activeTab.id} path={activeTab.path} />
+      </div>
+    );
+  }
+
+  if (activeTab.logFollow && activeTab.path) {
+    return (
+      <div className="editor-container">
+        <LogFollowView path={activeTab.path} />
+      </div>
+    );
+  }
+
+  if (activeTab.largeFile && activeTab.path) {
+    return (
+      <div className="editor-container">
+        <LargeFileView path={activeTab.path} />
+      </div>
+    );
+  }
+
   return (
     <div className="editor-container">
       <MonacoEditor
@@ -0,0 +1,340 @@
+import { useEffect, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useTranslation } from 'react-i18next';
+import { Search, ChevronUp, ChevronDown, CaseSensitive } from 'lucide-react';
+import { useStore } from '../store';
+import '../styles/HexView.css';
+
+interface HexStatus {
+  path: string;
+  size: number;
+  originalSize: number;
+  modified: boolean;
+  requiresSaveAs: boolean;
+}
+
+interface HexRange {
+  offset: number;
+  bytes: number[];
+  modified: [number, number][];
+  size: number;
+}
+
+interface HexSearchResult {
+  offsets: number[];
+  length: number;
+  truncated: boolean;
+}
+
+const BYTES_PER_ROW = 16;
+// 每次请求的字节数
+const PAGE_SIZE = 4096;
+const OVERSCAN = 10;
+// 浏览器对元素高度有上限，超过时按比例映射滚动位置
+const MAX_SCROLL_HEIGHT = 10_000_000;
+const SEARCH_ENCODINGS = ['UTF-8', 'UTF-16LE', 'UTF-16BE', 'Windows-1252', 'GBK', 'Shift_JIS', 'EUC-KR'];
+
+const hex = (value: number, width: number) => value.toString(16).toUpperCase().padStart(width, '0');
+const printable = (byte: number) => (byte >= 0x20 && byte < 0x7f ? String.fromCharCode(byte) : '.');
+
+// 二进制文件的十六进制视图：按页读取，覆盖/插入/删除字节，按十六进制或编码后的文本搜索
+export function HexView({ tabId, path }: { tabId: string; path: string }) {
+  const { t } = useTranslation();
+  const { settings, setTabModified } = useStore();
+  const rowHeight = Math.round(settings.fontSize * settings.lineHeight);
+
+  const [status, setStatus] = useState<HexStatus | null>(null);
+  const [pages, setPages] = useState<Map<number, HexRange>>(new Map());
+  const [scrollTop, setScrollTop] = useState(0);
+  const [viewHeight, setViewHeight] = useState(0);
+  const [cursor, setCursor] = useState(0);
+  const [nibble, setNibble] = useState<number | null>(null);
+  const [pane, setPane] = useState<'hex' | 'text'>('hex');
+  const [insertMode, setInsertMode] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const [query, setQuery] = useState('');
+  const [searchMode, setSearchMode] = useState<'hex' | 'text'>('hex');
+  const [searchEncoding, setSearchEncoding] = useState('UTF-8');
+  const [caseSensitive, setCaseSensitive] = useState(false);
+  const [results, setResults] = useState<HexSearchResult | null>(null);
+  const [resultIndex, setResultIndex] = useState(0);
+
+  const containerRef = useRef<HTMLDivElement>(null);
+  const loadingRef = useRef<Set<number>>(new Set());
+
+  useEffect(() => {
+    setPages(new Map());
+    setCursor(0);
+    setResults(null);
+    invoke<HexStatus>('open_hex_document', { path })
+      .then(setStatus)
+      .catch((e) => setError(String(e)));
+  }, [path]);
+
+  useEffect(() => {
+    const container = containerRef.current;
+    if (!container) return;
+    const observer = new ResizeObserver(() => setViewHeight(container.clientHeight));
+    observer.observe(container);
+    return () => observer.disconnect();
+  }, []);
+
+  const size = status?.size ?? 0;
+  // 末尾多留一行，供在文件末尾插入
+  const rowCount = Math.floor(size / BYTES_PER_ROW) + 1;
+  const totalHeight = rowCount * rowHeight;
+  const scrollHeight = Math.min(totalHeight, MAX_SCROLL_HEIGHT);
+  const ratio = scrollHeight > viewHeight ? (totalHeight - viewHeight) / (scrollHeight - viewHeight) : 1;
+  const virtualTop = scrollTop * ratio;
+  const firstRow = Math.max(0, Math.floor(virtualTop / rowHeight) - OVERSCAN);
+  const lastRow = Math.min(rowCount, Math.ceil((virtualTop + viewHeight) / rowHeight) + OVERSCAN);
+
+  useEffect(() => {
+    if (!status) return;
+    const firstPage = Math.floor((firstRow * BYTES_PER_ROW) / PAGE_SIZE);
+    for (let page = firstPage; page * PAGE_SIZE < Math.min(lastRow * BYTES_PER_ROW, size); page++) {
+      if (pages.has(page) || loadingRef.current.has(page)) continue;
+      loadingRef.current.add(page);
+      invoke<HexRange>('read_hex_bytes', { path, offset: page * PAGE_SIZE, length: PAGE_SIZE })
+        .then((range) => setPages((prev) => new Map(prev).set(page, range)))
+        .catch((e) => setError(String(e)))
+        .finally(() => loadingRef.current.delete(page));
+    }
+  }, [path, status, size, firstRow, lastRow, pages]);
+
+  const byteAt = (offset: number) => {
+    const page = pages.get(Math.floor(offset / PAGE_SIZE));
+    return page?.bytes[offset - page.offset];
+  };
+
+  const isModified = (offset: number) => {
+    const page = pages.get(Math.floor(offset / PAGE_SIZE));
+    return !!page?.modified.some(([start, end]) => offset >= start && offset < end);
+  };
+
+  const moveCursor = (offset: number, limit = size) => {
+    const next = Math.max(0, Math.min(limit, offset));
+    setCursor(next);
+    setNibble(null);
+    const container = containerRef.current;
+    if (!container) return;
+    const rowTop = Math.floor(next / BYTES_PER_ROW) * rowHeight;
+    if (rowTop < virtualTop) {
+      container.scrollTop = rowTop / ratio;
+    } else if (rowTop + rowHeight > virtualTop + viewHeight) {
+      container.scrollTop = (rowTop + rowHeight - viewHeight) / ratio;
+    }
+  };
+
+  // 修改后丢弃受影响的页：长度不变时只有所在页，否则之后的所有页
+  const applyEdit = async (command: string, args: Record<string, unknown>, offset: number, nextCursor: number) => {
+    try {
+      const next = await invoke<HexStatus>(command, { path, ...args });
+      const firstPage = Math.floor(offset / PAGE_SIZE);
+      const lengthChanged = next.size !== size;
+      setPages((prev) => {
+        const pagesLeft = new Map(prev);
+        for (const index of prev.keys()) {
+          if (index === firstPage || (lengthChanged && index > firstPage)) pagesLeft.delete(index);
+        }
+        return pagesLeft;
+      });
+      setStatus(next);
+      setTabModified(tabId, next.modified);
+      setResults(null);
+      setError(null);
+      moveCursor(nextCursor, next.size);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const inputByte = (byte: number) => {
+    if (insertMode || cursor >= size) {
+      applyEdit('insert_hex_bytes', { offset: cursor, bytes: [byte] }, cursor, cursor + 1);
+    } else {
+      applyEdit('write_hex_bytes', { offset: cursor, bytes: [byte] }, cursor, cursor + 1);
+    }
+  };
+
+  const handleKeyDown = (e: React.KeyboardEvent) => {
+    if (e.ctrlKey || e.metaKey || e.altKey) return;
+    const pageRows = Math.max(1, Math.floor(viewHeight / rowHeight));
+    const moves: Record<string, number> = {
+      ArrowLeft: -1,
+      ArrowRight: 1,
+      ArrowUp: -BYTES_PER_ROW,
+      ArrowDown: BYTES_PER_ROW,
+      PageUp: -BYTES_PER_ROW * pageRows,
+      PageDown: BYTES_PER_ROW * pageRows,
+    };
+    if (e.key in moves) {
+      moveCursor(cursor + moves[e.key]);
+    } else if (e.key === 'Home') {
+      moveCursor(cursor - (cursor % BYTES_PER_ROW));
+    } else if (e.key === 'End') {
+      moveCursor(cursor - (cursor % BYTES_PER_ROW) + BYTES_PER_ROW - 1);
+    } else if (e.key === 'Insert') {
+      setInsertMode(!insertMode);
+    } else if (e.key === 'Tab') {
+      setPane(pane === 'hex' ? 'text' : 'hex');
+    } else if (e.key === 'Delete' && cursor < size) {
+      applyEdit('delete_hex_bytes', { offset: cursor, length: 1 }, cursor, cursor);
+    } else if (e.key === 'Backspace' && cursor > 0) {
+      applyEdit('delete_hex_bytes', { offset: cursor - 1, length: 1 }, cursor - 1, cursor - 1);
+    } else if (pane === 'hex' && /^[0-9a-fA-F]$/.test(e.key)) {
+      const digit = parseInt(e.key, 16);
+      if (nibble === null) {
+        setNibble(digit);
+      } else {
+        inputByte(nibble * 16 + digit);
+      }
+    } else if (pane === 'text' && e.key.length === 1 && e.key.charCodeAt(0) < 0x7f) {
+      inputByte(e.key.charCodeAt(0));
+    } else {
+      return;
+    }
+    e.preventDefault();
+  };
+
+  const runSearch = async () => {
+    if (!query) return;
+    try {
+      const result = await invoke<HexSearchResult>('search_hex', {
+        path,
+        query: { pattern: query, mode: searchMode, encoding: searchEncoding, caseSensitive },
+      });
+      setResults(result);
+      setError(null);
+      // 跳到光标之后的第一个结果
+      const index = Math.max(0, result.offsets.findIndex((offset) => offset >= cursor));
+      setResultIndex(index);
+      if (result.offsets.length) moveCursor(result.offsets[index]);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  // 已有结果时跳到下一个，否则开始搜索
+  const findNext = (delta: number) => {
+    if (results) gotoResult(delta);
+    else runSearch();
+  };
+
+  const gotoResult = (delta: number) => {
+    if (!results?.offsets.length) return;
+    const index = (resultIndex + delta + results.offsets.length) % results.offsets.length;
+    setResultIndex(index);
+    moveCursor(results.offsets[index]);
+  };
+
+  const isMatch = (offset: number) =>
+    !!results?.offsets.some((start) => offset >= start && offset < start + results.length);
+
+  const rows = [];
+  for (let row = firstRow; row < lastRow; row++) {
+    const start = row * BYTES_PER_ROW;
+    const hexCells = [];
+    const textCells = [];
+    for (let i = 0; i < BYTES_PER_ROW; i++) {
+      const offset = start + i;
+      const byte = offset < size ? byteAt(offset) : undefined;
+      const classes = [
+        'hex-cell',
+        offset === cursor ? `hex-cursor${pane === 'hex' ? ' hex-cursor-active' : ''}` : '',
+        offset < size && isModified(offset) ? 'hex-modified' : '',
+        offset < size && isMatch(offset) ? 'hex-match' : '',
+      ].join(' ');
+      const text = offset === cursor && nibble !== null ? `${hex(nibble, 1)}_` : byte === undefined ? '  ' : hex(byte, 2);
+      hexCells.push(
+        <span key={i} className={classes} onMouseDown={() => { setPane('hex'); moveCursor(offset); }}>
+          {offset <= size ? text : '  '}
+        </span>
+      );
+      textCells.push(
+        <span
+          key={i}
+          className={classes.replace('hex-cursor-active', '') + (offset === cursor && pane === 'text' ? ' hex-cursor-active' : '')}
+          onMouseDown={() => { setPane('text'); moveCursor(offset); }}
+        >
+          {byte === undefined ? ' ' : printable(byte)}
+        </span>
+      );
+    }
+    rows.push(
+      <div
+        key={row}
+        className="hex-row"
+        style={{ top: scrollTop + row * rowHeight - virtualTop, height: rowHeight, lineHeight: `${rowHeight}px` }}
+      >
+        <span className="hex-offset">{hex(start, 8)}</span>
+        <span className="hex-bytes">{hexCells}</span>
+        <span className="hex-text">{textCells}</span>
+      </div>
+    );
+  }
+
+  return (
+    <div className="hex-view" style={{ fontFamily: settings.fontFamily, fontSize: settings.fontSize }}>
+      <div className="hex-toolbar">
+        <Search size={14} />
+        <input
+          className="hex-search-input"
+          placeholder={searchMode === 'hex' ? t('hexView.hexPlaceholder') : t('hexView.textPlaceholder')}
+          value={query}
+          onChange={(e) => { setQuery(e.target.value); setResults(null); }}
+          onKeyDown={(e) => { if (e.key === 'Enter') findNext(e.shiftKey ? -1 : 1); }}
+        />
+        <select className="hex-select" value={searchMode} onChange={(e) => { setSearchMode(e.target.value as 'hex' | 'text'); setResults(null); }}>
+          <option value="hex">{t('hexView.hex')}</option>
+          <option value="text">{t('hexView.text')}</option>
+        </select>
+        {searchMode === 'text' && (
+          <>
+            <select className="hex-select" value={searchEncoding} onChange={(e) => { setSearchEncoding(e.target.value); setResults(null); }}>
+              {SEARCH_ENCODINGS.map((encoding) => <option key={encoding} value={encoding}>{encoding}</option>)}
+            </select>
+            <button
+              className={`hex-btn ${caseSensitive ? 'active' : ''}`}
+              onClick={() => { setCaseSensitive(!caseSensitive); setResults(null); }}
+              title={t('search.matchCase')}
+            >
+              <CaseSensitive size={14} />
+            </button>
+          </>
+        )}
+        <button className="hex-btn" onClick={() => gotoResult(-1)} title={t('hexView.previousMatch')}>
+          <ChevronUp size={14} />
+        </button>
+        <button className="hex-btn" onClick={() => findNext(1)} title={t('hexView.nextMatch')}>
+          <ChevronDown size={14} />
+        </button>
+        {results && (
+          <span className="hex-results">
+            {results.offsets.length
+              ? `${resultIndex + 1} / ${results.offsets.length}${results.truncated ? '+' : ''}`
+              : t('search.noResults')}
+          </span>
+        )}
+        <span className="hex-status">
+          {error ?? [
+            `${t('hexView.offset')}: 0x${hex(cursor, 8)} (${cursor})`,
+            `${size} ${t('hexView.bytes')}`,
+            insertMode ? t('hexView.insert') : t('hexView.overwrite'),
+            status?.requiresSaveAs ? t('hexView.requiresSaveAs') : '',
+          ].filter(Boolean).join(' · ')}
+        </span>
+      </div>
+      <div
+        ref={containerRef}
+        className="hex-scroll"
+        tabIndex={0}
+        onKeyDown={handleKeyDown}
+        onScroll={(e) => setScrollTop(e.currentTarget.scrollTop)}
+      >
+        <div style={{ height: scrollHeight, position: 'relative' }}>{rows}</div>
+      </div>
+    </div>
+  );
+}
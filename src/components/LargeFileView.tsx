@@ -0,0 +1,187 @@
+import { useEffect, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useTranslation } from 'react-i18next';
+import { RefreshCw, ArrowDownToLine } from 'lucide-react';
+import { useStore } from '../store';
+import '../styles/LargeFileView.css';
+
+interface LargeFileStatus {
+  path: string;
+  size: number;
+  encoding: string;
+  lineCount: number;
+  indexedBytes: number;
+  indexing: boolean;
+}
+
+interface LineRange {
+  start: number;
+  lines: string[];
+  truncated: number[];
+  lineCount: number;
+  indexing: boolean;
+}
+
+// 每次请求的行数
+const PAGE_SIZE = 500;
+// 可视区域上下额外渲染的行数
+const OVERSCAN = 20;
+// 浏览器对元素高度有上限，超过时按比例映射滚动位置
+const MAX_SCROLL_HEIGHT = 10_000_000;
+
+const formatSize = (bytes: number) => {
+  const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+  let size = bytes;
+  let unit = 0;
+  while (size >= 1024 && unit < units.length - 1) {
+    size /= 1024;
+    unit++;
+  }
+  return `${size.toFixed(unit === 0 ? 0 : 1)} ${units[unit]}`;
+};
+
+// 大文件的虚拟滚动视图：只读，支持在末尾追加
+export function LargeFileView({ path }: { path: string }) {
+  const { t } = useTranslation();
+  const { settings } = useStore();
+  const lineHeight = Math.round(settings.fontSize * settings.lineHeight);
+
+  const [status, setStatus] = useState<LargeFileStatus | null>(null);
+  const [pages, setPages] = useState<Map<number, LineRange>>(new Map());
+  const [scrollTop, setScrollTop] = useState(0);
+  const [viewHeight, setViewHeight] = useState(0);
+  const [appendText, setAppendText] = useState('');
+  const containerRef = useRef<HTMLDivElement>(null);
+  const loadingRef = useRef<Set<number>>(new Set());
+
+  useEffect(() => {
+    setPages(new Map());
+    invoke<LargeFileStatus>('open_large_file', { path })
+      .then(setStatus)
+      .catch((error) => console.error('Failed to open large file:', error));
+    // 卸载时监听可能尚未注册完成，等注册后再取消
+    const unlisten = listen<LargeFileStatus>('large-file-index-progress', (event) => {
+      if (event.payload.path === path) setStatus(event.payload);
+    });
+    return () => { unlisten.then(fn => fn()); };
+  }, [path]);
+
+  useEffect(() => {
+    const container = containerRef.current;
+    if (!container) return;
+    const observer = new ResizeObserver(() => setViewHeight(container.clientHeight));
+    observer.observe(container);
+    return () => observer.disconnect();
+  }, []);
+
+  // 行数变化后丢弃包含当时最后一行的页，最后一行可能尚未写完
+  useEffect(() => {
+    setPages((prev) => {
+      const next = new Map(prev);
+      for (const [index, page] of prev) {
+        if (page.start + page.lines.length >= page.lineCount) next.delete(index);
+      }
+      return next.size === prev.size ? prev : next;
+    });
+  }, [status?.lineCount]);
+
+  const lineCount = status?.lineCount ?? 0;
+  const totalHeight = lineCount * lineHeight;
+  const scrollHeight = Math.min(totalHeight, MAX_SCROLL_HEIGHT);
+  const ratio = scrollHeight > viewHeight ? (totalHeight - viewHeight) / (scrollHeight - viewHeight) : 1;
+  const virtualTop = scrollTop * ratio;
+  const firstLine = Math.max(0, Math.floor(virtualTop / lineHeight) - OVERSCAN);
+  const lastLine = Math.min(lineCount, Math.ceil((virtualTop + viewHeight) / lineHeight) + OVERSCAN);
+
+  useEffect(() => {
+    if (lastLine <= firstLine) return;
+    for (let page = Math.floor(firstLine / PAGE_SIZE); page * PAGE_SIZE < lastLine; page++) {
+      if (pages.has(page) || loadingRef.current.has(page)) continue;
+      loadingRef.current.add(page);
+      invoke<LineRange>('read_large_file_lines', { path, start: page * PAGE_SIZE, count: PAGE_SIZE })
+        .then((range) => setPages((prev) => new Map(prev).set(page, range)))
+        .catch((error) => console.error('Failed to read lines:', error))
+        .finally(() => loadingRef.current.delete(page));
+    }
+  }, [path, firstLine, lastLine, pages]);
+
+  const refresh = async () => {
+    try {
+      setStatus(await invoke<LargeFileStatus>('refresh_large_file', { path }));
+    } catch (error) {
+      console.error('Failed to refresh large file:', error);
+    }
+  };
+
+  const scrollToEnd = () => {
+    const container = containerRef.current;
+    if (container) container.scrollTop = container.scrollHeight;
+  };
+
+  const append = async () => {
+    if (!appendText) return;
+    try {
+      setStatus(await invoke<LargeFileStatus>('append_to_large_file', { path, text: `${appendText}\n` }));
+      setAppendText('');
+      scrollToEnd();
+    } catch (error) {
+      console.error('Failed to append to large file:', error);
+    }
+  };
+
+  const rows = [];
+  for (let line = firstLine; line < lastLine; line++) {
+    const page = pages.get(Math.floor(line / PAGE_SIZE));
+    const text = page?.lines[line - page.start];
+    rows.push(
+      <div
+        key={line}
+        className="large-file-line"
+        style={{ top: scrollTop + line * lineHeight - virtualTop, height: lineHeight, lineHeight: `${lineHeight}px` }}
+      >
+        <span className="large-file-gutter">{line + 1}</span>
+        <span className="large-file-text">
+          {text ?? ''}
+          {page?.truncated.includes(line) && <span className="large-file-truncated">{t('largeFile.truncated')}</span>}
+        </span>
+      </div>
+    );
+  }
+
+  return (
+    <div className="large-file-view" style={{ fontFamily: settings.fontFamily, fontSize: settings.fontSize }}>
+      <div className="large-file-toolbar">
+        <span className="large-file-badge">{t('largeFile.readOnly')}</span>
+        {status && (
+          <span className="large-file-status">
+            {t('largeFile.lines', { count: status.lineCount })} · {formatSize(status.size)} · {status.encoding}
+            {status.indexing && ` · ${t('largeFile.indexing', {
+              percent: status.size ? Math.floor((status.indexedBytes / status.size) * 100) : 100,
+            })}`}
+          </span>
+        )}
+        <button className="large-file-btn" onClick={refresh} title={t('largeFile.refresh')}>
+          <RefreshCw size={14} />
+        </button>
+        <button className="large-file-btn" onClick={scrollToEnd} title={t('largeFile.goToEnd')}>
+          <ArrowDownToLine size={14} />
+        </button>
+      </div>
+      <div
+        ref={containerRef}
+        className="large-file-scroll"
+        onScroll={(e) => setScrollTop(e.currentTarget.scrollTop)}
+      >
+        <div style={{ height: scrollHeight, position: 'relative' }}>{rows}</div>
+      </div>
+      <input
+        className="large-file-append"
+        placeholder={t('largeFile.appendPlaceholder')}
+        value={appendText}
+        onChange={(e) => setAppendText(e.target.value)}
+        onKeyDown={(e) => { if (e.key === 'Enter') append(); }}
+      />
+    </div>
+  );
+}
@@ -0,0 +1,187 @@
+import { useEffect, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useTranslation } from 'react-i18next';
+import { Pause, Play, Trash2 } from 'lucide-react';
+import { useStore } from '../store';
+import '../styles/LogFollowView.css';
+
+type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error' | 'fatal';
+
+interface LogLine {
+  text: string;
+  level: LogLevel | null;
+  offset: number;
+}
+
+interface LogFollowBatch {
+  id: string;
+  lines: LogLine[];
+  reset?: 'rotated' | 'truncated';
+  filtered: number;
+}
+
+type Row = LogLine | { notice: string };
+
+const LEVELS: LogLevel[] = ['trace', 'debug', 'info', 'warn', 'error', 'fatal'];
+// 前端最多保留的行数，超出时丢弃最早的行
+const MAX_ROWS = 20_000;
+// 开始跟踪时先显示的末尾行数
+const INITIAL_LINES = 1000;
+const OVERSCAN = 20;
+
+// 日志跟踪视图：过滤条件在后端应用，修改后重新开始跟踪
+export function LogFollowView({ path }: { path: string }) {
+  const { t } = useTranslation();
+  const { settings } = useStore();
+  const lineHeight = Math.round(settings.fontSize * settings.lineHeight);
+
+  const [include, setInclude] = useState('');
+  const [exclude, setExclude] = useState('');
+  const [minLevel, setMinLevel] = useState<LogLevel | ''>('');
+  const [rows, setRows] = useState<Row[]>([]);
+  const [filtered, setFiltered] = useState(0);
+  const [error, setError] = useState<string | null>(null);
+  const [paused, setPaused] = useState(false);
+  const [scrollTop, setScrollTop] = useState(0);
+  const [viewHeight, setViewHeight] = useState(0);
+  const containerRef = useRef<HTMLDivElement>(null);
+
+  useEffect(() => {
+    const container = containerRef.current;
+    if (!container) return;
+    const observer = new ResizeObserver(() => setViewHeight(container.clientHeight));
+    observer.observe(container);
+    return () => observer.disconnect();
+  }, []);
+
+  useEffect(() => {
+    let id: string | null = null;
+    let cancelled = false;
+    let unlisten: (() => void) | undefined;
+    // 跟踪 ID 返回之前到达的事件先缓存
+    const pending: LogFollowBatch[] = [];
+
+    const applyBatch = (batch: LogFollowBatch) => {
+      setFiltered((count) => count + batch.filtered);
+      setRows((prev) => {
+        const next: Row[] = batch.reset ? [...prev, { notice: t(`logFollow.${batch.reset}`) }] : [...prev];
+        next.push(...batch.lines);
+        return next.length > MAX_ROWS ? next.slice(next.length - MAX_ROWS) : next;
+      });
+    };
+
+    const timer = setTimeout(async () => {
+      setRows([]);
+      setFiltered(0);
+      setError(null);
+      unlisten = await listen<LogFollowBatch>('log-follow-lines', (event) => {
+        if (id === null) pending.push(event.payload);
+        else if (event.payload.id === id) applyBatch(event.payload);
+      });
+      if (cancelled) {
+        unlisten();
+        return;
+      }
+      try {
+        const followId = await invoke<string>('start_log_follow', {
+          path,
+          options: {
+            include: include || null,
+            exclude: exclude || null,
+            minLevel: minLevel || null,
+            initialLines: INITIAL_LINES,
+          },
+        });
+        if (cancelled) {
+          invoke('stop_log_follow', { id: followId }).catch(() => {});
+          return;
+        }
+        id = followId;
+        pending.filter((batch) => batch.id === id).forEach(applyBatch);
+      } catch (e) {
+        setError(String(e));
+      }
+    }, 300);
+
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+      unlisten?.();
+      if (id) invoke('stop_log_follow', { id }).catch(() => {});
+    };
+  }, [path, include, exclude, minLevel, t]);
+
+  // 未暂停时自动滚动到底部
+  useEffect(() => {
+    const container = containerRef.current;
+    if (container && !paused) container.scrollTop = container.scrollHeight;
+  }, [rows, paused]);
+
+  const firstRow = Math.max(0, Math.floor(scrollTop / lineHeight) - OVERSCAN);
+  const lastRow = Math.min(rows.length, Math.ceil((scrollTop + viewHeight) / lineHeight) + OVERSCAN);
+
+  const visible = [];
+  for (let index = firstRow; index < lastRow; index++) {
+    const row = rows[index];
+    const style = { top: index * lineHeight, height: lineHeight, lineHeight: `${lineHeight}px` };
+    visible.push('notice' in row ? (
+      <div key={index} className="log-follow-line log-follow-notice" style={style}>{row.notice}</div>
+    ) : (
+      <div key={index} className={`log-follow-line log-level-${row.level ?? 'none'}`} style={style}>
+        {row.text}
+      </div>
+    ));
+  }
+
+  return (
+    <div className="log-follow-view" style={{ fontFamily: settings.fontFamily, fontSize: settings.fontSize }}>
+      <div className="log-follow-toolbar">
+        <span className="log-follow-badge">{t('logFollow.following')}</span>
+        <input
+          className="log-follow-input"
+          placeholder={t('logFollow.include')}
+          value={include}
+          onChange={(e) => setInclude(e.target.value)}
+        />
+        <input
+          className="log-follow-input"
+          placeholder={t('logFollow.exclude')}
+          value={exclude}
+          onChange={(e) => setExclude(e.target.value)}
+        />
+        <select
+          className="log-follow-select"
+          value={minLevel}
+          title={t('logFollow.minLevel')}
+          onChange={(e) => setMinLevel(e.target.value as LogLevel | '')}
+        >
+          <option value="">{t('logFollow.allLevels')}</option>
+          {LEVELS.map((level) => (
+            <option key={level} value={level}>{level.toUpperCase()}</option>
+          ))}
+        </select>
+        <span className="log-follow-status">
+          {error ?? `${t('logFollow.lines', { count: rows.length })} · ${t('logFollow.filtered', { count: filtered })}`}
+        </span>
+        <button
+          className="log-follow-btn"
+          onClick={() => setPaused(!paused)}
+          title={paused ? t('logFollow.resume') : t('logFollow.pause')}
+        >
+          {paused ? <Play size={14} /> : <Pause size={14} />}
+        </button>
+        <button className="log-follow-btn" onClick={() => setRows([])} title={t('logFollow.clear')}>
+          <Trash2 size={14} />
+        </button>
+      </div>
+      <div
+        ref={containerRef}
+        className="log-follow-scroll"
+        onScroll={(e) => setScrollTop(e.currentTarget.scrollTop)}
+      >
+        <div style={{ height: rows.length * lineHeight, position: 'relative' }}>{visible}</div>
+      </div>
+    </div>
+  );
+}
@@ -1,17 +1,69 @@
 // Markdown Preview Component
-import { useMemo } from 'react';
+import { useEffect, useMemo } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
+import { invoke, convertFileSrc } from '@tauri-apps/api/core';
 import { useStore } from '../store';
 import { X } from 'lucide-react';
 import '../styles/MarkdownPreview.css';
 
+const VIDEO_EXTENSIONS = ['mp4', 'm4v', 'webm', 'mov', 'ogv'];
+const AUDIO_EXTENSIONS = ['mp3', 'm4a', 'wav', 'flac', 'ogg', 'oga', 'opus', 'aac'];
+
+const dirname = (path: string) => path.replace(/[/\\][^/\\]*$/, '');
+
+// 文本和属性值都会被转义，引号也要处理，否则属性值可以提前结束
+const escapeHtml = (text: string) => text
+  .replace(/&/g, '&amp;')
+  .replace(/</g, '&lt;')
+  .replace(/>/g, '&gt;')
+  .replace(/"/g, '&quot;')
+  .replace(/'/g, '&#39;');
+
+const unescapeHtml = (text: string) => text
+  .replace(/&lt;/g, '<')
+  .replace(/&gt;/g, '>')
+  .replace(/&quot;/g, '"')
+  .replace(/&#39;/g, "'")
+  .replace(/&amp;/g, '&');
+
+const SAFE_SCHEMES = ['http:', 'https:', 'mailto:', 'data:'];
+
+// 带协议的地址只允许常见的安全协议（拒绝 javascript: 等）
+const hasUnsafeScheme = (url: string) => {
+  const scheme = url.match(/^([a-z][a-z0-9+.-]*:)/i)?.[1].toLowerCase();
+  return !!scheme && !/^[a-z]:[\\/]/i.test(url) && !SAFE_SCHEMES.includes(scheme);
+};
+
+// 本地附件通过 kairofile:// 协议加载，相对路径基于笔记所在目录
+function resolveAsset(url: string, baseDir: string | null): string {
+  if (hasUnsafeScheme(url)) return '';
+  if (/^[a-z][a-z0-9+.-]*:/i.test(url) && !/^[a-z]:[\\/]/i.test(url)) return url;
+  const isAbsolute = url.startsWith('/') || /^[a-z]:[\\/]/i.test(url);
+  if (!isAbsolute && !baseDir) return url;
+  let path = url.split(/[?#]/)[0];
+  try {
+    path = decodeURIComponent(path);
+  } catch {
+    // 保留无法解码的原始路径
+  }
+  const separator = baseDir?.includes('\\') ? '\\' : '/';
+  return convertFileSrc(isAbsolute ? path : `${baseDir}${separator}${path}`, 'kairofile');
+}
+
+// 图片语法引用的音视频和 PDF 使用对应的元素嵌入
+function renderAsset(alt: string, target: string, baseDir: string | null): string {
+  const src = unescapeHtml(target.trim().split(/\s+/)[0]);
+  const url = escapeHtml(resolveAsset(src, baseDir));
+  const extension = src.split(/[?#]/)[0].split('.').pop()?.toLowerCase() ?? '';
+  if (VIDEO_EXTENSIONS.includes(extension)) return `<video controls src="${url}" title="${alt}"></video>`;
+  if (AUDIO_EXTENSIONS.includes(extension)) return `<audio controls src="${url}" title="${alt}"></audio>`;
+  if (extension === 'pdf') return `<iframe class="markdown-pdf" src="${url}" title="${alt}"></iframe>`;
+  return `<img src="${url}" alt="${alt}" />`;
+}
+
 // Simple markdown parser
-function parseMarkdown(text: string): string {
-  let html = text
-    // Escape HTML
-    .replace(/&/g, '&amp;')
-    .replace(/</g, '&lt;')
-    .replace(/>/g, '&gt;')
+function parseMarkdown(text: string, baseDir: string | null = null): string {
+  let html = escapeHtml(text)
     // Headers
     .replace(/^### (.*$)/gm, '<h3>$1</h3>')
     .replace(/^## (.*$)/gm, '<h2>$1</h2>')
@@ -26,10 +78,13 @@ function parseMarkdown(text: string): string {
     .replace(/```(\w*)\n([\s\S]*?)```/g, '<pre><code class="language-$1">$2</code></pre>')
     // Inline code
     .replace(/`([^`]+)`/g, '<code>$1</code>')
+    // Images（先于链接处理，否则 `[alt](src)` 部分会被当作链接）
+    .replace(/!\[([^\]]*)\]\(([^)]+)\)/g, (_, alt: string, target: string) => renderAsset(alt, target, baseDir))
     // Links
-    .replace(/\[([^\]]+)\]\(([^)]+)\)/g, '<a href="$2" target="_blank">$1</a>')
-    // Images
-    .replace(/!\[([^\]]*)\]\(([^)]+)\)/g, '<img src="$2" alt="$1" />')
+    .replace(/\[([^\]]+)\]\(([^)]+)\)/g, (_, label: string, href: string) => {
+      const url = unescapeHtml(href);
+      return `<a href="${hasUnsafeScheme(url) ? '#' : escapeHtml(url)}" target="_blank">${label}</a>`;
+    })
     // Blockquotes
     .replace(/^> (.*$)/gm, '<blockquote>$1</blockquote>')
     // Unordered lists
@@ -66,15 +121,21 @@ function parseMarkdown(text: string): string {
 }
 
 export function MarkdownPreview() {
-  const { showMarkdownPreview, toggleMarkdownPreview, tabs, activeTabId } = useStore();
+  const { showMarkdownPreview, toggleMarkdownPreview, tabs, activeTabId, openFolder, settings } = useStore();
   
   const activeTab = tabs.find(t => t.id === activeTabId);
   const isMarkdown = activeTab?.language === 'markdown' || activeTab?.name.endsWith('.md');
+  const noteDir = activeTab?.path ? dirname(activeTab.path) : null;
+  
+  // 附件协议只允许访问当前文件夹、笔记所在目录和设置中额外允许的目录（由后端根据设置登记）
+  useEffect(() => {
+    invoke('set_file_protocol_roots', { workspace: openFolder || null, note: activeTab?.path ?? null }).catch(() => {});
+  }, [openFolder, activeTab?.path, settings.assetRoots]);
   
   const html = useMemo(() => {
     if (!activeTab || !isMarkdown) return '';
-    return parseMarkdown(activeTab.content);
-  }, [activeTab?.content, isMarkdown]);
+    return parseMarkdown(activeTab.content, noteDir);
+  }, [activeTab?.content, isMarkdown, noteDir]);
 
   if (!showMarkdownPreview || !isMarkdown) return null;
 
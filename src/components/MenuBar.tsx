@@ -124,11 +124,11 @@ function Menu({ label, items }: MenuProps) {
 export function MenuBar() {
   const { t } = useTranslation();
   const {
-    createTab, openFile, saveFile, saveFileAs, saveAllFiles, closeTab, closeAllTabs, closeSavedTabs,
-    activeTabId, toggleSearch, toggleGlobalSearch, toggleSettings, toggleCommandPalette,
+    createTab, openFile, followLogFile, saveFile, saveFileAs, saveAllFiles, closeTab, closeAllTabs, closeSavedTabs,
+    activeTabId, toggleSearch, toggleGlobalSearch, toggleSettings, toggleCommandPalette, toggleQuickOpen,
     toggleTerminal, toggleMarkdownPreview, toggleDiffView, toggleAbout,
     setTheme, theme, settings, updateSettings, openFolderDialog, closeFolder,
-    splitEditor, formatDocument, recentFiles, clearRecentFiles,
+    splitEditor, formatDocument, convertDocument, recentFiles, clearRecentFiles,
     editorUndo, editorRedo, editorCut, editorCopy, editorPaste, editorSelectAll, editorGoToLine,
     showTerminal, showMarkdownPreview, showDiffView
   } = useStore();
@@ -263,7 +263,7 @@ export function MenuBar() {
     { id: 'back', label: t('go.back'), shortcut: 'Alt+←' },
     { id: 'forward', label: t('go.forward'), shortcut: 'Alt+→' },
     { id: 'separator1', label: '', separator: true },
-    { id: 'goToFile', label: t('go.goToFile'), shortcut: 'Ctrl+P', action: toggleCommandPalette },
+    { id: 'goToFile', label: t('go.goToFile'), shortcut: 'Ctrl+P', action: toggleQuickOpen },
     { id: 'goToSymbol', label: t('go.goToSymbol'), shortcut: 'Ctrl+Shift+O' },
     { id: 'goToLine', label: t('go.goToLine'), shortcut: 'Ctrl+G', action: editorGoToLine },
     { id: 'separator2', label: '', separator: true },
@@ -291,6 +291,17 @@ export function MenuBar() {
         { id: 'removeDuplicates', label: t('tools.removeDuplicates') },
       ],
     },
+    {
+      id: 'convertTo', label: t('tools.convertTo'),
+      submenu: [
+        { id: 'convertToJson', label: 'JSON', action: () => convertDocument('json') },
+        { id: 'convertToYaml', label: 'YAML', action: () => convertDocument('yaml') },
+        { id: 'convertToToml', label: 'TOML', action: () => convertDocument('toml') },
+        { id: 'convertToXml', label: 'XML', action: () => convertDocument('xml') },
+        { id: 'convertToPlist', label: 'plist', action: () => convertDocument('plist') },
+        { id: 'convertToCsv', label: 'CSV', action: () => convertDocument('csv') },
+      ],
+    },
     {
       id: 'textTools', label: t('tools.textTools'),
       submenu: [
@@ -320,6 +331,7 @@ export function MenuBar() {
     { id: 'characterCount', label: t('tools.characterCount') },
     { id: 'separator2', label: '', separator: true },
     { id: 'compareFiles', label: t('tools.compareFiles'), action: toggleDiffView },
+    { id: 'followLog', label: t('tools.followLog'), action: () => followLogFile() },
   ];
 
   const helpMenu: MenuItem[] = [
@@ -1,10 +1,13 @@
 import React, { useEffect, useRef, useState } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
 import { useTranslation } from 'react-i18next';
-import { useStore } from '../store';
-import { X, ChevronDown, ChevronUp, Replace, ReplaceAll } from 'lucide-react';
+import { useStore, ReplacePreview, invokeDocument, structuralTarget, structuralQuery, searchOptions } from '../store';
+import { X, ChevronDown, ChevronUp, Replace, ReplaceAll, Braces, SquareDashed } from 'lucide-react';
 import '../styles/SearchPanel.css';
 
+// 预览列表最多显示的条目数
+const MAX_PREVIEW = 50;
+
 export function SearchPanel() {
   const { t } = useTranslation();
   const {
@@ -12,11 +15,15 @@ export function SearchPanel() {
     searchQuery, setSearchQuery,
     replaceQuery, setReplaceQuery,
     searchResults, performSearch, performReplace,
-    settings, updateSettings
+    searchInSelection, toggleSearchInSelection,
+    settings, updateSettings, tabs, activeTabId
   } = useStore();
   
   const searchInputRef = useRef<HTMLInputElement>(null);
   const [showReplace, setShowReplace] = useState(false);
+  const [previews, setPreviews] = useState<ReplacePreview[]>([]);
+  const activeTab = tabs.find((tab) => tab.id === activeTabId);
+  const activeContent = activeTab?.content;
 
   useEffect(() => {
     if (showSearch && searchInputRef.current) {
@@ -31,7 +38,47 @@ export function SearchPanel() {
       }
     }, 300);
     return () => clearTimeout(timer);
-  }, [searchQuery, settings.searchCaseSensitive, settings.searchRegex]);
+  }, [searchQuery, settings.searchCaseSensitive, settings.searchWholeWord, settings.searchRegex, settings.searchStructural,
+      settings.searchStructuralQuery, settings.searchMultiline, settings.searchDotAll, searchInSelection]);
+
+  // 替换预览：显示每个匹配将被替换成的文本（含捕获组展开）
+  useEffect(() => {
+    if (!showSearch || !showReplace || !searchQuery || !activeTab) {
+      setPreviews([]);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      const request = settings.searchStructural
+        ? invokeDocument<ReplacePreview[]>(activeTab, 'structural_search_document', 'structural_search', {
+            ...structuralTarget(activeTab),
+            query: structuralQuery(searchQuery, settings.searchStructuralQuery, replaceQuery),
+          })
+        : invokeDocument<ReplacePreview[]>(activeTab, 'preview_document_replace', 'preview_replace', {
+            search: searchQuery,
+            replace: replaceQuery,
+            options: {
+              ...searchOptions(settings, searchInSelection),
+              preserveCase: settings.searchPreserveCase,
+              replaceAll: true,
+            },
+          });
+      request
+        .then((result) => {
+          if (!cancelled) setPreviews(result);
+        })
+        .catch(() => {
+          if (!cancelled) setPreviews([]);
+        });
+    }, 300);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [showSearch, showReplace, searchQuery, replaceQuery, activeContent,
+      settings.searchCaseSensitive, settings.searchRegex, settings.searchWholeWord, settings.searchPreserveCase,
+      settings.searchStructural, settings.searchStructuralQuery, settings.searchMultiline, settings.searchDotAll,
+      searchInSelection]);
 
   const handleKeyDown = (e: React.KeyboardEvent) => {
     if (e.key === 'Escape') {
@@ -85,13 +132,57 @@ export function SearchPanel() {
                   >
                     ab
                   </button>
+                  {settings.searchStructural ? (
+                    <button
+                      className={`option-btn ${settings.searchStructuralQuery ? 'active' : ''}`}
+                      onClick={() => updateSettings({ searchStructuralQuery: !settings.searchStructuralQuery })}
+                      title={t('search.structuralQuery')}
+                    >
+                      ()
+                    </button>
+                  ) : (
+                    <button
+                      className={`option-btn ${settings.searchRegex ? 'active' : ''}`}
+                      onClick={() => updateSettings({ searchRegex: !settings.searchRegex })}
+                      title={t('search.useRegex')}
+                    >
+                      .*
+                    </button>
+                  )}
                   <button
-                    className={`option-btn ${settings.searchRegex ? 'active' : ''}`}
-                    onClick={() => updateSettings({ searchRegex: !settings.searchRegex })}
-                    title={t('search.useRegex')}
+                    className={`option-btn ${settings.searchStructural ? 'active' : ''}`}
+                    onClick={() => updateSettings({ searchStructural: !settings.searchStructural })}
+                    title={t('search.structural')}
                   >
-                    .*
+                    <Braces size={12} />
                   </button>
+                  {settings.searchRegex && !settings.searchStructural && (
+                    <>
+                      <button
+                        className={`option-btn ${settings.searchMultiline ? 'active' : ''}`}
+                        onClick={() => updateSettings({ searchMultiline: !settings.searchMultiline })}
+                        title={t('search.multiline')}
+                      >
+                        ^$
+                      </button>
+                      <button
+                        className={`option-btn ${settings.searchDotAll ? 'active' : ''}`}
+                        onClick={() => updateSettings({ searchDotAll: !settings.searchDotAll })}
+                        title={t('search.dotAll')}
+                      >
+                        .\n
+                      </button>
+                    </>
+                  )}
+                  {!settings.searchStructural && (
+                    <button
+                      className={`option-btn ${searchInSelection ? 'active' : ''}`}
+                      onClick={toggleSearchInSelection}
+                      title={t('search.inSelection')}
+                    >
+                      <SquareDashed size={12} />
+                    </button>
+                  )}
                 </div>
               </div>
               
@@ -104,6 +195,15 @@ export function SearchPanel() {
                     value={replaceQuery}
                     onChange={(e) => setReplaceQuery(e.target.value)}
                   />
+                  <div className="search-options">
+                    <button
+                      className={`option-btn ${settings.searchPreserveCase ? 'active' : ''}`}
+                      onClick={() => updateSettings({ searchPreserveCase: !settings.searchPreserveCase })}
+                      title={t('search.preserveCase')}
+                    >
+                      AB
+                    </button>
+                  </div>
                   <div className="replace-actions">
                     <button
                       className="replace-btn"
@@ -138,6 +238,18 @@ export function SearchPanel() {
               </span>
             )}
           </div>
+
+          {previews.length > 0 && (
+            <div className="replace-preview">
+              {previews.slice(0, MAX_PREVIEW).map((preview) => (
+                <div key={preview.byteStart} className="replace-preview-item">
+                  <span className="replace-preview-line">{preview.line}</span>
+                  <span className="replace-preview-old">{preview.text}</span>
+                  <span className="replace-preview-new">{preview.replacement}</span>
+                </div>
+              ))}
+            </div>
+          )}
         </motion.div>
       )}
     </AnimatePresence>
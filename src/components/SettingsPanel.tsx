@@ -23,12 +23,26 @@ interface EncodingInfo {
 
 type SettingsTab = 'appearance' | 'editor' | 'files' | 'keyboard';
 
+// 内置终端类型的显示名称，其他值（如自定义 Shell 路径）原样显示
+const TERMINAL_LABELS: Record<string, string> = {
+  powershell: 'PowerShell',
+  cmd: 'CMD (命令提示符)',
+  pwsh: 'PowerShell Core (pwsh)',
+  wsl: 'WSL (Windows Subsystem for Linux)',
+  gitbash: 'Git Bash',
+  bash: 'Bash',
+  zsh: 'Zsh',
+  fish: 'Fish',
+  sh: 'sh',
+};
+
 export function SettingsPanel() {
   const { t, i18n } = useTranslation();
   const { showSettings, toggleSettings, settings, updateSettings } = useStore();
   const [activeTab, setActiveTab] = useState<SettingsTab>('appearance');
   const [fonts, setFonts] = useState<FontInfo[]>([]);
   const [encodings, setEncodings] = useState<EncodingInfo[]>([]);
+  const [terminals, setTerminals] = useState<string[]>([]);
   const [uiScale, setUiScale] = useState(100);
 
   // 加载字体列表
@@ -57,6 +71,11 @@ export function SettingsPanel() {
       invoke<EncodingInfo[]>('get_supported_encodings')
         .then(setEncodings)
         .catch(console.error);
+
+      // 可用终端由后端按平台检测
+      invoke<string[]>('get_available_terminals')
+        .then(setTerminals)
+        .catch(console.error);
     }
   }, [showSettings]);
 
@@ -459,11 +478,14 @@ export function SettingsPanel() {
                         value={settings.terminalType}
                         onChange={(e) => updateSettings({ terminalType: e.target.value })}
                       >
-                        <option value="powershell">PowerShell</option>
-                        <option value="cmd">CMD (命令提示符)</option>
-                        <option value="pwsh">PowerShell Core (pwsh)</option>
-                        <option value="wsl">WSL (Windows Subsystem for Linux)</option>
-                        <option value="gitbash">Git Bash</option>
+                        {(terminals.includes(settings.terminalType) || !settings.terminalType
+                          ? terminals
+                          : [settings.terminalType, ...terminals]
+                        ).map((terminal) => (
+                          <option key={terminal} value={terminal}>
+                            {TERMINAL_LABELS[terminal] ?? terminal}
+                          </option>
+                        ))}
                       </select>
                     </div>
                     <p className="setting-description">{t('settings.terminalTypeDesc')}</p>
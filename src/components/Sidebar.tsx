@@ -24,6 +24,38 @@ interface FileEntry {
   modified: string | null;
 }
 
+interface NoteSnippet {
+  line: number;
+  column: number;
+  text: string;
+  highlights: [number, number][];
+}
+
+interface NoteHit {
+  path: string;
+  score: number;
+  snippets: NoteSnippet[];
+}
+
+interface NoteSearchResult {
+  hits: NoteHit[];
+  total: number;
+  indexing: boolean;
+}
+
+// 按高亮范围（UTF-16 偏移）拆分片段文本
+function renderSnippet(snippet: NoteSnippet) {
+  const parts: React.ReactNode[] = [];
+  let last = 0;
+  snippet.highlights.forEach(([start, end], i) => {
+    if (start > last) parts.push(snippet.text.slice(last, start));
+    parts.push(<mark key={i} className="search-match">{snippet.text.slice(start, end)}</mark>);
+    last = end;
+  });
+  if (last < snippet.text.length) parts.push(snippet.text.slice(last));
+  return parts;
+}
+
 interface TreeNode extends FileEntry {
   children?: TreeNode[];
   isExpanded?: boolean;
@@ -39,7 +71,8 @@ export function Sidebar() {
   const [tree, setTree] = useState<TreeNode[]>([]);
   const [loading, setLoading] = useState(false);
   const [searchQuery, setSearchQuery] = useState('');
-  const [searchResults, setSearchResults] = useState<FileEntry[]>([]);
+  const [searchResults, setSearchResults] = useState<NoteHit[]>([]);
+  const [searchIndexing, setSearchIndexing] = useState(false);
   const [contextMenu, setContextMenu] = useState<{ position: { x: number; y: number } | null; node: TreeNode | null }>({
     position: null,
     node: null,
@@ -53,6 +86,44 @@ export function Sidebar() {
     }
   }, [openFolder]);
 
+  // 全文搜索（索引在打开文件夹时建立）
+  useEffect(() => {
+    if (!openFolder || !searchQuery.trim()) {
+      setSearchResults([]);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      invoke<NoteSearchResult>('search_full_text', { root: openFolder, query: searchQuery, limit: 50 })
+        .then((result) => {
+          if (cancelled) return;
+          setSearchResults(result.hits);
+          setSearchIndexing(result.indexing);
+        })
+        .catch((error) => {
+          console.error('Full text search failed:', error);
+          if (!cancelled) setSearchResults([]);
+        });
+    }, 200);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [searchQuery, openFolder]);
+
+  const openSearchHit = async (hit: NoteHit, snippet?: NoteSnippet) => {
+    if (!openFolder) return;
+    const separator = openFolder.includes('\\') ? '\\' : '/';
+    const root = openFolder.replace(/[/\\]$/, '');
+    await openFile(root + separator + hit.path.split('/').join(separator));
+    const editor = useStore.getState().editorInstance;
+    if (editor && snippet) {
+      editor.setPosition({ lineNumber: snippet.line, column: snippet.column });
+      editor.revealLineInCenter(snippet.line);
+      editor.focus();
+    }
+  };
+
   const loadDirectory = async (path: string) => {
     setLoading(true);
     try {
@@ -421,14 +492,32 @@ export function Sidebar() {
                 onChange={(e) => setSearchQuery(e.target.value)}
               />
             </div>
+            {searchIndexing && (
+              <div className="search-status">{t('search.indexing')}</div>
+            )}
             {searchResults.length > 0 && (
               <div className="search-results">
-                {searchResults.map((result, i) => (
-                  <div key={i} className="search-result-item" onClick={() => openFile(result.path)}>
-                    <FileIcon name={result.name} size={14} />
-                    <span>{result.name}</span>
-                  </div>
-                ))}
+                {searchResults.map((hit) => {
+                  const name = hit.path.split('/').pop() || hit.path;
+                  return (
+                    <div key={hit.path} className="search-result-group">
+                      <div className="search-result-item" onClick={() => openSearchHit(hit, hit.snippets[0])} title={hit.path}>
+                        <FileIcon name={name} size={14} />
+                        <span>{name}</span>
+                        <span className="search-result-dir">{hit.path.slice(0, hit.path.length - name.length)}</span>
+                      </div>
+                      {hit.snippets.map((snippet) => (
+                        <div
+                          key={snippet.line}
+                          className="search-result-snippet"
+                          onClick={() => openSearchHit(hit, snippet)}
+                        >
+                          {renderSnippet(snippet)}
+                        </div>
+                      ))}
+                    </div>
+                  );
+                })}
               </div>
             )}
           </div>
@@ -1,23 +1,248 @@
 // Terminal Component - 内置终端
 import { useState, useRef, useEffect } from 'react';
-import { motion, AnimatePresence } from 'framer-motion';
+import { motion } from 'framer-motion';
 import { useTranslation } from 'react-i18next';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { Terminal as XTerm } from '@xterm/xterm';
+import { FitAddon } from '@xterm/addon-fit';
 import { useStore } from '../store';
-import { X, Plus, ChevronUp, ChevronDown, Terminal as TerminalIcon } from 'lucide-react';
+import { X, Plus, ChevronUp, ChevronDown, Trash2, History, Terminal as TerminalIcon } from 'lucide-react';
+import '@xterm/xterm/css/xterm.css';
 import '../styles/Terminal.css';
 
-interface TerminalLine {
-  id: number;
-  type: 'input' | 'output' | 'error';
-  content: string;
-  timestamp: Date;
+interface TerminalSessionInfo {
+  id: string;
+  shell: string;
+  cwd: string | null;
+  pid: number | null;
+  cols: number;
+  rows: number;
 }
 
-interface TerminalOutput {
-  stdout: string;
-  stderr: string;
+interface TerminalOutputEvent {
+  sessionId: string;
+  data: string;
+}
+
+interface TerminalExitEvent {
+  sessionId: string;
+  exitCode: number | null;
+}
+
+interface HistoryEntry {
+  command: string;
   exitCode: number | null;
+  timestamp: number;
+  count: number;
+}
+
+interface TaskStartedEvent {
+  runId: string;
+  label: string;
+  command: string;
+}
+
+interface TaskOutputEvent {
+  runId: string;
+  label: string;
+  stream: 'stdout' | 'stderr';
+  data: string;
+}
+
+interface TaskFinishedEvent {
+  runId: string;
+  label: string;
+  exitCode: number | null;
+  success: boolean;
+}
+
+/** 面板中每个终端标签对应的 xterm 实例和后端 PTY 会话 */
+export interface TerminalHandle {
+  term: XTerm;
+  sessionId: string | null;
+}
+
+const XTERM_THEME = {
+  background: '#0d1117',
+  foreground: '#c9d1d9',
+  cursor: '#c9d1d9',
+  selectionBackground: '#264f78',
+};
+
+// 任务输出只用于显示，不写入 PTY
+const toTerminalText = (text: string) => text.replace(/\r?\n/g, '\r\n');
+
+interface TerminalSessionProps {
+  cwd: string;
+  active: boolean;
+  onHandle: (handle: TerminalHandle | null) => void;
+}
+
+// 单个交互式终端：xterm 负责渲染和按键，输入输出经由后端 PTY 会话
+function TerminalSession({ cwd, active, onHandle }: TerminalSessionProps) {
+  const { t } = useTranslation();
+  const containerRef = useRef<HTMLDivElement>(null);
+  const handleRef = useRef<TerminalHandle | null>(null);
+  const fitRef = useRef<FitAddon | null>(null);
+
+  useEffect(() => {
+    const container = containerRef.current;
+    if (!container) return;
+    const { settings } = useStore.getState();
+
+    const term = new XTerm({
+      cursorBlink: true,
+      fontFamily: settings.fontFamily,
+      fontSize: 13,
+      scrollback: 5000,
+      theme: XTERM_THEME,
+    });
+    const fit = new FitAddon();
+    term.loadAddon(fit);
+    term.open(container);
+    fit.fit();
+    fitRef.current = fit;
+
+    const handle: TerminalHandle = { term, sessionId: null };
+    handleRef.current = handle;
+    onHandle(handle);
+
+    let disposed = false;
+    // 会话 ID 返回之前到达的输出（如 Shell 提示符）先缓存
+    const pending: TerminalOutputEvent[] = [];
+
+    // 跟踪当前输入行，回车时记入命令历史；出现方向键、Tab 补全等无法还原的输入时放弃本行
+    let line = '';
+    let tracking = true;
+    const trackInput = (data: string) => {
+      for (const ch of data) {
+        if (ch === '\r') {
+          const command = line.trim();
+          if (tracking && command) {
+            invoke('add_terminal_history', {
+              workspace: useStore.getState().openFolder || null,
+              command,
+              exitCode: null,
+            }).catch(() => {});
+          }
+          line = '';
+          tracking = true;
+        } else if (ch === '\x03') {
+          line = '';
+          tracking = true;
+        } else if (ch === '\x7f' || ch === '\b') {
+          line = line.slice(0, -1);
+        } else if (ch < ' ' || ch === '\x1b') {
+          tracking = false;
+        } else {
+          line += ch;
+        }
+      }
+    };
+
+    const unlisteners = [
+      listen<TerminalOutputEvent>('terminal-output', (event) => {
+        if (handle.sessionId === null) pending.push(event.payload);
+        else if (event.payload.sessionId === handle.sessionId) term.write(event.payload.data);
+      }),
+      listen<TerminalExitEvent>('terminal-exit', (event) => {
+        if (event.payload.sessionId !== handle.sessionId) return;
+        handle.sessionId = null;
+        term.write(`\r\n\x1b[90m${t('terminal.exited', { code: event.payload.exitCode ?? '-' })}\x1b[0m\r\n`);
+      }),
+    ];
+
+    invoke<TerminalSessionInfo>('create_terminal_session', {
+      terminalType: settings.terminalType,
+      cwd: cwd && cwd !== '~' ? cwd : null,
+      cols: term.cols,
+      rows: term.rows,
+    })
+      .then((info) => {
+        if (disposed) {
+          invoke('kill_terminal_session', { sessionId: info.id }).catch(() => {});
+          return;
+        }
+        handle.sessionId = info.id;
+        pending.filter((event) => event.sessionId === info.id).forEach((event) => term.write(event.data));
+        pending.length = 0;
+      })
+      .catch((error) => {
+        term.write(`\x1b[31m${t('terminal.startFailed', { error: String(error) })}\x1b[0m\r\n`);
+      });
+
+    const dataListener = term.onData((data) => {
+      if (!handle.sessionId) return;
+      trackInput(data);
+      invoke('write_terminal_session', { sessionId: handle.sessionId, data }).catch((error) => {
+        console.error('Failed to write to terminal:', error);
+      });
+    });
+    const resizeListener = term.onResize(({ cols, rows }) => {
+      if (!handle.sessionId) return;
+      invoke('resize_terminal_session', { sessionId: handle.sessionId, cols, rows }).catch(() => {});
+    });
+
+    // 面板高度变化或标签切换后重新计算行列数
+    const observer = new ResizeObserver(() => {
+      if (container.clientWidth > 0 && container.clientHeight > 0) fit.fit();
+    });
+    observer.observe(container);
+
+    return () => {
+      disposed = true;
+      observer.disconnect();
+      dataListener.dispose();
+      resizeListener.dispose();
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()));
+      if (handle.sessionId) {
+        invoke('kill_terminal_session', { sessionId: handle.sessionId }).catch(() => {});
+      }
+      onHandle(null);
+      handleRef.current = null;
+      term.dispose();
+    };
+  }, []);
+
+  // 当前标签显示任务运行的流式输出
+  useEffect(() => {
+    if (!active) return;
+    const term = handleRef.current?.term;
+    if (!term) return;
+    fitRef.current?.fit();
+    term.focus();
+
+    const unlisteners = [
+      listen<TaskStartedEvent>('task-started', (event) => {
+        const { label, command } = event.payload;
+        term.write(`\r\n\x1b[36m> ${label}: ${toTerminalText(command)}\x1b[0m\r\n`);
+      }),
+      listen<TaskOutputEvent>('task-output', (event) => {
+        const { stream, data } = event.payload;
+        const text = toTerminalText(data);
+        term.write(stream === 'stderr' ? `\x1b[31m${text}\x1b[0m` : text);
+      }),
+      listen<TaskFinishedEvent>('task-finished', (event) => {
+        const { label, exitCode, success } = event.payload;
+        term.write(success
+          ? `\x1b[32m${t('tasks.finished', { label })}\x1b[0m\r\n`
+          : `\x1b[31m${t('tasks.failed', { label, code: exitCode ?? '-' })}\x1b[0m\r\n`);
+      }),
+    ];
+
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()));
+    };
+  }, [active, t]);
+
+  return (
+    <div
+      ref={containerRef}
+      className="terminal-session"
+      style={{ display: active ? 'block' : 'none' }}
+    />
+  );
 }
 
 export function Terminal() {
@@ -25,19 +250,14 @@ export function Terminal() {
   const {
     showTerminal, toggleTerminal, terminalHeight, setTerminalHeight,
     terminals, activeTerminalId, createTerminal, closeTerminal, setActiveTerminal,
-    openFolder, settings
+    openFolder,
   } = useStore();
-  
-  const [lines, setLines] = useState<TerminalLine[]>([]);
-  const [input, setInput] = useState('');
-  const [history, setHistory] = useState<string[]>([]);
-  const [historyIndex, setHistoryIndex] = useState(-1);
+
   const [isResizing, setIsResizing] = useState(false);
-  const [isExecuting, setIsExecuting] = useState(false);
-  
-  const inputRef = useRef<HTMLInputElement>(null);
-  const outputRef = useRef<HTMLDivElement>(null);
-  const lineIdRef = useRef(0);
+  const [showHistory, setShowHistory] = useState(false);
+  const [historyQuery, setHistoryQuery] = useState('');
+  const [historyEntries, setHistoryEntries] = useState<HistoryEntry[]>([]);
+  const handlesRef = useRef(new Map<string, TerminalHandle>());
 
   useEffect(() => {
     if (showTerminal && terminals.length === 0) {
@@ -45,111 +265,32 @@ export function Terminal() {
     }
   }, [showTerminal, terminals.length, createTerminal]);
 
-  useEffect(() => {
-    if (outputRef.current) {
-      outputRef.current.scrollTop = outputRef.current.scrollHeight;
-    }
-  }, [lines]);
-
-  const handleSubmit = async (e: React.FormEvent) => {
-    e.preventDefault();
-    if (!input.trim() || isExecuting) return;
+  const activeHandle = () => (activeTerminalId ? handlesRef.current.get(activeTerminalId) : undefined);
 
-    const newLine: TerminalLine = {
-      id: ++lineIdRef.current,
-      type: 'input',
-      content: `$ ${input}`,
-      timestamp: new Date(),
-    };
-    setLines(prev => [...prev, newLine]);
-    setHistory(prev => [input, ...prev].slice(0, 100));
-    setHistoryIndex(-1);
-
-    const cmd = input.trim();
-    const cmdLower = cmd.toLowerCase();
-    
-    // Handle built-in commands
-    if (cmdLower === 'clear' || cmdLower === 'cls') {
-      setLines([]);
-      setInput('');
-      return;
-    }
-    
-    setInput('');
-    setIsExecuting(true);
-    
-    try {
-      // Execute command using the configured terminal type
-      const result: TerminalOutput = await invoke('execute_terminal_command', {
-        command: cmd,
-        cwd: openFolder || undefined,
-        terminalType: settings.terminalType || 'powershell',
-      });
-      
-      // Add stdout
-      if (result.stdout) {
-        const outputLine: TerminalLine = {
-          id: ++lineIdRef.current,
-          type: 'output',
-          content: result.stdout.trimEnd(),
-          timestamp: new Date(),
-        };
-        setLines(prev => [...prev, outputLine]);
-      }
-      
-      // Add stderr
-      if (result.stderr) {
-        const errorLine: TerminalLine = {
-          id: ++lineIdRef.current,
-          type: 'error',
-          content: result.stderr.trimEnd(),
-          timestamp: new Date(),
-        };
-        setLines(prev => [...prev, errorLine]);
-      }
-      
-      // Show exit code if non-zero
-      if (result.exitCode !== null && result.exitCode !== 0) {
-        const exitLine: TerminalLine = {
-          id: ++lineIdRef.current,
-          type: 'error',
-          content: `Process exited with code ${result.exitCode}`,
-          timestamp: new Date(),
-        };
-        setLines(prev => [...prev, exitLine]);
-      }
-    } catch (error) {
-      const errorLine: TerminalLine = {
-        id: ++lineIdRef.current,
-        type: 'error',
-        content: `Error: ${error}`,
-        timestamp: new Date(),
-      };
-      setLines(prev => [...prev, errorLine]);
-    } finally {
-      setIsExecuting(false);
-    }
-  };
+  // 当前文件夹的命令历史，输入时反向搜索
+  useEffect(() => {
+    if (!showHistory) return;
+    const workspace = openFolder || null;
+    const request = historyQuery.trim()
+      ? invoke<HistoryEntry[]>('search_terminal_history', { workspace, query: historyQuery, limit: 50 })
+      : invoke<HistoryEntry[]>('get_terminal_history', { workspace, limit: 50 });
+    let cancelled = false;
+    request
+      .then((entries) => { if (!cancelled) setHistoryEntries(entries); })
+      .catch((error) => console.error('Failed to load terminal history:', error));
+    return () => { cancelled = true; };
+  }, [showHistory, historyQuery, openFolder]);
 
-  const handleKeyDown = (e: React.KeyboardEvent) => {
-    if (e.key === 'ArrowUp') {
-      e.preventDefault();
-      if (historyIndex < history.length - 1) {
-        const newIndex = historyIndex + 1;
-        setHistoryIndex(newIndex);
-        setInput(history[newIndex]);
-      }
-    } else if (e.key === 'ArrowDown') {
-      e.preventDefault();
-      if (historyIndex > 0) {
-        const newIndex = historyIndex - 1;
-        setHistoryIndex(newIndex);
-        setInput(history[newIndex]);
-      } else if (historyIndex === 0) {
-        setHistoryIndex(-1);
-        setInput('');
-      }
-    }
+  // 在当前终端会话中重新执行历史命令
+  const rerunCommand = (command: string) => {
+    const handle = activeHandle();
+    setShowHistory(false);
+    setHistoryQuery('');
+    if (!handle?.sessionId) return;
+    invoke('rerun_terminal_history', { sessionId: handle.sessionId, command }).catch((error) => {
+      console.error('Failed to rerun command:', error);
+    });
+    handle.term.focus();
   };
 
   const handleResizeStart = (e: React.MouseEvent) => {
@@ -174,7 +315,8 @@ export function Terminal() {
     document.addEventListener('mouseup', handleMouseUp);
   };
 
-  if (!showTerminal) return null;
+  // 面板隐藏时保留终端会话，只在关闭全部标签后卸载
+  if (!showTerminal && terminals.length === 0) return null;
 
   return (
     <motion.div
@@ -182,10 +324,10 @@ export function Terminal() {
       initial={{ height: 0 }}
       animate={{ height: terminalHeight }}
       exit={{ height: 0 }}
-      style={{ height: terminalHeight }}
+      style={{ height: terminalHeight, display: showTerminal ? undefined : 'none' }}
     >
       <div className="terminal-resize-handle" onMouseDown={handleResizeStart} />
-      
+
       <div className="terminal-header">
         <div className="terminal-tabs">
           {terminals.map(term => (
@@ -204,12 +346,22 @@ export function Terminal() {
               </button>
             </div>
           ))}
-          <button className="terminal-add" onClick={() => createTerminal()}>
+          <button className="terminal-add" onClick={() => createTerminal()} title={t('terminal.newTerminal')}>
             <Plus size={14} />
           </button>
         </div>
-        
+
         <div className="terminal-actions">
+          <button
+            className={showHistory ? 'active' : ''}
+            onClick={() => setShowHistory(!showHistory)}
+            title={t('terminal.history')}
+          >
+            <History size={14} />
+          </button>
+          <button onClick={() => activeHandle()?.term.clear()} title={t('terminal.clear')}>
+            <Trash2 size={14} />
+          </button>
           <button onClick={toggleTerminal}>
             {showTerminal ? <ChevronDown size={16} /> : <ChevronUp size={16} />}
           </button>
@@ -218,25 +370,52 @@ export function Terminal() {
           </button>
         </div>
       </div>
-      
-      <div className="terminal-body" ref={outputRef} onClick={() => inputRef.current?.focus()}>
-        {lines.map(line => (
-          <div key={line.id} className={`terminal-line ${line.type}`}>
-            <pre>{line.content}</pre>
-          </div>
-        ))}
-        
-        <form className="terminal-input-line" onSubmit={handleSubmit}>
-          <span className="terminal-prompt">$</span>
+
+      {showHistory && (
+        <div className="terminal-history">
           <input
-            ref={inputRef}
-            type="text"
-            value={input}
-            onChange={(e) => setInput(e.target.value)}
-            onKeyDown={handleKeyDown}
+            className="terminal-history-search"
+            placeholder={t('terminal.searchHistory')}
+            value={historyQuery}
+            onChange={(e) => setHistoryQuery(e.target.value)}
+            onKeyDown={(e) => {
+              if (e.key === 'Escape') setShowHistory(false);
+              else if (e.key === 'Enter' && historyEntries[0]) rerunCommand(historyEntries[0].command);
+            }}
             autoFocus
           />
-        </form>
+          <div className="terminal-history-list">
+            {historyEntries.length === 0 ? (
+              <div className="terminal-history-empty">{t('terminal.noHistory')}</div>
+            ) : historyEntries.map((entry) => (
+              <div
+                key={entry.command}
+                className="terminal-history-item"
+                onClick={() => rerunCommand(entry.command)}
+                title={new Date(entry.timestamp).toLocaleString()}
+              >
+                <span className="terminal-history-command">{entry.command}</span>
+                {entry.exitCode !== null && entry.exitCode !== 0 && (
+                  <span className="terminal-history-exit">{entry.exitCode}</span>
+                )}
+              </div>
+            ))}
+          </div>
+        </div>
+      )}
+
+      <div className="terminal-body">
+        {terminals.map(term => (
+          <TerminalSession
+            key={term.id}
+            cwd={term.cwd}
+            active={term.id === activeTerminalId}
+            onHandle={(handle) => {
+              if (handle) handlesRef.current.set(term.id, handle);
+              else handlesRef.current.delete(term.id);
+            }}
+          />
+        ))}
       </div>
     </motion.div>
   );
@@ -1,8 +1,10 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
 import { invoke } from '@tauri-apps/api/core';
-import { open, save } from '@tauri-apps/plugin-dialog';
+import { open, save, confirm } from '@tauri-apps/plugin-dialog';
 import { readTextFile, writeTextFile } from '@tauri-apps/plugin-fs';
+import type { editor } from 'monaco-editor';
+import i18n from '../i18n';
 
 // Types
 export interface EditorTab {
@@ -18,6 +20,12 @@ export interface EditorTab {
   selections?: { startLine: number; startCol: number; endLine: number; endCol: number }[];
   scrollPosition?: { top: number; left: number };
   viewState?: any;
+  // 大文件模式：内容不读入前端，由后端按行提供
+  largeFile?: boolean;
+  // 日志跟踪模式：只读，由后端推送新追加的行
+  logFollow?: boolean;
+  // 十六进制模式：二进制文件的内容由后端分页提供，修改保存在后端
+  hexMode?: boolean;
 }
 
 export interface EditorSettings {
@@ -44,6 +52,11 @@ export interface EditorSettings {
   searchCaseSensitive: boolean;
   searchWholeWord: boolean;
   searchRegex: boolean;
+  searchPreserveCase: boolean;
+  searchStructural: boolean;
+  searchStructuralQuery: boolean;
+  searchMultiline: boolean;
+  searchDotAll: boolean;
   restoreWindows: boolean;
   showStatusBar: boolean;
   showActivityBar: boolean;
@@ -63,17 +76,31 @@ export interface EditorSettings {
   addToContextMenu: boolean;
   // 终端设置
   terminalType: string;
+  // 文件名到 JSON Schema 的映射
+  jsonSchemas: { fileMatch: string[]; url: string }[];
+  // 预览中本地附件（kairofile://）额外允许访问的目录
+  assetRoots: string[];
 }
 
 export interface SearchResult {
   line: number;
   column: number;
+  endLine: number;
+  endColumn: number;
   length: number;
+  byteStart: number;
+  byteEnd: number;
+  charStart: number;
+  charEnd: number;
   text: string;
   context: string;
   filePath?: string;
 }
 
+export interface ReplacePreview extends SearchResult {
+  replacement: string;
+}
+
 export interface GlobalSearchResult {
   filePath: string;
   fileName: string;
@@ -138,6 +165,7 @@ interface AppState {
   showGlobalSearch: boolean;
   showSettings: boolean;
   showCommandPalette: boolean;
+  quickOpen: boolean;
   showTerminal: boolean;
   showMarkdownPreview: boolean;
   showDiffView: boolean;
@@ -152,6 +180,8 @@ interface AppState {
   globalSearchResults: GlobalSearchResult[];
   replaceQuery: string;
   searchHistory: string[];
+  // 只在打开“在选区中查找”时的选区内搜索
+  searchInSelection: boolean;
   
   // Settings
   settings: EditorSettings;
@@ -199,6 +229,7 @@ interface AppState {
   closeSavedTabs: () => void;
   setActiveTab: (id: string) => void;
   updateTabContent: (id: string, content: string) => void;
+  setTabModified: (id: string, isModified: boolean) => void;
   updateCursorPosition: (id: string, line: number, column: number) => void;
   updateTabViewState: (id: string, viewState: any) => void;
   moveTab: (fromIndex: number, toIndex: number) => void;
@@ -207,6 +238,7 @@ interface AppState {
   // Actions - Files
   openFile: (path?: string) => Promise<void>;
   openFileInNewTab: (path: string) => Promise<void>;
+  followLogFile: (path?: string) => Promise<void>;
   saveFile: (id?: string) => Promise<void>;
   saveFileAs: (id?: string) => Promise<void>;
   saveAllFiles: () => Promise<void>;
@@ -218,6 +250,7 @@ interface AppState {
   toggleGlobalSearch: () => void;
   toggleSettings: () => void;
   toggleCommandPalette: () => void;
+  toggleQuickOpen: () => void;
   toggleTerminal: () => void;
   toggleMarkdownPreview: () => void;
   toggleDiffView: () => void;
@@ -229,6 +262,7 @@ interface AppState {
   setSearchQuery: (query: string) => void;
   setReplaceQuery: (query: string) => void;
   performSearch: () => Promise<void>;
+  toggleSearchInSelection: () => void;
   performGlobalSearch: (query: string, folder: string) => Promise<void>;
   performReplace: (all: boolean) => Promise<void>;
   addToSearchHistory: (query: string) => void;
@@ -267,7 +301,8 @@ interface AppState {
   
   // Actions - Utilities
   formatDocument: () => Promise<void>;
-  updateCharacterCount: () => void;
+  convertDocument: (target: 'json' | 'yaml' | 'toml' | 'xml' | 'plist' | 'csv') => Promise<void>;
+  updateCharacterCount: () => Promise<void>;
 }
 
 const defaultSettings: EditorSettings = {
@@ -294,6 +329,11 @@ const defaultSettings: EditorSettings = {
   searchCaseSensitive: false,
   searchWholeWord: false,
   searchRegex: false,
+  searchPreserveCase: false,
+  searchStructural: false,
+  searchStructuralQuery: false,
+  searchMultiline: true,
+  searchDotAll: false,
   restoreWindows: true,
   showStatusBar: true,
   showActivityBar: true,
@@ -307,9 +347,79 @@ const defaultSettings: EditorSettings = {
   registerAsDefaultEditor: false,
   registerAsPathEditor: false,
   addToContextMenu: false,
-  terminalType: 'powershell',
+  // 留空，由后端 get_settings 返回当前平台的默认终端
+  terminalType: '',
+  jsonSchemas: [],
+  assetRoots: [],
+};
+
+// 每个标签页发往后端文档模型的同步请求（打开、增量编辑、关闭）按顺序执行
+const documentSyncs = new Map<string, Promise<void>>();
+
+export const syncDocument = (tabId: string, task: () => Promise<unknown>) => {
+  const next = (documentSyncs.get(tabId) ?? Promise.resolve()).then(task).then(() => {}, () => {});
+  documentSyncs.set(tabId, next);
+  next.then(() => {
+    if (documentSyncs.get(tabId) === next) documentSyncs.delete(tabId);
+  });
+  return next;
+};
+
+// 优先在后端文档模型上执行（只传标签页 ID），文档未打开时回退为发送全文；
+// 执行前等待已排队的编辑同步完成，保证查询的是当前内容
+export const invokeDocument = async <T>(
+  tab: EditorTab,
+  command: string,
+  fallback: string,
+  args: Record<string, unknown>,
+): Promise<T> => {
+  await documentSyncs.get(tab.id);
+  try {
+    return await invoke<T>(command, { tabId: tab.id, ...args });
+  } catch (error) {
+    if (!String(error).startsWith('Document not open')) throw error;
+    return invoke<T>(fallback, { content: tab.content, ...args });
+  }
+};
+
+// 结构化搜索：有路径时由后端按扩展名选择语法，否则使用标签页的语言
+export const structuralTarget = (tab: EditorTab) => ({
+  path: tab.path,
+  language: tab.path ? null : tab.language,
+});
+
+// 结构化搜索中正则开关切换为 tree-sitter 查询表达式
+// 查找范围：开启“在选区中查找”时记录的选区，用装饰跟踪编辑引起的位置变化
+let searchScope: editor.IEditorDecorationsCollection | null = null;
+
+const clearSearchScope = () => {
+  searchScope?.clear();
+  searchScope = null;
 };
 
+// 内容搜索选项（替换时还需要 preserveCase、replaceAll）
+export const searchOptions = (settings: EditorSettings, inSelection: boolean) => ({
+  caseSensitive: settings.searchCaseSensitive,
+  useRegex: settings.searchRegex,
+  wholeWord: settings.searchWholeWord,
+  multiline: settings.searchMultiline,
+  dotAll: settings.searchDotAll,
+  selections: inSelection && searchScope
+    ? searchScope.getRanges().map((range) => ({
+        startLine: range.startLineNumber,
+        startColumn: range.startColumn,
+        endLine: range.endLineNumber,
+        endColumn: range.endColumn,
+      }))
+    : [],
+});
+
+export const structuralQuery = (pattern: string, useQuery: boolean, replacement?: string) => ({
+  pattern,
+  mode: useQuery ? 'query' : 'pattern',
+  replacement: replacement ?? null,
+});
+
 let tabCounter = 0;
 let terminalCounter = 0;
 let splitCounter = 0;
@@ -327,6 +437,7 @@ export const useStore = create<AppState>()(
       showGlobalSearch: false,
       showSettings: false,
       showCommandPalette: false,
+      quickOpen: false,
       showTerminal: false,
       showMarkdownPreview: false,
       showDiffView: false,
@@ -336,6 +447,7 @@ export const useStore = create<AppState>()(
       panelPosition: 'bottom',
       searchQuery: '',
       searchResults: [],
+      searchInSelection: false,
       globalSearchResults: [],
       replaceQuery: '',
       searchHistory: [],
@@ -355,7 +467,11 @@ export const useStore = create<AppState>()(
       editorInstance: null,
 
       // Editor Instance
-      setEditorInstance: (editor) => set({ editorInstance: editor }),
+      setEditorInstance: (editor) => {
+        // 切换标签页后编辑器重新挂载，原选区范围失效
+        clearSearchScope();
+        set({ editorInstance: editor, searchInSelection: false });
+      },
       
       // Editor Actions
       editorUndo: () => {
@@ -461,6 +577,12 @@ export const useStore = create<AppState>()(
         get().updateCharacterCount();
       },
 
+      setTabModified: (id: string, isModified: boolean) => {
+        set((state) => ({
+          tabs: state.tabs.map((t) => (t.id === id ? { ...t, isModified } : t)),
+        }));
+      },
+
       updateCursorPosition: (id: string, line: number, column: number) => {
         set((state) => ({
           tabs: state.tabs.map((t) =>
@@ -515,14 +637,26 @@ export const useStore = create<AppState>()(
             return;
           }
           
-          const content = await readTextFile(path);
+          // 二进制文件使用十六进制模式，超过阈值的文本文件使用大文件模式，内容都不读入前端
+          const info = await invoke<{ isLarge: boolean; isBinary: boolean; encoding: string }>('get_file_info', { path });
+          const hexMode = info.isBinary;
+          const largeFile = info.isLarge && !hexMode;
+          if (hexMode) {
+            await invoke('open_hex_document', { path });
+          } else if (largeFile) {
+            await invoke('open_large_file', { path });
+          }
+          const content = hexMode || largeFile ? '' : await readTextFile(path);
           const name = path.split(/[/\\]/).pop() || 'Untitled';
           const language: string = await invoke('detect_language', { path, content: null });
           
           const id = `tab-${++tabCounter}`;
           const newTab: EditorTab = {
             id, path, name, content, originalContent: content, language,
-            encoding: 'UTF-8', isModified: false, cursorPosition: { line: 1, column: 1 },
+            encoding: hexMode ? 'binary' : largeFile ? info.encoding : 'UTF-8', isModified: false,
+            cursorPosition: { line: 1, column: 1 },
+            largeFile: largeFile || undefined,
+            hexMode: hexMode || undefined,
           };
           
           set((state) => ({ tabs: [...state.tabs, newTab], activeTabId: id }));
@@ -536,18 +670,79 @@ export const useStore = create<AppState>()(
         await get().openFile(path);
       },
 
+      followLogFile: async (filePath?: string) => {
+        let path = filePath;
+        if (!path) {
+          const selected = await open({
+            multiple: false,
+            filters: [
+              { name: 'Log Files', extensions: ['log', 'txt', 'out', 'jsonl'] },
+              { name: 'All Files', extensions: ['*'] },
+            ],
+          });
+          if (!selected) return;
+          path = selected as string;
+        }
+
+        const existingTab = get().tabs.find((t) => t.path === path && t.logFollow);
+        if (existingTab) {
+          set({ activeTabId: existingTab.id });
+          return;
+        }
+
+        const id = `tab-${++tabCounter}`;
+        const newTab: EditorTab = {
+          id, path, name: path.split(/[/\\]/).pop() || 'Untitled', content: '', originalContent: '',
+          language: 'plaintext', encoding: 'UTF-8', isModified: false, cursorPosition: { line: 1, column: 1 },
+          logFollow: true,
+        };
+        set((state) => ({ tabs: [...state.tabs, newTab], activeTabId: id }));
+      },
+
       saveFile: async (id?: string) => {
         const state = get();
         const tabId = id || state.activeTabId;
         const tab = state.tabs.find((t) => t.id === tabId);
-        if (!tab) return;
+        if (!tab || tab.largeFile || tab.logFollow) return;
         
         if (!tab.path) {
           return get().saveFileAs(tabId ?? undefined);
         }
+
+        if (tab.hexMode) {
+          try {
+            await invoke('save_hex_document', { path: tab.path });
+            get().setTabModified(tab.id, false);
+          } catch (error) {
+            // 插入或删除过字节时不能原地写回，改为另存为
+            console.warn('Hex save requires Save As:', error);
+            await get().saveFileAs(tab.id);
+          }
+          return;
+        }
         
         try {
           let content = tab.content;
+          if (state.settings.formatOnSave) {
+            try {
+              const result = await invoke<{ text: string; warnings: string[] }>('format_document', {
+                content,
+                path: tab.path,
+                languageId: tab.language || null,
+                workspace: state.openFolder || null,
+                options: { indent: state.settings.tabSize },
+              });
+              // 格式化会丢失信息（如 YAML/TOML 注释）时保存原始内容
+              if (result.warnings.length > 0) {
+                console.warn('Format on save skipped:', result.warnings.join('; '));
+              } else {
+                content = result.text;
+              }
+            } catch (error) {
+              // 没有可用的格式化程序或格式化失败时，仍然保存原始内容
+              console.warn('Format on save skipped:', error);
+            }
+          }
           if (state.settings.trimTrailingWhitespace) {
             content = content.split('\n').map(line => line.trimEnd()).join('\n');
           }
@@ -561,6 +756,7 @@ export const useStore = create<AppState>()(
               t.id === tabId ? { ...t, content, originalContent: content, isModified: false } : t
             ),
           }));
+          invoke('lsp_save_document', { path: tab.path, text: content }).catch(() => {});
         } catch (error) {
           console.error('Failed to save file:', error);
         }
@@ -570,11 +766,19 @@ export const useStore = create<AppState>()(
         const state = get();
         const tabId = id || state.activeTabId;
         const tab = state.tabs.find((t) => t.id === tabId);
-        if (!tab) return;
+        // 大文件和日志跟踪标签页的 content 不是完整文件内容，十六进制标签页只能从原文件另存
+        if (!tab || tab.largeFile || tab.logFollow || (tab.hexMode && !tab.path)) return;
         
         try {
           const path = await save({ defaultPath: tab.name, filters: [{ name: 'All Files', extensions: ['*'] }] });
-          if (path) {
+          if (path && tab.hexMode && tab.path) {
+            await invoke('save_hex_document_as', { path: tab.path, target: path });
+            const name = path.split(/[/\\]/).pop() || 'Untitled';
+            set((state) => ({
+              tabs: state.tabs.map((t) => (t.id === tabId ? { ...t, path, name, isModified: false } : t)),
+            }));
+            get().addRecentFile(path);
+          } else if (path) {
             await writeTextFile(path, tab.content);
             const name = path.split(/[/\\]/).pop() || 'Untitled';
             const language: string = await invoke('detect_language', { path, content: null });
@@ -600,7 +804,11 @@ export const useStore = create<AppState>()(
 
       reloadFile: async (id: string) => {
         const tab = get().tabs.find((t) => t.id === id);
-        if (!tab?.path) return;
+        if (!tab?.path || tab.logFollow || tab.hexMode) return;
+        if (tab.largeFile) {
+          await invoke('refresh_large_file', { path: tab.path }).catch(() => {});
+          return;
+        }
         try {
           const content = await readTextFile(tab.path);
           set((state) => ({
@@ -620,10 +828,17 @@ export const useStore = create<AppState>()(
         // 同时保存到配置文件
         get().updateSettings({ theme: themeValue });
       },
-      toggleSearch: () => set((state) => ({ showSearch: !state.showSearch, showGlobalSearch: false })),
+      toggleSearch: () => {
+        if (get().showSearch) {
+          clearSearchScope();
+          set({ searchInSelection: false });
+        }
+        set((state) => ({ showSearch: !state.showSearch, showGlobalSearch: false }));
+      },
       toggleGlobalSearch: () => set((state) => ({ showGlobalSearch: !state.showGlobalSearch, showSearch: false })),
       toggleSettings: () => set((state) => ({ showSettings: !state.showSettings })),
-      toggleCommandPalette: () => set((state) => ({ showCommandPalette: !state.showCommandPalette })),
+      toggleCommandPalette: () => set((state) => ({ showCommandPalette: !state.showCommandPalette, quickOpen: false })),
+      toggleQuickOpen: () => set((state) => ({ showCommandPalette: !state.showCommandPalette, quickOpen: true })),
       toggleTerminal: () => set((state) => ({ showTerminal: !state.showTerminal })),
       toggleMarkdownPreview: () => set((state) => ({ showMarkdownPreview: !state.showMarkdownPreview })),
       toggleDiffView: () => set((state) => ({ showDiffView: !state.showDiffView })),
@@ -643,12 +858,15 @@ export const useStore = create<AppState>()(
           return;
         }
         try {
-          const results: SearchResult[] = await invoke('search_in_file', {
-            content: tab.content,
-            query: state.searchQuery,
-            caseSensitive: state.settings.searchCaseSensitive,
-            useRegex: state.settings.searchRegex,
-          });
+          const results: SearchResult[] = state.settings.searchStructural
+            ? await invokeDocument(tab, 'structural_search_document', 'structural_search', {
+                ...structuralTarget(tab),
+                query: structuralQuery(state.searchQuery, state.settings.searchStructuralQuery),
+              })
+            : await invokeDocument(tab, 'search_document', 'search_in_file', {
+                query: state.searchQuery,
+                options: searchOptions(state.settings, state.searchInSelection),
+              });
           set({ searchResults: results });
           get().addToSearchHistory(state.searchQuery);
         } catch (error) {
@@ -657,6 +875,24 @@ export const useStore = create<AppState>()(
         }
       },
 
+      toggleSearchInSelection: () => {
+        const { searchInSelection, editorInstance } = get();
+        clearSearchScope();
+        if (searchInSelection || !editorInstance) {
+          set({ searchInSelection: false });
+          return;
+        }
+        const selections: editor.Selection[] = (editorInstance.getSelections() ?? [])
+          .filter((selection: editor.Selection) => !selection.isEmpty());
+        if (selections.length === 0) return;
+        searchScope = editorInstance.createDecorationsCollection(selections.map((range) => ({
+          range,
+          options: { className: 'find-scope', isWholeLine: false },
+        })));
+        set({ searchInSelection: true });
+        get().performSearch();
+      },
+
       performGlobalSearch: async (query: string, folder: string) => {
         // This would need backend implementation
         set({ globalSearchResults: [] });
@@ -666,13 +902,36 @@ export const useStore = create<AppState>()(
       performReplace: async (all: boolean) => {
         const state = get();
         const tab = state.tabs.find((t) => t.id === state.activeTabId);
-        if (!tab || !state.searchQuery) return;
+        const editor = state.editorInstance;
+        if (!tab || !state.searchQuery || !editor) return;
         try {
-          const newContent: string = await invoke('search_and_replace', {
-            content: tab.content, search: state.searchQuery, replace: state.replaceQuery,
-            caseSensitive: state.settings.searchCaseSensitive, useRegex: state.settings.searchRegex, replaceAll: all,
-          });
-          get().updateTabContent(tab.id, newContent);
+          const previews: ReplacePreview[] = state.settings.searchStructural
+            ? await invokeDocument(tab, 'structural_search_document', 'structural_search', {
+                ...structuralTarget(tab),
+                query: structuralQuery(state.searchQuery, state.settings.searchStructuralQuery, state.replaceQuery),
+              })
+            : await invokeDocument(tab, 'preview_document_replace', 'preview_replace', {
+                search: state.searchQuery,
+                replace: state.replaceQuery,
+                options: {
+                  ...searchOptions(state.settings, state.searchInSelection),
+                  preserveCase: state.settings.searchPreserveCase,
+                  replaceAll: all,
+                },
+              });
+          // 替换作为编辑应用到 Monaco（可撤销），变化再以增量同步到后端文档
+          const edits = (all ? previews : previews.slice(0, 1)).map((preview) => ({
+            range: {
+              startLineNumber: preview.line,
+              startColumn: preview.column,
+              endLineNumber: preview.endLine,
+              endColumn: preview.endColumn,
+            },
+            text: preview.replacement,
+          }));
+          if (edits.length === 0) return;
+          editor.executeEdits('replace', edits);
+          editor.pushUndoStop();
           get().performSearch();
         } catch (error) {
           console.error('Replace failed:', error);
@@ -839,6 +1098,7 @@ export const useStore = create<AppState>()(
         const session = {
           tabs: state.tabs.map((t) => ({
             path: t.path,
+            logFollow: t.logFollow,
             cursorPosition: t.cursorPosition,
             viewState: t.viewState,
           })),
@@ -861,7 +1121,9 @@ export const useStore = create<AppState>()(
           }
           
           for (const tabData of session.tabs || []) {
-            if (tabData.path) {
+            if (tabData.path && tabData.logFollow) {
+              await get().followLogFile(tabData.path);
+            } else if (tabData.path) {
               await get().openFile(tabData.path);
             }
           }
@@ -878,23 +1140,67 @@ export const useStore = create<AppState>()(
         if (!tab) return;
         
         try {
-          // Format based on language
-          let formatted = tab.content;
-          if (tab.language === 'json') {
-            formatted = JSON.stringify(JSON.parse(tab.content), null, 2);
+          // 外部格式化程序未安装时，JSON/TOML/YAML/XML 使用内置格式化
+          const result = await invoke<{ text: string; changed: boolean; warnings: string[] }>('format_document', {
+            content: tab.content,
+            path: tab.path,
+            languageId: tab.language || null,
+            workspace: get().openFolder || null,
+            options: { indent: get().settings.tabSize },
+          });
+          result.warnings.forEach((warning) => console.warn('Format:', warning));
+          if (result.changed) {
+            get().updateTabContent(tab.id, result.text);
           }
-          get().updateTabContent(tab.id, formatted);
         } catch (error) {
           console.error('Format failed:', error);
         }
       },
 
-      updateCharacterCount: () => {
+      convertDocument: async (target) => {
+        const tab = get().tabs.find((t) => t.id === get().activeTabId);
+        if (!tab) return;
+
+        try {
+          // 转换结果在新标签页中打开，原文件保持不变
+          const result = await invoke<{ text: string; languageId: string; extension: string; warnings: string[] }>('convert_data', {
+            content: tab.content,
+            to: target,
+            path: tab.path,
+            languageId: tab.language || null,
+            options: { indent: get().settings.tabSize },
+          });
+          // 有损转换（丢失注释、类型等）时先让用户确认
+          if (result.warnings.length > 0) {
+            const proceed = await confirm(
+              `${i18n.t('dialog.lossyConversion')}\n\n${result.warnings.map((w) => `• ${w}`).join('\n')}`,
+              { title: i18n.t('tools.convertTo'), kind: 'warning' },
+            );
+            if (!proceed) return;
+          }
+          const baseName = tab.name.replace(/\.[^.]+$/, '');
+          get().createTab(result.text, result.languageId, `${baseName}.${result.extension}`);
+        } catch (error) {
+          console.error('Convert failed:', error);
+        }
+      },
+
+      updateCharacterCount: async () => {
         const tab = get().tabs.find((t) => t.id === get().activeTabId);
         if (!tab) {
           set({ characterCount: null });
           return;
         }
+        try {
+          await documentSyncs.get(tab.id);
+          const { chars, words, lines } = await invoke<{ chars: number; words: number; lines: number }>(
+            'get_document_stats', { tabId: tab.id },
+          );
+          set({ characterCount: { chars, words, lines } });
+          return;
+        } catch {
+          // 文档尚未同步到后端时在前端统计
+        }
         const content = tab.content;
         const chars = content.length;
         const words = content.trim() ? content.trim().split(/\s+/).length : 0;